@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::types::ThermostatMode;
+use crate::types::{ControlStrategy, TemperatureUnit, ThermostatMode};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThermostatConfig {
@@ -19,6 +19,105 @@ pub struct ThermostatConfig {
     pub max_valid_temp_f: f32,
     pub max_hold_minutes: u16,
     pub absolute_max_temp_f: f32,
+    /// How long a humidity reading may go without *changing* before
+    /// `ThermostatEngine::is_humidity_data_valid` treats it as frozen and
+    /// stops honoring `PersistedSettings::humidity_target`. Deliberately
+    /// based on "last change" rather than "last update" like
+    /// `sensor_stale_timeout_ms`, since a stuck humidity sensor often keeps
+    /// reporting on schedule with the same repeated value rather than going
+    /// silent.
+    #[serde(default = "default_humidity_stale_timeout_ms")]
+    pub humidity_stale_timeout_ms: u64,
+    #[serde(default)]
+    pub pid: PidParameters,
+    #[serde(default)]
+    pub setpoint_pid: SetpointPidParameters,
+    #[serde(default)]
+    pub time_proportional: TimeProportionalParameters,
+    #[serde(default)]
+    pub autotune: AutotuneParameters,
+    /// Assumed heating rate (°F/min) used for anticipatory pre-heat before
+    /// enough live samples have been observed.
+    #[serde(default = "default_preheat_rate_f_per_min")]
+    pub preheat_default_rate_f_per_min: f32,
+    /// Upper bound on how early a scheduled heat-up transition may be
+    /// started, regardless of how large the estimated deficit is.
+    #[serde(default = "default_preheat_max_lookahead_ms")]
+    pub preheat_max_lookahead_ms: u64,
+    /// How far below `absolute_max_temp_f` the room must cool before
+    /// `reset_safety` will clear a latched `OverTemp` fault, so the
+    /// controller doesn't immediately re-trip on noise right at the ceiling.
+    #[serde(default = "default_fault_recovery_hysteresis_f")]
+    pub fault_recovery_hysteresis_f: f32,
+    /// Steinhart-Hart coefficients and calibration used by
+    /// `ThermostatEngine::update_sensor_raw` to convert a room thermistor's
+    /// resistance into °F.
+    #[serde(default)]
+    pub thermistor: ThermistorConfig,
+    /// Ordered bands for `ThermostatEngine::thermal_load`'s graded response,
+    /// consulted ahead of the hard `absolute_max_temp_f` shutoff.
+    #[serde(default)]
+    pub thermal_load: ThermalLoadParameters,
+    /// Independent upper alarm limit, checked by `update_sensor_data`/`tick`
+    /// separately from `absolute_max_temp_f`. Deliberately set above the
+    /// hard ceiling by default so it only fires as a backstop if the
+    /// ceiling's own shutoff somehow fails to hold the room; crossing it
+    /// latches `ThermostatState::Fault` until `clear_alarm` succeeds, rather
+    /// than the ceiling's `Idle` state with an `OverTemp` fault that
+    /// `reset_safety` clears.
+    #[serde(default = "default_alarm_high_f")]
+    pub alarm_high_f: f32,
+    /// Independent lower alarm limit (freeze protection); same latching
+    /// behavior as `alarm_high_f`.
+    #[serde(default = "default_alarm_low_f")]
+    pub alarm_low_f: f32,
+    /// Once the engine has reached `Satisfied` at least once, `current_temp_f`
+    /// drifting more than this far from `target_temp_f` for
+    /// `alarm_band_ticks` consecutive `tick` calls also trips the same
+    /// latch as `alarm_high_f`/`alarm_low_f`. Catches a fireplace that's
+    /// silently stopped satisfying the setpoint (stuck relay, exhausted
+    /// fuel) well before the room drifts all the way out to the absolute
+    /// alarm limits; `alarm_high_f`/`alarm_low_f` stay in force the whole
+    /// time as a hard backstop.
+    #[serde(default = "default_alarm_band_f")]
+    pub alarm_band_f: f32,
+    /// Consecutive `tick` calls the room must stay outside
+    /// `alarm_band_f` of target before the band check latches, so one noisy
+    /// reading right after the setpoint changes doesn't trip it.
+    #[serde(default = "default_alarm_band_ticks")]
+    pub alarm_band_ticks: u32,
+}
+
+fn default_preheat_rate_f_per_min() -> f32 {
+    0.5
+}
+
+fn default_preheat_max_lookahead_ms() -> u64 {
+    7_200_000
+}
+
+fn default_fault_recovery_hysteresis_f() -> f32 {
+    5.0
+}
+
+fn default_humidity_stale_timeout_ms() -> u64 {
+    1_800_000
+}
+
+fn default_alarm_high_f() -> f32 {
+    105.0
+}
+
+fn default_alarm_low_f() -> f32 {
+    40.0
+}
+
+fn default_alarm_band_f() -> f32 {
+    0.5
+}
+
+fn default_alarm_band_ticks() -> u32 {
+    3
 }
 
 impl Default for ThermostatConfig {
@@ -39,6 +138,189 @@ impl Default for ThermostatConfig {
             max_valid_temp_f: 150.0,
             max_hold_minutes: 1_440,
             absolute_max_temp_f: 95.0,
+            humidity_stale_timeout_ms: default_humidity_stale_timeout_ms(),
+            pid: PidParameters::default(),
+            setpoint_pid: SetpointPidParameters::default(),
+            time_proportional: TimeProportionalParameters::default(),
+            autotune: AutotuneParameters::default(),
+            preheat_default_rate_f_per_min: default_preheat_rate_f_per_min(),
+            preheat_max_lookahead_ms: default_preheat_max_lookahead_ms(),
+            fault_recovery_hysteresis_f: default_fault_recovery_hysteresis_f(),
+            thermistor: ThermistorConfig::default(),
+            thermal_load: ThermalLoadParameters::default(),
+            alarm_high_f: default_alarm_high_f(),
+            alarm_low_f: default_alarm_low_f(),
+            alarm_band_f: default_alarm_band_f(),
+            alarm_band_ticks: default_alarm_band_ticks(),
+        }
+    }
+}
+
+/// Ordered thermal-load bands (see `ThermostatEngine::thermal_load`, which
+/// normalizes `current_temp_f`'s position between `target_temp_f` and
+/// `absolute_max_temp_f` onto `0..255`) that drive a graded response ahead of
+/// the hard ceiling: crossing `throttle_load` shaves
+/// `throttle_extra_hysteresis_f` off the normal heat-call upper bound so the
+/// fireplace shuts off earlier and runs a shorter duty cycle; crossing
+/// `shutoff_load` cuts power outright, same as the old single-threshold
+/// emergency shutoff but before the room ever reaches `absolute_max_temp_f`;
+/// crossing `lockout_load` additionally latches out any further heat call
+/// until the room falls `lockout_recovery_margin_f` below `target_temp_f`,
+/// not merely back under the ceiling. Bands are meant to sit below 255 so
+/// `lockout_load` is reached (and lockout engaged) before the absolute-max
+/// fault ever has to trip.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ThermalLoadParameters {
+    pub throttle_load: u8,
+    pub shutoff_load: u8,
+    pub lockout_load: u8,
+    pub throttle_extra_hysteresis_f: f32,
+    pub lockout_recovery_margin_f: f32,
+}
+
+impl Default for ThermalLoadParameters {
+    fn default() -> Self {
+        Self {
+            throttle_load: 100,
+            shutoff_load: 180,
+            lockout_load: 230,
+            throttle_extra_hysteresis_f: 2.0,
+            lockout_recovery_margin_f: 3.0,
+        }
+    }
+}
+
+/// Steinhart-Hart coefficients `{a, b, c}` in `1/T = a + b*ln(R) + c*(ln R)^3`
+/// (T in Kelvin, R in ohms), plus a per-install linear calibration applied
+/// after the Kelvin-to-Fahrenheit conversion so field trimming a particular
+/// probe doesn't require recompiling.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ThermistorConfig {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub calibration_offset_f: f32,
+    pub calibration_gain: f32,
+}
+
+impl Default for ThermistorConfig {
+    fn default() -> Self {
+        Self {
+            // Typical coefficients for a common 10k NTC room thermistor,
+            // valid over roughly -20..100 C.
+            a: 0.001_129_148,
+            b: 0.000_234_125,
+            c: 0.000_000_087_674_1,
+            calibration_offset_f: 0.0,
+            calibration_gain: 1.0,
+        }
+    }
+}
+
+/// Tuning for the PID control strategy (`ControlStrategy::Pid`). `output_*`
+/// bounds the raw 0..1 control signal before it's converted into a
+/// time-proportioned on/off duty cycle; `integral_*` bounds the accumulated
+/// error to prevent windup while the fireplace is held off by hold/cooldown.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PidParameters {
+    pub kp: f32,
+    pub ki: f32,
+    pub kd: f32,
+    pub output_min: f32,
+    pub output_max: f32,
+    pub integral_min: f32,
+    pub integral_max: f32,
+}
+
+impl Default for PidParameters {
+    fn default() -> Self {
+        Self {
+            kp: 1.5,
+            ki: 0.1,
+            kd: 150.0,
+            output_min: 0.0,
+            output_max: 1.0,
+            integral_min: -10.0,
+            integral_max: 10.0,
+        }
+    }
+}
+
+/// Tuning for `ControlStrategy::SetpointPid`. Unlike [`PidParameters`], this
+/// loop's output is a fireplace setpoint offset (°F) rather than a duty
+/// cycle, so there's no `output_min`/`output_max` pair to clamp against;
+/// `integral_min`/`integral_max` directly bound the accumulated error for
+/// anti-windup, and the final offset is range-limited by
+/// `ThermostatEngine::normalize_fireplace_temp` once added to the base
+/// setpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SetpointPidParameters {
+    pub kp: f32,
+    pub ki: f32,
+    pub kd: f32,
+    pub integral_min: f32,
+    pub integral_max: f32,
+}
+
+impl Default for SetpointPidParameters {
+    fn default() -> Self {
+        Self {
+            kp: 1.0,
+            ki: 0.02,
+            kd: 4.0,
+            integral_min: -20.0,
+            integral_max: 20.0,
+        }
+    }
+}
+
+/// Tuning for `ControlStrategy::TimeProportional`. Structurally identical to
+/// [`PidParameters`] (a 0..1 duty cycle with anti-windup clamping), but
+/// `window_ms` is its own PWM period instead of reusing `min_cycle_ms`, so it
+/// can be tuned independently of the minimum on/off debounce.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimeProportionalParameters {
+    pub window_ms: u64,
+    pub kp: f32,
+    pub ki: f32,
+    pub kd: f32,
+    pub integral_min: f32,
+    pub integral_max: f32,
+}
+
+impl Default for TimeProportionalParameters {
+    fn default() -> Self {
+        Self {
+            window_ms: 600_000,
+            kp: 1.5,
+            ki: 0.1,
+            kd: 150.0,
+            integral_min: -10.0,
+            integral_max: 10.0,
+        }
+    }
+}
+
+/// Tuning for `ThermostatEngine::start_autotune`'s FOPDT step-response fit.
+/// `noise_threshold_f` is how far the reading must rise above its starting
+/// point before a change counts as real (vs. sensor noise); it doubles as
+/// the minimum total rise required for the fit to be considered valid.
+/// `settle_window_ms` is how long the peak reading must hold steady before
+/// the run is considered complete; `max_duration_ms` aborts the run (and
+/// falls back to `ControlStrategy::Hysteresis`) if it never settles.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AutotuneParameters {
+    pub noise_threshold_f: f32,
+    pub settle_window_ms: u64,
+    pub max_duration_ms: u64,
+}
+
+impl Default for AutotuneParameters {
+    fn default() -> Self {
+        Self {
+            noise_threshold_f: 0.3,
+            settle_window_ms: 600_000,
+            max_duration_ms: 5_400_000,
         }
     }
 }
@@ -49,6 +331,39 @@ pub struct PersistedSettings {
     pub hysteresis_f: f32,
     pub mode: ThermostatMode,
     pub fireplace_offset_f: i32,
+    #[serde(default)]
+    pub control_strategy: ControlStrategy,
+    /// Dehumidify target, in percent relative humidity. `None` (the default)
+    /// disables humidity-aware control entirely, leaving the engine driven
+    /// by `target_temp_f` alone.
+    #[serde(default)]
+    pub humidity_target: Option<f32>,
+    /// Deadband around `humidity_target` a dehumidify cycle must clear
+    /// before it's allowed to start again, mirroring `hysteresis_f`'s role
+    /// for temperature.
+    #[serde(default = "default_humidity_hysteresis_f")]
+    pub humidity_hysteresis_f: f32,
+    /// Cooling setpoint used only in `ThermostatMode::Auto`, so heat and
+    /// cool never chase the same `target_temp_f`: Auto heats toward
+    /// `target_temp_f` and cools toward this, independently. Unused in
+    /// `Heat`/`Cool`, which both run off `target_temp_f` alone like they
+    /// always have.
+    #[serde(default = "default_auto_cool_setpoint_f")]
+    pub auto_cool_setpoint_f: f32,
+    /// Unit outward-facing readings/logs are additionally rendered in.
+    /// Every `*_f` field in this struct (and the engine's internal state)
+    /// stays Fahrenheit no matter what this is set to; see
+    /// `crate::types::Temperature`.
+    #[serde(default)]
+    pub display_unit: TemperatureUnit,
+}
+
+fn default_humidity_hysteresis_f() -> f32 {
+    5.0
+}
+
+fn default_auto_cool_setpoint_f() -> f32 {
+    78.0
 }
 
 impl Default for PersistedSettings {
@@ -58,10 +373,27 @@ impl Default for PersistedSettings {
             hysteresis_f: 2.0,
             mode: ThermostatMode::Off,
             fireplace_offset_f: 4,
+            control_strategy: ControlStrategy::default(),
+            humidity_target: None,
+            humidity_hysteresis_f: default_humidity_hysteresis_f(),
+            auto_cool_setpoint_f: default_auto_cool_setpoint_f(),
+            display_unit: TemperatureUnit::default(),
         }
     }
 }
 
+/// A named snapshot of [`PersistedSettings`], so users can switch between
+/// presets like "Away" or "Evening" instead of editing individual fields.
+/// `id` is stable for the lifetime of the profile (assigned by
+/// `ThermostatEngine::save_profile`) so clients can reference it across
+/// `list_profiles` calls even after the display name changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsProfile {
+    pub id: u32,
+    pub name: String,
+    pub settings: PersistedSettings,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkConfig {
     pub wifi_ssid: String,
@@ -76,6 +408,44 @@ pub struct NetworkConfig {
     pub gateway: Option<[u8; 4]>,
     pub subnet: Option<[u8; 4]>,
     pub dns: Option<[u8; 4]>,
+    /// Backup resolver applied alongside `dns`, so name resolution (MQTT by
+    /// hostname, OTA manifest/image URLs) survives the primary DNS going down.
+    /// Applies in both static-IP and DHCP mode.
+    #[serde(default)]
+    pub secondary_dns: Option<[u8; 4]>,
+    #[serde(default = "default_ha_discovery_enabled")]
+    pub ha_discovery_enabled: bool,
+    /// Connects to the broker over TLS (`mqtts://`) instead of plaintext.
+    /// When no custom CA cert has been uploaded, the device's built-in
+    /// certificate bundle is used to validate the broker.
+    #[serde(default)]
+    pub mqtt_tls: bool,
+    /// Explicit WiFi auth method to join with. `None` keeps the historical
+    /// auto-detect behavior (open if `wifi_pass` is empty, WPA/WPA2-Personal
+    /// otherwise), which can't reach WPA3-only or WPA2-Enterprise networks.
+    #[serde(default)]
+    pub wifi_auth: Option<WifiAuthMode>,
+    /// 802.1X identity, required when `wifi_auth` is `Wpa2Enterprise`.
+    #[serde(default)]
+    pub wifi_identity: Option<String>,
+    /// 802.1X username, required when `wifi_auth` is `Wpa2Enterprise`.
+    /// `wifi_pass` doubles as the EAP password in that mode.
+    #[serde(default)]
+    pub wifi_username: Option<String>,
+    /// IPv6 addressing mode for the station interface. Defaults to `Slaac`,
+    /// which is all a dual-stack network needs; `Static` requires
+    /// `ipv6_address`, `ipv6_prefix_len`, and `ipv6_gateway`.
+    #[serde(default)]
+    pub ipv6_mode: Ipv6Mode,
+    pub ipv6_address: Option<[u8; 16]>,
+    pub ipv6_prefix_len: Option<u8>,
+    pub ipv6_gateway: Option<[u8; 16]>,
+    #[serde(default)]
+    pub ipv6_dns: Option<[u8; 16]>,
+}
+
+fn default_ha_discovery_enabled() -> bool {
+    true
 }
 
 impl Default for NetworkConfig {
@@ -93,15 +463,94 @@ impl Default for NetworkConfig {
             gateway: None,
             subnet: None,
             dns: None,
+            secondary_dns: None,
+            ha_discovery_enabled: default_ha_discovery_enabled(),
+            mqtt_tls: false,
+            wifi_auth: None,
+            wifi_identity: None,
+            wifi_username: None,
+            ipv6_mode: Ipv6Mode::default(),
+            ipv6_address: None,
+            ipv6_prefix_len: None,
+            ipv6_gateway: None,
+            ipv6_dns: None,
         }
     }
 }
 
+/// WiFi auth method selectable for the station connection, mirroring the
+/// `esp_idf_svc` `AuthMethod` variants this firmware is able to join.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WifiAuthMode {
+    #[serde(rename = "open")]
+    Open,
+    #[serde(rename = "wpa2")]
+    Wpa2,
+    #[serde(rename = "wpa2wpa3")]
+    Wpa2Wpa3,
+    #[serde(rename = "wpa3")]
+    Wpa3,
+    #[serde(rename = "wpa2-enterprise")]
+    Wpa2Enterprise,
+}
+
+/// How the station interface acquires its IPv6 address. `Slaac` (the
+/// default) takes the address and default route from router advertisements,
+/// same as nearly every consumer IPv6 deployment; `Static` assigns
+/// `ipv6_address`/`ipv6_gateway` fixed, mirroring `use_static_ip` for IPv4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Ipv6Mode {
+    Slaac,
+    Static,
+}
+
+impl Default for Ipv6Mode {
+    fn default() -> Self {
+        Ipv6Mode::Slaac
+    }
+}
+
+/// Which IR encoding scheme drives the heater. `Pronto` replays a fixed raw
+/// pulse/space table per action (the original, hard-wired behavior); `Nec`,
+/// `Rc5`, `Rc6`, and `Sirc` synthesize frames procedurally for remotes that
+/// follow those well-known protocols (the actual bit timing lives in
+/// `ir_protocol::encode`, shared so it can be unit-tested without the
+/// `esp32` feature); `Learned` replays commands captured via the
+/// learn-and-replay capture flow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum IrProtocol {
+    Nec,
+    Rc5,
+    Rc6,
+    Sirc,
+    Pronto,
+    Learned,
+}
+
+impl Default for IrProtocol {
+    fn default() -> Self {
+        IrProtocol::Pronto
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct IrHardwareConfig {
     pub tx_pin: i32,
     pub rmt_channel: u8,
     pub carrier_khz: u32,
+    #[serde(default)]
+    pub protocol: IrProtocol,
+    /// GPIO used to capture learned commands. `None` disables learn mode.
+    #[serde(default)]
+    pub rx_pin: Option<i32>,
+    /// Pulses shorter than this are treated as receiver noise and dropped
+    /// before adjacent same-level runs are merged. 36kHz demodulated
+    /// receivers commonly chatter for a few microseconds at mark/space
+    /// edges, especially near the start/end of a frame.
+    #[serde(default = "default_learn_glitch_floor_us")]
+    pub learn_glitch_floor_us: u16,
 }
 
 impl Default for IrHardwareConfig {
@@ -110,10 +559,80 @@ impl Default for IrHardwareConfig {
             tx_pin: 4,
             rmt_channel: 0,
             carrier_khz: 36,
+            protocol: IrProtocol::default(),
+            rx_pin: None,
+            learn_glitch_floor_us: default_learn_glitch_floor_us(),
+        }
+    }
+}
+
+fn default_learn_glitch_floor_us() -> u16 {
+    120
+}
+
+/// Which hardware drives the status LED. `Mono` is a single GPIO blinked in
+/// patterns; `Rgb` is a WS2812/NeoPixel addressable LED driven over its own
+/// RMT channel, which can render distinct colors per condition instead of
+/// just blink rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusLedBackend {
+    Mono,
+    Rgb,
+}
+
+impl Default for StatusLedBackend {
+    fn default() -> Self {
+        StatusLedBackend::Mono
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StatusLedConfig {
+    pub backend: StatusLedBackend,
+    pub pin: i32,
+    /// Only consulted when `backend` is `Rgb`; a mono LED is driven directly
+    /// off a GPIO and doesn't need an RMT channel of its own.
+    #[serde(default)]
+    pub rmt_channel: u8,
+}
+
+impl Default for StatusLedConfig {
+    fn default() -> Self {
+        Self {
+            backend: StatusLedBackend::default(),
+            pin: 48,
+            rmt_channel: 1,
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    pub state_interval_secs: u64,
+    pub schedule_interval_secs: u64,
+    pub state_enabled: bool,
+    pub schedule_enabled: bool,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            state_interval_secs: 10,
+            schedule_interval_secs: 10,
+            state_enabled: true,
+            schedule_enabled: true,
+        }
+    }
+}
+
+impl TelemetryConfig {
+    pub fn sanitize(&mut self) {
+        self.state_interval_secs = self.state_interval_secs.clamp(1, 3_600);
+        self.schedule_interval_secs = self.schedule_interval_secs.clamp(1, 3_600);
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RuntimeConfig {
     pub thermostat: ThermostatConfig,
@@ -122,6 +641,14 @@ pub struct RuntimeConfig {
     pub network: NetworkConfig,
     #[serde(default)]
     pub ir: IrHardwareConfig,
+    #[serde(default)]
+    pub status_led: StatusLedConfig,
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    #[serde(default)]
+    pub influx: InfluxConfig,
+    #[serde(default)]
+    pub uploader: UploaderConfig,
 }
 
 impl Default for RuntimeConfig {
@@ -132,10 +659,80 @@ impl Default for RuntimeConfig {
             timezone: "America/Los_Angeles".to_string(),
             network: NetworkConfig::default(),
             ir: IrHardwareConfig::default(),
+            status_led: StatusLedConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            influx: InfluxConfig::default(),
+            uploader: UploaderConfig::default(),
         }
     }
 }
 
+/// Settings for the optional InfluxDB line-protocol export that runs
+/// alongside the MQTT state/schedule telemetry pipeline. Lives next to the
+/// WiFi/MQTT credentials in `RuntimeConfig` since it's the same kind of
+/// "where do my readings go" secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InfluxConfig {
+    pub enabled: bool,
+    /// Base URL of the InfluxDB server, e.g. `http://localhost:8086`. Points
+    /// are written to `{url}/api/v2/write?org={org}&bucket={bucket}`.
+    pub url: String,
+    pub org: String,
+    pub bucket: String,
+    pub token: String,
+    pub push_interval_secs: u64,
+}
+
+impl Default for InfluxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            org: String::new(),
+            bucket: String::new(),
+            token: String::new(),
+            push_interval_secs: 10,
+        }
+    }
+}
+
+impl InfluxConfig {
+    pub fn sanitize(&mut self) {
+        self.push_interval_secs = self.push_interval_secs.clamp(1, 3_600);
+    }
+}
+
+/// Settings for periodically POSTing a signed `ControllerStatus` snapshot to
+/// a remote logging/dashboarding endpoint, independent of the MQTT and
+/// InfluxDB telemetry pipelines. Lives next to those in `RuntimeConfig` for
+/// the same "where do my readings go" reason `InfluxConfig` does.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploaderConfig {
+    pub enabled: bool,
+    pub server_url: String,
+    /// Shared secret the device signs each upload's body with
+    /// (HMAC-SHA256), so the server can reject forged or replayed readings.
+    pub hmac_key: String,
+    pub push_interval_secs: u64,
+}
+
+impl Default for UploaderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            server_url: String::new(),
+            hmac_key: String::new(),
+            push_interval_secs: 30,
+        }
+    }
+}
+
+impl UploaderConfig {
+    pub fn sanitize(&mut self) {
+        self.push_interval_secs = self.push_interval_secs.clamp(5, 3_600);
+    }
+}
+
 impl PersistedSettings {
     pub fn sanitize(&mut self) {
         self.target_temp_f = self.target_temp_f.clamp(60.0, 84.0);
@@ -150,6 +747,16 @@ impl PersistedSettings {
         if self.fireplace_offset_f < 2 {
             self.fireplace_offset_f = 2;
         }
+
+        if let Some(target) = self.humidity_target {
+            self.humidity_target = Some(target.clamp(20.0, 80.0));
+        }
+        self.humidity_hysteresis_f = self.humidity_hysteresis_f.clamp(1.0, 20.0);
+
+        self.auto_cool_setpoint_f = self.auto_cool_setpoint_f.clamp(60.0, 90.0);
+        if self.auto_cool_setpoint_f < self.target_temp_f + 2.0 {
+            self.auto_cool_setpoint_f = self.target_temp_f + 2.0;
+        }
     }
 }
 
@@ -164,5 +771,25 @@ impl IrHardwareConfig {
         }
 
         self.carrier_khz = self.carrier_khz.clamp(10, 100);
+
+        if let Some(rx_pin) = self.rx_pin {
+            if rx_pin < 0 {
+                self.rx_pin = None;
+            }
+        }
+
+        self.learn_glitch_floor_us = self.learn_glitch_floor_us.clamp(10, 2_000);
+    }
+}
+
+impl StatusLedConfig {
+    pub fn sanitize(&mut self) {
+        if self.pin < 0 {
+            self.pin = StatusLedConfig::default().pin;
+        }
+
+        if self.backend == StatusLedBackend::Rgb && self.rmt_channel > 7 {
+            self.rmt_channel = StatusLedConfig::default().rmt_channel;
+        }
     }
 }
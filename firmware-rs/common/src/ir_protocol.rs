@@ -0,0 +1,249 @@
+//! Protocol-level IR timing generation: given a wire protocol and a raw
+//! address/command pair, produces the `&[u16]` mark/space timing table (in
+//! microseconds) that `IrTransmitter::send_raw` (controller/src/ir.rs)
+//! already consumes. This lets the transmitter synthesize frames for any
+//! remote using one of these well-known protocols instead of storing a
+//! captured raw table per command.
+//!
+//! Deliberately separate from [`crate::config::IrProtocol`], which selects
+//! which *codec* the transmitter uses overall, including the non-protocol
+//! `Pronto` (fixed raw table) and `Learned` (capture-and-replay) options;
+//! this `IrProtocol` enumerates only the protocols this module actually
+//! knows how to synthesize, so it stays meaningful if `config::IrProtocol`
+//! grows more non-protocol variants later.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrProtocol {
+    Nec,
+    Rc5,
+    Rc6,
+    Sirc,
+}
+
+/// NEC unit: 562.5us, rounded down to whole microseconds since RMT ticks
+/// are already an integer approximation of the real waveform.
+const NEC_UNIT_US: u16 = 562;
+
+/// RC5 half-bit period.
+const RC5_HALF_BIT_US: u16 = 889;
+
+/// RC6 base unit ("T"); the leader and toggle bit are multiples of it.
+const RC6_UNIT_US: u16 = 444;
+
+/// Sony SIRC unit; marks are always 1 unit, spaces are 1 unit (`0`) or 2
+/// units (`1`).
+const SIRC_UNIT_US: u16 = 600;
+
+/// Encodes `address`/`command` under `protocol` into the same mark-first
+/// alternating timing list `send_raw` expects. Each protocol masks its
+/// inputs down to the field widths it actually defines (e.g. RC5's address
+/// is 5 bits), so callers can pass a plain `u16` without pre-masking.
+pub fn encode(protocol: IrProtocol, address: u16, command: u16) -> Vec<u16> {
+    match protocol {
+        IrProtocol::Nec => encode_nec(address as u8, command as u8),
+        IrProtocol::Rc5 => encode_rc5(address as u8 & 0x1F, command as u8 & 0x3F),
+        IrProtocol::Rc6 => encode_rc6(address as u8, command as u8),
+        IrProtocol::Sirc => encode_sirc(address as u8 & 0x1F, command as u8 & 0x7F),
+    }
+}
+
+/// 9000/4500us header, then address, inverted address, command, inverted
+/// command (8 bits each, LSB-first); a bit is a 562.5us mark followed by a
+/// 562.5us (`0`) or 1687.5us (`1`) space, with a trailing mark to close the
+/// final space out.
+fn encode_nec(address: u8, command: u8) -> Vec<u16> {
+    let mut raw = Vec::with_capacity(4 + 32 * 2 + 1);
+    raw.push(NEC_UNIT_US * 16);
+    raw.push(NEC_UNIT_US * 8);
+
+    for byte in [address, !address, command, !command] {
+        for bit in 0..8 {
+            let is_one = (byte >> bit) & 1 == 1;
+            raw.push(NEC_UNIT_US);
+            raw.push(if is_one { NEC_UNIT_US * 3 } else { NEC_UNIT_US });
+        }
+    }
+    raw.push(NEC_UNIT_US);
+    raw
+}
+
+/// 14-bit biphase/Manchester frame: two start bits (fixed `1`, `1`), a
+/// toggle bit (fixed `0`, since this encoder has no session state to flip
+/// it between repeats), 5 address bits, 6 command bits, each half-bit
+/// `RC5_HALF_BIT_US` long.
+fn encode_rc5(address: u8, command: u8) -> Vec<u16> {
+    let mut bits = vec![true, true, false];
+    for i in (0..5).rev() {
+        bits.push((address >> i) & 1 == 1);
+    }
+    for i in (0..6).rev() {
+        bits.push((command >> i) & 1 == 1);
+    }
+    manchester_frame(&bits, RC5_HALF_BIT_US)
+}
+
+/// Mode-0 RC6 frame: a 6T mark / 2T space leader, a start bit, 3 mode bits
+/// (fixed 0 for the basic mode this thermostat needs), a double-width
+/// toggle bit (fixed `0`), 8 address bits, 8 command bits, each regular
+/// half-bit `RC6_UNIT_US` long and the toggle half-bit twice that.
+fn encode_rc6(address: u8, command: u8) -> Vec<u16> {
+    // Start bit (fixed 1) + 3 mode bits (fixed 0) at the regular half-bit
+    // width, then the double-width toggle bit (fixed 0), then address and
+    // command at the regular width.
+    let mut bits = vec![true, false, false, false, false];
+    let mut widths = vec![RC6_UNIT_US; 4];
+    widths.push(RC6_UNIT_US * 2);
+
+    for i in (0..8).rev() {
+        bits.push((address >> i) & 1 == 1);
+        widths.push(RC6_UNIT_US);
+    }
+    for i in (0..8).rev() {
+        bits.push((command >> i) & 1 == 1);
+        widths.push(RC6_UNIT_US);
+    }
+
+    let mut raw = vec![RC6_UNIT_US * 6, RC6_UNIT_US * 2];
+    raw.extend(manchester_bits(&bits, &widths));
+    raw
+}
+
+/// 2.4ms/0.6ms header, then 7 command bits + 5 address bits, LSB-first; a
+/// bit is a 600us mark followed by a 600us (`0`) or 1200us (`1`) space.
+fn encode_sirc(address: u8, command: u8) -> Vec<u16> {
+    let mut raw = Vec::with_capacity(2 + 24);
+    raw.push(SIRC_UNIT_US * 4);
+    raw.push(SIRC_UNIT_US);
+
+    for i in 0..7 {
+        let is_one = (command >> i) & 1 == 1;
+        raw.push(SIRC_UNIT_US);
+        raw.push(if is_one { SIRC_UNIT_US * 2 } else { SIRC_UNIT_US });
+    }
+    for i in 0..5 {
+        let is_one = (address >> i) & 1 == 1;
+        raw.push(SIRC_UNIT_US);
+        raw.push(if is_one { SIRC_UNIT_US * 2 } else { SIRC_UNIT_US });
+    }
+    raw
+}
+
+/// Manchester/biphase-encodes `bits` at a uniform `half_bit_us` width: a
+/// logical `1` is space-then-mark, a logical `0` is mark-then-space, and
+/// adjacent equal half-bits merge into one timing slot. `send_raw` always
+/// treats `timings[0]` as a mark duration, so a leading zero-length mark is
+/// prepended when the sequence would otherwise start on a space.
+fn manchester_frame(bits: &[bool], half_bit_us: u16) -> Vec<u16> {
+    let widths = vec![half_bit_us; bits.len()];
+    manchester_bits(bits, &widths)
+}
+
+/// Like [`manchester_frame`], but with a per-bit half-bit width so callers
+/// that need a double-width bit (e.g. RC6's toggle bit) aren't forced into
+/// a single uniform period.
+fn manchester_bits(bits: &[bool], half_bit_us: &[u16]) -> Vec<u16> {
+    assert_eq!(bits.len(), half_bit_us.len());
+
+    let mut half_bit_levels = Vec::with_capacity(bits.len() * 2);
+    for (&bit, &width) in bits.iter().zip(half_bit_us) {
+        if bit {
+            half_bit_levels.push((false, width));
+            half_bit_levels.push((true, width));
+        } else {
+            half_bit_levels.push((true, width));
+            half_bit_levels.push((false, width));
+        }
+    }
+
+    let mut runs = Vec::new();
+    let (mut run_is_mark, _) = half_bit_levels[0];
+    let mut run_len: u32 = half_bit_levels[0].1 as u32;
+    for &(is_mark, width) in &half_bit_levels[1..] {
+        if is_mark == run_is_mark {
+            run_len += width as u32;
+        } else {
+            runs.push(run_len.min(u16::MAX as u32) as u16);
+            run_is_mark = is_mark;
+            run_len = width as u32;
+        }
+    }
+    runs.push(run_len.min(u16::MAX as u32) as u16);
+
+    if !half_bit_levels[0].0 {
+        runs.insert(0, 0);
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nec_frame_has_header_bytes_and_trailer() {
+        let raw = encode_nec(0x00, 0x01);
+        assert_eq!(raw[0], NEC_UNIT_US * 16);
+        assert_eq!(raw[1], NEC_UNIT_US * 8);
+        // 2 header timings + 64 bit timings (4 bytes * 8 bits * 2) + 1 trailing mark.
+        assert_eq!(raw.len(), 2 + 64 + 1);
+        assert_eq!(*raw.last().unwrap(), NEC_UNIT_US);
+    }
+
+    #[test]
+    fn nec_encodes_inverted_address_and_command_bytes() {
+        let raw = encode_nec(0b0000_0001, 0b0000_0010);
+        // First byte (address, LSB-first): bit0 = 1 -> space = 3 units.
+        assert_eq!(raw[3], NEC_UNIT_US * 3);
+        // Second byte (inverted address = 0xFE): bit0 = 0 -> space = 1 unit.
+        assert_eq!(raw[19], NEC_UNIT_US);
+        // Third byte (command, LSB-first): bit1 = 1 -> space = 3 units.
+        assert_eq!(raw[37], NEC_UNIT_US * 3);
+        // Fourth byte (inverted command = 0xFD): bit1 = 0 -> space = 1 unit.
+        assert_eq!(raw[53], NEC_UNIT_US);
+    }
+
+    #[test]
+    fn rc5_frame_is_14_bits_of_half_bit_runs() {
+        let raw = encode_rc5(0x00, 0x00);
+        // Two start bits (1,1) are two full half-bit runs each merged to a
+        // single mark/space pair; don't assert an exact length since equal
+        // adjacent half-bits merge, just that every run is a multiple of
+        // the half-bit width (plus the possible leading zero-length mark).
+        for &run in &raw {
+            assert!(run == 0 || run as u32 % RC5_HALF_BIT_US as u32 == 0);
+        }
+        // 14 bits of two half-bits each.
+        let total: u32 = raw.iter().map(|&run| run as u32).sum();
+        assert_eq!(total, 28 * RC5_HALF_BIT_US as u32);
+    }
+
+    #[test]
+    fn rc5_address_and_command_masked_to_field_width() {
+        let full = encode(IrProtocol::Rc5, 0xFF, 0xFF);
+        let masked = encode(IrProtocol::Rc5, 0x1F, 0x3F);
+        assert_eq!(full, masked);
+    }
+
+    #[test]
+    fn rc6_leader_is_six_to_two_ratio() {
+        let raw = encode_rc6(0x00, 0x00);
+        assert_eq!(raw[0], RC6_UNIT_US * 6);
+        assert_eq!(raw[1], RC6_UNIT_US * 2);
+    }
+
+    #[test]
+    fn sirc_frame_has_header_and_twelve_bits() {
+        let raw = encode_sirc(0x00, 0x00);
+        assert_eq!(raw[0], SIRC_UNIT_US * 4);
+        assert_eq!(raw[1], SIRC_UNIT_US);
+        // 2 header timings + 12 bits * 2 timings each.
+        assert_eq!(raw.len(), 2 + 24);
+    }
+
+    #[test]
+    fn sirc_one_bit_doubles_space_width() {
+        let raw = encode_sirc(0x00, 0b0000_0001);
+        assert_eq!(raw[2], SIRC_UNIT_US);
+        assert_eq!(raw[3], SIRC_UNIT_US * 2);
+    }
+}
@@ -1,11 +1,27 @@
 pub mod config;
+pub mod ir_protocol;
 pub mod schedule;
+pub mod solar;
 pub mod thermostat;
 pub mod topics;
 pub mod types;
 
-pub use config::{IrHardwareConfig, PersistedSettings, RuntimeConfig, ThermostatConfig};
-pub use schedule::{DayOfWeek, Schedule, ScheduleAction, ScheduleEntry};
-pub use thermostat::{EngineAction, HoldReason, ThermostatEngine};
+pub use config::{
+    AutotuneParameters, InfluxConfig, IrHardwareConfig, IrProtocol, PersistedSettings,
+    PidParameters, RuntimeConfig, SetpointPidParameters, SettingsProfile, StatusLedBackend,
+    StatusLedConfig, TelemetryConfig, ThermalLoadParameters, ThermistorConfig, ThermostatConfig,
+    TimeProportionalParameters, UploaderConfig,
+};
+pub use schedule::{
+    BoostWindow, DateException, DayOfWeek, Schedule, ScheduleAction, ScheduleAnchor, ScheduleEntry,
+    ScheduleOverride,
+};
+pub use thermostat::{
+    CooldownMode, EngineAction, EngineMode, HeatingMode, HoldReason, IdleMode, PreHeatMode,
+    ThermostatEngine, WarmupMode,
+};
 pub use topics::*;
-pub use types::{ControllerStatePayload, ControllerStatus, ThermostatMode, ThermostatState};
+pub use types::{
+    ControlStrategy, ControllerStatePayload, ControllerStatus, SettingsSummary, Temperature,
+    TemperatureUnit, ThermalLoadBand, ThermostatMode, ThermostatState, TunableRange,
+};
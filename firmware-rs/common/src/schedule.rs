@@ -1,6 +1,7 @@
-use chrono::{DateTime, Datelike, Duration, FixedOffset, TimeZone, Timelike, Weekday};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, TimeZone, Timelike, Weekday};
 use serde::{Deserialize, Serialize};
 
+use crate::solar;
 use crate::types::ThermostatMode;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -53,28 +54,124 @@ impl DayOfWeek {
     }
 }
 
+/// What wall-clock moment a `ScheduleEntry` fires at. `Fixed` uses
+/// `start_minutes` directly, the same as before this existed. `Sunrise`/
+/// `Sunset` instead resolve to that solar event (via `solar`) on the entry's
+/// calendar date, offset by `anchor_offset_minutes`, falling back to
+/// `start_minutes` on days the sun doesn't rise or set (polar day/night).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleAnchor {
+    Fixed,
+    Sunrise,
+    Sunset,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ScheduleEntry {
     pub day: DayOfWeek,
+    /// Fallback/clock-frame minute-of-day; for `Sunrise`/`Sunset` anchors
+    /// this also decides which logical day the entry nominally belongs to
+    /// and is used if the solar lookup has no answer that day.
     #[serde(rename = "startMinutes")]
     pub start_minutes: u16,
+    #[serde(default)]
+    pub anchor: ScheduleAnchor,
+    /// Minutes added to the resolved solar event before comparing against
+    /// the wall clock; negative runs the entry before sunrise/sunset.
+    /// Ignored when `anchor` is `Fixed`.
+    #[serde(default, rename = "anchorOffsetMinutes")]
+    pub anchor_offset_minutes: i16,
     pub mode: ThermostatMode,
     #[serde(rename = "targetTemp")]
     pub target_temp_f: f32,
 }
 
+impl Default for ScheduleAnchor {
+    fn default() -> Self {
+        ScheduleAnchor::Fixed
+    }
+}
+
 impl ScheduleEntry {
     pub fn validate(&self) -> bool {
         self.start_minutes < 24 * 60
+            && self.anchor_offset_minutes.unsigned_abs() < 24 * 60
             && self.target_temp_f.is_finite()
             && (60.0..=84.0).contains(&self.target_temp_f)
     }
 }
 
+/// A recurring daily window that guarantees a minimum temperature regardless
+/// of what the weekly program says, e.g. "66°F between 05:30 and 07:00" for
+/// morning comfort. `end_minutes <= start_minutes` means the window wraps
+/// past midnight into the following day; `end_minutes == 24 * 60` ends it
+/// exactly at midnight.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BoostWindow {
+    pub day: DayOfWeek,
+    #[serde(rename = "startMinutes")]
+    pub start_minutes: u16,
+    #[serde(rename = "endMinutes")]
+    pub end_minutes: u16,
+    #[serde(rename = "minTemp")]
+    pub min_temp_f: f32,
+}
+
+impl BoostWindow {
+    pub fn validate(&self) -> bool {
+        self.start_minutes < 24 * 60
+            && self.end_minutes <= 24 * 60
+            && self.min_temp_f.is_finite()
+            && (60.0..=84.0).contains(&self.min_temp_f)
+    }
+}
+
+/// A temporary pinned mode/target that takes precedence over the weekly
+/// `Schedule` until `until_epoch` (Unix seconds), after which the engine
+/// seamlessly reverts to whatever `current_action` would otherwise select.
+/// Unlike `HoldReason`, it carries its own target/mode rather than freezing
+/// whatever is currently active.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleOverride {
+    pub mode: ThermostatMode,
+    #[serde(rename = "targetTemp")]
+    pub target_temp_f: f32,
+    #[serde(rename = "untilEpoch")]
+    pub until_epoch: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Schedule {
     pub enabled: bool,
     pub entries: Vec<ScheduleEntry>,
+    /// Minutes after local midnight where the logical program day begins
+    /// (default 0, i.e. midnight). `ScheduleEntry::start_minutes` is
+    /// expressed in this shifted frame, so e.g. an entry at `day: Sun,
+    /// start_minutes: 0` with `day_start_minutes: 1320` (22:00) starts at
+    /// Sunday 22:00 and owns the small hours through Monday's boundary,
+    /// without needing a duplicate per-day entry.
+    #[serde(default, rename = "dayStartMinutes")]
+    pub day_start_minutes: u16,
+    #[serde(default, rename = "override")]
+    pub override_action: Option<ScheduleOverride>,
+    /// Floor windows layered independently on top of the weekly program; see
+    /// `BoostWindow`.
+    #[serde(default, rename = "boostWindows")]
+    pub boost_windows: Vec<BoostWindow>,
+    /// Calendar-date programs that take precedence over the weekly entries
+    /// for holidays and trips; see `DateException`.
+    #[serde(default, rename = "dateExceptions")]
+    pub date_exceptions: Vec<DateException>,
+    /// Site latitude/longitude in degrees (positive north/east), used to
+    /// resolve `ScheduleEntry::anchor` sunrise/sunset entries via `solar`.
+    /// Defaults to `0.0, 0.0` until the user configures the device's actual
+    /// location, which will resolve anchored entries for the wrong place
+    /// until corrected.
+    #[serde(default)]
+    pub latitude: f64,
+    #[serde(default)]
+    pub longitude: f64,
 }
 
 impl Default for Schedule {
@@ -82,47 +179,219 @@ impl Default for Schedule {
         Self {
             enabled: false,
             entries: Vec::new(),
+            day_start_minutes: 0,
+            latitude: 0.0,
+            longitude: 0.0,
+            override_action: None,
+            boost_windows: Vec::new(),
+            date_exceptions: Vec::new(),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct ScheduleAction {
     pub mode: ThermostatMode,
+    #[serde(rename = "targetTemp")]
     pub target_temp_f: f32,
 }
 
+/// A one-off calendar-date program that takes precedence over the weekly
+/// `Schedule` for a single day, keyed by calendar date in the configured
+/// `timezone`. `action: None` suppresses all heating that day (vacation/
+/// away); `Some` replaces the day's normal program with a fixed mode/target
+/// for the whole day. Multi-day away periods are expressed as one entry per
+/// date.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DateException {
+    #[serde(with = "iso_date")]
+    pub date: NaiveDate,
+    pub action: Option<ScheduleAction>,
+}
+
+/// `NaiveDate` as a plain `"YYYY-MM-DD"` JSON string, since the crate doesn't
+/// otherwise depend on chrono's `serde` feature.
+mod iso_date {
+    use chrono::NaiveDate;
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(date: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&date.format("%Y-%m-%d").to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDate, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        NaiveDate::parse_from_str(&raw, "%Y-%m-%d").map_err(D::Error::custom)
+    }
+}
+
 impl Schedule {
     pub fn normalize(&mut self) {
+        self.day_start_minutes = self.day_start_minutes.min(24 * 60 - 1);
         self.entries.retain(ScheduleEntry::validate);
         self.entries
             .sort_by_key(|entry| (entry.day.index(), entry.start_minutes));
+        self.boost_windows.retain(BoostWindow::validate);
+        self.boost_windows
+            .sort_by_key(|window| (window.day.index(), window.start_minutes));
+        self.date_exceptions.sort_by_key(|exception| exception.date);
+        self.date_exceptions.dedup_by_key(|exception| exception.date);
+    }
+
+    /// Converts a wall-clock weekday/minute-of-day into the logical program
+    /// day/minute used to match against `ScheduleEntry::start_minutes`,
+    /// after subtracting `day_start_minutes` and wrapping the weekday (and
+    /// calendar date, needed to resolve solar-anchored entries) back by one
+    /// when that offset pushes the time before the boundary.
+    fn shifted_day_and_minutes(&self, now: DateTime<FixedOffset>) -> (DayOfWeek, i64, NaiveDate) {
+        let day = DayOfWeek::from_chrono(now.weekday());
+        let minutes = now.hour() as i64 * 60 + now.minute() as i64;
+        let shifted = minutes - self.day_start_minutes as i64;
+
+        if shifted < 0 {
+            (
+                DayOfWeek::from_index((day.index() + 6) % 7),
+                shifted + 24 * 60,
+                now.date_naive() - Duration::days(1),
+            )
+        } else {
+            (day, shifted, now.date_naive())
+        }
     }
 
-    pub fn current_action(&self, now: DateTime<FixedOffset>) -> Option<ScheduleAction> {
+    /// Inverse of `shifted_day_and_minutes`: maps an entry's logical
+    /// day/`start_minutes` back to the wall-clock weekday/minute-of-day it
+    /// actually falls on.
+    fn wall_day_and_minutes(&self, day: DayOfWeek, start_minutes: u16) -> (DayOfWeek, u16) {
+        let total = start_minutes as i64 + self.day_start_minutes as i64;
+
+        if total >= 24 * 60 {
+            (
+                DayOfWeek::from_index((day.index() + 1) % 7),
+                (total - 24 * 60) as u16,
+            )
+        } else {
+            (day, total as u16)
+        }
+    }
+
+    /// Resolves a `Sunrise`/`Sunset` entry's wall-clock minute-of-day on
+    /// `date`, or `None` for `Fixed` entries and for days the solar
+    /// calculation has no answer (polar day/night) - callers fall back to
+    /// `start_minutes` in both cases.
+    fn anchor_wall_minutes(
+        &self,
+        entry: &ScheduleEntry,
+        date: NaiveDate,
+        offset: FixedOffset,
+    ) -> Option<f64> {
+        let (sunrise_utc, sunset_utc) =
+            solar::sunrise_sunset_utc_minutes(date, self.latitude, self.longitude)?;
+        let utc_minutes = match entry.anchor {
+            ScheduleAnchor::Fixed => return None,
+            ScheduleAnchor::Sunrise => sunrise_utc,
+            ScheduleAnchor::Sunset => sunset_utc,
+        };
+
+        let local_minutes = utc_minutes
+            + offset.local_minus_utc() as f64 / 60.0
+            + entry.anchor_offset_minutes as f64;
+        // Clamp away from 24*60 so rounding never produces an out-of-range
+        // hour/minute for the callers that build a timestamp from this.
+        Some(local_minutes.rem_euclid(24.0 * 60.0).min(24.0 * 60.0 - 1.0))
+    }
+
+    /// Resolves the weekly program plus any active `BoostWindow` floor and
+    /// `DateException`. A date exception for today wins outright (it's a
+    /// deliberate override for the whole day, vacation-mode included);
+    /// otherwise the boost floor wins (forcing `Heat` at its `min_temp_f`,
+    /// raised further by the scheduled target if that's already higher)
+    /// whenever `current_temp_f` is below floor and a window is active;
+    /// otherwise the plain weekly program entry applies.
+    pub fn current_action(
+        &self,
+        now: DateTime<FixedOffset>,
+        current_temp_f: f32,
+    ) -> Option<ScheduleAction> {
+        let scheduled = self.scheduled_action(now);
+
+        if let Some(exception) = self.date_exception_for(now.date_naive()) {
+            return match exception.action {
+                Some(action) => Some(action),
+                None => Some(ScheduleAction {
+                    mode: ThermostatMode::Off,
+                    target_temp_f: scheduled.map(|a| a.target_temp_f).unwrap_or(60.0),
+                }),
+            };
+        }
+
+        if let Some(min_temp_f) = self.active_boost_floor(now, current_temp_f) {
+            let target_temp_f = scheduled
+                .map(|action| action.target_temp_f.max(min_temp_f))
+                .unwrap_or(min_temp_f);
+            return Some(ScheduleAction {
+                mode: ThermostatMode::Heat,
+                target_temp_f,
+            });
+        }
+
+        scheduled
+    }
+
+    fn date_exception_for(&self, date: NaiveDate) -> Option<&DateException> {
+        self.date_exceptions
+            .iter()
+            .find(|exception| exception.date == date)
+    }
+
+    /// Resolves `entry`'s effective minute in the shifted frame used by
+    /// `scheduled_action`, for an entry landing on logical `date`: `Fixed`
+    /// entries just use `start_minutes`; `Sunrise`/`Sunset` entries resolve
+    /// the solar event on `date` and re-apply the `day_start_minutes` shift
+    /// so it compares against `current_minutes` on the same footing.
+    fn entry_shifted_minutes(
+        &self,
+        entry: &ScheduleEntry,
+        date: NaiveDate,
+        offset: FixedOffset,
+    ) -> i64 {
+        let Some(wall_minutes) = self.anchor_wall_minutes(entry, date, offset) else {
+            return entry.start_minutes as i64;
+        };
+
+        let shifted = wall_minutes - self.day_start_minutes as f64;
+        let shifted = if shifted < 0.0 { shifted + 24.0 * 60.0 } else { shifted };
+        shifted.round() as i64
+    }
+
+    fn scheduled_action(&self, now: DateTime<FixedOffset>) -> Option<ScheduleAction> {
         if !self.enabled || self.entries.is_empty() {
             return None;
         }
 
-        let day = DayOfWeek::from_chrono(now.weekday());
-        let current_minutes = now.hour() as u16 * 60 + now.minute() as u16;
+        let (day, current_minutes, shifted_date) = self.shifted_day_and_minutes(now);
+        let offset = *now.offset();
 
-        // Current day, last entry <= now.
+        // Current logical day, last entry <= now.
         let mut best: Option<&ScheduleEntry> = self
             .entries
             .iter()
-            .filter(|entry| entry.day == day && entry.start_minutes <= current_minutes)
-            .max_by_key(|entry| entry.start_minutes);
+            .filter(|entry| {
+                entry.day == day
+                    && self.entry_shifted_minutes(entry, shifted_date, offset) <= current_minutes
+            })
+            .max_by_key(|entry| self.entry_shifted_minutes(entry, shifted_date, offset));
 
-        // Wrap to previous days until we find one.
+        // Wrap to previous logical days until we find one.
         if best.is_none() {
             for i in 1..=7 {
                 let candidate_day = DayOfWeek::from_index((day.index() + 7 - i) % 7);
+                let candidate_date = shifted_date - Duration::days(i as i64);
                 best = self
                     .entries
                     .iter()
                     .filter(|entry| entry.day == candidate_day)
-                    .max_by_key(|entry| entry.start_minutes);
+                    .max_by_key(|entry| self.entry_shifted_minutes(entry, candidate_date, offset));
 
                 if best.is_some() {
                     break;
@@ -136,7 +405,89 @@ impl Schedule {
         })
     }
 
+    /// Whether a `BoostWindow` is active right now and the sensor is still
+    /// below its floor, returning that floor. Windows are keyed to the wall
+    /// clock day/minute, independent of `day_start_minutes`.
+    fn active_boost_floor(&self, now: DateTime<FixedOffset>, current_temp_f: f32) -> Option<f32> {
+        let day = DayOfWeek::from_chrono(now.weekday());
+        let minutes = now.hour() as i64 * 60 + now.minute() as i64;
+        let prev_day = DayOfWeek::from_index((day.index() + 6) % 7);
+
+        self.boost_windows.iter().find_map(|window| {
+            let start = window.start_minutes as i64;
+            let end = window.end_minutes as i64;
+
+            let in_window = if end <= start {
+                (window.day == day && minutes >= start)
+                    || (window.day == prev_day && minutes < end)
+            } else {
+                window.day == day && minutes >= start && minutes < end
+            };
+
+            if in_window && current_temp_f < window.min_temp_f {
+                Some(window.min_temp_f)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Resolves the action that should actually drive the engine right now:
+    /// the pinned `override_action` while it hasn't expired, otherwise
+    /// whatever `current_action` selects (weekly program plus any active
+    /// boost floor).
+    pub fn effective_action(
+        &self,
+        now: DateTime<FixedOffset>,
+        current_temp_f: f32,
+    ) -> Option<ScheduleAction> {
+        if let Some(over) = self.override_action {
+            if over.until_epoch > now.timestamp() {
+                return Some(ScheduleAction {
+                    mode: over.mode,
+                    target_temp_f: over.target_temp_f,
+                });
+            }
+        }
+
+        self.current_action(now, current_temp_f)
+    }
+
     pub fn next_event_epoch(&self, now: DateTime<FixedOffset>) -> Option<i64> {
+        let override_expiry = self
+            .override_action
+            .filter(|over| over.until_epoch > now.timestamp())
+            .map(|over| over.until_epoch);
+
+        [
+            override_expiry,
+            self.next_scheduled_event_epoch(now),
+            self.next_boost_edge_epoch(now),
+            self.next_exception_edge_epoch(now),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+
+    fn next_scheduled_event_epoch(&self, now: DateTime<FixedOffset>) -> Option<i64> {
+        self.next_matching_entry(now, |_| true)
+            .map(|(dt, _)| dt.timestamp())
+    }
+
+    /// Finds the earliest upcoming entry whose `mode` is `Heat`, returning
+    /// its wall-clock epoch and target temperature. Used to anticipate
+    /// heat-up transitions for `should_preheat`.
+    pub fn next_heat_transition(&self, now: DateTime<FixedOffset>) -> Option<(i64, f32)> {
+        self.next_matching_entry(now, |entry| entry.mode == ThermostatMode::Heat)
+            .map(|(dt, entry)| (dt.timestamp(), entry.target_temp_f))
+    }
+
+    fn next_matching_entry(
+        &self,
+        now: DateTime<FixedOffset>,
+        predicate: impl Fn(&ScheduleEntry) -> bool,
+    ) -> Option<(DateTime<FixedOffset>, ScheduleEntry)> {
         if !self.enabled || self.entries.is_empty() {
             return None;
         }
@@ -144,19 +495,97 @@ impl Schedule {
         let now_day = DayOfWeek::from_chrono(now.weekday());
         let now_minute = now.hour() as i64 * 60 + now.minute() as i64;
 
+        let mut best: Option<(DateTime<FixedOffset>, ScheduleEntry)> = None;
+
+        for day_offset in 0..7i64 {
+            let day = DayOfWeek::from_index((now_day.index() + day_offset as usize) % 7);
+            for entry in self.entries.iter().filter(|entry| predicate(entry)) {
+                let (wall_day, fallback_wall_minute) =
+                    self.wall_day_and_minutes(entry.day, entry.start_minutes);
+                if wall_day != day {
+                    continue;
+                }
+
+                let date = now.date_naive() + Duration::days(day_offset);
+                let wall_minute = self
+                    .anchor_wall_minutes(entry, date, *now.offset())
+                    .map(|minutes| minutes.round() as u16)
+                    .unwrap_or(fallback_wall_minute);
+
+                let candidate_minutes = wall_minute as i64;
+                if day_offset == 0 && candidate_minutes <= now_minute {
+                    continue;
+                }
+
+                let hour = (wall_minute / 60) as u32;
+                let minute = (wall_minute % 60) as u32;
+
+                let Some(naive) = date.and_hms_opt(hour, minute, 0) else {
+                    continue;
+                };
+
+                let Some(candidate) = now.offset().from_local_datetime(&naive).single() else {
+                    continue;
+                };
+
+                if best
+                    .as_ref()
+                    .map(|(current, _)| candidate < *current)
+                    .unwrap_or(true)
+                {
+                    best = Some((candidate, entry.clone()));
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Earliest upcoming `BoostWindow` start/end edge, wrapped end edges
+    /// normalized onto the following wall-clock day. These count as wake
+    /// events since a window's floor can kick in or release outside of any
+    /// weekly-program transition.
+    fn next_boost_edge_epoch(&self, now: DateTime<FixedOffset>) -> Option<i64> {
+        if self.boost_windows.is_empty() {
+            return None;
+        }
+
+        let now_day = DayOfWeek::from_chrono(now.weekday());
+        let now_minute = now.hour() as i64 * 60 + now.minute() as i64;
+
+        let mut edges: Vec<(DayOfWeek, u16)> = Vec::new();
+        for window in &self.boost_windows {
+            edges.push((window.day, window.start_minutes));
+
+            let (end_day, end_minutes) = if window.end_minutes <= window.start_minutes {
+                (DayOfWeek::from_index((window.day.index() + 1) % 7), window.end_minutes)
+            } else {
+                (window.day, window.end_minutes)
+            };
+            edges.push(if end_minutes >= 24 * 60 {
+                (DayOfWeek::from_index((end_day.index() + 1) % 7), 0)
+            } else {
+                (end_day, end_minutes)
+            });
+        }
+
         let mut best: Option<DateTime<FixedOffset>> = None;
 
         for day_offset in 0..7i64 {
             let day = DayOfWeek::from_index((now_day.index() + day_offset as usize) % 7);
-            for entry in self.entries.iter().filter(|entry| entry.day == day) {
-                let candidate_minutes = entry.start_minutes as i64;
+            for &(edge_day, edge_minute) in &edges {
+                if edge_day != day {
+                    continue;
+                }
+
+                let candidate_minutes = edge_minute as i64;
                 if day_offset == 0 && candidate_minutes <= now_minute {
                     continue;
                 }
 
                 let date = now.date_naive() + Duration::days(day_offset);
-                let hour = (entry.start_minutes / 60) as u32;
-                let minute = (entry.start_minutes % 60) as u32;
+                let hour = (edge_minute / 60) as u32;
+                let minute = (edge_minute % 60) as u32;
 
                 let Some(naive) = date.and_hms_opt(hour, minute, 0) else {
                     continue;
@@ -174,6 +603,20 @@ impl Schedule {
 
         best.map(|dt| dt.timestamp())
     }
+
+    /// If today has a matching `DateException`, the next wake event is the
+    /// following local midnight, when the exception ends and control reverts
+    /// to the weekly program (or to the next day's own exception, if any).
+    fn next_exception_edge_epoch(&self, now: DateTime<FixedOffset>) -> Option<i64> {
+        let today = now.date_naive();
+        self.date_exception_for(today)?;
+
+        let next_midnight = today.succ_opt()?.and_hms_opt(0, 0, 0)?;
+        now.offset()
+            .from_local_datetime(&next_midnight)
+            .single()
+            .map(|dt| dt.timestamp())
+    }
 }
 
 #[cfg(test)]
@@ -194,15 +637,23 @@ mod tests {
             entries: vec![ScheduleEntry {
                 day: DayOfWeek::Sun,
                 start_minutes: 23 * 60,
+                anchor: ScheduleAnchor::Fixed,
+                anchor_offset_minutes: 0,
                 mode: ThermostatMode::Heat,
                 target_temp_f: 69.0,
             }],
+            day_start_minutes: 0,
+            latitude: 0.0,
+            longitude: 0.0,
+            override_action: None,
+            boost_windows: Vec::new(),
+            date_exceptions: Vec::new(),
         };
         schedule.normalize();
 
         // Monday 08:00 should still be affected by Sunday 23:00 program.
         let now = fixed_time(5, 8, 0); // Jan 5, 2026 is Monday.
-        let action = schedule.current_action(now).unwrap();
+        let action = schedule.current_action(now, 70.0).unwrap();
 
         assert_eq!(action.mode, ThermostatMode::Heat);
         assert_eq!(action.target_temp_f, 69.0);
@@ -216,16 +667,26 @@ mod tests {
                 ScheduleEntry {
                     day: DayOfWeek::Mon,
                     start_minutes: 9 * 60,
+                    anchor: ScheduleAnchor::Fixed,
+                    anchor_offset_minutes: 0,
                     mode: ThermostatMode::Heat,
                     target_temp_f: 71.0,
                 },
                 ScheduleEntry {
                     day: DayOfWeek::Mon,
                     start_minutes: 18 * 60,
+                    anchor: ScheduleAnchor::Fixed,
+                    anchor_offset_minutes: 0,
                     mode: ThermostatMode::Off,
                     target_temp_f: 68.0,
                 },
             ],
+            day_start_minutes: 0,
+            latitude: 0.0,
+            longitude: 0.0,
+            override_action: None,
+            boost_windows: Vec::new(),
+            date_exceptions: Vec::new(),
         };
         schedule.normalize();
 
@@ -235,4 +696,388 @@ mod tests {
 
         assert_eq!(next, expected);
     }
+
+    #[test]
+    fn day_start_minutes_shifts_logical_day_boundary() {
+        let mut schedule = Schedule {
+            enabled: true,
+            entries: vec![ScheduleEntry {
+                day: DayOfWeek::Sun,
+                start_minutes: 0,
+                anchor: ScheduleAnchor::Fixed,
+                anchor_offset_minutes: 0,
+                mode: ThermostatMode::Heat,
+                target_temp_f: 65.0,
+            }],
+            day_start_minutes: 22 * 60,
+            latitude: 0.0,
+            longitude: 0.0,
+            override_action: None,
+            boost_windows: Vec::new(),
+            date_exceptions: Vec::new(),
+        };
+        schedule.normalize();
+
+        // With a 22:00 day boundary, Monday 08:00 wall-clock is still the
+        // logical Sunday that began at Sunday 22:00.
+        let now = fixed_time(5, 8, 0); // Jan 5, 2026 is Monday.
+        let action = schedule.current_action(now, 70.0).unwrap();
+        assert_eq!(action.mode, ThermostatMode::Heat);
+        assert_eq!(action.target_temp_f, 65.0);
+
+        let next = schedule.next_event_epoch(now).unwrap();
+        let expected = fixed_time(11, 22, 0).timestamp(); // Next Sunday 22:00.
+        assert_eq!(next, expected);
+    }
+
+    #[test]
+    fn override_takes_precedence_until_expiry() {
+        let mut schedule = Schedule {
+            enabled: true,
+            entries: vec![ScheduleEntry {
+                day: DayOfWeek::Mon,
+                start_minutes: 9 * 60,
+                anchor: ScheduleAnchor::Fixed,
+                anchor_offset_minutes: 0,
+                mode: ThermostatMode::Heat,
+                target_temp_f: 71.0,
+            }],
+            day_start_minutes: 0,
+            latitude: 0.0,
+            longitude: 0.0,
+            override_action: None,
+            boost_windows: Vec::new(),
+            date_exceptions: Vec::new(),
+        };
+        schedule.normalize();
+
+        let now = fixed_time(5, 10, 0); // Jan 5, 2026 is Monday, 10:00.
+        schedule.override_action = Some(ScheduleOverride {
+            mode: ThermostatMode::Off,
+            target_temp_f: 62.0,
+            until_epoch: fixed_time(5, 11, 0).timestamp(),
+        });
+
+        let action = schedule.effective_action(now, 70.0).unwrap();
+        assert_eq!(action.mode, ThermostatMode::Off);
+        assert_eq!(action.target_temp_f, 62.0);
+
+        // The override expiry is sooner than any schedule transition, so it
+        // should win as the next wake-up event.
+        let next = schedule.next_event_epoch(now).unwrap();
+        assert_eq!(next, fixed_time(5, 11, 0).timestamp());
+
+        // Once expired, effective_action reverts to the weekly schedule.
+        let after_expiry = fixed_time(5, 11, 1);
+        let action = schedule.effective_action(after_expiry, 70.0).unwrap();
+        assert_eq!(action.mode, ThermostatMode::Heat);
+        assert_eq!(action.target_temp_f, 71.0);
+    }
+
+    #[test]
+    fn next_heat_transition_skips_non_heat_entries() {
+        let mut schedule = Schedule {
+            enabled: true,
+            entries: vec![
+                ScheduleEntry {
+                    day: DayOfWeek::Mon,
+                    start_minutes: 9 * 60,
+                    anchor: ScheduleAnchor::Fixed,
+                    anchor_offset_minutes: 0,
+                    mode: ThermostatMode::Off,
+                    target_temp_f: 62.0,
+                },
+                ScheduleEntry {
+                    day: DayOfWeek::Mon,
+                    start_minutes: 18 * 60,
+                    anchor: ScheduleAnchor::Fixed,
+                    anchor_offset_minutes: 0,
+                    mode: ThermostatMode::Heat,
+                    target_temp_f: 71.0,
+                },
+            ],
+            day_start_minutes: 0,
+            latitude: 0.0,
+            longitude: 0.0,
+            override_action: None,
+            boost_windows: Vec::new(),
+            date_exceptions: Vec::new(),
+        };
+        schedule.normalize();
+
+        let now = fixed_time(5, 9, 1);
+        let (epoch, target_temp_f) = schedule.next_heat_transition(now).unwrap();
+
+        assert_eq!(epoch, fixed_time(5, 18, 0).timestamp());
+        assert_eq!(target_temp_f, 71.0);
+    }
+
+    #[test]
+    fn boost_window_forces_heat_below_floor() {
+        let mut schedule = Schedule {
+            enabled: true,
+            entries: vec![ScheduleEntry {
+                day: DayOfWeek::Mon,
+                start_minutes: 0,
+                anchor: ScheduleAnchor::Fixed,
+                anchor_offset_minutes: 0,
+                mode: ThermostatMode::Off,
+                target_temp_f: 62.0,
+            }],
+            day_start_minutes: 0,
+            latitude: 0.0,
+            longitude: 0.0,
+            override_action: None,
+            boost_windows: vec![BoostWindow {
+                day: DayOfWeek::Mon,
+                start_minutes: 5 * 60 + 30,
+                end_minutes: 7 * 60,
+                min_temp_f: 66.0,
+            }],
+            date_exceptions: Vec::new(),
+        };
+        schedule.normalize();
+
+        // Inside the window, below floor: boost forces Heat at the floor.
+        let now = fixed_time(5, 6, 0);
+        let action = schedule.current_action(now, 64.0).unwrap();
+        assert_eq!(action.mode, ThermostatMode::Heat);
+        assert_eq!(action.target_temp_f, 66.0);
+
+        // Inside the window, already at/above floor: released back to the
+        // normal program.
+        let action = schedule.current_action(now, 66.0).unwrap();
+        assert_eq!(action.mode, ThermostatMode::Off);
+
+        // Outside the window: normal program, regardless of temperature.
+        let outside = fixed_time(5, 8, 0);
+        let action = schedule.current_action(outside, 40.0).unwrap();
+        assert_eq!(action.mode, ThermostatMode::Off);
+    }
+
+    #[test]
+    fn boost_window_wraps_past_midnight() {
+        let schedule = Schedule {
+            enabled: true,
+            entries: Vec::new(),
+            day_start_minutes: 0,
+            latitude: 0.0,
+            longitude: 0.0,
+            override_action: None,
+            boost_windows: vec![BoostWindow {
+                day: DayOfWeek::Sun,
+                start_minutes: 23 * 60,
+                end_minutes: 60,
+                min_temp_f: 66.0,
+            }],
+            date_exceptions: Vec::new(),
+        };
+
+        // Monday 00:30 is still inside the Sunday-23:00-to-01:00 window.
+        let now = fixed_time(5, 0, 30); // Jan 5, 2026 is Monday.
+        let action = schedule.current_action(now, 60.0).unwrap();
+        assert_eq!(action.mode, ThermostatMode::Heat);
+        assert_eq!(action.target_temp_f, 66.0);
+    }
+
+    #[test]
+    fn boost_window_edges_count_as_wake_events() {
+        let mut schedule = Schedule {
+            enabled: false,
+            entries: Vec::new(),
+            day_start_minutes: 0,
+            latitude: 0.0,
+            longitude: 0.0,
+            override_action: None,
+            boost_windows: vec![BoostWindow {
+                day: DayOfWeek::Mon,
+                start_minutes: 5 * 60 + 30,
+                end_minutes: 7 * 60,
+                min_temp_f: 66.0,
+            }],
+            date_exceptions: Vec::new(),
+        };
+        schedule.normalize();
+
+        let now = fixed_time(5, 4, 0);
+        let next = schedule.next_event_epoch(now).unwrap();
+        assert_eq!(next, fixed_time(5, 5, 30).timestamp());
+
+        let during_window = fixed_time(5, 6, 0);
+        let next = schedule.next_event_epoch(during_window).unwrap();
+        assert_eq!(next, fixed_time(5, 7, 0).timestamp());
+    }
+
+    #[test]
+    fn date_exception_suppresses_heating_for_vacation_day() {
+        let mut schedule = Schedule {
+            enabled: true,
+            entries: vec![ScheduleEntry {
+                day: DayOfWeek::Mon,
+                start_minutes: 9 * 60,
+                anchor: ScheduleAnchor::Fixed,
+                anchor_offset_minutes: 0,
+                mode: ThermostatMode::Heat,
+                target_temp_f: 71.0,
+            }],
+            day_start_minutes: 0,
+            latitude: 0.0,
+            longitude: 0.0,
+            override_action: None,
+            boost_windows: Vec::new(),
+            date_exceptions: vec![DateException {
+                date: NaiveDate::from_ymd_opt(2026, 1, 5).unwrap(), // Monday.
+                action: None,
+            }],
+        };
+        schedule.normalize();
+
+        let now = fixed_time(5, 10, 0);
+        let action = schedule.current_action(now, 50.0).unwrap();
+        assert_eq!(action.mode, ThermostatMode::Off);
+
+        // The exception expires at local midnight, which wins as the next
+        // wake-up event since it's sooner than the next weekly transition.
+        let next = schedule.next_event_epoch(now).unwrap();
+        assert_eq!(next, fixed_time(6, 0, 0).timestamp());
+
+        // The following day, the weekly program applies normally again
+        // (wrapping back to Monday's entry, since nothing starts Tuesday).
+        let tomorrow = fixed_time(6, 9, 1);
+        let action = schedule.current_action(tomorrow, 50.0).unwrap();
+        assert_eq!(action.mode, ThermostatMode::Heat);
+    }
+
+    #[test]
+    fn date_exception_replaces_program_with_fixed_action() {
+        let schedule = Schedule {
+            enabled: true,
+            entries: vec![ScheduleEntry {
+                day: DayOfWeek::Mon,
+                start_minutes: 9 * 60,
+                anchor: ScheduleAnchor::Fixed,
+                anchor_offset_minutes: 0,
+                mode: ThermostatMode::Heat,
+                target_temp_f: 71.0,
+            }],
+            day_start_minutes: 0,
+            latitude: 0.0,
+            longitude: 0.0,
+            override_action: None,
+            boost_windows: Vec::new(),
+            date_exceptions: vec![DateException {
+                date: NaiveDate::from_ymd_opt(2026, 1, 5).unwrap(), // Monday.
+                action: Some(ScheduleAction {
+                    mode: ThermostatMode::Heat,
+                    target_temp_f: 64.0,
+                }),
+            }],
+        };
+
+        let now = fixed_time(5, 10, 0);
+        let action = schedule.current_action(now, 50.0).unwrap();
+        assert_eq!(action.mode, ThermostatMode::Heat);
+        assert_eq!(action.target_temp_f, 64.0);
+    }
+
+    #[test]
+    fn sunset_anchored_entry_resolves_solar_time() {
+        // Seattle, Jan 5 2026: sunset is ~16:31 PST, so "30 min before
+        // sunset" lands around 16:01.
+        let mut schedule = Schedule {
+            enabled: true,
+            entries: vec![
+                ScheduleEntry {
+                    day: DayOfWeek::Mon,
+                    start_minutes: 0,
+                    anchor: ScheduleAnchor::Fixed,
+                    anchor_offset_minutes: 0,
+                    mode: ThermostatMode::Off,
+                    target_temp_f: 62.0,
+                },
+                ScheduleEntry {
+                    day: DayOfWeek::Mon,
+                    start_minutes: 16 * 60,
+                    anchor: ScheduleAnchor::Sunset,
+                    anchor_offset_minutes: -30,
+                    mode: ThermostatMode::Heat,
+                    target_temp_f: 70.0,
+                },
+            ],
+            day_start_minutes: 0,
+            latitude: 47.6,
+            longitude: -122.3,
+            override_action: None,
+            boost_windows: Vec::new(),
+            date_exceptions: Vec::new(),
+        };
+        schedule.normalize();
+
+        let before_sunset_offset = fixed_time(5, 15, 59);
+        let action = schedule.current_action(before_sunset_offset, 65.0).unwrap();
+        assert_eq!(action.mode, ThermostatMode::Off);
+
+        let after_sunset_offset = fixed_time(5, 16, 2);
+        let action = schedule.current_action(after_sunset_offset, 65.0).unwrap();
+        assert_eq!(action.mode, ThermostatMode::Heat);
+        assert_eq!(action.target_temp_f, 70.0);
+    }
+
+    #[test]
+    fn next_heat_transition_resolves_sunrise_anchor() {
+        // Seattle, Jan 5 2026: sunrise is ~07:57 PST.
+        let mut schedule = Schedule {
+            enabled: true,
+            entries: vec![ScheduleEntry {
+                day: DayOfWeek::Mon,
+                start_minutes: 7 * 60,
+                anchor: ScheduleAnchor::Sunrise,
+                anchor_offset_minutes: 0,
+                mode: ThermostatMode::Heat,
+                target_temp_f: 71.0,
+            }],
+            day_start_minutes: 0,
+            latitude: 47.6,
+            longitude: -122.3,
+            override_action: None,
+            boost_windows: Vec::new(),
+            date_exceptions: Vec::new(),
+        };
+        schedule.normalize();
+
+        let now = fixed_time(5, 6, 0);
+        let (epoch, target_temp_f) = schedule.next_heat_transition(now).unwrap();
+
+        assert_eq!(epoch, fixed_time(5, 7, 57).timestamp());
+        assert_eq!(target_temp_f, 71.0);
+    }
+
+    #[test]
+    fn anchored_entry_falls_back_to_fixed_time_during_polar_night() {
+        // Above the Arctic Circle in January the sun never rises, so the
+        // solar lookup returns None and `start_minutes` applies as-is.
+        let mut schedule = Schedule {
+            enabled: true,
+            entries: vec![ScheduleEntry {
+                day: DayOfWeek::Mon,
+                start_minutes: 9 * 60,
+                anchor: ScheduleAnchor::Sunrise,
+                anchor_offset_minutes: 0,
+                mode: ThermostatMode::Heat,
+                target_temp_f: 71.0,
+            }],
+            day_start_minutes: 0,
+            latitude: 78.0,
+            longitude: 15.0,
+            override_action: None,
+            boost_windows: Vec::new(),
+            date_exceptions: Vec::new(),
+        };
+        schedule.normalize();
+
+        let now = fixed_time(5, 9, 1);
+        let action = schedule.current_action(now, 50.0).unwrap();
+        assert_eq!(action.mode, ThermostatMode::Heat);
+        assert_eq!(action.target_temp_f, 71.0);
+    }
 }
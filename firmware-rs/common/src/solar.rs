@@ -0,0 +1,76 @@
+//! Self-contained NOAA-style sunrise/sunset calculation used to anchor
+//! `schedule::ScheduleEntry` values to local solar events instead of a fixed
+//! clock time. Accurate to within a minute or two of the full NOAA solar
+//! position algorithm, which is plenty for scheduling a fireplace.
+use chrono::{Datelike, NaiveDate};
+
+/// Returns `(sunrise_utc_minutes, sunset_utc_minutes)` since UTC midnight for
+/// `date` at the given `latitude`/`longitude` (degrees), or `None` if the sun
+/// never rises or sets that day (polar day/night), in which case callers
+/// should fall back to a fixed time.
+pub fn sunrise_sunset_utc_minutes(
+    date: NaiveDate,
+    latitude: f64,
+    longitude: f64,
+) -> Option<(f64, f64)> {
+    let day_of_year = date.ordinal() as f64;
+    let gamma = 2.0 * std::f64::consts::PI / 365.0 * (day_of_year - 1.0);
+
+    let eq_time_minutes = 229.18
+        * (0.000075 + 0.001868 * gamma.cos()
+            - 0.032077 * gamma.sin()
+            - 0.014615 * (2.0 * gamma).cos()
+            - 0.040849 * (2.0 * gamma).sin());
+
+    let declination = 0.006918 - 0.399912 * gamma.cos() + 0.070257 * gamma.sin()
+        - 0.006758 * (2.0 * gamma).cos()
+        + 0.000907 * (2.0 * gamma).sin()
+        - 0.002697 * (3.0 * gamma).cos()
+        + 0.00148 * (3.0 * gamma).sin();
+
+    let phi = latitude.to_radians();
+    let cos_hour_angle = 90.833_f64.to_radians().cos() / (phi.cos() * declination.cos())
+        - phi.tan() * declination.tan();
+    if !(-1.0..=1.0).contains(&cos_hour_angle) {
+        return None;
+    }
+    let hour_angle_deg = cos_hour_angle.acos().to_degrees();
+
+    let sunrise = 720.0 - 4.0 * (longitude + hour_angle_deg) - eq_time_minutes;
+    let sunset = 720.0 - 4.0 * (longitude - hour_angle_deg) - eq_time_minutes;
+    Some((sunrise, sunset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seattle_summer_solstice_has_long_day() {
+        // Seattle, June 20 2026: sunrise well before 6am local, sunset well
+        // after 8pm local (PDT is UTC-7).
+        let date = NaiveDate::from_ymd_opt(2026, 6, 20).unwrap();
+        let (sunrise, sunset) = sunrise_sunset_utc_minutes(date, 47.6, -122.3).unwrap();
+
+        let sunrise_local = sunrise / 60.0 - 7.0;
+        let sunset_local = sunset / 60.0 - 7.0;
+
+        assert!((4.5..6.0).contains(&sunrise_local), "sunrise_local={sunrise_local}");
+        assert!((20.0..21.5).contains(&sunset_local), "sunset_local={sunset_local}");
+    }
+
+    #[test]
+    fn equator_day_length_is_roughly_twelve_hours() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 20).unwrap();
+        let (sunrise, sunset) = sunrise_sunset_utc_minutes(date, 0.0, 0.0).unwrap();
+
+        assert!((sunset - sunrise - 12.0 * 60.0).abs() < 5.0);
+    }
+
+    #[test]
+    fn polar_summer_has_no_sunset() {
+        // Above the Arctic Circle at the summer solstice, the sun never sets.
+        let date = NaiveDate::from_ymd_opt(2026, 6, 20).unwrap();
+        assert_eq!(sunrise_sunset_utc_minutes(date, 78.0, 15.0), None);
+    }
+}
@@ -1,6 +1,10 @@
 use crate::{
-    config::{PersistedSettings, ThermostatConfig},
-    types::{ControllerStatePayload, ControllerStatus, ThermostatMode, ThermostatState},
+    config::{PersistedSettings, PidParameters, SettingsProfile, ThermostatConfig},
+    types::{
+        ControlStrategy, ControllerStatePayload, ControllerStatus, FaultReason, SettingsSummary,
+        Temperature, TemperatureUnit, ThermalLoadBand, ThermostatMode, ThermostatState,
+        TunableRange,
+    },
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -16,12 +20,28 @@ pub enum EngineAction {
     PowerOff,
     HeatOn,
     HeatOff,
+    CoolOn,
+    CoolOff,
     TempUp,
     TempDown,
     SetTemp(i32),
     Delay(u64),
     LightToggle,
     TimerToggle,
+    /// Emitted by `apply_thermal_load_response` when `thermal_load()` crosses
+    /// into `ThermalLoadBand::Throttle`; purely informational (no IR command
+    /// of its own), since the duty reduction itself happens via the
+    /// narrowed heat-call upper bound already baked into `evaluate_hysteresis`.
+    ThrottleHeat,
+    /// Emitted once when `thermal_load()` crosses into `ThermalLoadBand::Lockout`,
+    /// right after (and in addition to) the `PowerOff` that comes with shutting
+    /// the fireplace off. The lockout itself is tracked internally and isn't
+    /// re-announced on every tick it remains in force.
+    EmergencyLockout,
+    /// Emitted by `advance_autotune` once a FOPDT autotune run settles on a
+    /// fit; purely informational (no IR command), so callers log/persist it
+    /// rather than forwarding it to `IrTransmitter`.
+    AutotuneComplete { kp: f32, ki: f32, kd: f32 },
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -31,6 +51,18 @@ struct HoldState {
     _reason: HoldReason,
 }
 
+/// In-progress `start_autotune` run: a full-heat step response recorded as
+/// `(elapsed_ms, temp_f)` samples so `fit_fopdt` can locate the dead time and
+/// 63.2%-rise point once the reading settles.
+#[derive(Debug, Clone)]
+struct AutotuneState {
+    start_ms: u64,
+    start_temp_f: f32,
+    peak_temp_f: f32,
+    last_rise_ms: u64,
+    samples: Vec<(u64, f32)>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ThermostatEngine {
     pub config: ThermostatConfig,
@@ -44,12 +76,64 @@ pub struct ThermostatEngine {
     last_sensor_update_ms: Option<u64>,
     last_state_change_ms: Option<u64>,
 
+    // Tracks the last time `current_humidity` actually *changed*, so a
+    // sensor stuck reporting the same value on schedule (unlike a dead
+    // sensor, which would also trip `is_sensor_data_valid`) can still be
+    // detected as stale by `is_humidity_data_valid`.
+    last_humidity_value: Option<f32>,
+    last_humidity_change_ms: Option<u64>,
+
     hold: Option<HoldState>,
 
     heating_start_ms: Option<u64>,
     cooldown_start_ms: Option<u64>,
     in_cooldown: bool,
 
+    // Which HVAC function (`Heat`/`Cool`) the device is currently running,
+    // `None` while idle. `ThermostatMode::Auto` consults this to ensure it
+    // never flips directly from one function to the other without first
+    // passing through idle.
+    active_hvac_mode: Option<ThermostatMode>,
+
+    // Latched safety fault. Set by `evaluate_state` when a safety condition
+    // trips, cleared only by `reset_safety` once the tripping condition has
+    // actually cleared.
+    fault: Option<FaultReason>,
+
+    // Independent over/under-temperature alarm latch (see
+    // `update_sensor_data`/`clear_alarm`). Deliberately separate from
+    // `fault`: it takes priority over everything else in `evaluate_state`,
+    // ignores `set_mode_with_actions` entirely while set, and only clears
+    // via an explicit `clear_alarm` call rather than `reset_safety`.
+    alarm_latched: bool,
+
+    // Drives `check_setpoint_band_alarm`: `ever_satisfied` turns the band
+    // check on (a cold start legitimately sits outside the band during
+    // warm-up), `band_violation_ticks` counts consecutive ticks spent
+    // outside `alarm_band_f` so a single noisy sample can't latch it.
+    ever_satisfied: bool,
+    band_violation_ticks: u32,
+
+    // Graded thermal-load response (see `apply_thermal_load_response`).
+    // `last_thermal_band` is the band observed on the previous tick, so a new
+    // band is only announced once on the tick it's first crossed rather than
+    // every tick it remains in force. `thermal_lockout` is a separate,
+    // self-clearing latch from `fault`: it requires the room to fall
+    // `lockout_recovery_margin_f` below `target_temp_f` (not just back under
+    // `absolute_max_temp_f`) before heat calls resume.
+    last_thermal_band: ThermalLoadBand,
+    thermal_lockout: bool,
+
+    // In-progress `start_autotune` run, `None` otherwise. While `Some`,
+    // `evaluate_state` hands exclusive control of the fireplace to
+    // `advance_autotune` instead of the normal per-mode dispatch.
+    autotune: Option<AutotuneState>,
+
+    // Named PersistedSettings snapshots; see `save_profile`/`apply_profile`.
+    profiles: Vec<SettingsProfile>,
+    next_profile_id: u32,
+    active_profile_id: Option<u32>,
+
     previous_temp_f: Option<f32>,
     last_trend_sample_ms: Option<u64>,
     trend_direction: i8,
@@ -59,11 +143,43 @@ pub struct ThermostatEngine {
     light_level: u8,
     timer_state: u8,
     fireplace_temp_f: i32,
+
+    // PID control strategy state, only advanced while
+    // `settings.control_strategy == ControlStrategy::Pid`.
+    pid_integral: f32,
+    pid_last_sensor_f: Option<f32>,
+    pid_last_tick_ms: Option<u64>,
+    pid_window_start_ms: Option<u64>,
+    pid_window_on_ms: u64,
+    // Last raw 0..1 `compute_pid_output` result, cached for `ControllerStatus::pid_output`
+    // so it can be read without re-running the loop.
+    pid_last_output: f32,
+
+    // Setpoint-modulation PID state, only advanced while
+    // `settings.control_strategy == ControlStrategy::SetpointPid`.
+    setpoint_pid_integral: f32,
+    setpoint_pid_last_temp_f: Option<f32>,
+    setpoint_pid_last_tick_ms: Option<u64>,
+
+    // Time-proportioned (PWM) control strategy state, only advanced while
+    // `settings.control_strategy == ControlStrategy::TimeProportional`.
+    time_proportional_integral: f32,
+    time_proportional_last_sensor_f: Option<f32>,
+    time_proportional_last_tick_ms: Option<u64>,
+    time_proportional_window_start_ms: Option<u64>,
+    time_proportional_window_on_ms: u64,
+
+    // Smoothed observed heating rate, used by `should_preheat` to start
+    // scheduled heat-up transitions early enough to land on time.
+    heating_rate_f_per_min: f32,
+    heating_rate_sample_start_ms: Option<u64>,
+    heating_rate_sample_temp_f: Option<f32>,
 }
 
 impl ThermostatEngine {
     pub fn new(config: ThermostatConfig, mut settings: PersistedSettings) -> Self {
         settings.sanitize();
+        let heating_rate_f_per_min = config.preheat_default_rate_f_per_min;
         Self {
             config,
             settings,
@@ -73,10 +189,23 @@ impl ThermostatEngine {
             fireplace_on: false,
             last_sensor_update_ms: None,
             last_state_change_ms: None,
+            last_humidity_value: None,
+            last_humidity_change_ms: None,
             hold: None,
             heating_start_ms: None,
             cooldown_start_ms: None,
             in_cooldown: false,
+            active_hvac_mode: None,
+            fault: None,
+            alarm_latched: false,
+            ever_satisfied: false,
+            band_violation_ticks: 0,
+            last_thermal_band: ThermalLoadBand::Nominal,
+            thermal_lockout: false,
+            autotune: None,
+            profiles: Vec::new(),
+            next_profile_id: 1,
+            active_profile_id: None,
             previous_temp_f: None,
             last_trend_sample_ms: None,
             trend_direction: 0,
@@ -84,6 +213,23 @@ impl ThermostatEngine {
             light_level: 0,
             timer_state: 0,
             fireplace_temp_f: 70,
+            pid_integral: 0.0,
+            pid_last_sensor_f: None,
+            pid_last_tick_ms: None,
+            pid_window_start_ms: None,
+            pid_window_on_ms: 0,
+            pid_last_output: 0.0,
+            setpoint_pid_integral: 0.0,
+            setpoint_pid_last_temp_f: None,
+            setpoint_pid_last_tick_ms: None,
+            time_proportional_integral: 0.0,
+            time_proportional_last_sensor_f: None,
+            time_proportional_last_tick_ms: None,
+            time_proportional_window_start_ms: None,
+            time_proportional_window_on_ms: 0,
+            heating_rate_f_per_min,
+            heating_rate_sample_start_ms: None,
+            heating_rate_sample_temp_f: None,
         }
     }
 
@@ -107,12 +253,69 @@ impl ThermostatEngine {
         self.fireplace_on
     }
 
+    /// Whether the actuator is currently running a cooling call, i.e. it's
+    /// on and the HVAC function it's running is `Cool` (whether reached via
+    /// `ThermostatMode::Cool` directly or `Auto` picking the cool side).
+    pub fn is_cooling_on(&self) -> bool {
+        self.fireplace_on && self.active_hvac_mode == Some(ThermostatMode::Cool)
+    }
+
     pub fn update_sensor_data(&mut self, temp_f: f32, humidity: f32, now_ms: u64) {
         self.current_temp_f = temp_f;
+        if temp_f >= self.config.alarm_high_f || temp_f <= self.config.alarm_low_f {
+            self.alarm_latched = true;
+        }
+        if self
+            .last_humidity_value
+            .map_or(true, |last| (last - humidity).abs() > f32::EPSILON)
+        {
+            self.last_humidity_change_ms = Some(now_ms);
+        }
+        self.last_humidity_value = Some(humidity);
         self.current_humidity = humidity;
         self.last_sensor_update_ms = Some(now_ms);
     }
 
+    /// Converts a raw thermistor `resistance_ohms` reading to °F via the
+    /// Steinhart-Hart equation and `config.thermistor`'s per-install
+    /// calibration, then delegates to `update_sensor_data`. A non-positive
+    /// or implausible resistance (one that converts outside
+    /// `min_valid_temp_f..=max_valid_temp_f`) is treated as a failed read:
+    /// `current_temp_f`/`last_sensor_update_ms` are left untouched, so
+    /// `is_sensor_data_valid` still correctly trips the stale-sensor
+    /// shutoff path.
+    pub fn update_sensor_raw(&mut self, resistance_ohms: f32, humidity: f32, now_ms: u64) {
+        if let Some(temp_f) = self.steinhart_hart_temp_f(resistance_ohms) {
+            self.update_sensor_data(temp_f, humidity, now_ms);
+        }
+    }
+
+    fn steinhart_hart_temp_f(&self, resistance_ohms: f32) -> Option<f32> {
+        if !resistance_ohms.is_finite() || resistance_ohms <= 0.0 {
+            return None;
+        }
+
+        let thermistor = &self.config.thermistor;
+        let ln_r = resistance_ohms.ln();
+        let inv_kelvin = thermistor.a + thermistor.b * ln_r + thermistor.c * ln_r.powi(3);
+        if !inv_kelvin.is_finite() || inv_kelvin <= 0.0 {
+            return None;
+        }
+
+        let kelvin = 1.0 / inv_kelvin;
+        let fahrenheit = (kelvin - 273.15) * 9.0 / 5.0 + 32.0;
+        let calibrated = fahrenheit * thermistor.calibration_gain + thermistor.calibration_offset_f;
+
+        if !calibrated.is_finite()
+            || calibrated < self.config.min_valid_temp_f
+            || calibrated > self.config.max_valid_temp_f
+        {
+            return None;
+        }
+
+        Some(calibrated)
+    }
+
     pub fn set_target_temp(&mut self, temp_f: f32) -> bool {
         let clamped = temp_f.clamp(60.0, 84.0);
         if (self.settings.target_temp_f - clamped).abs() > f32::EPSILON {
@@ -123,6 +326,21 @@ impl ThermostatEngine {
         }
     }
 
+    /// Sets the `ThermostatMode::Auto`-only cooling setpoint, clamped the
+    /// same way `sanitize` would (60..90, and never less than
+    /// `target_temp_f + 2.0` so Auto's heat and cool calls can't overlap).
+    pub fn set_auto_cool_setpoint(&mut self, temp_f: f32) -> bool {
+        let clamped = temp_f
+            .clamp(60.0, 90.0)
+            .max(self.settings.target_temp_f + 2.0);
+        if (self.settings.auto_cool_setpoint_f - clamped).abs() > f32::EPSILON {
+            self.settings.auto_cool_setpoint_f = clamped;
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn set_hysteresis(&mut self, hysteresis_f: f32) -> bool {
         let clamped = hysteresis_f.clamp(0.5, 5.0);
         if (self.settings.hysteresis_f - clamped).abs() > f32::EPSILON {
@@ -133,11 +351,48 @@ impl ThermostatEngine {
         }
     }
 
+    /// Sets or clears the dehumidify target. `None` disables humidity-aware
+    /// control entirely; `Some` is clamped the same way `sanitize` would.
+    pub fn set_humidity_target(&mut self, target: Option<f32>) -> bool {
+        let clamped = target.map(|value| value.clamp(20.0, 80.0));
+        if self.settings.humidity_target != clamped {
+            self.settings.humidity_target = clamped;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn set_humidity_hysteresis(&mut self, humidity_hysteresis_f: f32) -> bool {
+        let clamped = humidity_hysteresis_f.clamp(1.0, 20.0);
+        if (self.settings.humidity_hysteresis_f - clamped).abs() > f32::EPSILON {
+            self.settings.humidity_hysteresis_f = clamped;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Sets the unit outward-facing readings are additionally rendered in.
+    /// Purely a reporting preference: `self.settings` and every engine
+    /// temperature field stay Fahrenheit-native regardless.
+    pub fn set_display_unit(&mut self, unit: TemperatureUnit) -> bool {
+        if self.settings.display_unit != unit {
+            self.settings.display_unit = unit;
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn set_mode(&mut self, mode: ThermostatMode) -> bool {
         if self.settings.mode != mode {
             self.settings.mode = mode;
             if mode == ThermostatMode::Off {
                 self.hold = None;
+                self.reset_pid_state();
+                self.reset_setpoint_pid_state();
+                self.reset_time_proportional_state();
             }
             true
         } else {
@@ -145,11 +400,27 @@ impl ThermostatEngine {
         }
     }
 
+    pub fn set_control_strategy(&mut self, strategy: ControlStrategy) -> bool {
+        if self.settings.control_strategy != strategy {
+            self.settings.control_strategy = strategy;
+            self.reset_pid_state();
+            self.reset_setpoint_pid_state();
+            self.reset_time_proportional_state();
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn set_mode_with_actions(
         &mut self,
         mode: ThermostatMode,
         now_ms: u64,
     ) -> (bool, Vec<EngineAction>) {
+        if self.alarm_latched {
+            return (false, Vec::new());
+        }
+
         let mut actions = Vec::new();
         let changed = self.set_mode(mode);
 
@@ -180,11 +451,41 @@ impl ThermostatEngine {
         self.complete_cooldown_if_needed(now_ms);
         self.check_runtime_limit(now_ms, &mut actions);
         self.detect_external_remote(now_ms);
+        self.update_heating_rate_estimate(now_ms);
         self.evaluate_state(now_ms, &mut actions);
 
+        if self.state == ThermostatState::Satisfied {
+            self.ever_satisfied = true;
+        }
+        self.check_setpoint_band_alarm();
+
         actions
     }
 
+    /// Once the engine has reached `Satisfied` at least once, trips the same
+    /// alarm latch as `alarm_high_f`/`alarm_low_f` if `current_temp_f` stays
+    /// more than `alarm_band_f` from `target_temp_f` for
+    /// `alarm_band_ticks` consecutive ticks - catching a fireplace that's
+    /// quietly stopped satisfying the setpoint well before the room drifts
+    /// all the way out to the absolute alarm limits. A no-op before the
+    /// first `Satisfied`, since a normal warm-up is expected to sit well
+    /// outside this band.
+    fn check_setpoint_band_alarm(&mut self) {
+        if !self.ever_satisfied || self.alarm_latched {
+            return;
+        }
+
+        let deviation = (self.current_temp_f - self.settings.target_temp_f).abs();
+        if deviation > self.config.alarm_band_f {
+            self.band_violation_ticks = self.band_violation_ticks.saturating_add(1);
+            if self.band_violation_ticks >= self.config.alarm_band_ticks {
+                self.alarm_latched = true;
+            }
+        } else {
+            self.band_violation_ticks = 0;
+        }
+    }
+
     pub fn manual_on(&mut self, now_ms: u64) -> Vec<EngineAction> {
         self.fireplace_on = true;
         self.heating_start_ms = Some(now_ms);
@@ -227,6 +528,24 @@ impl ThermostatEngine {
         vec![EngineAction::HeatOff]
     }
 
+    pub fn manual_cool_on(&mut self, now_ms: u64) -> Vec<EngineAction> {
+        self.enter_hold_internal(
+            self.config.hold_duration_ms,
+            HoldReason::ManualOverride,
+            now_ms,
+        );
+        vec![EngineAction::CoolOn]
+    }
+
+    pub fn manual_cool_off(&mut self, now_ms: u64) -> Vec<EngineAction> {
+        self.enter_hold_internal(
+            self.config.hold_duration_ms,
+            HoldReason::ManualOverride,
+            now_ms,
+        );
+        vec![EngineAction::CoolOff]
+    }
+
     pub fn manual_heat_up(&mut self) -> Vec<EngineAction> {
         if self.fireplace_temp_f >= 80 {
             return Vec::new();
@@ -248,6 +567,29 @@ impl ThermostatEngine {
         vec![EngineAction::LightToggle]
     }
 
+    /// Starts a FOPDT autotune run from idle: commands full heat and begins
+    /// recording a step response that `advance_autotune` analyzes on every
+    /// subsequent `tick`, fitting `config.time_proportional`'s gains once the
+    /// reading settles. No-op (returns no actions) if the fireplace is
+    /// already running or a safety fault is latched, since the run needs a
+    /// clean baseline to measure the rise from.
+    pub fn start_autotune(&mut self, now_ms: u64) -> Vec<EngineAction> {
+        if self.fireplace_on || self.fault.is_some() {
+            return Vec::new();
+        }
+
+        let mut actions = Vec::new();
+        self.turn_device_on(ThermostatMode::Heat, now_ms, &mut actions);
+        self.autotune = Some(AutotuneState {
+            start_ms: now_ms,
+            start_temp_f: self.current_temp_f,
+            peak_temp_f: self.current_temp_f,
+            last_rise_ms: now_ms,
+            samples: vec![(0, self.current_temp_f)],
+        });
+        actions
+    }
+
     pub fn manual_timer_toggle(&mut self) -> Vec<EngineAction> {
         self.timer_state = (self.timer_state + 1) % 11;
         vec![EngineAction::TimerToggle]
@@ -265,10 +607,75 @@ impl ThermostatEngine {
         self.hold = None;
     }
 
-    pub fn reset_safety(&mut self) {
+    /// Clears cooldown/PID safety state unconditionally, but only clears a
+    /// latched fault if the condition that tripped it has actually cleared:
+    /// `OverTemp` requires the room to have cooled `fault_recovery_hysteresis_f`
+    /// below the ceiling, and `SensorLost` requires a currently-valid sensor
+    /// reading. An unmet fault is left latched so a manual reset can't be
+    /// used to paper over a still-dangerous condition.
+    pub fn reset_safety(&mut self, now_ms: u64) {
         self.in_cooldown = false;
         self.cooldown_start_ms = None;
         self.heating_start_ms = None;
+        self.reset_setpoint_pid_state();
+
+        let recovered = match self.fault {
+            Some(FaultReason::OverTemp) => {
+                self.current_temp_f
+                    <= self.config.absolute_max_temp_f - self.config.fault_recovery_hysteresis_f
+            }
+            Some(FaultReason::SensorLost) => self.is_sensor_data_valid(now_ms),
+            None => true,
+        };
+        if recovered {
+            self.fault = None;
+        }
+    }
+
+    /// Clears a latched over/under-temperature alarm, but only once
+    /// `current_temp_f` is back strictly inside `alarm_low_f..alarm_high_f`
+    /// and, once the engine has ever reached `Satisfied`, also within
+    /// `alarm_band_f` of `target_temp_f`. Returns whether the clear actually succeeded, same convention as
+    /// `set_target_temp`/`set_mode`. Deliberately independent of
+    /// `reset_safety`, which only clears `fault`.
+    pub fn clear_alarm(&mut self, now_ms: u64) -> bool {
+        if !self.alarm_latched {
+            return false;
+        }
+        let within_absolute_limits =
+            self.current_temp_f > self.config.alarm_low_f
+                && self.current_temp_f < self.config.alarm_high_f;
+        let within_band = !self.ever_satisfied
+            || (self.current_temp_f - self.settings.target_temp_f).abs()
+                <= self.config.alarm_band_f;
+        let safe = within_absolute_limits && within_band;
+        if safe {
+            self.alarm_latched = false;
+            self.band_violation_ticks = 0;
+            self.state = ThermostatState::Idle;
+            self.last_state_change_ms = Some(now_ms);
+        }
+        safe
+    }
+
+    pub fn is_alarm_latched(&self) -> bool {
+        self.alarm_latched
+    }
+
+    /// Human-readable reason the alarm is currently latched, for surfacing
+    /// in `ControllerStatus`/`ControllerStatePayload`. `None` when the alarm
+    /// isn't latched.
+    pub fn alarm_reason(&self) -> Option<&'static str> {
+        if !self.alarm_latched {
+            return None;
+        }
+        if self.current_temp_f >= self.config.alarm_high_f {
+            Some("OVER_TEMP_ALARM")
+        } else if self.current_temp_f <= self.config.alarm_low_f {
+            Some("UNDER_TEMP_ALARM")
+        } else {
+            Some("SETPOINT_DEVIATION_ALARM")
+        }
     }
 
     pub fn is_sensor_data_valid(&self, now_ms: u64) -> bool {
@@ -281,6 +688,44 @@ impl ThermostatEngine {
         self.last_sensor_update_ms
     }
 
+    /// Parallel to `is_sensor_data_valid`, but keyed on the last time
+    /// `current_humidity` actually changed rather than the last time a
+    /// reading was delivered, since a humidity sensor failing "stuck" often
+    /// keeps reporting on its normal schedule with an unchanging value.
+    pub fn is_humidity_data_valid(&self, now_ms: u64) -> bool {
+        self.last_humidity_change_ms
+            .map(|last| now_ms.saturating_sub(last) < self.config.humidity_stale_timeout_ms)
+            .unwrap_or(false)
+    }
+
+    /// Normalized 0..255 thermal load: 0 at/below `target_temp_f`, 255 at
+    /// `absolute_max_temp_f`. Feeds `apply_thermal_load_response`'s graded
+    /// bands, and is exposed here so callers can surface it directly.
+    pub fn thermal_load(&self) -> u8 {
+        let span = self.config.absolute_max_temp_f - self.settings.target_temp_f;
+        if span <= 0.0 {
+            return 0;
+        }
+        let load = (self.current_temp_f - self.settings.target_temp_f) / span;
+        (load.clamp(0.0, 1.0) * 255.0).round() as u8
+    }
+
+    /// Which configured band `thermal_load()` currently falls in; see
+    /// `ThermalLoadParameters` for what each band means.
+    pub fn thermal_load_band(&self) -> ThermalLoadBand {
+        let load = self.thermal_load();
+        let params = &self.config.thermal_load;
+        if load >= params.lockout_load {
+            ThermalLoadBand::Lockout
+        } else if load >= params.shutoff_load {
+            ThermalLoadBand::Shutoff
+        } else if load >= params.throttle_load {
+            ThermalLoadBand::Throttle
+        } else {
+            ThermalLoadBand::Nominal
+        }
+    }
+
     pub fn is_in_hold(&self) -> bool {
         self.hold.is_some()
     }
@@ -299,6 +744,14 @@ impl ThermostatEngine {
         self.in_cooldown
     }
 
+    pub fn is_fault_active(&self) -> bool {
+        self.fault.is_some()
+    }
+
+    pub fn fault_reason(&self) -> Option<FaultReason> {
+        self.fault
+    }
+
     pub fn cooldown_remaining_ms(&self, now_ms: u64) -> u64 {
         if !self.in_cooldown {
             return 0;
@@ -345,6 +798,108 @@ impl ThermostatEngine {
         (changed, actions)
     }
 
+    /// Snapshots the current settings as a new named profile and selects it
+    /// as active, returning its assigned id.
+    pub fn save_profile(&mut self, name: String) -> u32 {
+        let id = self.next_profile_id;
+        self.next_profile_id += 1;
+        self.profiles.push(SettingsProfile {
+            id,
+            name,
+            settings: self.settings.clone(),
+        });
+        self.active_profile_id = Some(id);
+        id
+    }
+
+    pub fn list_profiles(&self) -> &[SettingsProfile] {
+        &self.profiles
+    }
+
+    pub fn active_profile_id(&self) -> Option<u32> {
+        self.active_profile_id
+    }
+
+    /// Removes a profile by id, clearing `active_profile_id` if it was the
+    /// one selected. Returns whether a profile was actually removed.
+    pub fn delete_profile(&mut self, id: u32) -> bool {
+        let before = self.profiles.len();
+        self.profiles.retain(|profile| profile.id != id);
+        let removed = self.profiles.len() != before;
+        if removed && self.active_profile_id == Some(id) {
+            self.active_profile_id = None;
+        }
+        removed
+    }
+
+    /// Applies a saved profile's settings through the same
+    /// `set_mode_with_actions`/`set_target_temp` path `apply_schedule_action`
+    /// uses, so the fireplace reconciles to the new mode/target, and
+    /// respects an active hold identically (no-op while held).
+    pub fn apply_profile(&mut self, id: u32, now_ms: u64) -> (bool, Vec<EngineAction>) {
+        if self.is_in_hold() {
+            return (false, Vec::new());
+        }
+
+        let Some(profile) = self.profiles.iter().find(|profile| profile.id == id) else {
+            return (false, Vec::new());
+        };
+        let settings = profile.settings.clone();
+
+        let mut actions = Vec::new();
+        let mut changed = false;
+        let (mode_changed, mut mode_actions) = self.set_mode_with_actions(settings.mode, now_ms);
+        changed |= mode_changed;
+        actions.append(&mut mode_actions);
+        changed |= self.set_target_temp(settings.target_temp_f);
+        changed |= self.set_auto_cool_setpoint(settings.auto_cool_setpoint_f);
+        changed |= self.set_hysteresis(settings.hysteresis_f);
+        changed |= self.set_fireplace_offset(settings.fireplace_offset_f);
+        changed |= self.set_control_strategy(settings.control_strategy);
+
+        self.active_profile_id = Some(id);
+
+        (changed, actions)
+    }
+
+    /// Bounds and step for every tunable, mirroring the clamps each setter
+    /// already enforces, so a UI can render correctly-bounded sliders and
+    /// steppers without duplicating those constants.
+    pub fn settings_summary(&self) -> SettingsSummary {
+        SettingsSummary {
+            target_temp: TunableRange {
+                value: self.settings.target_temp_f,
+                min: 60.0,
+                max: 84.0,
+                step: 1.0,
+            },
+            hysteresis: TunableRange {
+                value: self.settings.hysteresis_f,
+                min: 0.5,
+                max: 5.0,
+                step: 0.5,
+            },
+            auto_cool_target: TunableRange {
+                value: self.settings.auto_cool_setpoint_f,
+                min: 60.0,
+                max: 90.0,
+                step: 1.0,
+            },
+            fireplace_offset: TunableRange {
+                value: self.settings.fireplace_offset_f as f32,
+                min: 2.0,
+                max: 10.0,
+                step: 2.0,
+            },
+            fireplace_temp: TunableRange {
+                value: self.fireplace_temp_f as f32,
+                min: 60.0,
+                max: 80.0,
+                step: 2.0,
+            },
+        }
+    }
+
     pub fn status(
         &self,
         now_ms: u64,
@@ -353,17 +908,35 @@ impl ThermostatEngine {
         time_synced: bool,
         timezone: &str,
     ) -> ControllerStatus {
+        let unit = self.settings.display_unit;
         ControllerStatus {
-            current_temp: self.current_temp_f,
+            current_temp: Temperature::from_fahrenheit(self.current_temp_f).value_in(unit),
             current_humidity: self.current_humidity,
-            target_temp: self.settings.target_temp_f,
-            hysteresis: self.settings.hysteresis_f,
-            fireplace_offset: self.settings.fireplace_offset_f,
-            fireplace_temp: self.fireplace_temp_f,
+            target_temp: Temperature::from_fahrenheit(self.settings.target_temp_f)
+                .value_in(unit),
+            hysteresis: unit.convert_delta_from_fahrenheit(self.settings.hysteresis_f),
+            humidity_target: self.settings.humidity_target,
+            humidity_hysteresis: self.settings.humidity_hysteresis_f,
+            humidity_valid: self.is_humidity_data_valid(now_ms),
+            control_strategy: self.settings.control_strategy.as_str(),
+            pid_output: self.pid_last_output,
+            fireplace_offset: unit
+                .convert_delta_from_fahrenheit(self.settings.fireplace_offset_f as f32)
+                .round() as i32,
+            fireplace_temp: Temperature::from_fahrenheit(self.fireplace_temp_f as f32)
+                .value_in(unit)
+                .round() as i32,
             mode: self.settings.mode.as_str(),
             state: self.state.as_str(),
             fireplace_on: self.fireplace_on,
+            actuator_on: self.fireplace_on,
+            cooling_on: self.is_cooling_on(),
             sensor_valid: self.is_sensor_data_valid(now_ms),
+            fault_active: self.fault.is_some(),
+            fault_reason: self.fault.map(FaultReason::as_str),
+            alarm_active: self.alarm_latched,
+            alarm_reason: self.alarm_reason(),
+            active_profile_id: self.active_profile_id,
             light_level: self.light_level,
             timer_state: self.timer_state,
             timer_string: self.timer_string(),
@@ -379,9 +952,20 @@ impl ThermostatEngine {
             next_schedule_event_epoch,
             time_synced,
             timezone: timezone.to_string(),
+            display_unit: self.settings.display_unit.as_str(),
+            // Overwritten by the host daemon, which is the only platform
+            // with a file-based config overlay to report.
+            config_version: 0,
+            config_path: None,
         }
     }
 
+    /// Unlike `status`, this is intentionally not converted per
+    /// `settings.display_unit`: it only ever feeds the Home Assistant MQTT
+    /// climate discovery topics, which have no per-entity unit override and
+    /// assume values match the HA instance's own configured unit system -
+    /// reformatting it per-device here would silently break that
+    /// integration for anyone whose `displayUnit` doesn't match HA's.
     pub fn state_payload(&self, now_ms: u64) -> ControllerStatePayload {
         ControllerStatePayload {
             temp: self.current_temp_f,
@@ -390,6 +974,12 @@ impl ThermostatEngine {
             mode: self.settings.mode.as_str(),
             state: self.state.as_str(),
             fireplace: self.fireplace_on,
+            actuator_on: self.fireplace_on,
+            cooling_on: self.is_cooling_on(),
+            fault_active: self.fault.is_some(),
+            fault_reason: self.fault.map(FaultReason::as_str),
+            alarm_active: self.alarm_latched,
+            alarm_reason: self.alarm_reason(),
             hold_active: self.is_in_hold(),
             hold_remaining_min: self.hold_remaining_ms(now_ms) / 60_000,
             in_cooldown: self.is_in_cooldown(),
@@ -498,9 +1088,39 @@ impl ThermostatEngine {
     }
 
     fn evaluate_state(&mut self, now_ms: u64, actions: &mut Vec<EngineAction>) {
-        // Emergency shutoff: absolute max temperature ceiling
-        if self.current_temp_f >= self.config.absolute_max_temp_f && self.fireplace_on {
-            self.turn_fireplace_off(now_ms, actions);
+        // Over/under-temperature alarm: takes priority over everything else,
+        // including an in-progress autotune, and stays latched across every
+        // tick it remains in force so an oscillating reading can't repeatedly
+        // re-ignite the fireplace. Only `clear_alarm` can release it (the
+        // `absolute_max_temp_f` shutoff below latches a separate `OverTemp`
+        // fault that only `reset_safety` clears).
+        if self.alarm_latched {
+            self.autotune = None;
+            if self.fireplace_on {
+                self.turn_fireplace_off(now_ms, actions);
+            }
+            self.freeze_time_proportional_clock(now_ms);
+            self.state = ThermostatState::Fault;
+            return;
+        }
+
+        // A running autotune owns the fireplace exclusively until it
+        // completes or aborts; skip the normal dispatch entirely rather than
+        // let it fight over the heat call.
+        if self.autotune.is_some() {
+            self.advance_autotune(now_ms, actions);
+            return;
+        }
+
+        // Emergency shutoff: absolute max temperature ceiling. Latches so the
+        // controller can't chatter by re-igniting the moment the reading
+        // dips a fraction below the ceiling; only `reset_safety` clears it.
+        if self.current_temp_f >= self.config.absolute_max_temp_f {
+            self.fault = Some(FaultReason::OverTemp);
+            if self.fireplace_on {
+                self.turn_fireplace_off(now_ms, actions);
+            }
+            self.freeze_time_proportional_clock(now_ms);
             self.state = ThermostatState::Idle;
             return;
         }
@@ -524,324 +1144,2305 @@ impl ThermostatEngine {
         }
 
         if !self.is_sensor_data_valid(now_ms) {
+            self.fault = Some(FaultReason::SensorLost);
             if self.fireplace_on {
                 self.turn_fireplace_off(now_ms, actions);
             }
+            self.freeze_time_proportional_clock(now_ms);
             self.state = ThermostatState::Idle;
             return;
         }
 
-        let lower_bound = self.settings.target_temp_f - self.settings.hysteresis_f;
-        let upper_bound = self.settings.target_temp_f + self.settings.hysteresis_f;
+        if self.fault.is_some() {
+            self.state = ThermostatState::Idle;
+            return;
+        }
 
-        if !self.fireplace_on {
-            if self.current_temp_f < lower_bound {
-                if self.can_change_state(now_ms) {
-                    self.turn_fireplace_on(now_ms, actions);
-                }
-            } else {
-                self.state = ThermostatState::Satisfied;
-            }
-        } else if self.current_temp_f > upper_bound {
-            if self.can_change_state(now_ms) {
+        // A direct mode change from Heat to Cool (or vice versa) must pass
+        // through idle too, same as Auto's internal switching: force the
+        // mismatched function off now and let the new mode re-engage on a
+        // later tick rather than running evaluate_cool/evaluate_hysteresis
+        // against a device actively driving the other function.
+        if let Some(active) = self.active_hvac_mode {
+            let mismatched = matches!(
+                (self.settings.mode, active),
+                (ThermostatMode::Heat, ThermostatMode::Cool)
+                    | (ThermostatMode::Cool, ThermostatMode::Heat)
+            );
+            if mismatched {
                 self.turn_fireplace_off(now_ms, actions);
+                self.state = ThermostatState::Idle;
+                return;
             }
-        } else {
-            self.state = ThermostatState::Heating;
         }
-    }
-
-    fn can_change_state(&self, now_ms: u64) -> bool {
-        self.last_state_change_ms
-            .map(|last| now_ms.saturating_sub(last) >= self.config.min_cycle_ms)
-            .unwrap_or(true)
-    }
 
-    fn turn_fireplace_on(&mut self, now_ms: u64, actions: &mut Vec<EngineAction>) {
-        if self.fireplace_on {
+        if self.settings.mode == ThermostatMode::Heat
+            && self.apply_thermal_load_response(now_ms, actions)
+        {
             return;
         }
 
-        actions.push(EngineAction::PowerOn);
-        actions.push(EngineAction::Delay(500));
-        actions.push(EngineAction::HeatOn);
-        actions.push(EngineAction::Delay(200));
+        match self.settings.mode {
+            // Handled by the early return above.
+            ThermostatMode::Off => {}
+            ThermostatMode::Heat => match self.settings.control_strategy {
+                ControlStrategy::Hysteresis => self.evaluate_hysteresis(now_ms, actions),
+                ControlStrategy::Pid => self.evaluate_pid(now_ms, actions),
+                ControlStrategy::SetpointPid => self.evaluate_setpoint_pid(now_ms, actions),
+                ControlStrategy::TimeProportional => {
+                    self.evaluate_time_proportional(now_ms, actions)
+                }
+            },
+            ThermostatMode::Cool => self.evaluate_cool(now_ms, actions),
+            ThermostatMode::Auto => self.evaluate_auto(now_ms, actions),
+        }
+    }
 
-        let desired = Self::normalize_fireplace_temp(
-            self.settings.target_temp_f as i32 + self.settings.fireplace_offset_f,
-        );
-        self.fireplace_temp_f = desired;
-        actions.push(EngineAction::SetTemp(desired));
-        actions.push(EngineAction::Delay(200));
+    /// Whether humidity alone justifies running (or continuing to run) a
+    /// dehumidify cycle: `humidity_target` is set, the reading is fresh per
+    /// `is_humidity_data_valid`, and it's above `target + humidity_hysteresis_f`.
+    /// A stale reading reads as "not too humid" so a frozen sensor can't pin
+    /// the fireplace on indefinitely.
+    fn humidity_too_high(&self, now_ms: u64) -> bool {
+        match self.settings.humidity_target {
+            Some(target) => {
+                self.is_humidity_data_valid(now_ms)
+                    && self.current_humidity > target + self.settings.humidity_hysteresis_f
+            }
+            None => false,
+        }
+    }
 
-        // Fireplace defaults light to 4 on power-on; send 4 toggles to return to OFF.
-        self.light_level = 4;
-        for step in 0..4 {
-            actions.push(EngineAction::LightToggle);
-            self.advance_light_state();
-            if step < 3 {
-                actions.push(EngineAction::Delay(200));
+    /// Graded response to `thermal_load()` crossing a configured band, run
+    /// just ahead of the normal Heat dispatch so a runaway is throttled, then
+    /// cut off, well before the hard `absolute_max_temp_f` ceiling in
+    /// `evaluate_state` ever has to catch it cold. Only `Shutoff`/`Lockout`
+    /// take exclusive control of the fireplace this tick (signaled by
+    /// returning `true`, which tells the caller to skip the normal per-mode
+    /// dispatch); `Throttle` just narrows the heat-call upper bound that
+    /// `evaluate_hysteresis` stops at and lets dispatch continue normally.
+    fn apply_thermal_load_response(
+        &mut self,
+        now_ms: u64,
+        actions: &mut Vec<EngineAction>,
+    ) -> bool {
+        if self.thermal_lockout {
+            let recovered = self.current_temp_f
+                <= self.settings.target_temp_f - self.config.thermal_load.lockout_recovery_margin_f;
+            if recovered {
+                self.thermal_lockout = false;
+            } else {
+                self.state = ThermostatState::Idle;
+                return true;
             }
         }
 
-        self.fireplace_on = true;
-        self.heating_start_ms = Some(now_ms);
-        self.last_state_change_ms = Some(now_ms);
-        self.state = ThermostatState::Heating;
+        let band = self.thermal_load_band();
+        let escalating = band > self.last_thermal_band;
+        self.last_thermal_band = band;
+
+        match band {
+            ThermalLoadBand::Lockout => {
+                self.turn_fireplace_off(now_ms, actions);
+                self.thermal_lockout = true;
+                if escalating {
+                    actions.push(EngineAction::EmergencyLockout);
+                }
+                self.state = ThermostatState::Idle;
+                true
+            }
+            ThermalLoadBand::Shutoff => {
+                self.turn_fireplace_off(now_ms, actions);
+                self.state = ThermostatState::Idle;
+                true
+            }
+            ThermalLoadBand::Throttle => {
+                if escalating {
+                    actions.push(EngineAction::ThrottleHeat);
+                }
+                false
+            }
+            ThermalLoadBand::Nominal => false,
+        }
     }
 
-    fn turn_fireplace_off(&mut self, now_ms: u64, actions: &mut Vec<EngineAction>) {
-        if !self.fireplace_on {
+    /// Advances an in-progress `start_autotune` run by one tick: records a
+    /// step-response sample, then either aborts safely (stale sensor, mode
+    /// changed away from `Heat`, temperature reaching `absolute_max_temp_f`,
+    /// or the run simply taking longer than `config.autotune.max_duration_ms`
+    /// to settle) or, once the peak has held for `settle_window_ms`, hands
+    /// off to `finish_autotune` to fit the model and derive gains.
+    fn advance_autotune(&mut self, now_ms: u64, actions: &mut Vec<EngineAction>) {
+        let Some(state) = &self.autotune else {
+            return;
+        };
+        let elapsed_ms = now_ms.saturating_sub(state.start_ms);
+        let start_temp_f = state.start_temp_f;
+        let mut peak_temp_f = state.peak_temp_f;
+        let mut last_rise_ms = state.last_rise_ms;
+        let current_temp_f = self.current_temp_f;
+
+        let should_abort = !self.is_sensor_data_valid(now_ms)
+            || self.settings.mode != ThermostatMode::Heat
+            || current_temp_f >= self.config.absolute_max_temp_f
+            || elapsed_ms >= self.config.autotune.max_duration_ms;
+        if should_abort {
+            self.abort_autotune(now_ms, actions);
             return;
         }
 
-        actions.push(EngineAction::PowerOff);
-        self.fireplace_on = false;
-        self.heating_start_ms = None;
-        self.last_state_change_ms = Some(now_ms);
-        self.state = ThermostatState::Satisfied;
-    }
+        if current_temp_f > peak_temp_f {
+            peak_temp_f = current_temp_f;
+            last_rise_ms = now_ms;
+        }
+        let settled = peak_temp_f - start_temp_f >= self.config.autotune.noise_threshold_f
+            && now_ms.saturating_sub(last_rise_ms) >= self.config.autotune.settle_window_ms;
 
-    fn normalize_fireplace_temp(temp: i32) -> i32 {
-        let mut normalized = temp.clamp(60, 80);
-        if normalized % 2 != 0 {
-            normalized += 1;
+        if let Some(autotune) = self.autotune.as_mut() {
+            autotune.peak_temp_f = peak_temp_f;
+            autotune.last_rise_ms = last_rise_ms;
+            autotune.samples.push((elapsed_ms, current_temp_f));
         }
-        normalized
-    }
 
-    fn advance_light_state(&mut self) {
-        self.light_level = if self.light_level == 0 {
-            4
+        if settled {
+            self.finish_autotune(now_ms, actions);
         } else {
-            self.light_level - 1
-        };
+            self.state = ThermostatState::Heating;
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Safely abandons an in-progress autotune run: clears the run state,
+    /// cuts the heat call, and falls back to `ControlStrategy::Hysteresis`
+    /// so the thermostat doesn't sit on whatever half-tuned strategy the run
+    /// was meant to replace.
+    fn abort_autotune(&mut self, now_ms: u64, actions: &mut Vec<EngineAction>) {
+        self.autotune = None;
+        self.settings.control_strategy = ControlStrategy::Hysteresis;
+        self.turn_fireplace_off(now_ms, actions);
+        self.state = ThermostatState::Idle;
+    }
+
+    /// Fits the completed step response to a FOPDT model and derives PID
+    /// gains via the standard Ziegler-Nichols reaction-curve relations
+    /// (`kp = 1.2*tau/(K*L)`, `ki = kp/(2L)`, `kd = kp*0.5L`), writing them
+    /// into `config.time_proportional`. Falls back to
+    /// `ControlStrategy::Hysteresis` if the fit can't locate a valid dead
+    /// time or time constant.
+    fn finish_autotune(&mut self, now_ms: u64, actions: &mut Vec<EngineAction>) {
+        let Some(state) = self.autotune.take() else {
+            return;
+        };
+
+        let total_rise = state.peak_temp_f - state.start_temp_f;
+        let fit = self.fit_fopdt(&state.samples, state.start_temp_f, total_rise);
+        self.turn_fireplace_off(now_ms, actions);
+        self.state = ThermostatState::Satisfied;
+
+        let Some((dead_time_ms, time_constant_ms)) = fit else {
+            self.settings.control_strategy = ControlStrategy::Hysteresis;
+            return;
+        };
+
+        let dead_time_s = (dead_time_ms as f32 / 1_000.0).max(0.1);
+        let time_constant_s = (time_constant_ms as f32 / 1_000.0).max(0.1);
+
+        let kp = 1.2 * time_constant_s / (total_rise * dead_time_s);
+        let ki = kp / (2.0 * dead_time_s);
+        let kd = kp * 0.5 * dead_time_s;
+
+        self.config.time_proportional.kp = kp;
+        self.config.time_proportional.ki = ki;
+        self.config.time_proportional.kd = kd;
+
+        actions.push(EngineAction::AutotuneComplete { kp, ki, kd });
+    }
+
+    /// Locates the dead time `L` (elapsed time before the reading rises past
+    /// `config.autotune.noise_threshold_f` above `start_temp_f`) and the time
+    /// constant `tau` (elapsed time from `L` to 63.2% of `total_rise`) in a
+    /// recorded step response. Returns `None` if either point can't be found,
+    /// which `finish_autotune` treats as a failed fit.
+    fn fit_fopdt(
+        &self,
+        samples: &[(u64, f32)],
+        start_temp_f: f32,
+        total_rise: f32,
+    ) -> Option<(u64, u64)> {
+        let noise_threshold_f = self.config.autotune.noise_threshold_f;
+        let dead_time_ms = samples
+            .iter()
+            .find(|(_, temp_f)| *temp_f - start_temp_f >= noise_threshold_f)
+            .map(|(ms, _)| *ms)?;
+
+        let rise_63_temp_f = start_temp_f + 0.632 * total_rise;
+        let time_63_ms = samples
+            .iter()
+            .find(|(_, temp_f)| *temp_f >= rise_63_temp_f)
+            .map(|(ms, _)| *ms)?;
+
+        let time_constant_ms = time_63_ms.checked_sub(dead_time_ms).filter(|tau| *tau > 0)?;
+        Some((dead_time_ms, time_constant_ms))
+    }
+
+    /// Bang-bang heat control: calls for heat below `target - hysteresis`,
+    /// or if humidity alone justifies a dehumidify cycle even though
+    /// temperature is satisfied (see `humidity_too_high`); stops once the
+    /// room is above `target + hysteresis` (less `throttle_extra_hysteresis_f`
+    /// while `ThermalLoadBand::Throttle` is in force, so a duty cycle already
+    /// running high load cuts itself shorter) *and* humidity has also
+    /// cleared, so a dehumidify cycle that started with temperature already
+    /// in-band is allowed to run itself out rather than being cut short.
+    fn evaluate_hysteresis(&mut self, now_ms: u64, actions: &mut Vec<EngineAction>) {
+        let lower_bound = self.settings.target_temp_f - self.settings.hysteresis_f;
+        let throttle_margin = if self.last_thermal_band == ThermalLoadBand::Throttle {
+            self.config.thermal_load.throttle_extra_hysteresis_f
+        } else {
+            0.0
+        };
+        let upper_bound =
+            self.settings.target_temp_f + self.settings.hysteresis_f - throttle_margin;
+        let humidity_too_high = self.humidity_too_high(now_ms);
+
+        if !self.fireplace_on {
+            if self.current_temp_f < lower_bound || humidity_too_high {
+                if self.can_change_state(now_ms) {
+                    self.turn_device_on(ThermostatMode::Heat, now_ms, actions);
+                }
+            } else {
+                self.state = ThermostatState::Satisfied;
+            }
+        } else if self.current_temp_f > upper_bound && !humidity_too_high {
+            if self.can_change_state(now_ms) {
+                self.turn_fireplace_off(now_ms, actions);
+            }
+        } else {
+            self.state = ThermostatState::Heating;
+        }
+    }
+
+    /// Mirror of `evaluate_hysteresis` for `ThermostatMode::Cool`: calls for
+    /// cooling once the room rises above `target + hysteresis`, or if
+    /// humidity alone justifies a dehumidify cycle; stops once it falls back
+    /// to `target - hysteresis` *and* humidity has also cleared. Delegates to
+    /// `evaluate_cool_against`, which takes the setpoint as a parameter so
+    /// `evaluate_auto` can drive it off `auto_cool_setpoint_f` instead.
+    fn evaluate_cool(&mut self, now_ms: u64, actions: &mut Vec<EngineAction>) {
+        self.evaluate_cool_against(self.settings.target_temp_f, now_ms, actions);
+    }
+
+    fn evaluate_cool_against(&mut self, target_f: f32, now_ms: u64, actions: &mut Vec<EngineAction>) {
+        let lower_bound = target_f - self.settings.hysteresis_f;
+        let upper_bound = target_f + self.settings.hysteresis_f;
+        let humidity_too_high = self.humidity_too_high(now_ms);
+
+        if !self.fireplace_on {
+            if self.current_temp_f > upper_bound || humidity_too_high {
+                if self.can_change_state(now_ms) {
+                    self.turn_device_on(ThermostatMode::Cool, now_ms, actions);
+                }
+            } else {
+                self.state = ThermostatState::Satisfied;
+            }
+        } else if self.current_temp_f < lower_bound && !humidity_too_high {
+            if self.can_change_state(now_ms) {
+                self.turn_fireplace_off(now_ms, actions);
+            }
+        } else {
+            self.state = ThermostatState::Cooling;
+        }
+    }
+
+    /// Picks heating vs. cooling based on which side of its own setpoint the
+    /// room is on: below `target_temp_f - hysteresis` calls for heat, above
+    /// `auto_cool_setpoint_f + hysteresis` calls for cool. The two setpoints
+    /// are independent (unlike `Heat`/`Cool`, which both run off the single
+    /// `target_temp_f`) precisely so they can't fight each other by chasing
+    /// the same number. While a function is already running, defers to
+    /// `evaluate_hysteresis`/`evaluate_cool` to decide when it stops; the
+    /// device is only ever re-armed for the opposite function once it has
+    /// gone through idle (`active_hvac_mode == None`), so Auto can't flip
+    /// directly from heating to cooling or back in a single tick.
+    fn evaluate_auto(&mut self, now_ms: u64, actions: &mut Vec<EngineAction>) {
+        match self.active_hvac_mode {
+            Some(ThermostatMode::Heat) => self.evaluate_hysteresis(now_ms, actions),
+            Some(ThermostatMode::Cool) => {
+                self.evaluate_cool_against(self.settings.auto_cool_setpoint_f, now_ms, actions)
+            }
+            _ => {
+                let lower_bound = self.settings.target_temp_f - self.settings.hysteresis_f;
+                let upper_bound = self.settings.auto_cool_setpoint_f + self.settings.hysteresis_f;
+
+                if self.current_temp_f < lower_bound {
+                    if self.can_change_state(now_ms) {
+                        self.turn_device_on(ThermostatMode::Heat, now_ms, actions);
+                    }
+                } else if self.current_temp_f > upper_bound {
+                    if self.can_change_state(now_ms) {
+                        self.turn_device_on(ThermostatMode::Cool, now_ms, actions);
+                    }
+                } else if self.humidity_too_high(now_ms) {
+                    // Temperature is already satisfied; humidity alone picks
+                    // a function to dehumidify with, defaulting to Heat.
+                    if self.can_change_state(now_ms) {
+                        self.turn_device_on(
+                            self.dehumidify_mode().unwrap_or(ThermostatMode::Heat),
+                            now_ms,
+                            actions,
+                        );
+                    }
+                } else {
+                    self.state = ThermostatState::Satisfied;
+                }
+            }
+        }
+    }
+
+    /// Drives the fireplace with a time-proportioned duty cycle computed
+    /// from a PID loop instead of bang-bang hysteresis. Each `min_cycle_ms`
+    /// window, the PID output (0..1) is resampled and converted into an
+    /// on-duration within that window.
+    fn evaluate_pid(&mut self, now_ms: u64, actions: &mut Vec<EngineAction>) {
+        let window_ms = self.config.min_cycle_ms.max(1);
+        let window_elapsed = self
+            .pid_window_start_ms
+            .map_or(true, |start| now_ms.saturating_sub(start) >= window_ms);
+
+        if window_elapsed {
+            let output = self.compute_pid_output(now_ms);
+            self.pid_window_start_ms = Some(now_ms);
+            self.pid_window_on_ms = (output * window_ms as f32) as u64;
+        }
+
+        let window_start = self.pid_window_start_ms.unwrap_or(now_ms);
+        let should_be_on = now_ms.saturating_sub(window_start) < self.pid_window_on_ms;
+
+        if should_be_on && !self.fireplace_on {
+            self.turn_device_on(ThermostatMode::Heat, now_ms, actions);
+        } else if !should_be_on && self.fireplace_on {
+            self.turn_fireplace_off(now_ms, actions);
+        } else if self.fireplace_on {
+            self.state = ThermostatState::Heating;
+        } else {
+            self.state = ThermostatState::Satisfied;
+        }
+    }
+
+    /// Computes `error = target - sensor`, integrates with anti-windup
+    /// clamping, takes the derivative on measurement (not on error, to avoid
+    /// derivative kick on target-temp changes), and returns the clamped 0..1
+    /// control signal.
+    fn compute_pid_output(&mut self, now_ms: u64) -> f32 {
+        let params = self.config.pid;
+        let sensor = self.current_temp_f;
+        let error = self.settings.target_temp_f - sensor;
+
+        let dt_seconds = match self.pid_last_tick_ms {
+            Some(last) => (now_ms.saturating_sub(last) as f32 / 1_000.0).max(0.001),
+            None => (self.config.min_cycle_ms as f32 / 1_000.0).max(0.001),
+        };
+        self.pid_last_tick_ms = Some(now_ms);
+
+        self.pid_integral =
+            (self.pid_integral + error * dt_seconds).clamp(params.integral_min, params.integral_max);
+
+        let derivative = match self.pid_last_sensor_f {
+            Some(last_sensor) => -params.kd * (sensor - last_sensor) / dt_seconds,
+            None => 0.0,
+        };
+        self.pid_last_sensor_f = Some(sensor);
+
+        let output = params.kp * error + params.ki * self.pid_integral + derivative;
+        let output = output.clamp(params.output_min, params.output_max);
+        self.pid_last_output = output;
+        output
+    }
+
+    /// Last 0..1 PID output computed by `compute_pid_output`, for
+    /// `ControllerStatus::pid_output`. Stays at its last value (or `0.0`
+    /// before the PID strategy has ever run) while a different
+    /// `control_strategy` is active.
+    pub fn pid_output(&self) -> f32 {
+        self.pid_last_output
+    }
+
+    pub fn pid_parameters(&self) -> PidParameters {
+        self.config.pid
+    }
+
+    pub fn set_kp(&mut self, kp: f32) -> bool {
+        let clamped = kp.clamp(0.0, 50.0);
+        if (self.config.pid.kp - clamped).abs() > f32::EPSILON {
+            self.config.pid.kp = clamped;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn set_ki(&mut self, ki: f32) -> bool {
+        let clamped = ki.clamp(0.0, 50.0);
+        if (self.config.pid.ki - clamped).abs() > f32::EPSILON {
+            self.config.pid.ki = clamped;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn set_kd(&mut self, kd: f32) -> bool {
+        let clamped = kd.clamp(0.0, 1_000.0);
+        if (self.config.pid.kd - clamped).abs() > f32::EPSILON {
+            self.config.pid.kd = clamped;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Sets the PID output clamp `[out_min, out_max]`. Rejected (returns
+    /// `false`, no change) if `out_min >= out_max`.
+    pub fn set_pid_output_limits(&mut self, out_min: f32, out_max: f32) -> bool {
+        if out_min >= out_max {
+            return false;
+        }
+        self.config.pid.output_min = out_min;
+        self.config.pid.output_max = out_max;
+        true
+    }
+
+    /// Sets the independent hard alarm band `[low_f, high_f]` checked by
+    /// `update_sensor_data`/`tick` (see `alarm_high_f`/`alarm_low_f`).
+    /// Rejected (returns `false`, no change) if `low_f >= high_f`, the same
+    /// ordering guard as `set_pid_output_limits`.
+    pub fn set_alarm_limits(&mut self, low_f: f32, high_f: f32) -> bool {
+        if low_f >= high_f {
+            return false;
+        }
+        self.config.alarm_low_f = low_f;
+        self.config.alarm_high_f = high_f;
+        true
+    }
+
+    /// Same shape as `evaluate_pid`, but resampled over its own
+    /// `config.time_proportional.window_ms` period instead of reusing
+    /// `min_cycle_ms`, and with the on/off transition itself gated by
+    /// `can_change_state` so a very small duty cycle can't chatter the relay
+    /// faster than the minimum cycle allows.
+    fn evaluate_time_proportional(&mut self, now_ms: u64, actions: &mut Vec<EngineAction>) {
+        let window_ms = self.config.time_proportional.window_ms.max(1);
+        let window_elapsed = self
+            .time_proportional_window_start_ms
+            .map_or(true, |start| now_ms.saturating_sub(start) >= window_ms);
+
+        if window_elapsed {
+            let output = self.compute_time_proportional_output(now_ms);
+            self.time_proportional_window_start_ms = Some(now_ms);
+            self.time_proportional_window_on_ms = (output * window_ms as f32) as u64;
+        }
+
+        let window_start = self.time_proportional_window_start_ms.unwrap_or(now_ms);
+        let should_be_on =
+            now_ms.saturating_sub(window_start) < self.time_proportional_window_on_ms;
+
+        if should_be_on && !self.fireplace_on {
+            if self.can_change_state(now_ms) {
+                self.turn_device_on(ThermostatMode::Heat, now_ms, actions);
+            }
+        } else if !should_be_on && self.fireplace_on {
+            if self.can_change_state(now_ms) {
+                self.turn_fireplace_off(now_ms, actions);
+            }
+        } else if self.fireplace_on {
+            self.state = ThermostatState::Heating;
+        } else {
+            self.state = ThermostatState::Satisfied;
+        }
+    }
+
+    /// Same PID math as `compute_pid_output`, tracked with its own
+    /// integrator/derivative state so switching between `Pid` and
+    /// `TimeProportional` doesn't carry one strategy's accumulated error into
+    /// the other. The duty cycle is always clamped to a fixed `0..1` (unlike
+    /// `PidParameters`, `TimeProportionalParameters` has no configurable
+    /// output bounds).
+    fn compute_time_proportional_output(&mut self, now_ms: u64) -> f32 {
+        let params = self.config.time_proportional;
+        let sensor = self.current_temp_f;
+        let error = self.settings.target_temp_f - sensor;
+
+        let dt_seconds = match self.time_proportional_last_tick_ms {
+            Some(last) => (now_ms.saturating_sub(last) as f32 / 1_000.0).max(0.001),
+            None => (params.window_ms as f32 / 1_000.0).max(0.001),
+        };
+        self.time_proportional_last_tick_ms = Some(now_ms);
+
+        self.time_proportional_integral = (self.time_proportional_integral + error * dt_seconds)
+            .clamp(params.integral_min, params.integral_max);
+
+        let derivative = match self.time_proportional_last_sensor_f {
+            Some(last_sensor) => -params.kd * (sensor - last_sensor) / dt_seconds,
+            None => 0.0,
+        };
+        self.time_proportional_last_sensor_f = Some(sensor);
+
+        let output =
+            params.kp * error + params.ki * self.time_proportional_integral + derivative;
+        output.clamp(0.0, 1.0)
+    }
+
+    /// Same on/off banding as `evaluate_hysteresis`, but the fireplace's
+    /// setpoint is continuously tracked by [`Self::apply_setpoint_pid`]
+    /// instead of being fixed at the value picked when it turned on.
+    fn evaluate_setpoint_pid(&mut self, now_ms: u64, actions: &mut Vec<EngineAction>) {
+        let lower_bound = self.settings.target_temp_f - self.settings.hysteresis_f;
+        let upper_bound = self.settings.target_temp_f + self.settings.hysteresis_f;
+        let humidity_too_high = self.humidity_too_high(now_ms);
+
+        if !self.fireplace_on {
+            if self.current_temp_f < lower_bound || humidity_too_high {
+                if self.can_change_state(now_ms) {
+                    self.turn_device_on(ThermostatMode::Heat, now_ms, actions);
+                }
+            } else {
+                self.state = ThermostatState::Satisfied;
+            }
+        } else if self.current_temp_f > upper_bound && !humidity_too_high {
+            if self.can_change_state(now_ms) {
+                self.turn_fireplace_off(now_ms, actions);
+            }
+        } else {
+            self.state = ThermostatState::Heating;
+        }
+
+        if self.fireplace_on {
+            self.apply_setpoint_pid(now_ms, actions);
+        }
+    }
+
+    /// Computes `error = target - current`, integrates with anti-windup
+    /// clamping, takes the derivative on measurement (to avoid a kick when
+    /// the target temp changes), and maps the result onto the fireplace's
+    /// legal setpoint band around `target + fireplace_offset_f`. Only emits
+    /// `SetTemp` when the normalized setpoint actually changes.
+    fn apply_setpoint_pid(&mut self, now_ms: u64, actions: &mut Vec<EngineAction>) {
+        let params = self.config.setpoint_pid;
+        let current = self.current_temp_f;
+        let error = self.settings.target_temp_f - current;
+
+        let dt_seconds = match self.setpoint_pid_last_tick_ms {
+            Some(last) => (now_ms.saturating_sub(last) as f32 / 1_000.0).max(0.001),
+            None => (self.config.min_cycle_ms as f32 / 1_000.0).max(0.001),
+        };
+        self.setpoint_pid_last_tick_ms = Some(now_ms);
+
+        self.setpoint_pid_integral = (self.setpoint_pid_integral + error * dt_seconds)
+            .clamp(params.integral_min, params.integral_max);
+
+        let derivative = match self.setpoint_pid_last_temp_f {
+            Some(previous) => -(current - previous) / dt_seconds,
+            None => 0.0,
+        };
+        self.setpoint_pid_last_temp_f = Some(current);
+
+        let u = params.kp * error + params.ki * self.setpoint_pid_integral + params.kd * derivative;
+        let base = self.settings.target_temp_f as i32 + self.settings.fireplace_offset_f;
+        let desired = Self::normalize_fireplace_temp(base + u.round() as i32);
+
+        if desired != self.fireplace_temp_f {
+            self.fireplace_temp_f = desired;
+            actions.push(EngineAction::SetTemp(desired));
+        }
+    }
+
+    fn reset_setpoint_pid_state(&mut self) {
+        self.setpoint_pid_integral = 0.0;
+        self.setpoint_pid_last_temp_f = None;
+        self.setpoint_pid_last_tick_ms = None;
+    }
+
+    fn reset_pid_state(&mut self) {
+        self.pid_integral = 0.0;
+        self.pid_last_sensor_f = None;
+        self.pid_last_tick_ms = None;
+        self.pid_window_start_ms = None;
+        self.pid_window_on_ms = 0;
+    }
+
+    fn reset_time_proportional_state(&mut self) {
+        self.time_proportional_integral = 0.0;
+        self.time_proportional_last_sensor_f = None;
+        self.time_proportional_last_tick_ms = None;
+        self.time_proportional_window_start_ms = None;
+        self.time_proportional_window_on_ms = 0;
+    }
+
+    /// Advances the time-proportional PID's dt clock (without touching its
+    /// accumulated integral) across a tick spent in a safety stop, so the
+    /// stale-sensor or emergency-shutoff gap isn't later charged as a single
+    /// huge `dt` once control resumes.
+    fn freeze_time_proportional_clock(&mut self, now_ms: u64) {
+        if self.time_proportional_last_tick_ms.is_some() {
+            self.time_proportional_last_tick_ms = Some(now_ms);
+        }
+    }
+
+    fn can_change_state(&self, now_ms: u64) -> bool {
+        self.last_state_change_ms
+            .map(|last| now_ms.saturating_sub(last) >= self.config.min_cycle_ms)
+            .unwrap_or(true)
+    }
+
+    /// Which HVAC function a dehumidify cycle should run under the current
+    /// `settings.mode`: `Heat`/`Cool` run their own function, `Auto` keeps
+    /// whichever function is already engaged or defaults to `Heat` from
+    /// idle, and `Off` has no function to dehumidify with.
+    fn dehumidify_mode(&self) -> Option<ThermostatMode> {
+        match self.settings.mode {
+            ThermostatMode::Off => None,
+            ThermostatMode::Heat => Some(ThermostatMode::Heat),
+            ThermostatMode::Cool => Some(ThermostatMode::Cool),
+            ThermostatMode::Auto => Some(self.active_hvac_mode.unwrap_or(ThermostatMode::Heat)),
+        }
+    }
+
+    /// Drives the power-on IR sequence for either HVAC function: `mode` picks
+    /// `HeatOn` or `CoolOn`, everything else (power, setpoint, light reset)
+    /// is shared. Records `active_hvac_mode` so `evaluate_auto` knows which
+    /// function is currently engaged.
+    fn turn_device_on(
+        &mut self,
+        mode: ThermostatMode,
+        now_ms: u64,
+        actions: &mut Vec<EngineAction>,
+    ) {
+        if self.fireplace_on || self.fault.is_some() {
+            return;
+        }
+
+        actions.push(EngineAction::PowerOn);
+        actions.push(EngineAction::Delay(500));
+        actions.push(if mode == ThermostatMode::Cool {
+            EngineAction::CoolOn
+        } else {
+            EngineAction::HeatOn
+        });
+        actions.push(EngineAction::Delay(200));
+
+        let desired = Self::normalize_fireplace_temp(
+            self.settings.target_temp_f as i32 + self.settings.fireplace_offset_f,
+        );
+        self.fireplace_temp_f = desired;
+        actions.push(EngineAction::SetTemp(desired));
+        actions.push(EngineAction::Delay(200));
+
+        // Fireplace defaults light to 4 on power-on; send 4 toggles to return to OFF.
+        self.light_level = 4;
+        for step in 0..4 {
+            actions.push(EngineAction::LightToggle);
+            self.advance_light_state();
+            if step < 3 {
+                actions.push(EngineAction::Delay(200));
+            }
+        }
+
+        self.active_hvac_mode = Some(mode);
+        self.fireplace_on = true;
+        self.heating_start_ms = Some(now_ms);
+        self.last_state_change_ms = Some(now_ms);
+        self.state = if mode == ThermostatMode::Cool {
+            ThermostatState::Cooling
+        } else {
+            ThermostatState::Heating
+        };
+        self.heating_rate_sample_start_ms = Some(now_ms);
+        self.heating_rate_sample_temp_f = Some(self.current_temp_f);
+    }
+
+    fn turn_fireplace_off(&mut self, now_ms: u64, actions: &mut Vec<EngineAction>) {
+        if !self.fireplace_on {
+            return;
+        }
+
+        actions.push(EngineAction::PowerOff);
+        self.active_hvac_mode = None;
+        self.fireplace_on = false;
+        self.heating_start_ms = None;
+        self.last_state_change_ms = Some(now_ms);
+        self.state = ThermostatState::Satisfied;
+        self.heating_rate_sample_start_ms = None;
+        self.heating_rate_sample_temp_f = None;
+    }
+
+    /// Updates the smoothed °F/min heating-rate estimate used by
+    /// `should_preheat`, resampling every `trend_sample_interval_ms` while
+    /// the fireplace is actively heating.
+    fn update_heating_rate_estimate(&mut self, now_ms: u64) {
+        if !self.fireplace_on {
+            return;
+        }
+
+        let Some(sample_start) = self.heating_rate_sample_start_ms else {
+            return;
+        };
+        let elapsed_ms = now_ms.saturating_sub(sample_start);
+        if elapsed_ms < self.config.trend_sample_interval_ms {
+            return;
+        }
+
+        let Some(sample_temp) = self.heating_rate_sample_temp_f else {
+            return;
+        };
+
+        let elapsed_min = elapsed_ms as f32 / 60_000.0;
+        let observed = (self.current_temp_f - sample_temp) / elapsed_min;
+        if observed > 0.0 {
+            // Exponential smoothing so a single noisy sample can't swing the
+            // estimate too far.
+            self.heating_rate_f_per_min = 0.3 * observed + 0.7 * self.heating_rate_f_per_min;
+        }
+
+        self.heating_rate_sample_start_ms = Some(now_ms);
+        self.heating_rate_sample_temp_f = Some(self.current_temp_f);
+    }
+
+    /// Returns the estimated minutes-until-target given the current temp
+    /// and observed heating rate, `None` if the target is already met or
+    /// the rate is non-positive (can't estimate).
+    fn estimated_heatup_minutes(&self, target_temp_f: f32) -> Option<f32> {
+        let deficit = target_temp_f - self.current_temp_f;
+        if deficit <= 0.0 || self.heating_rate_f_per_min <= 0.0 {
+            return None;
+        }
+        Some(deficit / self.heating_rate_f_per_min)
+    }
+
+    /// Whether a scheduled heat-up transition to `target_temp_f` at
+    /// `transition_epoch` (Unix seconds) should be started now so the
+    /// target is reached on time, based on the observed heating rate.
+    /// Falls back to starting exactly on schedule when the rate can't
+    /// estimate a deficit (i.e. `estimated_heatup_minutes` returns `None`).
+    pub fn should_preheat(&self, target_temp_f: f32, transition_epoch: i64, now_epoch: i64) -> bool {
+        if self.fireplace_on || self.settings.mode == ThermostatMode::Off {
+            return false;
+        }
+
+        let Some(minutes) = self.estimated_heatup_minutes(target_temp_f) else {
+            return false;
+        };
+
+        let lead_secs = (minutes * 60.0).ceil() as i64;
+        let max_lookahead_secs = (self.config.preheat_max_lookahead_ms / 1_000) as i64;
+        let lead_secs = lead_secs.clamp(0, max_lookahead_secs);
+
+        now_epoch >= transition_epoch - lead_secs
+    }
+
+    fn normalize_fireplace_temp(temp: i32) -> i32 {
+        let mut normalized = temp.clamp(60, 80);
+        if normalized % 2 != 0 {
+            normalized += 1;
+        }
+        normalized
+    }
+
+    fn advance_light_state(&mut self) {
+        self.light_level = if self.light_level == 0 {
+            4
+        } else {
+            self.light_level - 1
+        };
+    }
+
+    /// Drives one `EngineMode` transition the same way `tick()` drives
+    /// `evaluate_state()`: runs `mode.update`, and if it hands back a new
+    /// mode, immediately runs that mode's `enter` too so the caller is never
+    /// left holding a mode it hasn't actually entered yet.
+    pub fn mode_tick(
+        &mut self,
+        mut mode: Box<dyn EngineMode>,
+        now_ms: u64,
+    ) -> (Box<dyn EngineMode>, Vec<EngineAction>) {
+        let (next, mut actions) = mode.update(self, now_ms);
+        let current = match next {
+            Some(mut next_mode) => {
+                actions.extend(mode.exit(self));
+                actions.extend(next_mode.enter(self));
+                next_mode
+            }
+            None => mode,
+        };
+        (current, actions)
+    }
+}
+
+/// Pluggable per-phase control sequencing, following the `Mode` trait
+/// architecture used by device automations like follow-heating-rust: each
+/// `EngineMode` owns the actions for one phase of a heating cycle and hands
+/// back the next mode (or `None` to stay) as conditions change, driven one
+/// step at a time by `ThermostatEngine::mode_tick`.
+///
+/// This is an alternate entry point alongside (not a replacement for)
+/// `tick()`/`evaluate_state()`, which remain the engine's primary, always-on
+/// loop — the fault latch, `apply_thermal_load_response`'s graded bands, and
+/// humidity-aware dehumidify cycling all live there and aren't rehosted onto
+/// mode transitions in this pass. `EngineMode` instead gives a caller that
+/// wants to compose a custom on/off sequence (e.g. a pre-purge before
+/// ignition) a place to do it without forking that safety core: the built-in
+/// `IdleMode`/`WarmupMode`/`HeatingMode`/`CooldownMode` reproduce the same
+/// bang-bang Heat startup sequence `evaluate_hysteresis`/`turn_device_on`
+/// already implement, and `PreHeatMode` composes a new one on top of
+/// `should_preheat`'s existing adaptive lead-time estimate, so swapping
+/// either in is a drop-in change rather than a rewrite.
+pub trait EngineMode: std::fmt::Debug {
+    /// Stable name for reporting which mode is active (logging, a future
+    /// `ControllerStatus`-style surface), independent of `{:?}`, which also
+    /// dumps field state.
+    fn name(&self) -> &'static str;
+    fn enter(&mut self, engine: &mut ThermostatEngine) -> Vec<EngineAction>;
+    fn update(
+        &mut self,
+        engine: &mut ThermostatEngine,
+        now_ms: u64,
+    ) -> (Option<Box<dyn EngineMode>>, Vec<EngineAction>);
+    /// Runs once right before `mode_tick` hands off to the next mode.
+    /// Default no-op, since most modes - including every built-in one here
+    /// - have nothing to release; override when a mode owns state that must
+    /// be torn down early (e.g. cancelling a timer-based ramp).
+    fn exit(&mut self, _engine: &mut ThermostatEngine) -> Vec<EngineAction> {
+        Vec::new()
+    }
+}
+
+impl Default for Box<dyn EngineMode> {
+    fn default() -> Self {
+        Box::new(IdleMode)
+    }
+}
+
+/// Waiting for a call for heat; transitions to `WarmupMode` (as
+/// `PreHeatMode` also does once its lead time elapses), mirroring `evaluate_hysteresis`'s `current_temp_f <
+/// lower_bound || humidity_too_high` turn-on condition.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IdleMode;
+
+impl EngineMode for IdleMode {
+    fn name(&self) -> &'static str {
+        "IDLE"
+    }
+
+    fn enter(&mut self, _engine: &mut ThermostatEngine) -> Vec<EngineAction> {
+        Vec::new()
+    }
+
+    fn update(
+        &mut self,
+        engine: &mut ThermostatEngine,
+        now_ms: u64,
+    ) -> (Option<Box<dyn EngineMode>>, Vec<EngineAction>) {
+        let lower_bound = engine.settings.target_temp_f - engine.settings.hysteresis_f;
+        let calls_for_heat =
+            engine.current_temp_f < lower_bound || engine.humidity_too_high(now_ms);
+        if engine.settings.mode == ThermostatMode::Heat
+            && calls_for_heat
+            && engine.can_change_state(now_ms)
+        {
+            (Some(Box::new(WarmupMode { started_ms: now_ms })), Vec::new())
+        } else {
+            (None, Vec::new())
+        }
+    }
+}
+
+/// Issues the `PowerOn` -> `Delay` -> `HeatOn` -> `Delay` -> `SetTemp` ->
+/// `Delay` -> `LightToggle`x4 startup sequence `turn_device_on` already
+/// asserts, then hands straight off to `HeatingMode` on the next tick.
+#[derive(Debug, Clone, Copy)]
+pub struct WarmupMode {
+    started_ms: u64,
+}
+
+impl EngineMode for WarmupMode {
+    fn name(&self) -> &'static str {
+        "WARMUP"
+    }
+
+    fn enter(&mut self, engine: &mut ThermostatEngine) -> Vec<EngineAction> {
+        let mut actions = Vec::new();
+        engine.turn_device_on(ThermostatMode::Heat, self.started_ms, &mut actions);
+        actions
+    }
+
+    fn update(
+        &mut self,
+        _engine: &mut ThermostatEngine,
+        _now_ms: u64,
+    ) -> (Option<Box<dyn EngineMode>>, Vec<EngineAction>) {
+        (Some(Box::new(HeatingMode)), Vec::new())
+    }
+}
+
+/// Actively heating; watches for the same `current_temp_f > upper_bound &&
+/// !humidity_too_high` stop condition `evaluate_hysteresis` uses, then
+/// shuts the fireplace off and moves to `CooldownMode`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeatingMode;
+
+impl EngineMode for HeatingMode {
+    fn name(&self) -> &'static str {
+        "HEATING"
+    }
+
+    fn enter(&mut self, _engine: &mut ThermostatEngine) -> Vec<EngineAction> {
+        Vec::new()
+    }
+
+    fn update(
+        &mut self,
+        engine: &mut ThermostatEngine,
+        now_ms: u64,
+    ) -> (Option<Box<dyn EngineMode>>, Vec<EngineAction>) {
+        let upper_bound = engine.settings.target_temp_f + engine.settings.hysteresis_f;
+        let satisfied =
+            engine.current_temp_f > upper_bound && !engine.humidity_too_high(now_ms);
+        if satisfied && engine.can_change_state(now_ms) {
+            let mut actions = Vec::new();
+            engine.turn_fireplace_off(now_ms, &mut actions);
+            (Some(Box::new(CooldownMode { started_ms: now_ms })), actions)
+        } else {
+            (None, Vec::new())
+        }
+    }
+}
+
+/// Brief settle period after the fireplace shuts off before the mode machine
+/// is willing to call for heat again, timed off `config.cooldown_duration_ms`
+/// independently of the engine's own `in_cooldown`/`max_runtime_ms` latch
+/// (a different feature: that one guards against a single call running too
+/// long, not against restarting one that just ended).
+#[derive(Debug, Clone, Copy)]
+pub struct CooldownMode {
+    started_ms: u64,
+}
+
+impl EngineMode for CooldownMode {
+    fn name(&self) -> &'static str {
+        "COOLDOWN"
+    }
+
+    fn enter(&mut self, _engine: &mut ThermostatEngine) -> Vec<EngineAction> {
+        Vec::new()
+    }
+
+    fn update(
+        &mut self,
+        engine: &mut ThermostatEngine,
+        now_ms: u64,
+    ) -> (Option<Box<dyn EngineMode>>, Vec<EngineAction>) {
+        if now_ms.saturating_sub(self.started_ms) >= engine.config.cooldown_duration_ms {
+            (Some(Box::new(IdleMode)), Vec::new())
+        } else {
+            (None, Vec::new())
+        }
+    }
+}
+
+/// Ramps toward target ahead of a scheduled transition, using the same
+/// adaptive lead-time estimate `should_preheat` already computes from the
+/// observed heating rate, rather than waiting for `IdleMode`'s plain
+/// hysteresis check to fire once the room has already drifted below
+/// `target - hysteresis`. Demonstrates `EngineMode`'s extension point: a
+/// caller wires this in ahead of `IdleMode` as the mode machine's resting
+/// state whenever it has a `(target_temp_f, transition_epoch)` lookahead
+/// from a schedule to offer, without forking `IdleMode`/`WarmupMode` to get
+/// there.
+#[derive(Debug, Clone, Copy)]
+pub struct PreHeatMode {
+    target_temp_f: f32,
+    transition_epoch: i64,
+}
+
+impl PreHeatMode {
+    pub fn new(target_temp_f: f32, transition_epoch: i64) -> Self {
+        Self {
+            target_temp_f,
+            transition_epoch,
+        }
+    }
+}
+
+impl EngineMode for PreHeatMode {
+    fn name(&self) -> &'static str {
+        "PRE_HEAT"
+    }
+
+    fn enter(&mut self, _engine: &mut ThermostatEngine) -> Vec<EngineAction> {
+        Vec::new()
+    }
+
+    fn update(
+        &mut self,
+        engine: &mut ThermostatEngine,
+        now_ms: u64,
+    ) -> (Option<Box<dyn EngineMode>>, Vec<EngineAction>) {
+        let now_epoch = (now_ms / 1_000) as i64;
+        let ready = engine.settings.mode == ThermostatMode::Heat
+            && engine.should_preheat(self.target_temp_f, self.transition_epoch, now_epoch)
+            && engine.can_change_state(now_ms);
+        if ready {
+            (Some(Box::new(WarmupMode { started_ms: now_ms })), Vec::new())
+        } else {
+            (None, Vec::new())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns_on_when_below_threshold() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        settings.hysteresis_f = 2.0;
+        engine.settings = settings;
+
+        engine.update_sensor_data(65.0, 40.0, 1_000);
+        let actions = engine.tick(300_999);
+
+        assert!(actions.contains(&EngineAction::PowerOn));
+        assert!(engine.is_fireplace_on());
+    }
+
+    #[test]
+    fn runtime_limit_triggers_cooldown() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        engine.settings = settings;
+        engine.fireplace_on = true;
+        engine.heating_start_ms = Some(0);
+
+        let actions = engine.tick(14_400_001);
+
+        assert!(actions.contains(&EngineAction::PowerOff));
+        assert!(!engine.is_fireplace_on());
+        assert!(engine.is_in_cooldown());
+        assert_eq!(engine.state(), ThermostatState::Cooldown);
+    }
+
+    #[test]
+    fn hold_expires() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        engine.enter_hold(Some(1_000), 100);
+
+        let _ = engine.tick(900);
+        assert!(engine.is_in_hold());
+
+        let _ = engine.tick(1_101);
+        assert!(!engine.is_in_hold());
+    }
+
+    #[test]
+    fn manual_heat_controls_respect_bounds() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+
+        engine.fireplace_temp_f = 80;
+        assert!(engine.manual_heat_up().is_empty());
+        assert_eq!(engine.fireplace_temp_f, 80);
+
+        engine.fireplace_temp_f = 60;
+        assert!(engine.manual_heat_down().is_empty());
+        assert_eq!(engine.fireplace_temp_f, 60);
+
+        engine.fireplace_temp_f = 78;
+        assert_eq!(engine.manual_heat_up(), vec![EngineAction::TempUp]);
+        assert_eq!(engine.fireplace_temp_f, 80);
+
+        engine.fireplace_temp_f = 62;
+        assert_eq!(engine.manual_heat_down(), vec![EngineAction::TempDown]);
+        assert_eq!(engine.fireplace_temp_f, 60);
+    }
+
+    #[test]
+    fn manual_light_toggle_cycles_levels() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut observed = Vec::new();
+
+        for _ in 0..5 {
+            assert_eq!(
+                engine.manual_light_toggle(),
+                vec![EngineAction::LightToggle]
+            );
+            observed.push(engine.light_level);
+        }
+
+        assert_eq!(observed, vec![4, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn manual_timer_toggle_cycles_states() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+
+        for state in 1..=10 {
+            assert_eq!(
+                engine.manual_timer_toggle(),
+                vec![EngineAction::TimerToggle]
+            );
+            assert_eq!(engine.timer_state, state);
+        }
+
+        assert_eq!(
+            engine.manual_timer_toggle(),
+            vec![EngineAction::TimerToggle]
+        );
+        assert_eq!(engine.timer_state, 0);
+    }
+
+    #[test]
+    fn thermal_load_throttle_band_narrows_heat_call_upper_bound() {
+        let mut config = ThermostatConfig::default();
+        config.thermal_load.throttle_load = 5;
+        config.thermal_load.throttle_extra_hysteresis_f = 10.0;
+        let mut engine = ThermostatEngine::new(config, PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        settings.hysteresis_f = 10.0;
+        engine.settings = settings;
+
+        engine.fireplace_on = true;
+        engine.heating_start_ms = Some(100);
+        // Load of (75-70)/25*255 ~= 51 clears throttle_load=5, narrowing the
+        // upper bound to 70+10-10=70: without throttling this temperature
+        // would stay comfortably inside the normal [60, 80] band.
+        engine.update_sensor_data(75.0, 40.0, 500);
+
+        let actions = engine.tick(600);
+
+        assert!(actions.contains(&EngineAction::ThrottleHeat));
+        assert!(actions.contains(&EngineAction::PowerOff));
+        assert!(!engine.is_fireplace_on());
+        assert_eq!(engine.thermal_load_band(), ThermalLoadBand::Throttle);
+    }
+
+    #[test]
+    fn thermal_load_shutoff_band_cuts_power_before_absolute_max() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        engine.settings = settings;
+
+        engine.fireplace_on = true;
+        engine.heating_start_ms = Some(100);
+        // Load of (88-70)/25*255 ~= 184 clears the default shutoff_load=180
+        // well short of absolute_max_temp_f=95.
+        engine.update_sensor_data(88.0, 40.0, 500);
+
+        let actions = engine.tick(600);
+
+        assert_eq!(engine.thermal_load_band(), ThermalLoadBand::Shutoff);
+        assert!(actions.contains(&EngineAction::PowerOff));
+        assert!(!engine.is_fireplace_on());
+        assert_eq!(engine.state(), ThermostatState::Idle);
+        assert!(!engine.is_fault_active());
+    }
+
+    #[test]
+    fn thermal_load_lockout_band_latches_until_margin_clears() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        engine.settings = settings;
+
+        engine.fireplace_on = true;
+        engine.heating_start_ms = Some(100);
+        // Load of (93-70)/25*255 ~= 235 clears the default lockout_load=230,
+        // still below absolute_max_temp_f=95 so the hard ceiling never trips.
+        engine.update_sensor_data(93.0, 40.0, 500);
+
+        let actions = engine.tick(600);
+        assert_eq!(engine.thermal_load_band(), ThermalLoadBand::Lockout);
+        assert!(actions.contains(&EngineAction::EmergencyLockout));
+        assert!(!engine.is_fireplace_on());
+
+        // Room falls back under the lower hysteresis bound (68), which would
+        // normally call for heat again, but it hasn't yet fallen the
+        // `lockout_recovery_margin_f` (3.0 by default) below target (67), so
+        // the lockout latch refuses it.
+        engine.update_sensor_data(67.5, 40.0, 700);
+        let actions = engine.tick(800);
+        assert!(!actions.contains(&EngineAction::PowerOn));
+        assert!(!engine.is_fireplace_on());
+
+        // Only once the room falls below that recovery margin does the
+        // lockout clear and heat resume (past `min_cycle_ms` since the
+        // lockout's shutoff, same as any other call-for-heat debounce).
+        engine.update_sensor_data(66.5, 40.0, 300_600);
+        let actions = engine.tick(300_700);
+        assert!(actions.contains(&EngineAction::PowerOn));
+        assert!(engine.is_fireplace_on());
+    }
+
+    #[test]
+    fn mode_off_immediately_turns_fireplace_off() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        engine.settings.mode = ThermostatMode::Heat;
+        engine.fireplace_on = true;
+        engine.heating_start_ms = Some(1_000);
+        engine.last_state_change_ms = Some(1_200);
+
+        let (changed, actions) = engine.set_mode_with_actions(ThermostatMode::Off, 1_300);
+
+        assert!(changed);
+        assert_eq!(actions, vec![EngineAction::PowerOff]);
+        assert!(!engine.is_fireplace_on());
+        assert_eq!(engine.state(), ThermostatState::Idle);
+    }
+
+    #[test]
+    fn odd_target_rounds_up_to_even() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 69.0;
+        settings.fireplace_offset_f = 4;
+        engine.settings = settings;
+
+        engine.update_sensor_data(60.0, 40.0, 0);
+        let actions = engine.tick(299_999);
+
+        assert!(actions.contains(&EngineAction::SetTemp(74)));
+    }
+
+    #[test]
+    fn power_on_sequence_contains_required_delays() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        settings.hysteresis_f = 2.0;
+        engine.settings = settings;
+
+        engine.update_sensor_data(65.0, 40.0, 1_000);
+        let actions = engine.tick(300_999);
+
+        assert_eq!(actions.first(), Some(&EngineAction::PowerOn));
+        assert_eq!(actions.get(1), Some(&EngineAction::Delay(500)));
+        assert_eq!(actions.get(2), Some(&EngineAction::HeatOn));
+        assert_eq!(actions.get(3), Some(&EngineAction::Delay(200)));
+    }
+
+    #[test]
+    fn sensor_stale_bypasses_min_cycle() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        engine.settings = settings;
+
+        engine.fireplace_on = true;
+        engine.heating_start_ms = Some(100);
+        engine.last_state_change_ms = Some(100);
+        engine.update_sensor_data(70.0, 40.0, 100);
+
+        // Sensor goes stale (300s timeout exceeded)
+        let actions = engine.tick(300_101);
+
+        assert!(actions.contains(&EngineAction::PowerOff));
+        assert!(!engine.is_fireplace_on());
+        assert_eq!(engine.state(), ThermostatState::Idle);
+    }
+
+    #[test]
+    fn absolute_max_temp_emergency_shutoff() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        engine.settings = settings;
+
+        engine.fireplace_on = true;
+        engine.heating_start_ms = Some(100);
+        engine.update_sensor_data(95.0, 40.0, 500);
+
+        let actions = engine.tick(600);
+
+        assert!(actions.contains(&EngineAction::PowerOff));
+        assert!(!engine.is_fireplace_on());
+        assert_eq!(engine.state(), ThermostatState::Idle);
+    }
+
+    #[test]
+    fn pid_strategy_turns_on_when_far_below_target() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        settings.control_strategy = ControlStrategy::Pid;
+        engine.settings = settings;
+
+        engine.update_sensor_data(60.0, 40.0, 1_000);
+        let actions = engine.tick(300_999);
+
+        assert!(actions.contains(&EngineAction::PowerOn));
+        assert!(engine.is_fireplace_on());
+    }
+
+    #[test]
+    fn pid_strategy_stays_off_when_at_target() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        settings.control_strategy = ControlStrategy::Pid;
+        engine.settings = settings;
+
+        engine.update_sensor_data(70.0, 40.0, 1_000);
+        let actions = engine.tick(300_999);
+
+        assert!(!actions.contains(&EngineAction::PowerOn));
+        assert!(!engine.is_fireplace_on());
+    }
+
+    #[test]
+    fn set_control_strategy_resets_pid_integral() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+
+        assert!(engine.set_control_strategy(ControlStrategy::Pid));
+        assert!(!engine.set_control_strategy(ControlStrategy::Pid));
+        assert_eq!(engine.pid_integral, 0.0);
+    }
+
+    #[test]
+    fn mode_off_bypasses_min_cycle() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        engine.settings = settings;
+
+        engine.fireplace_on = true;
+        engine.heating_start_ms = Some(100);
+        engine.last_state_change_ms = Some(100);
+        engine.update_sensor_data(70.0, 40.0, 100);
+
+        // Set mode to Off immediately (within min_cycle window)
+        let (changed, actions) = engine.set_mode_with_actions(ThermostatMode::Off, 200);
+
+        assert!(changed);
+        assert!(actions.contains(&EngineAction::PowerOff));
+        assert!(!engine.is_fireplace_on());
+        assert_eq!(engine.state(), ThermostatState::Idle);
+    }
+
+    #[test]
+    fn heating_rate_estimate_updates_after_sample_interval() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 90.0;
+        engine.settings = settings;
+
+        engine.update_sensor_data(60.0, 40.0, 1_000);
+        engine.tick(300_999);
+        assert!(engine.is_fireplace_on());
+
+        let default_rate = engine.heating_rate_f_per_min;
+
+        // 2°F over 40s (beyond the 30s trend_sample_interval_ms) is a brisk
+        // 3°F/min, which should pull the smoothed estimate up from default.
+        engine.update_sensor_data(62.0, 40.0, 340_999);
+        engine.tick(340_999);
+
+        assert!(engine.heating_rate_f_per_min > default_rate);
+    }
+
+    #[test]
+    fn should_preheat_when_estimated_start_time_has_passed() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        engine.settings.mode = ThermostatMode::Heat;
+        engine.update_sensor_data(65.0, 40.0, 0);
+
+        // Default rate 0.5°F/min, 5°F deficit => 10 minutes (600s) lead time.
+        assert!(engine.should_preheat(70.0, 1_000 + 500, 1_000));
+        assert!(!engine.should_preheat(70.0, 1_000 + 1_000, 1_000));
+    }
+
+    #[test]
+    fn should_preheat_clamps_to_max_lookahead() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        engine.settings.mode = ThermostatMode::Heat;
+        // A huge deficit would imply a lead time far beyond
+        // preheat_max_lookahead_ms (2 hours by default), so it should clamp.
+        engine.update_sensor_data(10.0, 40.0, 0);
+
+        assert!(engine.should_preheat(90.0, 1_000 + 7_199, 1_000));
+        assert!(!engine.should_preheat(90.0, 1_000 + 7_201, 1_000));
+    }
+
+    #[test]
+    fn should_preheat_false_once_fireplace_is_already_on() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        engine.settings.mode = ThermostatMode::Heat;
+        engine.update_sensor_data(65.0, 40.0, 0);
+        engine.fireplace_on = true;
+
+        assert!(!engine.should_preheat(70.0, 1_500, 1_000));
+    }
+
+    #[test]
+    fn setpoint_pid_strategy_turns_on_when_far_below_target() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        settings.control_strategy = ControlStrategy::SetpointPid;
+        engine.settings = settings;
+
+        engine.update_sensor_data(60.0, 40.0, 1_000);
+        let actions = engine.tick(300_999);
+
+        assert!(actions.contains(&EngineAction::PowerOn));
+        assert!(engine.is_fireplace_on());
+    }
+
+    #[test]
+    fn setpoint_pid_strategy_stays_off_when_at_target() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        settings.control_strategy = ControlStrategy::SetpointPid;
+        engine.settings = settings;
+
+        engine.update_sensor_data(70.0, 40.0, 1_000);
+        let actions = engine.tick(300_999);
+
+        assert!(!actions.contains(&EngineAction::PowerOn));
+        assert!(!engine.is_fireplace_on());
+    }
+
+    #[test]
+    fn setpoint_pid_raises_setpoint_above_base_when_room_is_cold() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        settings.fireplace_offset_f = 4;
+        settings.control_strategy = ControlStrategy::SetpointPid;
+        engine.settings = settings;
+        engine.fireplace_on = true;
+        engine.heating_start_ms = Some(0);
+        engine.fireplace_temp_f = 74;
+
+        engine.update_sensor_data(60.0, 40.0, 1_000);
+        let actions = engine.tick(300_999);
+
+        assert!(actions
+            .iter()
+            .any(|action| matches!(action, EngineAction::SetTemp(n) if *n > 74)));
+    }
+
+    #[test]
+    fn setpoint_pid_holds_setpoint_when_output_is_negligible() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        settings.fireplace_offset_f = 4;
+        settings.control_strategy = ControlStrategy::SetpointPid;
+        engine.settings = settings;
+        engine.fireplace_on = true;
+        engine.heating_start_ms = Some(0);
+        engine.fireplace_temp_f = 74;
+
+        engine.update_sensor_data(70.0, 40.0, 1_000);
+        let actions = engine.tick(300_999);
+
+        assert!(!actions
+            .iter()
+            .any(|action| matches!(action, EngineAction::SetTemp(_))));
+        assert_eq!(engine.fireplace_temp_f, 74);
+    }
+
+    #[test]
+    fn reset_safety_clears_setpoint_pid_integral() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        engine.setpoint_pid_integral = 5.0;
+        engine.setpoint_pid_last_temp_f = Some(68.0);
+        engine.setpoint_pid_last_tick_ms = Some(1_000);
+
+        engine.reset_safety(1_000);
+
+        assert_eq!(engine.setpoint_pid_integral, 0.0);
+        assert_eq!(engine.setpoint_pid_last_temp_f, None);
+        assert_eq!(engine.setpoint_pid_last_tick_ms, None);
+    }
+
+    #[test]
+    fn save_and_apply_profile_restores_settings() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        engine.settings.mode = ThermostatMode::Heat;
+        engine.settings.target_temp_f = 68.0;
+        let away_id = engine.save_profile("Away".to_string());
+
+        engine.set_mode(ThermostatMode::Off);
+        engine.set_target_temp(76.0);
+        assert_ne!(engine.settings().target_temp_f, 68.0);
+
+        let (changed, _) = engine.apply_profile(away_id, 1_000);
+
+        assert!(changed);
+        assert_eq!(engine.settings().mode, ThermostatMode::Heat);
+        assert_eq!(engine.settings().target_temp_f, 68.0);
+        assert_eq!(engine.active_profile_id(), Some(away_id));
+        assert_eq!(engine.list_profiles().len(), 1);
+    }
+
+    #[test]
+    fn apply_profile_is_noop_while_held() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let profile_id = engine.save_profile("Evening".to_string());
+        engine.enter_hold(Some(1_000), 0);
+
+        let (changed, actions) = engine.apply_profile(profile_id, 100);
+
+        assert!(!changed);
+        assert!(actions.is_empty());
+        assert_eq!(engine.active_profile_id(), Some(profile_id));
+    }
+
+    #[test]
+    fn settings_summary_reports_current_values_and_bounds() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        engine.settings.target_temp_f = 72.0;
+        engine.settings.fireplace_offset_f = 6;
+        engine.fireplace_temp_f = 76;
+
+        let summary = engine.settings_summary();
+
+        assert_eq!(summary.target_temp.value, 72.0);
+        assert_eq!(summary.target_temp.min, 60.0);
+        assert_eq!(summary.target_temp.max, 84.0);
+        assert_eq!(summary.fireplace_offset.value, 6.0);
+        assert_eq!(summary.fireplace_offset.step, 2.0);
+        assert_eq!(summary.fireplace_temp.value, 76.0);
+    }
+
+    #[test]
+    fn update_sensor_raw_converts_resistance_to_temp() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+
+        // A 10k NTC at room temperature (~25C / 77F) should read close to
+        // its nominal resistance.
+        engine.update_sensor_raw(10_000.0, 40.0, 1_000);
+
+        assert!(engine.last_sensor_update_ms().is_some());
+        assert!((engine.current_temp_f() - 77.0).abs() < 5.0);
+    }
+
+    #[test]
+    fn update_sensor_raw_rejects_non_positive_resistance() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+
+        engine.update_sensor_raw(-1.0, 40.0, 1_000);
+
+        assert_eq!(engine.last_sensor_update_ms(), None);
+        assert!(!engine.is_sensor_data_valid(1_000));
+    }
+
+    #[test]
+    fn update_sensor_raw_rejects_out_of_range_conversion() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+
+        // An absurdly large resistance converts to a temperature far below
+        // `min_valid_temp_f`.
+        engine.update_sensor_raw(1.0e12, 40.0, 1_000);
+
+        assert_eq!(engine.last_sensor_update_ms(), None);
+    }
+
+    #[test]
+    fn delete_profile_clears_active_selection() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let profile_id = engine.save_profile("Guest".to_string());
+
+        assert!(engine.delete_profile(profile_id));
+        assert!(engine.list_profiles().is_empty());
+        assert_eq!(engine.active_profile_id(), None);
+        assert!(!engine.delete_profile(profile_id));
+    }
+
+    #[test]
+    fn cool_mode_turns_on_when_above_threshold() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Cool;
+        settings.target_temp_f = 70.0;
+        settings.hysteresis_f = 2.0;
+        engine.settings = settings;
+
+        engine.update_sensor_data(75.0, 40.0, 1_000);
+        let actions = engine.tick(300_999);
+
+        assert!(actions.contains(&EngineAction::CoolOn));
+        assert!(engine.is_fireplace_on());
+        assert_eq!(engine.state(), ThermostatState::Cooling);
+    }
+
+    #[test]
+    fn cool_mode_turns_off_at_lower_bound() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Cool;
+        settings.target_temp_f = 70.0;
+        settings.hysteresis_f = 2.0;
+        engine.settings = settings;
+
+        engine.fireplace_on = true;
+        engine.active_hvac_mode = Some(ThermostatMode::Cool);
+        engine.heating_start_ms = Some(100);
+        engine.last_state_change_ms = Some(100);
+        engine.update_sensor_data(67.0, 40.0, 1_000);
+
+        let actions = engine.tick(300_101);
+
+        assert!(actions.contains(&EngineAction::PowerOff));
+        assert!(!engine.is_fireplace_on());
+        assert_eq!(engine.state(), ThermostatState::Satisfied);
+    }
+
+    #[test]
+    fn cool_mode_respects_min_cycle_and_stale_sensor_safety_paths() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Cool;
+        settings.target_temp_f = 70.0;
+        settings.hysteresis_f = 2.0;
+        engine.settings = settings;
+
+        // Min-cycle: a call-for-cooling is suppressed until min_cycle_ms has
+        // elapsed since the last state change.
+        engine.last_state_change_ms = Some(0);
+        engine.update_sensor_data(80.0, 40.0, 1_000);
+        let actions = engine.tick(1_100);
+        assert!(!actions.contains(&EngineAction::CoolOn));
+        assert!(!engine.is_fireplace_on());
+
+        // Sensor stale: an active cooling call shuts off even mid-cycle.
+        engine.fireplace_on = true;
+        engine.active_hvac_mode = Some(ThermostatMode::Cool);
+        engine.heating_start_ms = Some(100);
+        engine.update_sensor_data(80.0, 40.0, 100);
+        let actions = engine.tick(300_101);
+        assert!(actions.contains(&EngineAction::PowerOff));
+        assert!(!engine.is_fireplace_on());
+        assert_eq!(engine.state(), ThermostatState::Idle);
+    }
+
+    #[test]
+    fn auto_mode_deadband_selects_heat_or_cool_from_idle() {
+        let mut settings = PersistedSettings::default();
+        settings.mode = ThermostatMode::Auto;
+        settings.target_temp_f = 70.0;
+        settings.hysteresis_f = 2.0;
+        settings.auto_cool_setpoint_f = 72.0;
+
+        let mut heat_engine =
+            ThermostatEngine::new(ThermostatConfig::default(), settings.clone());
+        heat_engine.update_sensor_data(65.0, 40.0, 1_000);
+        let heat_actions = heat_engine.tick(300_999);
+        assert!(heat_actions.contains(&EngineAction::HeatOn));
+        assert!(heat_engine.is_fireplace_on());
+        assert_eq!(heat_engine.state(), ThermostatState::Heating);
+
+        let mut cool_engine = ThermostatEngine::new(ThermostatConfig::default(), settings);
+        cool_engine.update_sensor_data(75.0, 40.0, 1_000);
+        let cool_actions = cool_engine.tick(300_999);
+        assert!(cool_actions.contains(&EngineAction::CoolOn));
+        assert!(cool_engine.is_fireplace_on());
+        assert_eq!(cool_engine.state(), ThermostatState::Cooling);
+    }
+
+    #[test]
+    fn auto_mode_never_switches_heat_to_cool_without_passing_through_idle() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Auto;
+        settings.target_temp_f = 70.0;
+        settings.hysteresis_f = 2.0;
+        settings.auto_cool_setpoint_f = 72.0;
+        engine.settings = settings;
+
+        engine.update_sensor_data(65.0, 40.0, 1_000);
+        engine.tick(300_999);
+        assert!(engine.is_fireplace_on());
+
+        // Room swings far above the cooling threshold, but only min_cycle_ms
+        // has elapsed: the engine must turn the heat off and go idle first,
+        // never emit CoolOn in the same tick it's still actively heating.
+        engine.update_sensor_data(80.0, 40.0, 601_000);
+        let actions = engine.tick(601_100);
+        assert!(actions.contains(&EngineAction::PowerOff));
+        assert!(!actions.contains(&EngineAction::CoolOn));
+        assert!(!engine.is_fireplace_on());
+        assert_eq!(engine.state(), ThermostatState::Satisfied);
+
+        // Only on a subsequent tick, now idle, does it engage cooling.
+        engine.update_sensor_data(80.0, 40.0, 901_100);
+        let actions = engine.tick(901_200);
+        assert!(actions.contains(&EngineAction::CoolOn));
+        assert!(engine.is_fireplace_on());
+        assert_eq!(engine.state(), ThermostatState::Cooling);
+    }
+
+    #[test]
+    fn time_proportional_strategy_turns_on_when_far_below_target() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        settings.control_strategy = ControlStrategy::TimeProportional;
+        engine.settings = settings;
+
+        engine.update_sensor_data(60.0, 40.0, 1_000);
+        let actions = engine.tick(300_999);
+
+        assert!(actions.contains(&EngineAction::PowerOn));
+        assert!(engine.is_fireplace_on());
+    }
 
     #[test]
-    fn turns_on_when_below_threshold() {
+    fn time_proportional_strategy_stays_off_when_at_target() {
         let mut engine =
             ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
         let mut settings = engine.settings.clone();
         settings.mode = ThermostatMode::Heat;
         settings.target_temp_f = 70.0;
-        settings.hysteresis_f = 2.0;
+        settings.control_strategy = ControlStrategy::TimeProportional;
         engine.settings = settings;
 
-        engine.update_sensor_data(65.0, 40.0, 1_000);
+        engine.update_sensor_data(70.0, 40.0, 1_000);
         let actions = engine.tick(300_999);
 
+        assert!(!actions.contains(&EngineAction::PowerOn));
+        assert!(!engine.is_fireplace_on());
+    }
+
+    #[test]
+    fn time_proportional_strategy_respects_min_cycle_guard() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        settings.control_strategy = ControlStrategy::TimeProportional;
+        engine.settings = settings;
+
+        // A call-for-heat is computed (far below target, full duty), but the
+        // min-cycle guard suppresses actually turning on this soon after the
+        // last state change.
+        engine.last_state_change_ms = Some(0);
+        engine.update_sensor_data(50.0, 40.0, 1_000);
+        let actions = engine.tick(1_100);
+
+        assert!(!actions.contains(&EngineAction::PowerOn));
+        assert!(!engine.is_fireplace_on());
+    }
+
+    #[test]
+    fn time_proportional_freezes_integrator_clock_during_emergency_shutoff() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        settings.control_strategy = ControlStrategy::TimeProportional;
+        engine.settings = settings;
+
+        engine.update_sensor_data(60.0, 40.0, 1_000);
+        engine.tick(300_999);
+        assert!(engine.is_fireplace_on());
+        let last_tick_before = engine.time_proportional_last_tick_ms;
+        assert!(last_tick_before.is_some());
+
+        // Room spikes over the absolute max ceiling; emergency shutoff
+        // engages for a long stretch before it clears.
+        engine.update_sensor_data(96.0, 40.0, 301_000);
+        engine.tick(301_100);
+        assert!(!engine.is_fireplace_on());
+
+        engine.update_sensor_data(70.0, 40.0, 4_000_000);
+        engine.tick(4_000_100);
+
+        // The frozen clock advances across the shutoff instead of sitting at
+        // the original on-time, so a stale gap of hours doesn't get charged
+        // to the control loop as one giant `dt` once it resumes.
+        assert!(engine.time_proportional_last_tick_ms.unwrap() >= 301_100);
+    }
+
+    #[test]
+    fn set_control_strategy_resets_time_proportional_integral() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+
+        assert!(engine.set_control_strategy(ControlStrategy::TimeProportional));
+        assert!(!engine.set_control_strategy(ControlStrategy::TimeProportional));
+        assert_eq!(engine.time_proportional_integral, 0.0);
+    }
+
+    #[test]
+    fn dehumidify_runs_cycle_even_when_temperature_satisfied() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        settings.hysteresis_f = 2.0;
+        settings.humidity_target = Some(50.0);
+        engine.settings = settings;
+
+        // Temperature is already satisfied, but humidity is 10 points above
+        // `target + humidity_hysteresis_f` (default 5.0), so a dehumidify
+        // cycle should start anyway.
+        engine.update_sensor_data(70.0, 65.0, 1_000);
+        let actions = engine.tick(1_100);
+
         assert!(actions.contains(&EngineAction::PowerOn));
         assert!(engine.is_fireplace_on());
     }
 
     #[test]
-    fn runtime_limit_triggers_cooldown() {
+    fn dehumidify_cycle_keeps_running_past_upper_bound_until_humidity_clears() {
         let mut engine =
             ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
         let mut settings = engine.settings.clone();
         settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        settings.hysteresis_f = 2.0;
+        settings.humidity_target = Some(50.0);
         engine.settings = settings;
-        engine.fireplace_on = true;
-        engine.heating_start_ms = Some(0);
 
-        let actions = engine.tick(14_400_001);
+        engine.update_sensor_data(70.0, 65.0, 1_000);
+        engine.tick(1_100);
+        assert!(engine.is_fireplace_on());
 
+        // Room has overheated past the upper bound, but humidity is still
+        // high: the normal "stop at upper bound" rule must not cut the
+        // dehumidify cycle short.
+        engine.update_sensor_data(73.0, 65.0, 310_000);
+        engine.tick(310_100);
+        assert!(engine.is_fireplace_on());
+
+        // Humidity finally clears; now both conditions agree it's time to
+        // stop.
+        engine.update_sensor_data(73.0, 50.0, 310_200);
+        let actions = engine.tick(310_300);
         assert!(actions.contains(&EngineAction::PowerOff));
         assert!(!engine.is_fireplace_on());
-        assert!(engine.is_in_cooldown());
-        assert_eq!(engine.state(), ThermostatState::Cooldown);
     }
 
     #[test]
-    fn hold_expires() {
+    fn dehumidify_ignores_stale_humidity_reading() {
         let mut engine =
             ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
-        engine.enter_hold(Some(1_000), 100);
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        settings.hysteresis_f = 2.0;
+        settings.humidity_target = Some(50.0);
+        engine.settings = settings;
 
-        let _ = engine.tick(900);
-        assert!(engine.is_in_hold());
+        engine.update_sensor_data(70.0, 65.0, 0);
+        // Humidity is republished unchanged well past
+        // `humidity_stale_timeout_ms`, so `last_humidity_change_ms` never
+        // advances past 0 even though the temp reading itself stays fresh.
+        engine.update_sensor_data(70.0, 65.0, 1_900_000);
+        let actions = engine.tick(1_900_100);
 
-        let _ = engine.tick(1_101);
-        assert!(!engine.is_in_hold());
+        assert!(!actions.contains(&EngineAction::PowerOn));
+        assert!(!engine.is_fireplace_on());
     }
 
     #[test]
-    fn manual_heat_controls_respect_bounds() {
+    fn auto_mode_dehumidifies_from_idle_when_temperature_satisfied() {
         let mut engine =
             ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Auto;
+        settings.target_temp_f = 70.0;
+        settings.hysteresis_f = 2.0;
+        settings.humidity_target = Some(50.0);
+        engine.settings = settings;
 
-        engine.fireplace_temp_f = 80;
-        assert!(engine.manual_heat_up().is_empty());
-        assert_eq!(engine.fireplace_temp_f, 80);
+        engine.update_sensor_data(70.0, 65.0, 1_000);
+        engine.tick(1_100);
 
-        engine.fireplace_temp_f = 60;
-        assert!(engine.manual_heat_down().is_empty());
-        assert_eq!(engine.fireplace_temp_f, 60);
+        assert!(engine.is_fireplace_on());
+        assert_eq!(engine.active_hvac_mode, Some(ThermostatMode::Heat));
+    }
 
-        engine.fireplace_temp_f = 78;
-        assert_eq!(engine.manual_heat_up(), vec![EngineAction::TempUp]);
-        assert_eq!(engine.fireplace_temp_f, 80);
+    #[test]
+    fn set_humidity_target_clamps_and_toggles() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
 
-        engine.fireplace_temp_f = 62;
-        assert_eq!(engine.manual_heat_down(), vec![EngineAction::TempDown]);
-        assert_eq!(engine.fireplace_temp_f, 60);
+        assert!(engine.set_humidity_target(Some(90.0)));
+        assert_eq!(engine.settings().humidity_target, Some(80.0));
+        assert!(!engine.set_humidity_target(Some(80.0)));
+
+        assert!(engine.set_humidity_target(None));
+        assert_eq!(engine.settings().humidity_target, None);
     }
 
     #[test]
-    fn manual_light_toggle_cycles_levels() {
+    fn set_humidity_hysteresis_clamps() {
         let mut engine =
             ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
-        let mut observed = Vec::new();
-
-        for _ in 0..5 {
-            assert_eq!(
-                engine.manual_light_toggle(),
-                vec![EngineAction::LightToggle]
-            );
-            observed.push(engine.light_level);
-        }
 
-        assert_eq!(observed, vec![4, 3, 2, 1, 0]);
+        assert!(engine.set_humidity_hysteresis(50.0));
+        assert_eq!(engine.settings().humidity_hysteresis_f, 20.0);
     }
 
     #[test]
-    fn manual_timer_toggle_cycles_states() {
+    fn engine_mode_idle_transitions_to_warmup_on_call_for_heat() {
         let mut engine =
             ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        engine.settings = settings;
+        engine.update_sensor_data(60.0, 40.0, 0);
 
-        for state in 1..=10 {
-            assert_eq!(
-                engine.manual_timer_toggle(),
-                vec![EngineAction::TimerToggle]
-            );
-            assert_eq!(engine.timer_state, state);
-        }
+        let mode: Box<dyn EngineMode> = Box::new(IdleMode);
+        let (mode, actions) = engine.mode_tick(mode, 1_000);
 
         assert_eq!(
-            engine.manual_timer_toggle(),
-            vec![EngineAction::TimerToggle]
+            format!("{mode:?}"),
+            format!("{:?}", WarmupMode { started_ms: 1_000 })
         );
-        assert_eq!(engine.timer_state, 0);
+        assert_eq!(actions.first(), Some(&EngineAction::PowerOn));
+        assert_eq!(actions.get(1), Some(&EngineAction::Delay(500)));
+        assert_eq!(actions.get(2), Some(&EngineAction::HeatOn));
+        assert!(engine.is_fireplace_on());
     }
 
     #[test]
-    fn mode_off_immediately_turns_fireplace_off() {
+    fn engine_mode_idle_stays_idle_when_no_call_for_heat() {
         let mut engine =
             ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
-        engine.settings.mode = ThermostatMode::Heat;
-        engine.fireplace_on = true;
-        engine.heating_start_ms = Some(1_000);
-        engine.last_state_change_ms = Some(1_200);
+        let mut settings = engine.settings.clone();
+        settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
+        engine.settings = settings;
+        engine.update_sensor_data(70.0, 40.0, 0);
 
-        let (changed, actions) = engine.set_mode_with_actions(ThermostatMode::Off, 1_300);
+        let mode: Box<dyn EngineMode> = Box::new(IdleMode);
+        let (mode, actions) = engine.mode_tick(mode, 1_000);
 
-        assert!(changed);
-        assert_eq!(actions, vec![EngineAction::PowerOff]);
+        assert_eq!(format!("{mode:?}"), format!("{:?}", IdleMode));
+        assert!(actions.is_empty());
         assert!(!engine.is_fireplace_on());
-        assert_eq!(engine.state(), ThermostatState::Idle);
     }
 
     #[test]
-    fn odd_target_rounds_up_to_even() {
+    fn engine_mode_preheat_transitions_to_warmup_once_lead_time_elapses() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        engine.settings.mode = ThermostatMode::Heat;
+        engine.update_sensor_data(65.0, 40.0, 0);
+
+        // Default rate 0.5°F/min, 5°F deficit => 10 minutes (600s) lead time.
+        let mode: Box<dyn EngineMode> = Box::new(PreHeatMode::new(70.0, 1_000 + 500));
+        let (mode, actions) = engine.mode_tick(mode, 1_000_000);
+        assert_eq!(
+            format!("{mode:?}"),
+            format!("{:?}", WarmupMode { started_ms: 1_000_000 })
+        );
+        assert_eq!(actions.first(), Some(&EngineAction::PowerOn));
+
+        // Too far ahead of the transition for the current lead-time
+        // estimate; stays in PreHeatMode. A fresh engine, so the fireplace
+        // turned on above doesn't short-circuit `should_preheat`.
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        engine.settings.mode = ThermostatMode::Heat;
+        engine.update_sensor_data(65.0, 40.0, 0);
+        let mode: Box<dyn EngineMode> = Box::new(PreHeatMode::new(70.0, 1_000 + 1_000));
+        let (mode, actions) = engine.mode_tick(mode, 1_000_000);
+        assert_eq!(
+            format!("{mode:?}"),
+            format!("{:?}", PreHeatMode::new(70.0, 1_000 + 1_000))
+        );
+        assert!(actions.is_empty());
+    }
+
+    #[test]
+    fn engine_mode_full_heating_to_cooldown_to_idle_cycle() {
         let mut engine =
             ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
         let mut settings = engine.settings.clone();
         settings.mode = ThermostatMode::Heat;
-        settings.target_temp_f = 69.0;
-        settings.fireplace_offset_f = 4;
+        settings.target_temp_f = 70.0;
         engine.settings = settings;
 
         engine.update_sensor_data(60.0, 40.0, 0);
-        let actions = engine.tick(299_999);
+        let mode: Box<dyn EngineMode> = Box::new(IdleMode);
+        let (mode, _) = engine.mode_tick(mode, 1_000);
+        assert_eq!(
+            format!("{mode:?}"),
+            format!("{:?}", WarmupMode { started_ms: 1_000 })
+        );
 
-        assert!(actions.contains(&EngineAction::SetTemp(74)));
+        // WarmupMode's startup sequence already fired in full during
+        // `enter`; the very next tick just hands off to HeatingMode.
+        let (mode, actions) = engine.mode_tick(mode, 1_100);
+        assert!(actions.is_empty());
+        assert_eq!(format!("{mode:?}"), format!("{:?}", HeatingMode));
+
+        // Room satisfies the upper bound; HeatingMode shuts the fireplace
+        // off and moves to CooldownMode.
+        engine.update_sensor_data(75.0, 40.0, 301_000);
+        let (mode, actions) = engine.mode_tick(mode, 301_100);
+        assert!(actions.contains(&EngineAction::PowerOff));
+        assert!(!engine.is_fireplace_on());
+        assert_eq!(
+            format!("{mode:?}"),
+            format!("{:?}", CooldownMode { started_ms: 301_100 })
+        );
+
+        // Before cooldown_duration_ms elapses, CooldownMode holds.
+        let (mode, actions) = engine.mode_tick(mode, 301_200);
+        assert!(actions.is_empty());
+        assert_eq!(
+            format!("{mode:?}"),
+            format!("{:?}", CooldownMode { started_ms: 301_100 })
+        );
+
+        // Once it elapses, CooldownMode hands back to IdleMode.
+        let (mode, actions) =
+            engine.mode_tick(mode, 301_100 + engine.config.cooldown_duration_ms);
+        assert!(actions.is_empty());
+        assert_eq!(format!("{mode:?}"), format!("{:?}", IdleMode));
     }
 
     #[test]
-    fn power_on_sequence_contains_required_delays() {
+    fn start_autotune_commands_full_heat_and_is_noop_if_already_running() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        engine.update_sensor_data(60.0, 40.0, 0);
+
+        let actions = engine.start_autotune(0);
+        assert_eq!(actions.first(), Some(&EngineAction::PowerOn));
+        assert_eq!(actions.get(2), Some(&EngineAction::HeatOn));
+        assert!(engine.is_fireplace_on());
+
+        assert!(engine.start_autotune(100).is_empty());
+    }
+
+    #[test]
+    fn advance_autotune_settles_and_fits_gains_into_time_proportional_config() {
         let mut engine =
             ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
         let mut settings = engine.settings.clone();
         settings.mode = ThermostatMode::Heat;
-        settings.target_temp_f = 70.0;
-        settings.hysteresis_f = 2.0;
         engine.settings = settings;
 
-        engine.update_sensor_data(65.0, 40.0, 1_000);
-        let actions = engine.tick(300_999);
+        engine.update_sensor_data(60.0, 40.0, 0);
+        assert!(engine.start_autotune(0).contains(&EngineAction::HeatOn));
+
+        // A step response rising from 60.0 to a 65.0 peak: dead time ends at
+        // 100s (first sample past the 0.3F noise threshold), 63.2% of the
+        // 5.0F rise (63.16F) is reached at 300s, and the peak holds flat for
+        // the full settle_window_ms before the last tick.
+        let samples = [
+            (50_000, 60.2),
+            (100_000, 60.5),
+            (200_000, 62.0),
+            (300_000, 63.5),
+            (400_000, 64.5),
+            (500_000, 65.0),
+            (1_100_000, 65.0),
+        ];
+        let mut actions = Vec::new();
+        for (ms, temp_f) in samples {
+            engine.update_sensor_data(temp_f, 40.0, ms);
+            actions = engine.tick(ms);
+        }
 
-        assert_eq!(actions.first(), Some(&EngineAction::PowerOn));
-        assert_eq!(actions.get(1), Some(&EngineAction::Delay(500)));
-        assert_eq!(actions.get(2), Some(&EngineAction::HeatOn));
-        assert_eq!(actions.get(3), Some(&EngineAction::Delay(200)));
+        assert!(!engine.is_fireplace_on());
+        let gains = actions.iter().find_map(|action| match action {
+            EngineAction::AutotuneComplete { kp, ki, kd } => Some((*kp, *ki, *kd)),
+            _ => None,
+        });
+        let (kp, ki, kd) = gains.expect("expected AutotuneComplete in the settling tick's actions");
+        assert!((kp - 0.48).abs() < 0.01);
+        assert!((ki - 0.0024).abs() < 0.0001);
+        assert!((kd - 24.0).abs() < 0.1);
+        assert!((engine.config.time_proportional.kp - kp).abs() < f32::EPSILON);
     }
 
     #[test]
-    fn sensor_stale_bypasses_min_cycle() {
+    fn advance_autotune_aborts_to_hysteresis_when_run_times_out() {
         let mut engine =
             ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
         let mut settings = engine.settings.clone();
         settings.mode = ThermostatMode::Heat;
+        settings.control_strategy = ControlStrategy::TimeProportional;
         engine.settings = settings;
 
-        engine.fireplace_on = true;
-        engine.heating_start_ms = Some(100);
-        engine.last_state_change_ms = Some(100);
-        engine.update_sensor_data(70.0, 40.0, 100);
+        engine.update_sensor_data(60.0, 40.0, 0);
+        let _ = engine.start_autotune(0);
 
-        // Sensor goes stale (300s timeout exceeded)
-        let actions = engine.tick(300_101);
+        engine.update_sensor_data(60.0, 40.0, 5_400_001);
+        let actions = engine.tick(5_400_001);
 
         assert!(actions.contains(&EngineAction::PowerOff));
         assert!(!engine.is_fireplace_on());
-        assert_eq!(engine.state(), ThermostatState::Idle);
+        assert_eq!(
+            engine.settings().control_strategy,
+            ControlStrategy::Hysteresis
+        );
     }
 
     #[test]
-    fn absolute_max_temp_emergency_shutoff() {
+    fn advance_autotune_aborts_when_temperature_reaches_absolute_max() {
         let mut engine =
             ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
         let mut settings = engine.settings.clone();
         settings.mode = ThermostatMode::Heat;
-        settings.target_temp_f = 70.0;
         engine.settings = settings;
 
-        engine.fireplace_on = true;
-        engine.heating_start_ms = Some(100);
-        engine.update_sensor_data(95.0, 40.0, 500);
+        engine.update_sensor_data(60.0, 40.0, 0);
+        let _ = engine.start_autotune(0);
 
-        let actions = engine.tick(600);
+        engine.update_sensor_data(95.0, 40.0, 1_000);
+        let actions = engine.tick(1_000);
 
         assert!(actions.contains(&EngineAction::PowerOff));
         assert!(!engine.is_fireplace_on());
-        assert_eq!(engine.state(), ThermostatState::Idle);
+        assert_eq!(
+            engine.settings().control_strategy,
+            ControlStrategy::Hysteresis
+        );
     }
 
     #[test]
-    fn mode_off_bypasses_min_cycle() {
+    fn over_temp_alarm_latches_and_powers_off() {
         let mut engine =
             ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
         let mut settings = engine.settings.clone();
         settings.mode = ThermostatMode::Heat;
+        settings.target_temp_f = 70.0;
         engine.settings = settings;
 
         engine.fireplace_on = true;
         engine.heating_start_ms = Some(100);
-        engine.last_state_change_ms = Some(100);
-        engine.update_sensor_data(70.0, 40.0, 100);
+        engine.update_sensor_data(106.0, 40.0, 500);
 
-        // Set mode to Off immediately (within min_cycle window)
-        let (changed, actions) = engine.set_mode_with_actions(ThermostatMode::Off, 200);
+        let actions = engine.tick(600);
 
-        assert!(changed);
         assert!(actions.contains(&EngineAction::PowerOff));
         assert!(!engine.is_fireplace_on());
+        assert!(engine.is_alarm_latched());
+        assert_eq!(engine.state(), ThermostatState::Fault);
+
+        // Stays latched (and keeps reporting Fault) even once the reading
+        // drops back into the safe band on its own.
+        engine.update_sensor_data(70.0, 40.0, 700);
+        engine.tick(800);
+        assert!(engine.is_alarm_latched());
+        assert_eq!(engine.state(), ThermostatState::Fault);
+    }
+
+    #[test]
+    fn under_temp_alarm_latches() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        engine.settings.mode = ThermostatMode::Heat;
+        engine.update_sensor_data(35.0, 40.0, 0);
+
+        engine.tick(0);
+
+        assert!(engine.is_alarm_latched());
+        assert_eq!(engine.state(), ThermostatState::Fault);
+    }
+
+    #[test]
+    fn alarm_ignores_mode_changes_while_latched() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        engine.settings.mode = ThermostatMode::Heat;
+        engine.update_sensor_data(106.0, 40.0, 0);
+        engine.tick(0);
+        assert!(engine.is_alarm_latched());
+
+        let (changed, actions) = engine.set_mode_with_actions(ThermostatMode::Off, 100);
+
+        assert!(!changed);
+        assert!(actions.is_empty());
+        assert_eq!(engine.settings().mode, ThermostatMode::Heat);
+    }
+
+    #[test]
+    fn clear_alarm_denied_until_reading_is_back_in_the_safe_band() {
+        let mut engine =
+            ThermostatEngine::new(ThermostatConfig::default(), PersistedSettings::default());
+        engine.settings.mode = ThermostatMode::Heat;
+        engine.update_sensor_data(106.0, 40.0, 0);
+        engine.tick(0);
+        assert!(engine.is_alarm_latched());
+
+        // Still over the limit: clear must be refused.
+        assert!(!engine.clear_alarm(100));
+        assert!(engine.is_alarm_latched());
+
+        // Back inside the safe band: clear succeeds and the engine resumes
+        // normal dispatch on the next tick.
+        engine.update_sensor_data(70.0, 40.0, 200);
+        assert!(engine.clear_alarm(200));
+        assert!(!engine.is_alarm_latched());
         assert_eq!(engine.state(), ThermostatState::Idle);
     }
 }
@@ -1,12 +1,34 @@
-pub const TOPIC_SENSOR_TEMP: &str = "thermostat/sensor/temperature";
-pub const TOPIC_SENSOR_HUMIDITY: &str = "thermostat/sensor/humidity";
-pub const TOPIC_SENSOR_STATUS: &str = "thermostat/sensor/status";
+/// Topic suffix a sensor node publishes under its own `<device_id>/` prefix
+/// (see `sensor::esp::device_topic`), e.g. `a1b2c3d4e5f6/sensor/temp`. Two
+/// sensor nodes sharing a broker land on distinct topics since their device
+/// ids differ.
+pub const TOPIC_SENSOR_TEMP: &str = "sensor/temp";
+pub const TOPIC_SENSOR_HUMIDITY: &str = "sensor/humidity";
+pub const TOPIC_SENSOR_STATUS: &str = "sensor/status";
+/// Single-level MQTT wildcard matching `TOPIC_SENSOR_TEMP`/
+/// `TOPIC_SENSOR_HUMIDITY` published by any device, for the controller to
+/// subscribe across a whole sensor fleet without knowing device ids ahead
+/// of time.
+pub const TOPIC_SENSOR_TEMP_WILDCARD: &str = "+/sensor/temp";
+pub const TOPIC_SENSOR_HUMIDITY_WILDCARD: &str = "+/sensor/humidity";
 
 pub const TOPIC_CONTROLLER_STATE: &str = "thermostat/controller/state";
 pub const TOPIC_CONTROLLER_SCHEDULE_STATE: &str = "thermostat/controller/schedule/state";
+pub const TOPIC_CONTROLLER_AVAILABILITY: &str = "thermostat/controller/availability";
+pub const TOPIC_CONTROLLER_OTA_STATE: &str = "thermostat/controller/ota/state";
 
 pub const TOPIC_CMD_POWER: &str = "thermostat/cmnd/fireplace/power";
 pub const TOPIC_CMD_TARGET: &str = "thermostat/cmnd/thermostat/target";
 pub const TOPIC_CMD_MODE: &str = "thermostat/cmnd/thermostat/mode";
 pub const TOPIC_CMD_HOLD: &str = "thermostat/cmnd/thermostat/hold";
 pub const TOPIC_CMD_SCHEDULE: &str = "thermostat/cmnd/thermostat/schedule";
+pub const TOPIC_CMD_OVERRIDE: &str = "thermostat/cmnd/thermostat/override";
+pub const TOPIC_CMD_DATE_EXCEPTIONS: &str = "thermostat/cmnd/thermostat/date_exceptions";
+pub const TOPIC_CMD_TELEMETRY_ONESHOT: &str = "thermostat/cmnd/thermostat/telemetry_oneshot";
+pub const TOPIC_CMD_SENSOR_CONFIG: &str = "thermostat/cmnd/sensor/config";
+pub const TOPIC_CMD: &str = "thermostat/cmnd/thermostat/cmd";
+pub const TOPIC_CMD_RESULT: &str = "thermostat/controller/cmd/result";
+pub const TOPIC_CMD_SENSOR_SETTINGS: &str = "thermostat/cmnd/sensor/settings";
+pub const TOPIC_SENSOR_SETTINGS_RESULT: &str = "thermostat/sensor/settings/result";
+pub const TOPIC_CMD_SENSOR_OTA_APPLY: &str = "thermostat/cmnd/sensor/ota/apply";
+pub const TOPIC_SENSOR_OTA_STATUS: &str = "thermostat/sensor/ota/status";
@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 pub enum ThermostatMode {
     Off,
     Heat,
+    Cool,
+    Auto,
 }
 
 impl ThermostatMode {
@@ -12,6 +14,93 @@ impl ThermostatMode {
         match self {
             Self::Off => "OFF",
             Self::Heat => "HEAT",
+            Self::Cool => "COOL",
+            Self::Auto => "AUTO",
+        }
+    }
+}
+
+/// How the engine decides when to turn the fireplace on and off while in
+/// `ThermostatMode::Heat`. `Hysteresis` is the original bang-bang behavior
+/// (on below `target - hysteresis`, off above `target + hysteresis`);
+/// `Pid` drives a closed-loop PID controller and converts its output into a
+/// time-proportioned duty cycle over a `min_cycle_ms` window, for tighter
+/// regulation of high-thermal-mass heat sources; `SetpointPid` keeps the
+/// same on/off banding as `Hysteresis` but continuously modulates the
+/// fireplace's internal setpoint via a second PID loop on room error, to
+/// reduce overshoot while the fireplace is running rather than
+/// time-proportioning whether it runs at all; `TimeProportional` is the same
+/// PID-into-duty-cycle idea as `Pid`, but with its own independently
+/// configurable window (`config.time_proportional.window_ms`) instead of
+/// reusing `min_cycle_ms`, for a PWM period tuned separately from the
+/// minimum on/off debounce. Tuning for `Pid`/`SetpointPid`/`TimeProportional`
+/// lives in the matching `ThermostatConfig` field rather than inline here,
+/// so it can be persisted/defaulted independently of which strategy is
+/// currently selected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ControlStrategy {
+    Hysteresis,
+    Pid,
+    SetpointPid,
+    TimeProportional,
+}
+
+impl Default for ControlStrategy {
+    fn default() -> Self {
+        ControlStrategy::Hysteresis
+    }
+}
+
+impl ControlStrategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Hysteresis => "HYSTERESIS",
+            Self::Pid => "PID",
+            Self::SetpointPid => "SETPOINT_PID",
+            Self::TimeProportional => "TIME_PROPORTIONAL",
+        }
+    }
+}
+
+/// Why a latched safety fault is currently blocking the fireplace from
+/// turning on. Cleared only by an explicit `reset_safety` call, and only
+/// once the condition that tripped it has actually cleared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FaultReason {
+    OverTemp,
+    SensorLost,
+}
+
+impl FaultReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::OverTemp => "OVER_TEMP",
+            Self::SensorLost => "SENSOR_LOST",
+        }
+    }
+}
+
+/// Graded severity of `ThermostatEngine::thermal_load`, in ascending order so
+/// callers (and the engine's own band-crossing check) can compare bands with
+/// `<`/`>` rather than matching pairs out by hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ThermalLoadBand {
+    Nominal,
+    Throttle,
+    Shutoff,
+    Lockout,
+}
+
+impl ThermalLoadBand {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Nominal => "NOMINAL",
+            Self::Throttle => "THROTTLE",
+            Self::Shutoff => "SHUTOFF",
+            Self::Lockout => "LOCKOUT",
         }
     }
 }
@@ -21,9 +110,16 @@ impl ThermostatMode {
 pub enum ThermostatState {
     Idle,
     Heating,
+    Cooling,
     Satisfied,
     Hold,
     Cooldown,
+    /// Latched by `ThermostatEngine::update_sensor_data`/`tick` when a
+    /// reading crosses `alarm_high_f`/`alarm_low_f`. Unlike the
+    /// `absolute_max_temp_f` shutoff (which reports `Idle` with an
+    /// `OverTemp` fault cleared by `reset_safety`), this stays latched across
+    /// ticks (and refuses mode changes) until `clear_alarm` succeeds.
+    Fault,
 }
 
 impl ThermostatState {
@@ -31,11 +127,143 @@ impl ThermostatState {
         match self {
             Self::Idle => "IDLE",
             Self::Heating => "HEATING",
+            Self::Cooling => "COOLING",
             Self::Satisfied => "SATISFIED",
             Self::Hold => "HOLD",
             Self::Cooldown => "COOLDOWN",
+            Self::Fault => "FAULT",
+        }
+    }
+}
+
+/// Unit a `Temperature` should be converted to for display/transmit.
+/// `ThermostatEngine` and `PersistedSettings` remain Fahrenheit-native
+/// internally regardless of this setting — see [`Temperature`] — so
+/// switching it is purely a reporting preference, not a control-logic
+/// change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TemperatureUnit {
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+impl Default for TemperatureUnit {
+    fn default() -> Self {
+        TemperatureUnit::Fahrenheit
+    }
+}
+
+impl TemperatureUnit {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Celsius => "CELSIUS",
+            Self::Fahrenheit => "FAHRENHEIT",
+            Self::Kelvin => "KELVIN",
         }
     }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Celsius => "C",
+            Self::Fahrenheit => "F",
+            Self::Kelvin => "K",
+        }
+    }
+
+    /// Converts a temperature *difference* (hysteresis, an offset between
+    /// two readings) rather than an absolute reading - a delta skips
+    /// Fahrenheit's `+32` offset, which only makes sense applied to an
+    /// absolute value, so this can't reuse `Temperature::value_in`.
+    pub fn convert_delta_from_fahrenheit(self, delta_f: f32) -> f32 {
+        match self {
+            Self::Fahrenheit => delta_f,
+            Self::Celsius | Self::Kelvin => delta_f * 5.0 / 9.0,
+        }
+    }
+}
+
+/// A temperature reading that carries its own native Fahrenheit value (the
+/// unit `ThermostatEngine` and `PersistedSettings` have always stored
+/// internally) and converts on demand, so outward-facing formatting stops
+/// being scattered bare-float `celsius_to_fahrenheit`/`fahrenheit_to_celsius`
+/// calls duplicated at each call site. Constructing from a non-Fahrenheit
+/// unit (e.g. a sensor that reports Celsius) normalizes immediately, so the
+/// stored value is always comparable/addable without a unit check.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Temperature {
+    fahrenheit: f32,
+}
+
+impl Temperature {
+    pub fn from_fahrenheit(value: f32) -> Self {
+        Self { fahrenheit: value }
+    }
+
+    pub fn from_celsius(value: f32) -> Self {
+        Self {
+            fahrenheit: value * 9.0 / 5.0 + 32.0,
+        }
+    }
+
+    pub fn from_kelvin(value: f32) -> Self {
+        Self::from_celsius(value - 273.15)
+    }
+
+    pub fn as_fahrenheit(self) -> f32 {
+        self.fahrenheit
+    }
+
+    pub fn as_celsius(self) -> f32 {
+        (self.fahrenheit - 32.0) * 5.0 / 9.0
+    }
+
+    pub fn as_kelvin(self) -> f32 {
+        self.as_celsius() + 273.15
+    }
+
+    pub fn value_in(self, unit: TemperatureUnit) -> f32 {
+        match unit {
+            TemperatureUnit::Fahrenheit => self.as_fahrenheit(),
+            TemperatureUnit::Celsius => self.as_celsius(),
+            TemperatureUnit::Kelvin => self.as_kelvin(),
+        }
+    }
+
+    /// Renders as e.g. `"72.3°F"`, for log lines and any outward-facing
+    /// payload that wants a human-readable string rather than a bare number
+    /// plus a separately-reported unit.
+    pub fn format(self, unit: TemperatureUnit) -> String {
+        format!("{:.1}°{}", self.value_in(unit), unit.symbol())
+    }
+}
+
+/// A single tunable's current value alongside the bounds and step a UI
+/// should render a slider/stepper with, so those limits live in one place
+/// instead of being duplicated between the engine's setters and the
+/// frontend. `step` doubles as the even-only constraint for
+/// `fireplace_offset`, since an even step from an even minimum never lands
+/// on an odd value.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TunableRange {
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsSummary {
+    #[serde(rename = "targetTemp")]
+    pub target_temp: TunableRange,
+    pub hysteresis: TunableRange,
+    #[serde(rename = "autoCoolTarget")]
+    pub auto_cool_target: TunableRange,
+    #[serde(rename = "fireplaceOffset")]
+    pub fireplace_offset: TunableRange,
+    #[serde(rename = "fireplaceTemp")]
+    pub fireplace_temp: TunableRange,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -47,6 +275,19 @@ pub struct ControllerStatus {
     #[serde(rename = "targetTemp")]
     pub target_temp: f32,
     pub hysteresis: f32,
+    #[serde(rename = "humidityTarget")]
+    pub humidity_target: Option<f32>,
+    #[serde(rename = "humidityHysteresis")]
+    pub humidity_hysteresis: f32,
+    #[serde(rename = "humidityValid")]
+    pub humidity_valid: bool,
+    #[serde(rename = "controlStrategy")]
+    pub control_strategy: &'static str,
+    /// Last 0..1 output computed by the PID loop, see
+    /// `ThermostatEngine::pid_output`. Only meaningful while
+    /// `controlStrategy` is `"PID"`; holds its last value otherwise.
+    #[serde(rename = "pidOutput")]
+    pub pid_output: f32,
     #[serde(rename = "fireplaceOffset")]
     pub fireplace_offset: i32,
     #[serde(rename = "fireplaceTemp")]
@@ -55,8 +296,27 @@ pub struct ControllerStatus {
     pub state: &'static str,
     #[serde(rename = "fireplaceOn")]
     pub fireplace_on: bool,
+    /// Generic alias for `fireplaceOn`, alongside `coolingOn`, for UIs that
+    /// want "is the actuator running" without caring which HVAC function.
+    #[serde(rename = "actuatorOn")]
+    pub actuator_on: bool,
+    /// Whether the actuator is specifically running a cooling call right
+    /// now (`Cool` or `Auto` actively cooling); `false` while heating, idle,
+    /// or off, same as `fireplaceOn` stays `true` for a heat call.
+    #[serde(rename = "coolingOn")]
+    pub cooling_on: bool,
     #[serde(rename = "sensorValid")]
     pub sensor_valid: bool,
+    #[serde(rename = "faultActive")]
+    pub fault_active: bool,
+    #[serde(rename = "faultReason")]
+    pub fault_reason: Option<&'static str>,
+    #[serde(rename = "alarmActive")]
+    pub alarm_active: bool,
+    #[serde(rename = "alarmReason")]
+    pub alarm_reason: Option<&'static str>,
+    #[serde(rename = "activeProfileId")]
+    pub active_profile_id: Option<u32>,
     #[serde(rename = "lightLevel")]
     pub light_level: u8,
     #[serde(rename = "timerState")]
@@ -86,6 +346,24 @@ pub struct ControllerStatus {
     #[serde(rename = "timeSynced")]
     pub time_synced: bool,
     pub timezone: String,
+    /// Unit every `*Temp`/`hysteresis`/`fireplaceOffset` field above is
+    /// actually reported in - `ThermostatEngine::status` converts from its
+    /// Fahrenheit-native storage via [`Temperature`] before this struct is
+    /// built, so a client doesn't need to convert anything itself. Humidity
+    /// fields are unaffected; they're never a temperature.
+    #[serde(rename = "displayUnit")]
+    pub display_unit: &'static str,
+    /// Reload counter for the host daemon's hot-reloadable `config.toml`
+    /// overlay, bumped each time a file edit is validated and applied; `0`
+    /// means no file config has ever been applied (including always, on
+    /// esp32, which has no such file). Populated here as a placeholder and
+    /// overwritten by the host daemon - see `host::build_status` - since
+    /// this crate has no notion of a filesystem config file itself.
+    #[serde(rename = "configVersion")]
+    pub config_version: u32,
+    /// Path of the active `config.toml`, if one has been applied this run.
+    #[serde(rename = "configPath")]
+    pub config_path: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -96,6 +374,18 @@ pub struct ControllerStatePayload {
     pub mode: &'static str,
     pub state: &'static str,
     pub fireplace: bool,
+    #[serde(rename = "actuatorOn")]
+    pub actuator_on: bool,
+    #[serde(rename = "coolingOn")]
+    pub cooling_on: bool,
+    #[serde(rename = "faultActive")]
+    pub fault_active: bool,
+    #[serde(rename = "faultReason")]
+    pub fault_reason: Option<&'static str>,
+    #[serde(rename = "alarmActive")]
+    pub alarm_active: bool,
+    #[serde(rename = "alarmReason")]
+    pub alarm_reason: Option<&'static str>,
     #[serde(rename = "holdActive")]
     pub hold_active: bool,
     #[serde(rename = "holdRemainingMin")]
@@ -1,9 +1,9 @@
 use core::convert::TryInto;
 use std::{
     collections::HashMap,
-    net::Ipv4Addr,
+    net::{Ipv4Addr, UdpSocket},
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU32, Ordering},
         Arc, Mutex, OnceLock,
     },
     thread,
@@ -11,18 +11,27 @@ use std::{
 };
 
 use anyhow::{anyhow, Context};
+use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine as _};
 use chrono::{Offset, Utc};
 use chrono_tz::Tz;
 use embedded_svc::{
     http::{client::Client as HttpClient, Headers, Method, Status},
     io::{Read, Write},
     mqtt::client::{Details, EventPayload, QoS},
-    wifi::{AccessPointConfiguration, AuthMethod, ClientConfiguration, Configuration},
+    wifi::{
+        AccessPointConfiguration, AccessPointInfo, AuthMethod, ClientConfiguration, Configuration,
+    },
 };
-use esp_idf_hal::gpio::{Output, PinDriver};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use esp_idf_hal::uart::{config::Config as UartConfig, UartDriver};
 use esp_idf_svc::{
     eventloop::EspSystemEventLoop,
-    hal::{gpio::AnyOutputPin, modem::Modem, prelude::Peripherals, rmt::RMT},
+    hal::{
+        gpio::{AnyIOPin, AnyOutputPin},
+        modem::Modem,
+        prelude::Peripherals,
+        rmt::RMT,
+    },
     http::client::{Configuration as HttpClientConfiguration, EspHttpConnection},
     http::server::{Configuration as HttpConfiguration, EspHttpServer},
     ipv4::{
@@ -30,44 +39,98 @@ use esp_idf_svc::{
         Configuration as IpConfiguration, Mask, Subnet,
     },
     log::EspLogger,
-    mqtt::client::{EspMqttClient, EspMqttConnection, MqttClientConfiguration},
+    mqtt::client::{
+        EspMqttClient, EspMqttConnection, LwtConfiguration, MqttClientConfiguration,
+    },
     netif::{EspNetif, NetifConfiguration},
     nvs::{EspDefaultNvsPartition, EspNvs},
     ota::EspOta,
-    sntp::EspSntp,
+    sntp::{EspSntp, SyncStatus},
+    tls::X509,
     wifi::{BlockingWifi, EspWifi},
 };
-use log::{info, warn};
+use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
 use thermostat_common::{
-    config::{IrHardwareConfig, NetworkConfig},
-    DayOfWeek, EngineAction, PersistedSettings, RuntimeConfig, Schedule, ScheduleAction,
-    ScheduleEntry, ThermostatConfig, ThermostatEngine, ThermostatMode, TOPIC_CMD_HOLD,
-    TOPIC_CMD_MODE, TOPIC_CMD_POWER, TOPIC_CMD_SCHEDULE, TOPIC_CMD_TARGET,
+    config::{
+        IrHardwareConfig, IrProtocol, NetworkConfig, StatusLedBackend, StatusLedConfig,
+        WifiAuthMode,
+    },
+    ControlStrategy, DateException, DayOfWeek, EngineAction, PersistedSettings, PidParameters,
+    RuntimeConfig, Schedule, ScheduleAction, ScheduleEntry, ScheduleOverride, TemperatureUnit,
+    ThermostatConfig, ThermostatEngine,
+    ThermostatMode, ThermostatState, TOPIC_CMD, TOPIC_CMD_DATE_EXCEPTIONS, TOPIC_CMD_HOLD,
+    TOPIC_CMD_MODE, TOPIC_CMD_OVERRIDE, TOPIC_CMD_POWER, TOPIC_CMD_RESULT, TOPIC_CMD_SCHEDULE,
+    TOPIC_CMD_TARGET, TOPIC_CONTROLLER_AVAILABILITY, TOPIC_CONTROLLER_OTA_STATE,
     TOPIC_CONTROLLER_SCHEDULE_STATE, TOPIC_CONTROLLER_STATE, TOPIC_SENSOR_HUMIDITY,
-    TOPIC_SENSOR_TEMP,
+    TOPIC_SENSOR_HUMIDITY_WILDCARD, TOPIC_SENSOR_TEMP, TOPIC_SENSOR_TEMP_WILDCARD,
 };
 
-use crate::ir::IrTransmitter;
+use crate::connectivity::{ConnectivityManager, Transport};
+use crate::ir::{IrLearner, IrRuntimeState, IrStateOrigin, IrTransmitter, LearnResult, LearnStatus};
+use crate::status_led;
 
 const NVS_NAMESPACE: &str = "thermostat";
 const NVS_RUNTIME_KEY: &str = "runtime_json";
 const NVS_SCHEDULE_KEY: &str = "schedule_json";
+const NVS_IR_LEARNED_KEY: &str = "ir_learned_json";
+const NVS_IR_STATE_KEY: &str = "ir_state_json";
+const NVS_OTA_PENDING_KEY: &str = "ota_pending";
+const NVS_OTA_LAST_RESULT_KEY: &str = "ota_last_result";
+const NVS_MQTT_CA_CERT_KEY: &str = "mqtt_ca_pem";
+const NVS_OTA_SIGNING_KEY_KEY: &str = "ota_sign_pub";
+/// Versioned, chunked storage keys for `RuntimeConfig`/`Schedule`, replacing
+/// the single fixed-size blobs above for these two payloads. Abbreviated to
+/// leave room under NVS's 15-byte key-name limit even with a two-digit chunk
+/// index appended to the `*_CHUNK_PREFIX` keys.
+const NVS_RUNTIME_VERSION_KEY: &str = "runtime_ver";
+const NVS_RUNTIME_CHUNKS_KEY: &str = "runtime_nch";
+const NVS_RUNTIME_CHUNK_PREFIX: &str = "runtime_c";
+const NVS_SCHEDULE_VERSION_KEY: &str = "sched_ver";
+const NVS_SCHEDULE_CHUNKS_KEY: &str = "sched_nchunks";
+const NVS_SCHEDULE_CHUNK_PREFIX: &str = "sched_c";
+/// Safe upper bound for a single NVS string entry. ESP-IDF's NVS string
+/// values top out around 4000 bytes; this leaves headroom and keeps
+/// `load_versioned`'s read buffer a fixed, small size.
+const NVS_CHUNK_MAX_BYTES: usize = 3500;
 const MAX_HTTP_BODY: usize = 4096;
 const OTA_CHUNK_SIZE: usize = 4096;
 const MAX_MQTT_PAYLOAD_BYTES: usize = 512;
+const HA_DEVICE_ID: &str = "thermostat-controller";
 const PROVISIONING_AP_SSID: &str = "ThermostatController-AP";
 const PROVISIONING_AP_PASSWORD: &str = "ThermostatSetup";
+/// esp-idf's default SoftAP gateway address; the captive-portal DNS responder
+/// steers every query here so clients land on the provisioning page.
+const PROVISIONING_AP_GATEWAY_IP: Ipv4Addr = Ipv4Addr::new(192, 168, 71, 1);
+/// Running firmware version, compared against an OTA manifest's `version`
+/// field to decide whether an update is actually newer.
+const FIRMWARE_VERSION: &str = env!("CARGO_PKG_VERSION");
 const WATCHDOG_TIMEOUT_SEC: u32 = 30;
+/// Dev-only fallback Ed25519 public key, used to verify signed OTA images
+/// before they're marked bootable when no fleet key has been provisioned via
+/// `NvsStore::load_ota_signing_public_key` (see `/api/ota/signing-key`). All
+/// zero bytes, so it can never validate a real signature - a device that
+/// hasn't had a real key pushed to it simply can't apply a signed OTA image,
+/// rather than silently trusting whoever built this binary.
+const OTA_SIGNING_PUBLIC_KEY: [u8; 32] = [0u8; 32];
+/// How long a newly applied OTA slot has to reconnect WiFi and MQTT before
+/// it's considered unhealthy and the device restarts to let the bootloader
+/// roll back to the previous slot.
+const OTA_HEALTH_GRACE_MS: u64 = 120_000;
+/// How many times a dropped OTA download connection is resumed via an HTTP
+/// Range request before the download is abandoned.
+const OTA_DOWNLOAD_MAX_RETRIES: u32 = 5;
 const SETTINGS_SAVE_RETRY_MS: u64 = 1_000;
 const WIFI_RESTART_GRACE_MS: u64 = 300_000;
 const WIFI_CONNECT_ATTEMPTS: u32 = 5;
 const WIFI_RETRY_DELAY_MS: u64 = 3_000;
-const STATUS_LED_PIN: i32 = 48;
-const LED_FAST_BLINK_MS: u64 = 200;
-const LED_SLOW_BLINK_MS: u64 = 900;
+/// UART pins wired to an external AT-command cellular modem, used as a PPP
+/// fallback transport when WiFi stays down past `WIFI_RESTART_GRACE_MS`.
+const MODEM_UART_TX_PIN: i32 = 17;
+const MODEM_UART_RX_PIN: i32 = 18;
+const MODEM_UART_BAUD_RATE: u32 = 115_200;
 
 const INDEX_HTML: &str = include_str!("../web/index.html");
 const APP_JS: &str = include_str!("../web/app.js");
@@ -91,6 +154,11 @@ const PROVISIONING_INDEX_HTML: &str = r#"<!doctype html>
   <p class="muted">Update WiFi and MQTT settings, then restart the device.</p>
   <p class="muted">Provisioning AP password: <code>ThermostatSetup</code></p>
   <div class="card">
+    <label>WiFi Network</label>
+    <div class="row">
+      <div><select id="wifiSsidPicker"><option value="">Scan for networks...</option></select></div>
+      <div style="flex:0 0 auto"><button id="scan" type="button">Refresh</button></div>
+    </div>
     <label>WiFi SSID</label><input id="wifiSsid" type="text">
     <label>WiFi Password (leave blank to keep current)</label><input id="wifiPass" type="password">
     <div class="row">
@@ -101,7 +169,7 @@ const PROVISIONING_INDEX_HTML: &str = r#"<!doctype html>
     <label>MQTT Password (leave blank to keep current)</label><input id="mqttPass" type="password">
     <label><input id="useStaticIp" type="checkbox"> Use static IP</label>
     <div class="row">
-      <div><label>Static IP</label><input id="staticIp" type="text" placeholder="192.168.1.50"></div>
+      <div><label>Static IP</label><input id="staticIp" type="text" placeholder="192.168.1.50 or 192.168.1.50/24"></div>
       <div><label>Gateway</label><input id="gateway" type="text" placeholder="192.168.1.1"></div>
     </div>
     <div class="row">
@@ -117,6 +185,20 @@ const PROVISIONING_INDEX_HTML: &str = r#"<!doctype html>
     const toStr=(arr)=>Array.isArray(arr)?arr.join('.'):'';
     const toArr=(value)=>{if(!value.trim())return null;const p=value.trim().split('.').map(Number);if(p.length!==4||p.some(n=>!Number.isInteger(n)||n<0||n>255))throw new Error('Invalid IPv4: '+value);return p;};
     async function api(path,opt){const r=await fetch(path,opt);let b={};try{b=await r.json();}catch(_){}if(!r.ok)throw new Error(b.error||('Request failed: '+r.status));return b;}
+    async function scan(){
+      const picker=q('wifiSsidPicker');
+      picker.innerHTML='<option value="">Scanning...</option>';
+      try{
+        const networks=await api('/api/wifi/scan');
+        picker.innerHTML='<option value="">Select a network...</option>'+networks.map((n)=>
+          `<option value="${n.ssid}">${n.ssid} (${n.rssi} dBm, ${n.authMethod})</option>`).join('');
+      }catch(err){picker.innerHTML='<option value="">Scan failed</option>';}
+    }
+    q('scan').addEventListener('click', scan);
+    q('wifiSsidPicker').addEventListener('change', ()=>{
+      const value=q('wifiSsidPicker').value;
+      if(value)q('wifiSsid').value=value;
+    });
     async function load(){
       const n=await api('/api/network');
       q('wifiSsid').value=n.wifiSsid||'';
@@ -140,7 +222,7 @@ const PROVISIONING_INDEX_HTML: &str = r#"<!doctype html>
           mqttUser:q('mqttUser').value.trim(),
           mqttPass:q('mqttPass').value||undefined,
           useStaticIp:q('useStaticIp').checked,
-          staticIp:toArr(q('staticIp').value),
+          staticIp:q('staticIp').value.trim()||null,
           gateway:toArr(q('gateway').value),
           subnet:toArr(q('subnet').value),
           dns:toArr(q('dns').value),
@@ -156,6 +238,7 @@ const PROVISIONING_INDEX_HTML: &str = r#"<!doctype html>
       catch(err){q('status').className='err';q('status').textContent=err.message;}
     });
     load().catch((err)=>{q('status').className='err';q('status').textContent=err.message;});
+    scan();
   </script>
 </body>
 </html>
@@ -172,16 +255,16 @@ struct SharedState {
     schedule: Arc<Mutex<Schedule>>,
     timezone: Arc<Mutex<String>>,
     time_synced: Arc<AtomicBool>,
+    last_sync_ms: Arc<Mutex<Option<u64>>>,
     ir_sender: Arc<Mutex<IrTransmitter>>,
+    ir_learner: Arc<IrLearner>,
+    ir_learn_pending: Arc<Mutex<Option<(String, bool)>>>,
     ota: Arc<Mutex<OtaRuntimeState>>,
     settings_save_deadline_ms: Arc<Mutex<Option<u64>>>,
     wifi_connected: Arc<AtomicBool>,
     mqtt_connected: Arc<AtomicBool>,
-}
-
-struct StatusLed {
-    pin: PinDriver<'static, AnyOutputPin, Output>,
-    lit: bool,
+    transport: Arc<Mutex<Transport>>,
+    nvs_store: NvsStore,
 }
 
 #[derive(Clone)]
@@ -190,6 +273,68 @@ struct NvsStore {
     lock: Arc<Mutex<()>>,
 }
 
+/// Upgrades a persisted payload's `serde_json::Value` from one schema version
+/// to the next. `SCHEDULE_MIGRATIONS`/`RUNTIME_MIGRATIONS` index migration
+/// `i` as the upgrade from version `i` to version `i + 1`, so
+/// `load_versioned` can apply a contiguous range of them to walk an old
+/// payload forward to the current version.
+type Migration = fn(serde_json::Value) -> anyhow::Result<serde_json::Value>;
+
+/// Current on-disk schema version for `Schedule`. Bump this and append a
+/// migration to `SCHEDULE_MIGRATIONS` whenever `Schedule`'s shape changes in
+/// a way plain serde can't absorb on its own (a rename, a field split, etc).
+const SCHEDULE_SCHEMA_VERSION: u8 = 0;
+/// No schema changes have shipped yet - version 0 is both the first
+/// versioned shape and the shape the legacy unversioned blob already had.
+const SCHEDULE_MIGRATIONS: &[Migration] = &[];
+
+const RUNTIME_SCHEMA_VERSION: u8 = 0;
+const RUNTIME_MIGRATIONS: &[Migration] = &[];
+
+/// Why a versioned NVS load failed, so callers can decide whether falling
+/// back to a default is fine (`Missing`) or something unexpected happened
+/// that's worth surfacing (`Corrupt`, `UnsupportedVersion`).
+#[derive(Debug)]
+enum NvsLoadError {
+    /// Nothing has been persisted under this key yet - a normal first boot.
+    Missing,
+    /// A payload was found but couldn't be read back into the expected shape.
+    Corrupt(String),
+    /// The stored payload's schema version is newer than this firmware
+    /// understands, e.g. after a downgrade following a newer build.
+    UnsupportedVersion(u8),
+}
+
+impl std::fmt::Display for NvsLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NvsLoadError::Missing => write!(f, "no value persisted"),
+            NvsLoadError::Corrupt(reason) => write!(f, "corrupt stored payload: {reason}"),
+            NvsLoadError::UnsupportedVersion(version) => {
+                write!(f, "unsupported schema version {version}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for NvsLoadError {}
+
+/// Splits `s` into pieces no longer than `max_len` bytes, breaking only on
+/// UTF-8 character boundaries so a chunk never ends mid-character.
+fn chunk_str(s: &str, max_len: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < s.len() {
+        let mut end = (start + max_len).min(s.len());
+        while end < s.len() && !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        chunks.push(&s[start..end]);
+        start = end;
+    }
+    chunks
+}
+
 #[derive(Debug, Serialize)]
 struct TimeStatus {
     #[serde(rename = "timeSynced")]
@@ -197,6 +342,8 @@ struct TimeStatus {
     timezone: String,
     #[serde(rename = "nowEpoch")]
     now_epoch: i64,
+    #[serde(rename = "lastSyncEpoch")]
+    last_sync_epoch: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -204,6 +351,14 @@ struct TimezoneUpdate {
     timezone: String,
 }
 
+#[derive(Debug, Serialize)]
+struct ProvisionStateView {
+    #[serde(rename = "apModeActive")]
+    ap_mode_active: bool,
+    #[serde(rename = "connectedClients")]
+    connected_clients: u32,
+}
+
 #[derive(Debug, Serialize)]
 struct NetworkConfigView {
     #[serde(rename = "wifiSsid")]
@@ -218,6 +373,8 @@ struct NetworkConfigView {
     mqtt_user: String,
     #[serde(rename = "mqttPassSet")]
     mqtt_pass_set: bool,
+    #[serde(rename = "mqttTls")]
+    mqtt_tls: bool,
     #[serde(rename = "otaPasswordSet")]
     ota_password_set: bool,
     #[serde(rename = "useStaticIp")]
@@ -227,6 +384,14 @@ struct NetworkConfigView {
     gateway: Option<[u8; 4]>,
     subnet: Option<[u8; 4]>,
     dns: Option<[u8; 4]>,
+    #[serde(rename = "secondaryDns")]
+    secondary_dns: Option<[u8; 4]>,
+    #[serde(rename = "wifiAuth")]
+    wifi_auth: Option<WifiAuthMode>,
+    #[serde(rename = "wifiIdentity")]
+    wifi_identity: Option<String>,
+    #[serde(rename = "wifiUsername")]
+    wifi_username: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -243,15 +408,44 @@ struct NetworkConfigUpdate {
     mqtt_user: String,
     #[serde(rename = "mqttPass", default)]
     mqtt_pass: Option<String>,
+    #[serde(rename = "mqttTls", default)]
+    mqtt_tls: bool,
     #[serde(rename = "otaPassword", default)]
     ota_password: Option<String>,
     #[serde(rename = "useStaticIp")]
     use_static_ip: bool,
+    /// Either a plain dotted-decimal address (`"192.168.1.50"`) or CIDR
+    /// notation (`"192.168.1.50/24"`), resolved by `resolve_static_network`.
     #[serde(rename = "staticIp")]
-    static_ip: Option<[u8; 4]>,
+    static_ip: Option<String>,
     gateway: Option<[u8; 4]>,
     subnet: Option<[u8; 4]>,
     dns: Option<[u8; 4]>,
+    #[serde(rename = "secondaryDns", default)]
+    secondary_dns: Option<[u8; 4]>,
+    #[serde(rename = "wifiAuth", default)]
+    wifi_auth: Option<String>,
+    #[serde(rename = "wifiIdentity", default)]
+    wifi_identity: Option<String>,
+    #[serde(rename = "wifiUsername", default)]
+    wifi_username: Option<String>,
+}
+
+/// A PEM-encoded CA certificate (or chain) to validate the MQTT broker
+/// against when `mqtt_tls` is enabled, overriding the bundled ESP-IDF CA
+/// store.
+#[derive(Debug, Deserialize)]
+struct MqttCaCertUpdate {
+    pem: String,
+}
+
+/// Per-fleet Ed25519 public key to verify signed OTA images against,
+/// overriding the placeholder `OTA_SIGNING_PUBLIC_KEY` compiled into this
+/// binary - lets a real key be deployed without a firmware rebuild.
+#[derive(Debug, Deserialize)]
+struct OtaSigningKeyUpdate {
+    #[serde(rename = "publicKeyBase64")]
+    public_key_base64: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -269,6 +463,11 @@ struct IrConfigView {
     rmt_channel: u8,
     #[serde(rename = "carrierKHz")]
     carrier_khz: u32,
+    protocol: IrProtocol,
+    #[serde(rename = "rxPin")]
+    rx_pin: Option<i32>,
+    #[serde(rename = "learnGlitchFloorUs")]
+    learn_glitch_floor_us: u16,
 }
 
 #[derive(Debug, Deserialize)]
@@ -279,6 +478,16 @@ struct IrConfigUpdate {
     rmt_channel: u8,
     #[serde(rename = "carrierKHz")]
     carrier_khz: u32,
+    #[serde(default)]
+    protocol: IrProtocol,
+    #[serde(rename = "rxPin", default)]
+    rx_pin: Option<i32>,
+    #[serde(rename = "learnGlitchFloorUs", default = "default_learn_glitch_floor_us")]
+    learn_glitch_floor_us: u16,
+}
+
+fn default_learn_glitch_floor_us() -> u16 {
+    IrHardwareConfig::default().learn_glitch_floor_us
 }
 
 #[derive(Debug, Serialize)]
@@ -288,6 +497,63 @@ struct IrConfigUpdateResponse {
     ir: IrConfigView,
 }
 
+#[derive(Debug, Serialize)]
+struct PidConfigView {
+    kp: f32,
+    ki: f32,
+    kd: f32,
+    #[serde(rename = "outputMin")]
+    output_min: f32,
+    #[serde(rename = "outputMax")]
+    output_max: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct PidConfigUpdate {
+    kp: f32,
+    ki: f32,
+    kd: f32,
+    #[serde(rename = "outputMin")]
+    output_min: f32,
+    #[serde(rename = "outputMax")]
+    output_max: f32,
+}
+
+fn build_pid_config_view(pid: &PidParameters) -> PidConfigView {
+    PidConfigView {
+        kp: pid.kp,
+        ki: pid.ki,
+        kd: pid.kd,
+        output_min: pid.output_min,
+        output_max: pid.output_max,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct IrLearnStartRequest {
+    command: String,
+}
+
+#[derive(Debug, Serialize)]
+struct IrLearnStartResponse {
+    accepted: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct IrLearnResultView {
+    status: LearnStatus,
+    timings: Option<Vec<u16>>,
+}
+
+impl From<LearnResult> for IrLearnResultView {
+    fn from(result: LearnResult) -> Self {
+        Self {
+            status: result.status,
+            timings: result.timings,
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 struct OtaRuntimeState {
     in_progress: bool,
@@ -298,6 +564,20 @@ struct OtaRuntimeState {
     last_sha256: Option<String>,
     last_source_url: Option<String>,
     last_completed_epoch: Option<i64>,
+    pending_verify: bool,
+    verify_deadline_ms: Option<u64>,
+    last_result: Option<OtaHealthState>,
+    available_version: Option<String>,
+    deferred_until_epoch: Option<i64>,
+    queued_update: Option<QueuedOtaUpdate>,
+}
+
+#[derive(Debug, Clone)]
+struct QueuedOtaUpdate {
+    url: String,
+    sha256: Option<String>,
+    signature: Option<String>,
+    password: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -306,6 +586,8 @@ struct OtaApplyRequest {
     #[serde(default)]
     sha256: Option<String>,
     #[serde(default)]
+    signature: Option<String>,
+    #[serde(default)]
     password: Option<String>,
     #[serde(default)]
     reboot: Option<bool>,
@@ -318,6 +600,38 @@ struct OtaApplyResponse {
     in_progress: bool,
 }
 
+#[derive(Debug, Deserialize)]
+struct OtaManifest {
+    version: String,
+    url: String,
+    #[serde(default)]
+    sha256: Option<String>,
+    #[serde(default)]
+    signature: Option<String>,
+    #[serde(rename = "minFreeHeap", default)]
+    min_free_heap: Option<u32>,
+    #[serde(rename = "notBefore", default)]
+    not_before: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OtaCheckRequest {
+    #[serde(rename = "manifestUrl")]
+    manifest_url: String,
+    #[serde(default)]
+    password: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct OtaCheckResponse {
+    accepted: bool,
+    reason: Option<String>,
+    #[serde(rename = "availableVersion")]
+    available_version: Option<String>,
+    #[serde(rename = "deferredUntilEpoch")]
+    deferred_until_epoch: Option<i64>,
+}
+
 #[derive(Debug, Serialize)]
 struct OtaStatusResponse {
     supported: bool,
@@ -343,6 +657,35 @@ struct OtaStatusResponse {
     boot_slot: Option<String>,
     #[serde(rename = "updateSlot")]
     update_slot: Option<String>,
+    #[serde(rename = "pendingVerify")]
+    pending_verify: bool,
+    #[serde(rename = "lastResult")]
+    last_result: Option<OtaHealthState>,
+    #[serde(rename = "healthCheckDeadlineEpoch")]
+    health_check_deadline_epoch: Option<i64>,
+    #[serde(rename = "availableVersion")]
+    available_version: Option<String>,
+    #[serde(rename = "deferredUntilEpoch")]
+    deferred_until_epoch: Option<i64>,
+}
+
+/// Outcome of the most recently completed OTA health check (confirm or
+/// rollback). `None` on `OtaStatusResponse` until the first health check
+/// resolves one way or the other; doesn't need a "pending" variant since
+/// that's already covered by `OtaStatusResponse::pending_verify`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum OtaHealthState {
+    Confirmed,
+    RolledBack,
+}
+
+/// Which network transport is currently carrying MQTT/HTTP traffic. Not
+/// part of the shared `ControllerStatus`, since that type is also built on
+/// the host platform, which has no concept of WiFi/PPP transports.
+#[derive(Debug, Serialize)]
+struct ConnectivityStatusResponse {
+    transport: Transport,
 }
 
 pub fn run() -> anyhow::Result<()> {
@@ -363,6 +706,7 @@ pub fn run() -> anyhow::Result<()> {
 
     runtime.settings.sanitize();
     runtime.ir.sanitize();
+    runtime.status_led.sanitize();
     ensure_wifi_defaults(&mut runtime);
 
     info!(
@@ -373,20 +717,30 @@ pub fn run() -> anyhow::Result<()> {
         runtime.network.mqtt_port,
     );
 
-    let Peripherals { modem, rmt, .. } = Peripherals::take()?;
-    let ir_sender = match init_ir_transmitter(rmt, &runtime.ir) {
-        Ok(transmitter) => {
-            info!(
-                "IR transmitter initialized on RMT channel{} / GPIO{} @ {}kHz",
-                runtime.ir.rmt_channel, runtime.ir.tx_pin, runtime.ir.carrier_khz
-            );
-            transmitter
-        }
-        Err(err) => {
-            warn!("failed to initialize IR transmitter, running disabled: {err:#}");
-            IrTransmitter::disabled()
+    let Peripherals { modem, rmt, uart1, .. } = Peripherals::take()?;
+    let learned_ir_commands = nvs_store.load_learned_ir_commands().unwrap_or_else(|err| {
+        warn!("failed to load learned IR commands from NVS: {err:#}");
+        HashMap::new()
+    });
+    let restored_ir_state = nvs_store.load_ir_runtime_state().unwrap_or_else(|err| {
+        warn!("failed to load IR runtime state from NVS: {err:#}");
+        None
+    });
+    let (mut ir_sender, status_led) = init_ir_and_status_led(
+        rmt,
+        &runtime.ir,
+        &runtime.status_led,
+        learned_ir_commands,
+        restored_ir_state,
+    );
+
+    if ir_sender.state_origin() == IrStateOrigin::Default {
+        if let Err(err) = ir_sender.resync() {
+            warn!("failed to resync IR runtime state: {err:#}");
+        } else {
+            info!("IR runtime state resynced to a known baseline");
         }
-    };
+    }
 
     let wifi = match connect_wifi(modem, sys_loop.clone(), nvs_partition, &runtime.network)
         .context("wifi startup failed")?
@@ -400,9 +754,17 @@ pub fn run() -> anyhow::Result<()> {
                 "wifi station connection unavailable; starting provisioning AP `{}`",
                 PROVISIONING_AP_SSID
             );
-            let server = create_provisioning_http_server(nvs_store.clone())?;
+            let provisioning = ProvisioningState::new();
+            provisioning.ap_mode_active.store(true, Ordering::Relaxed);
+            spawn_captive_portal_dns(PROVISIONING_AP_GATEWAY_IP, provisioning.clone());
+            let wifi = Arc::new(Mutex::new(wifi));
+            let server = create_provisioning_http_server(
+                nvs_store.clone(),
+                provisioning.clone(),
+                wifi.clone(),
+                sys_loop.clone(),
+            )?;
 
-            let _wifi = wifi;
             let _server = server;
             loop {
                 thread::sleep(Duration::from_secs(60));
@@ -411,12 +773,28 @@ pub fn run() -> anyhow::Result<()> {
     };
     disable_wifi_power_save();
 
-    let _sntp = EspSntp::new_default().context("failed to start SNTP")?;
-    info!("SNTP initialized");
+    let sntp = EspSntp::new_default().context("failed to start SNTP")?;
+    info!("SNTP initialized; schedule evaluation is gated until the first sync completes");
 
     init_watchdog(WATCHDOG_TIMEOUT_SEC)?;
+    let watchdog = WatchdogRegistry::default();
+    spawn_watchdog_supervisor(watchdog.clone());
+
+    let ota_pending_verify = nvs_store.load_ota_pending().unwrap_or_else(|err| {
+        warn!("failed to load OTA pending-verify flag from NVS: {err:#}");
+        false
+    });
+    let ota_last_result = nvs_store.load_ota_last_result().unwrap_or_else(|err| {
+        warn!("failed to load OTA last-result flag from NVS: {err:#}");
+        None
+    });
 
-    if let Ok(mut ota) = EspOta::new() {
+    if ota_pending_verify {
+        warn!(
+            "booted into a newly applied OTA image; deferring slot validation until \
+             wifi and mqtt both reconnect"
+        );
+    } else if let Ok(mut ota) = EspOta::new() {
         if let Err(err) = ota.mark_running_slot_valid() {
             warn!("failed to mark running OTA slot valid: {err:?}");
         }
@@ -435,15 +813,27 @@ pub fn run() -> anyhow::Result<()> {
         schedule: Arc::new(Mutex::new(schedule)),
         timezone: Arc::new(Mutex::new(runtime.timezone.clone())),
         time_synced: Arc::new(AtomicBool::new(false)),
+        last_sync_ms: Arc::new(Mutex::new(None)),
         ir_sender: Arc::new(Mutex::new(ir_sender)),
-        ota: Arc::new(Mutex::new(OtaRuntimeState::default())),
+        ir_learner: Arc::new(IrLearner::new()),
+        ir_learn_pending: Arc::new(Mutex::new(None)),
+        ota: Arc::new(Mutex::new(OtaRuntimeState {
+            pending_verify: ota_pending_verify,
+            verify_deadline_ms: ota_pending_verify
+                .then(|| monotonic_ms().saturating_add(OTA_HEALTH_GRACE_MS)),
+            last_result: ota_last_result,
+            ..OtaRuntimeState::default()
+        })),
         settings_save_deadline_ms: Arc::new(Mutex::new(None)),
         wifi_connected: Arc::new(AtomicBool::new(true)),
         mqtt_connected: Arc::new(AtomicBool::new(false)),
+        transport: Arc::new(Mutex::new(Transport::Wifi)),
+        nvs_store: nvs_store.clone(),
     };
-    let status_led = init_status_led(STATUS_LED_PIN);
+    let connectivity_manager =
+        init_connectivity_manager(uart1, shared_state.transport.clone());
 
-    let (mqtt_client, mqtt_conn) = create_mqtt_client(&runtime.network)?;
+    let (mqtt_client, mqtt_conn) = create_mqtt_client(&runtime.network, &nvs_store)?;
     let mqtt_client = Arc::new(Mutex::new(mqtt_client));
 
     subscribe_topics(&mqtt_client)?;
@@ -452,15 +842,20 @@ pub fn run() -> anyhow::Result<()> {
         nvs_store.clone(),
         mqtt_conn,
         mqtt_client.clone(),
+        watchdog.clone(),
     );
     spawn_control_loop(
         shared_state.clone(),
         nvs_store.clone(),
         mqtt_client.clone(),
         status_led,
+        connectivity_manager,
+        sntp,
+        watchdog,
     );
 
-    let server = create_http_server(shared_state.clone(), nvs_store)?;
+    let wifi = Arc::new(Mutex::new(wifi));
+    let server = create_http_server(shared_state.clone(), nvs_store, wifi.clone(), sys_loop)?;
 
     // Keep services alive for the program lifetime.
     let _wifi = wifi;
@@ -485,9 +880,110 @@ fn ensure_wifi_defaults(runtime: &mut RuntimeConfig) {
     }
 }
 
+#[derive(Debug, Clone, Serialize)]
+struct WifiScanEntry {
+    ssid: String,
+    rssi: i8,
+    #[serde(rename = "authMethod")]
+    auth_method: &'static str,
+    channel: u8,
+}
+
+fn auth_method_label(auth_method: Option<AuthMethod>) -> &'static str {
+    match auth_method {
+        Some(AuthMethod::None) => "NONE",
+        Some(AuthMethod::WEP) => "WEP",
+        Some(AuthMethod::WPA) => "WPA",
+        Some(AuthMethod::WPA2Personal) => "WPA2_PERSONAL",
+        Some(AuthMethod::WPAWPA2Personal) => "WPA_WPA2_PERSONAL",
+        Some(AuthMethod::WPA2Enterprise) => "WPA2_ENTERPRISE",
+        Some(AuthMethod::WPA3Personal) => "WPA3_PERSONAL",
+        Some(AuthMethod::WPA2WPA3Personal) => "WPA2_WPA3_PERSONAL",
+        Some(AuthMethod::WAPIPersonal) => "WAPI_PERSONAL",
+        None => "UNKNOWN",
+    }
+}
+
+/// Deduplicates SSIDs seen from more than one radio (common with mesh/repeater
+/// setups) down to the strongest entry, then sorts by descending RSSI so the
+/// provisioning picker shows the best candidate for a given network name
+/// first.
+fn dedup_scan_results(access_points: Vec<AccessPointInfo>) -> Vec<WifiScanEntry> {
+    let mut strongest: HashMap<String, WifiScanEntry> = HashMap::new();
+    for ap in access_points {
+        let ssid = ap.ssid.to_string();
+        if ssid.is_empty() {
+            continue;
+        }
+        strongest
+            .entry(ssid.clone())
+            .and_modify(|entry| {
+                if ap.signal_strength > entry.rssi {
+                    entry.rssi = ap.signal_strength;
+                    entry.auth_method = auth_method_label(ap.auth_method);
+                    entry.channel = ap.channel;
+                }
+            })
+            .or_insert(WifiScanEntry {
+                ssid,
+                rssi: ap.signal_strength,
+                auth_method: auth_method_label(ap.auth_method),
+                channel: ap.channel,
+            });
+    }
+
+    let mut entries: Vec<WifiScanEntry> = strongest.into_values().collect();
+    entries.sort_by(|a, b| b.rssi.cmp(&a.rssi));
+    entries
+}
+
+/// Scans for nearby access points. Used by the main server, where the radio
+/// is already in client (or client+AP) mode and can scan directly.
+fn scan_wifi_networks(
+    wifi: &Mutex<EspWifi<'static>>,
+    sys_loop: &EspSystemEventLoop,
+) -> anyhow::Result<Vec<WifiScanEntry>> {
+    let mut esp_wifi = wifi.lock().unwrap();
+    let mut blocking_wifi = BlockingWifi::wrap(&mut *esp_wifi, sys_loop.clone())?;
+    let access_points: Vec<AccessPointInfo> = blocking_wifi.scan()?;
+    Ok(dedup_scan_results(access_points))
+}
+
+/// Scans for nearby access points from the provisioning server, where the
+/// radio is running AP-only and ESP-IDF can't scan without an active STA
+/// interface. Temporarily switches to Mixed (AP + client) mode for the
+/// duration of the scan and restores the original AP-only configuration
+/// afterward, even if the scan itself failed.
+fn scan_wifi_networks_from_ap(
+    wifi: &Mutex<EspWifi<'static>>,
+    sys_loop: &EspSystemEventLoop,
+) -> anyhow::Result<Vec<WifiScanEntry>> {
+    let mut esp_wifi = wifi.lock().unwrap();
+    let mut blocking_wifi = BlockingWifi::wrap(&mut *esp_wifi, sys_loop.clone())?;
+
+    let Configuration::AccessPoint(ap_conf) = blocking_wifi.get_configuration()? else {
+        return Err(anyhow!("provisioning wifi radio is not in AP-only mode"));
+    };
+
+    blocking_wifi.set_configuration(&Configuration::Mixed(
+        ClientConfiguration::default(),
+        ap_conf.clone(),
+    ))?;
+
+    let scan_result = blocking_wifi.scan();
+
+    if let Err(err) = blocking_wifi.set_configuration(&Configuration::AccessPoint(ap_conf)) {
+        warn!("failed to restore AP-only wifi configuration after scan: {err:#}");
+    }
+
+    Ok(dedup_scan_results(scan_result?))
+}
+
 fn create_http_server(
     state: SharedState,
     nvs_store: NvsStore,
+    wifi: Arc<Mutex<EspWifi<'static>>>,
+    sys_loop: EspSystemEventLoop,
 ) -> anyhow::Result<EspHttpServer<'static>> {
     let conf = HttpConfiguration {
         stack_size: 16 * 1024,
@@ -511,6 +1007,17 @@ fn create_http_server(
         Ok(())
     })?;
 
+    {
+        let wifi = wifi.clone();
+        let sys_loop = sys_loop.clone();
+        server.fn_handler("/api/wifi/scan", Method::Get, move |req| {
+            match scan_wifi_networks(&wifi, &sys_loop) {
+                Ok(networks) => write_json(req, &networks),
+                Err(err) => write_error(req, 500, &format!("wifi scan failed: {err:#}")),
+            }
+        })?;
+    }
+
     {
         let state = state.clone();
         server.fn_handler("/api/status", Method::Get, move |req| {
@@ -519,6 +1026,14 @@ fn create_http_server(
         })?;
     }
 
+    {
+        let state = state.clone();
+        server.fn_handler("/api/settings/summary", Method::Get, move |req| {
+            let summary = state.engine.lock().unwrap().settings_summary();
+            write_json(req, &summary)
+        })?;
+    }
+
     {
         let state = state.clone();
         server.fn_handler("/api/target", Method::Post, move |req| {
@@ -543,6 +1058,30 @@ fn create_http_server(
         })?;
     }
 
+    {
+        let state = state.clone();
+        server.fn_handler("/api/auto_cool_target", Method::Post, move |req| {
+            let uri = req.uri().to_string();
+            let Some(target) =
+                query_param(&uri, "value").and_then(|value| value.parse::<f32>().ok())
+            else {
+                return write_error(req, 400, "Missing or invalid 'value' parameter");
+            };
+
+            let now_ms = monotonic_ms();
+            {
+                let mut engine = state.engine.lock().unwrap();
+                let changed = engine.set_auto_cool_setpoint(target);
+                if changed {
+                    queue_settings_save(&state, now_ms, engine.config.settings_save_debounce_ms);
+                }
+            }
+
+            let status = build_status(&state);
+            write_json(req, &status)
+        })?;
+    }
+
     {
         let state = state.clone();
         server.fn_handler("/api/mode", Method::Post, move |req| {
@@ -553,8 +1092,10 @@ fn create_http_server(
 
             let mode = match value.to_ascii_uppercase().as_str() {
                 "HEAT" => ThermostatMode::Heat,
+                "COOL" => ThermostatMode::Cool,
+                "AUTO" => ThermostatMode::Auto,
                 "OFF" => ThermostatMode::Off,
-                _ => return write_error(req, 400, "Invalid mode. Use 'HEAT' or 'OFF'"),
+                _ => return write_error(req, 400, "Invalid mode. Use 'HEAT', 'COOL', 'AUTO', or 'OFF'"),
             };
 
             let now_ms = monotonic_ms();
@@ -605,23 +1146,37 @@ fn create_http_server(
 
     {
         let state = state.clone();
-        server.fn_handler("/api/offset", Method::Post, move |req| {
+        server.fn_handler("/api/humidity-target", Method::Post, move |req| {
             let uri = req.uri().to_string();
             let Some(value) = query_param(&uri, "value") else {
                 return write_error(req, 400, "Missing 'value' parameter");
             };
-            let Ok(offset) = value.parse::<i32>() else {
-                return write_error(req, 400, "Invalid offset value (2-10, even only)");
-            };
 
-            if !(2..=10).contains(&offset) || offset % 2 != 0 {
-                return write_error(req, 400, "Invalid offset value (2-10, even only)");
-            }
+            // An empty value disables humidity-aware control.
+            let target = if value.is_empty() {
+                None
+            } else {
+                let Ok(parsed) = value.parse::<f32>() else {
+                    return write_error(
+                        req,
+                        400,
+                        "Invalid humidity target value (20-80, or empty to disable)",
+                    );
+                };
+                if !(20.0..=80.0).contains(&parsed) {
+                    return write_error(
+                        req,
+                        400,
+                        "Invalid humidity target value (20-80, or empty to disable)",
+                    );
+                }
+                Some(parsed)
+            };
 
             let now_ms = monotonic_ms();
             {
                 let mut engine = state.engine.lock().unwrap();
-                let changed = engine.set_fireplace_offset(offset);
+                let changed = engine.set_humidity_target(target);
                 if changed {
                     queue_settings_save(&state, now_ms, engine.config.settings_save_debounce_ms);
                 }
@@ -634,12 +1189,27 @@ fn create_http_server(
 
     {
         let state = state.clone();
-        server.fn_handler("/api/ir/on", Method::Post, move |req| {
-            let actions = {
-                let mut engine = state.engine.lock().unwrap();
-                engine.manual_on(monotonic_ms())
+        server.fn_handler("/api/humidity-hysteresis", Method::Post, move |req| {
+            let uri = req.uri().to_string();
+            let Some(value) = query_param(&uri, "value") else {
+                return write_error(req, 400, "Missing 'value' parameter");
             };
-            execute_engine_actions(&state, actions);
+            let Ok(humidity_hysteresis) = value.parse::<f32>() else {
+                return write_error(req, 400, "Invalid humidity hysteresis value (1.0-20.0)");
+            };
+
+            if !(1.0..=20.0).contains(&humidity_hysteresis) {
+                return write_error(req, 400, "Invalid humidity hysteresis value (1.0-20.0)");
+            }
+
+            let now_ms = monotonic_ms();
+            {
+                let mut engine = state.engine.lock().unwrap();
+                let changed = engine.set_humidity_hysteresis(humidity_hysteresis);
+                if changed {
+                    queue_settings_save(&state, now_ms, engine.config.settings_save_debounce_ms);
+                }
+            }
 
             let status = build_status(&state);
             write_json(req, &status)
@@ -648,12 +1218,34 @@ fn create_http_server(
 
     {
         let state = state.clone();
-        server.fn_handler("/api/ir/off", Method::Post, move |req| {
-            let actions = {
-                let mut engine = state.engine.lock().unwrap();
-                engine.manual_off(monotonic_ms())
+        server.fn_handler("/api/control-strategy", Method::Post, move |req| {
+            let uri = req.uri().to_string();
+            let Some(value) = query_param(&uri, "value") else {
+                return write_error(req, 400, "Missing 'value' parameter");
             };
-            execute_engine_actions(&state, actions);
+
+            let strategy = match value.to_ascii_uppercase().as_str() {
+                "HYSTERESIS" => ControlStrategy::Hysteresis,
+                "PID" => ControlStrategy::Pid,
+                "SETPOINT_PID" => ControlStrategy::SetpointPid,
+                "TIME_PROPORTIONAL" => ControlStrategy::TimeProportional,
+                _ => {
+                    return write_error(
+                        req,
+                        400,
+                        "Invalid control strategy. Use 'HYSTERESIS', 'PID', 'SETPOINT_PID', or 'TIME_PROPORTIONAL'",
+                    )
+                }
+            };
+
+            let now_ms = monotonic_ms();
+            {
+                let mut engine = state.engine.lock().unwrap();
+                let changed = engine.set_control_strategy(strategy);
+                if changed {
+                    queue_settings_save(&state, now_ms, engine.config.settings_save_debounce_ms);
+                }
+            }
 
             let status = build_status(&state);
             write_json(req, &status)
@@ -662,12 +1254,33 @@ fn create_http_server(
 
     {
         let state = state.clone();
-        server.fn_handler("/api/ir/heat/on", Method::Post, move |req| {
-            let actions = {
-                let mut engine = state.engine.lock().unwrap();
-                engine.manual_heat_on(monotonic_ms())
+        server.fn_handler("/api/display-unit", Method::Post, move |req| {
+            let uri = req.uri().to_string();
+            let Some(value) = query_param(&uri, "value") else {
+                return write_error(req, 400, "Missing 'value' parameter");
             };
-            execute_engine_actions(&state, actions);
+
+            let unit = match value.to_ascii_uppercase().as_str() {
+                "CELSIUS" => TemperatureUnit::Celsius,
+                "FAHRENHEIT" => TemperatureUnit::Fahrenheit,
+                "KELVIN" => TemperatureUnit::Kelvin,
+                _ => {
+                    return write_error(
+                        req,
+                        400,
+                        "Invalid display unit. Use 'CELSIUS', 'FAHRENHEIT', or 'KELVIN'",
+                    )
+                }
+            };
+
+            let now_ms = monotonic_ms();
+            {
+                let mut engine = state.engine.lock().unwrap();
+                let changed = engine.set_display_unit(unit);
+                if changed {
+                    queue_settings_save(&state, now_ms, engine.config.settings_save_debounce_ms);
+                }
+            }
 
             let status = build_status(&state);
             write_json(req, &status)
@@ -676,38 +1289,147 @@ fn create_http_server(
 
     {
         let state = state.clone();
-        server.fn_handler("/api/ir/heat/off", Method::Post, move |req| {
-            let actions = {
-                let mut engine = state.engine.lock().unwrap();
-                engine.manual_heat_off(monotonic_ms())
-            };
-            execute_engine_actions(&state, actions);
-
-            let status = build_status(&state);
-            write_json(req, &status)
+        server.fn_handler("/api/pid", Method::Get, move |req| {
+            let pid = state.engine.lock().unwrap().pid_parameters();
+            write_json(req, &build_pid_config_view(&pid))
         })?;
     }
 
     {
         let state = state.clone();
-        server.fn_handler("/api/ir/heat/up", Method::Post, move |req| {
-            let actions = {
+        let nvs_store = nvs_store.clone();
+        server.fn_handler::<anyhow::Error, _>("/api/pid", Method::Put, move |mut req| {
+            let body = read_request_body(&mut req)?;
+            let update: PidConfigUpdate =
+                serde_json::from_slice(&body).context("invalid pid config payload")?;
+
+            {
                 let mut engine = state.engine.lock().unwrap();
-                engine.manual_heat_up()
-            };
-            execute_engine_actions(&state, actions);
+                engine.set_kp(update.kp);
+                engine.set_ki(update.ki);
+                engine.set_kd(update.kd);
+                if !engine.set_pid_output_limits(update.output_min, update.output_max) {
+                    return write_error(req, 400, "outputMin must be < outputMax");
+                }
+            }
 
-            let status = build_status(&state);
-            write_json(req, &status)
+            let pid = state.engine.lock().unwrap().pid_parameters();
+            let mut runtime = nvs_store.load_runtime_config().unwrap_or_default();
+            runtime.thermostat.pid = pid;
+            if let Err(err) = nvs_store.save_runtime_config(&runtime) {
+                warn!("failed to persist pid update: {err:#}");
+                return write_error(req, 500, "failed to persist runtime settings");
+            }
+
+            write_json(req, &build_pid_config_view(&pid))
         })?;
     }
 
     {
         let state = state.clone();
-        server.fn_handler("/api/ir/heat/down", Method::Post, move |req| {
-            let actions = {
-                let mut engine = state.engine.lock().unwrap();
-                engine.manual_heat_down()
+        server.fn_handler("/api/offset", Method::Post, move |req| {
+            let uri = req.uri().to_string();
+            let Some(value) = query_param(&uri, "value") else {
+                return write_error(req, 400, "Missing 'value' parameter");
+            };
+            let Ok(offset) = value.parse::<i32>() else {
+                return write_error(req, 400, "Invalid offset value (2-10, even only)");
+            };
+
+            if !(2..=10).contains(&offset) || offset % 2 != 0 {
+                return write_error(req, 400, "Invalid offset value (2-10, even only)");
+            }
+
+            let now_ms = monotonic_ms();
+            {
+                let mut engine = state.engine.lock().unwrap();
+                let changed = engine.set_fireplace_offset(offset);
+                if changed {
+                    queue_settings_save(&state, now_ms, engine.config.settings_save_debounce_ms);
+                }
+            }
+
+            let status = build_status(&state);
+            write_json(req, &status)
+        })?;
+    }
+
+    {
+        let state = state.clone();
+        server.fn_handler("/api/ir/on", Method::Post, move |req| {
+            let actions = {
+                let mut engine = state.engine.lock().unwrap();
+                engine.manual_on(monotonic_ms())
+            };
+            execute_engine_actions(&state, actions);
+
+            let status = build_status(&state);
+            write_json(req, &status)
+        })?;
+    }
+
+    {
+        let state = state.clone();
+        server.fn_handler("/api/ir/off", Method::Post, move |req| {
+            let actions = {
+                let mut engine = state.engine.lock().unwrap();
+                engine.manual_off(monotonic_ms())
+            };
+            execute_engine_actions(&state, actions);
+
+            let status = build_status(&state);
+            write_json(req, &status)
+        })?;
+    }
+
+    {
+        let state = state.clone();
+        server.fn_handler("/api/ir/heat/on", Method::Post, move |req| {
+            let actions = {
+                let mut engine = state.engine.lock().unwrap();
+                engine.manual_heat_on(monotonic_ms())
+            };
+            execute_engine_actions(&state, actions);
+
+            let status = build_status(&state);
+            write_json(req, &status)
+        })?;
+    }
+
+    {
+        let state = state.clone();
+        server.fn_handler("/api/ir/heat/off", Method::Post, move |req| {
+            let actions = {
+                let mut engine = state.engine.lock().unwrap();
+                engine.manual_heat_off(monotonic_ms())
+            };
+            execute_engine_actions(&state, actions);
+
+            let status = build_status(&state);
+            write_json(req, &status)
+        })?;
+    }
+
+    {
+        let state = state.clone();
+        server.fn_handler("/api/ir/heat/up", Method::Post, move |req| {
+            let actions = {
+                let mut engine = state.engine.lock().unwrap();
+                engine.manual_heat_up()
+            };
+            execute_engine_actions(&state, actions);
+
+            let status = build_status(&state);
+            write_json(req, &status)
+        })?;
+    }
+
+    {
+        let state = state.clone();
+        server.fn_handler("/api/ir/heat/down", Method::Post, move |req| {
+            let actions = {
+                let mut engine = state.engine.lock().unwrap();
+                engine.manual_heat_down()
             };
             execute_engine_actions(&state, actions);
 
@@ -781,7 +1503,20 @@ fn create_http_server(
         server.fn_handler("/api/safety/reset", Method::Post, move |req| {
             {
                 let mut engine = state.engine.lock().unwrap();
-                engine.reset_safety();
+                engine.reset_safety(monotonic_ms());
+            }
+
+            let status = build_status(&state);
+            write_json(req, &status)
+        })?;
+    }
+
+    {
+        let state = state.clone();
+        server.fn_handler("/api/alarm/clear", Method::Post, move |req| {
+            {
+                let mut engine = state.engine.lock().unwrap();
+                engine.clear_alarm(monotonic_ms());
             }
 
             let status = build_status(&state);
@@ -820,10 +1555,12 @@ fn create_http_server(
         let state = state.clone();
         server.fn_handler("/api/time", Method::Get, move |req| {
             let timezone = state.timezone.lock().unwrap().clone();
+            let last_sync_epoch = state.last_sync_ms.lock().unwrap().map(monotonic_ms_to_epoch);
             let payload = TimeStatus {
                 time_synced: state.time_synced.load(Ordering::Relaxed),
                 timezone,
                 now_epoch: Utc::now().timestamp(),
+                last_sync_epoch,
             };
 
             write_json(req, &payload)
@@ -849,10 +1586,12 @@ fn create_http_server(
 
             persist_runtime_from_state(&nvs_store, &state)?;
 
+            let last_sync_epoch = state.last_sync_ms.lock().unwrap().map(monotonic_ms_to_epoch);
             let payload = TimeStatus {
                 time_synced: state.time_synced.load(Ordering::Relaxed),
                 timezone: update.timezone,
                 now_epoch: Utc::now().timestamp(),
+                last_sync_epoch,
             };
 
             write_json(req, &payload)
@@ -884,6 +1623,47 @@ fn create_http_server(
         })?;
     }
 
+    {
+        let nvs_store = nvs_store.clone();
+        server.fn_handler::<anyhow::Error, _>("/api/network/mqtt-ca", Method::Put, move |mut req| {
+            let body = read_request_body(&mut req)?;
+            let update: MqttCaCertUpdate =
+                serde_json::from_slice(&body).context("invalid mqtt CA payload")?;
+
+            if update.pem.trim().is_empty() {
+                return write_error(req, 400, "pem must not be empty");
+            }
+
+            nvs_store.save_mqtt_ca_cert(&update.pem)?;
+            write_json(req, &serde_json::json!({ "ok": true }))
+        })?;
+    }
+
+    {
+        let nvs_store = nvs_store.clone();
+        server.fn_handler::<anyhow::Error, _>(
+            "/api/ota/signing-key",
+            Method::Put,
+            move |mut req| {
+                let body = read_request_body(&mut req)?;
+                let update: OtaSigningKeyUpdate =
+                    serde_json::from_slice(&body).context("invalid OTA signing key payload")?;
+
+                let decoded = BASE64_STANDARD
+                    .decode(update.public_key_base64.trim())
+                    .map_err(|_| anyhow!("publicKeyBase64 must be valid base64"))?;
+                let key_bytes: [u8; 32] = decoded
+                    .try_into()
+                    .map_err(|_| anyhow!("publicKeyBase64 must decode to 32 bytes"))?;
+                VerifyingKey::from_bytes(&key_bytes)
+                    .map_err(|err| anyhow!("not a valid Ed25519 public key: {err}"))?;
+
+                nvs_store.save_ota_signing_public_key(&key_bytes)?;
+                write_json(req, &serde_json::json!({ "ok": true }))
+            },
+        )?;
+    }
+
     {
         let nvs_store = nvs_store.clone();
         server.fn_handler("/api/ir/config", Method::Get, move |req| {
@@ -917,6 +1697,70 @@ fn create_http_server(
         })?;
     }
 
+    {
+        let state = state.clone();
+        let nvs_store = nvs_store.clone();
+        server.fn_handler::<anyhow::Error, _>(
+            "/api/ir/learn/start",
+            Method::Post,
+            move |mut req| {
+                let body = read_request_body(&mut req)?;
+                let request: IrLearnStartRequest =
+                    serde_json::from_slice(&body).context("invalid ir learn payload")?;
+                if request.command.trim().is_empty() {
+                    return write_error(req, 400, "command cannot be empty");
+                }
+
+                let runtime = nvs_store.load_runtime_config().unwrap_or_default();
+                let rx_pin = match validate_ir_learn_rx_pin(&runtime.ir) {
+                    Ok(rx_pin) => rx_pin,
+                    Err(message) => return write_error(req, 400, message),
+                };
+
+                let glitch_floor_us = runtime.ir.learn_glitch_floor_us;
+                match unsafe { state.ir_learner.start(AnyIOPin::new(rx_pin), glitch_floor_us) } {
+                    Ok(()) => {
+                        *state.ir_learn_pending.lock().unwrap() =
+                            Some((request.command, false));
+                        write_json(req, &IrLearnStartResponse { accepted: true })
+                    }
+                    Err(err) => write_error(req, 409, &err.to_string()),
+                }
+            },
+        )?;
+    }
+
+    {
+        let state = state.clone();
+        let nvs_store = nvs_store.clone();
+        server.fn_handler("/api/ir/learn/result", Method::Get, move |req| {
+            let result = state.ir_learner.result();
+            if result.status == LearnStatus::Done {
+                let mut pending = state.ir_learn_pending.lock().unwrap();
+                if let Some((command, persisted)) = pending.as_mut() {
+                    if !*persisted {
+                        if let Some(timings) = result.timings.as_ref() {
+                            if let Err(err) =
+                                nvs_store.save_learned_ir_command(command, timings)
+                            {
+                                warn!("failed to persist learned IR command: {err:#}");
+                            } else {
+                                state
+                                    .ir_sender
+                                    .lock()
+                                    .unwrap()
+                                    .learn_command(command.clone(), timings.clone());
+                                *persisted = true;
+                            }
+                        }
+                    }
+                }
+            }
+
+            write_json(req, &IrLearnResultView::from(result))
+        })?;
+    }
+
     {
         let state = state.clone();
         server.fn_handler("/api/ota/status", Method::Get, move |req| {
@@ -925,6 +1769,42 @@ fn create_http_server(
         })?;
     }
 
+    {
+        let state = state.clone();
+        server.fn_handler("/api/connectivity/status", Method::Get, move |req| {
+            let payload = build_connectivity_status(&state);
+            write_json(req, &payload)
+        })?;
+    }
+
+    {
+        let state = state.clone();
+        let nvs_store = nvs_store.clone();
+        server.fn_handler::<anyhow::Error, _>("/api/ota/confirm", Method::Post, move |req| {
+            match confirm_ota_update(&state, &nvs_store) {
+                Ok(payload) => write_json(req, &payload),
+                Err(err) => {
+                    warn!("OTA confirm failed: {err:#}");
+                    write_error(req, 500, "Failed to confirm OTA update")
+                }
+            }
+        })?;
+    }
+
+    {
+        let state = state.clone();
+        let nvs_store = nvs_store.clone();
+        server.fn_handler::<anyhow::Error, _>("/api/ota/revert", Method::Post, move |req| {
+            match revert_ota_update(&state, &nvs_store) {
+                Ok(()) => write_json(req, &serde_json::json!({ "reverting": true })),
+                Err(err) => {
+                    warn!("OTA revert failed: {err:#}");
+                    write_error(req, 500, "Failed to revert OTA update")
+                }
+            }
+        })?;
+    }
+
     {
         let state = state.clone();
         let nvs_store = nvs_store.clone();
@@ -953,16 +1833,89 @@ fn create_http_server(
         })?;
     }
 
+    {
+        let state = state.clone();
+        let nvs_store = nvs_store.clone();
+        server.fn_handler::<anyhow::Error, _>("/api/ota/check", Method::Post, move |mut req| {
+            let body = read_request_body(&mut req)?;
+            let request: OtaCheckRequest =
+                serde_json::from_slice(&body).context("invalid ota check payload")?;
+
+            match check_ota_manifest(&state, &nvs_store, &request) {
+                Ok(payload) => write_json(req, &payload),
+                Err(err) => {
+                    let message = err.to_string();
+                    if message.contains("invalid OTA password") {
+                        write_error(req, 403, &message)
+                    } else {
+                        write_error(req, 500, &format!("OTA manifest check failed: {message}"))
+                    }
+                }
+            }
+        })?;
+    }
+
     Ok(server)
 }
 
-fn create_provisioning_http_server(nvs_store: NvsStore) -> anyhow::Result<EspHttpServer<'static>> {
+fn create_provisioning_http_server(
+    nvs_store: NvsStore,
+    provisioning: ProvisioningState,
+    wifi: Arc<Mutex<EspWifi<'static>>>,
+    sys_loop: EspSystemEventLoop,
+) -> anyhow::Result<EspHttpServer<'static>> {
     let conf = HttpConfiguration {
         stack_size: 16 * 1024,
         ..Default::default()
     };
     let mut server = EspHttpServer::new(&conf)?;
 
+    {
+        server.fn_handler("/api/wifi/scan", Method::Get, move |req| {
+            match scan_wifi_networks_from_ap(&wifi, &sys_loop) {
+                Ok(networks) => write_json(req, &networks),
+                Err(err) => write_error(req, 500, &format!("wifi scan failed: {err:#}")),
+            }
+        })?;
+    }
+
+    {
+        let provisioning = provisioning.clone();
+        server.fn_handler("/api/provision/state", Method::Get, move |req| {
+            let payload = ProvisionStateView {
+                ap_mode_active: provisioning.ap_mode_active.load(Ordering::Relaxed),
+                connected_clients: provisioning.connected_clients.load(Ordering::Relaxed),
+            };
+            write_json(req, &payload)
+        })?;
+    }
+
+    {
+        let nvs_store = nvs_store.clone();
+        server.fn_handler::<anyhow::Error, _>(
+            "/api/provision/complete",
+            Method::Post,
+            move |req| {
+                let runtime = nvs_store.load_runtime_config().unwrap_or_default();
+                info!(
+                    "provisioning complete requested; rejoining `{}`",
+                    runtime.network.wifi_ssid
+                );
+                // Rejoining WiFi from station mode requires reinitializing the
+                // driver, so follow the same restart-based rejoin already used
+                // by the network config PUT handler.
+                thread::Builder::new()
+                    .name("prov-restart".into())
+                    .spawn(|| {
+                        thread::sleep(Duration::from_secs(3));
+                        unsafe { esp_idf_svc::sys::esp_restart() };
+                    })
+                    .expect("failed to spawn restart thread");
+                write_json(req, &serde_json::json!({ "restarting": true }))
+            },
+        )?;
+    }
+
     for path in [
         "/",
         "/generate_204",
@@ -1144,42 +2097,104 @@ fn query_param(uri: &str, key: &str) -> Option<String> {
     None
 }
 
-fn init_ir_transmitter(rmt: RMT, ir: &IrHardwareConfig) -> anyhow::Result<IrTransmitter> {
+fn init_ir_transmitter(
+    rmt: RMT,
+    ir: &IrHardwareConfig,
+    learned_commands: HashMap<String, Vec<u16>>,
+    restored_state: Option<IrRuntimeState>,
+) -> anyhow::Result<IrTransmitter> {
     if ir.tx_pin < 0 {
         return Err(anyhow!("invalid tx pin: {}", ir.tx_pin));
     }
 
     let pin = ir.tx_pin;
     let carrier_khz = ir.carrier_khz;
+    let protocol = ir.protocol;
 
     match ir.rmt_channel {
         0 => unsafe {
-            IrTransmitter::new_with_carrier(rmt.channel0, AnyOutputPin::new(pin), carrier_khz)
+            IrTransmitter::new_with_carrier(
+                rmt.channel0,
+                AnyOutputPin::new(pin),
+                carrier_khz,
+                protocol,
+                learned_commands,
+                restored_state,
+            )
         },
         1 => unsafe {
-            IrTransmitter::new_with_carrier(rmt.channel1, AnyOutputPin::new(pin), carrier_khz)
+            IrTransmitter::new_with_carrier(
+                rmt.channel1,
+                AnyOutputPin::new(pin),
+                carrier_khz,
+                protocol,
+                learned_commands,
+                restored_state,
+            )
         },
         2 => unsafe {
-            IrTransmitter::new_with_carrier(rmt.channel2, AnyOutputPin::new(pin), carrier_khz)
+            IrTransmitter::new_with_carrier(
+                rmt.channel2,
+                AnyOutputPin::new(pin),
+                carrier_khz,
+                protocol,
+                learned_commands,
+                restored_state,
+            )
         },
         3 => unsafe {
-            IrTransmitter::new_with_carrier(rmt.channel3, AnyOutputPin::new(pin), carrier_khz)
+            IrTransmitter::new_with_carrier(
+                rmt.channel3,
+                AnyOutputPin::new(pin),
+                carrier_khz,
+                protocol,
+                learned_commands,
+                restored_state,
+            )
         },
         #[cfg(any(esp32, esp32s3))]
         4 => unsafe {
-            IrTransmitter::new_with_carrier(rmt.channel4, AnyOutputPin::new(pin), carrier_khz)
+            IrTransmitter::new_with_carrier(
+                rmt.channel4,
+                AnyOutputPin::new(pin),
+                carrier_khz,
+                protocol,
+                learned_commands,
+                restored_state,
+            )
         },
         #[cfg(any(esp32, esp32s3))]
         5 => unsafe {
-            IrTransmitter::new_with_carrier(rmt.channel5, AnyOutputPin::new(pin), carrier_khz)
+            IrTransmitter::new_with_carrier(
+                rmt.channel5,
+                AnyOutputPin::new(pin),
+                carrier_khz,
+                protocol,
+                learned_commands,
+                restored_state,
+            )
         },
         #[cfg(any(esp32, esp32s3))]
         6 => unsafe {
-            IrTransmitter::new_with_carrier(rmt.channel6, AnyOutputPin::new(pin), carrier_khz)
+            IrTransmitter::new_with_carrier(
+                rmt.channel6,
+                AnyOutputPin::new(pin),
+                carrier_khz,
+                protocol,
+                learned_commands,
+                restored_state,
+            )
         },
         #[cfg(any(esp32, esp32s3))]
         7 => unsafe {
-            IrTransmitter::new_with_carrier(rmt.channel7, AnyOutputPin::new(pin), carrier_khz)
+            IrTransmitter::new_with_carrier(
+                rmt.channel7,
+                AnyOutputPin::new(pin),
+                carrier_khz,
+                protocol,
+                learned_commands,
+                restored_state,
+            )
         },
         _ => Err(anyhow!("unsupported RMT channel: {}", ir.rmt_channel)),
     }
@@ -1221,7 +2236,7 @@ fn build_sta_netif(network: &NetworkConfig) -> anyhow::Result<Option<EspNetif>>
                     mask,
                 },
                 dns: network.dns.map(ipv4_from_octets),
-                secondary_dns: None,
+                secondary_dns: network.secondary_dns.map(ipv4_from_octets),
             },
         ))),
         ..NetifConfiguration::wifi_default_client()
@@ -1230,6 +2245,106 @@ fn build_sta_netif(network: &NetworkConfig) -> anyhow::Result<Option<EspNetif>>
     Ok(Some(EspNetif::new_with_conf(&conf)?))
 }
 
+/// Overrides the DHCP-assigned DNS resolver(s) with `network.dns`/
+/// `secondary_dns`, letting users pin a preferred resolver even when the
+/// station otherwise gets its address over DHCP. No-op if neither is set.
+fn override_dhcp_dns(netif: &EspNetif, network: &NetworkConfig) -> anyhow::Result<()> {
+    if let Some(dns) = network.dns {
+        set_netif_dns(netif, esp_idf_svc::sys::esp_netif_dns_type_t_ESP_NETIF_DNS_MAIN, dns)?;
+    }
+    if let Some(secondary_dns) = network.secondary_dns {
+        set_netif_dns(
+            netif,
+            esp_idf_svc::sys::esp_netif_dns_type_t_ESP_NETIF_DNS_BACKUP,
+            secondary_dns,
+        )?;
+    }
+    Ok(())
+}
+
+fn set_netif_dns(
+    netif: &EspNetif,
+    dns_type: esp_idf_svc::sys::esp_netif_dns_type_t,
+    addr: [u8; 4],
+) -> anyhow::Result<()> {
+    let mut dns_info = esp_idf_svc::sys::esp_netif_dns_info_t::default();
+    unsafe {
+        dns_info.ip.type_ = esp_idf_svc::sys::esp_ip_addr_type_t_ESP_IPADDR_TYPE_V4;
+        dns_info.ip.u_addr.ip4.addr = u32::from_le_bytes(addr);
+    }
+    let rc = unsafe {
+        esp_idf_svc::sys::esp_netif_set_dns_info(netif.handle(), dns_type, &mut dns_info)
+    };
+    if rc != esp_idf_svc::sys::ESP_OK {
+        return Err(anyhow!("esp_netif_set_dns_info failed with code {rc}"));
+    }
+    Ok(())
+}
+
+/// Maps `network.wifi_auth` onto the matching `AuthMethod`. When unset, keeps
+/// the historical auto-detect behavior: open if `wifi_pass` is empty,
+/// WPA/WPA2-Personal otherwise.
+fn resolve_wifi_auth_method(network: &NetworkConfig) -> AuthMethod {
+    match network.wifi_auth {
+        Some(WifiAuthMode::Open) => AuthMethod::None,
+        Some(WifiAuthMode::Wpa2) => AuthMethod::WPA2Personal,
+        Some(WifiAuthMode::Wpa2Wpa3) => AuthMethod::WPA2WPA3Personal,
+        Some(WifiAuthMode::Wpa3) => AuthMethod::WPA3Personal,
+        Some(WifiAuthMode::Wpa2Enterprise) => AuthMethod::WPA2Enterprise,
+        None if network.wifi_pass.is_empty() => AuthMethod::None,
+        None => AuthMethod::WPAWPA2Personal,
+    }
+}
+
+/// Configures the ESP-IDF WPA2-Enterprise (802.1X) supplicant with the
+/// identity/username/password needed to join a corporate or campus network.
+/// Must run before `wifi.set_configuration`/`wifi.connect()` for
+/// `AuthMethod::WPA2Enterprise` networks; `network.wifi_pass` doubles as the
+/// EAP password.
+fn configure_wifi_enterprise_credentials(network: &NetworkConfig) -> anyhow::Result<()> {
+    let identity = network
+        .wifi_identity
+        .as_deref()
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| anyhow!("wifi_identity is required for WPA2-Enterprise"))?;
+    let username = network
+        .wifi_username
+        .as_deref()
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| anyhow!("wifi_username is required for WPA2-Enterprise"))?;
+
+    let rc = unsafe {
+        esp_idf_svc::sys::esp_eap_client_set_identity(identity.as_ptr(), identity.len() as i32)
+    };
+    if rc != esp_idf_svc::sys::ESP_OK {
+        return Err(anyhow!("esp_eap_client_set_identity failed with code {rc}"));
+    }
+
+    let rc = unsafe {
+        esp_idf_svc::sys::esp_eap_client_set_username(username.as_ptr(), username.len() as i32)
+    };
+    if rc != esp_idf_svc::sys::ESP_OK {
+        return Err(anyhow!("esp_eap_client_set_username failed with code {rc}"));
+    }
+
+    let rc = unsafe {
+        esp_idf_svc::sys::esp_eap_client_set_password(
+            network.wifi_pass.as_ptr(),
+            network.wifi_pass.len() as i32,
+        )
+    };
+    if rc != esp_idf_svc::sys::ESP_OK {
+        return Err(anyhow!("esp_eap_client_set_password failed with code {rc}"));
+    }
+
+    let rc = unsafe { esp_idf_svc::sys::esp_wifi_sta_enterprise_enable() };
+    if rc != esp_idf_svc::sys::ESP_OK {
+        return Err(anyhow!("esp_wifi_sta_enterprise_enable failed with code {rc}"));
+    }
+
+    Ok(())
+}
+
 fn connect_wifi(
     modem: Modem,
     sys_loop: EspSystemEventLoop,
@@ -1263,11 +2378,10 @@ fn connect_wifi(
         return Ok(WifiStartup::Provisioning(esp_wifi));
     }
 
-    let auth_method = if network.wifi_pass.is_empty() {
-        AuthMethod::None
-    } else {
-        AuthMethod::WPAWPA2Personal
-    };
+    let auth_method = resolve_wifi_auth_method(network);
+    if matches!(auth_method, AuthMethod::WPA2Enterprise) {
+        configure_wifi_enterprise_credentials(network)?;
+    }
 
     wifi.set_configuration(&Configuration::Client(ClientConfiguration {
         ssid: network
@@ -1294,6 +2408,11 @@ fn connect_wifi(
             Ok(()) => match wifi.wait_netif_up() {
                 Ok(()) => {
                     info!("wifi connected and netif up on attempt {attempt}");
+                    if !network.use_static_ip {
+                        if let Err(err) = override_dhcp_dns(wifi.wifi_mut().sta_netif(), network) {
+                            warn!("failed to apply DNS override over DHCP: {err:#}");
+                        }
+                    }
                     last_err = None;
                     break;
                 }
@@ -1326,10 +2445,126 @@ fn connect_wifi(
     }
 }
 
-fn start_provisioning_ap(wifi: &mut BlockingWifi<&mut EspWifi<'static>>) -> anyhow::Result<()> {
-    wifi.set_configuration(&Configuration::AccessPoint(AccessPointConfiguration {
-        ssid: PROVISIONING_AP_SSID
-            .try_into()
+#[derive(Clone)]
+struct ProvisioningState {
+    ap_mode_active: Arc<AtomicBool>,
+    connected_clients: Arc<AtomicU32>,
+}
+
+impl ProvisioningState {
+    fn new() -> Self {
+        Self {
+            ap_mode_active: Arc::new(AtomicBool::new(false)),
+            connected_clients: Arc::new(AtomicU32::new(0)),
+        }
+    }
+}
+
+/// Builds a DNS response that answers an A query in `request` with a single A
+/// record pointing at `answer_ip`, echoing the query ID and question section
+/// per RFC 1035 (12-byte header, then the original question verbatim). An
+/// AAAA query gets a response with no answers; any other query type, or a
+/// request that isn't exactly one well-formed question, yields `None`.
+fn build_dns_redirect_response(request: &[u8], answer_ip: Ipv4Addr) -> Option<Vec<u8>> {
+    if request.len() < 12 {
+        return None;
+    }
+
+    let question_count = u16::from_be_bytes([request[4], request[5]]);
+    if question_count != 1 {
+        return None;
+    }
+
+    // Walk the QNAME labels to find the end of the question section.
+    let mut pos = 12usize;
+    loop {
+        let len = *request.get(pos)? as usize;
+        pos += 1;
+        if len == 0 {
+            break;
+        }
+        pos += len;
+        if pos >= request.len() {
+            return None;
+        }
+    }
+    let question_end = pos + 4; // QNAME terminator already consumed + QTYPE(2) + QCLASS(2)
+    if question_end > request.len() {
+        return None;
+    }
+    let qtype = u16::from_be_bytes([request[pos], request[pos + 1]]);
+
+    // Only answer A (1) and AAAA (28) queries; ignore everything else gracefully.
+    if qtype != 1 && qtype != 28 {
+        return None;
+    }
+
+    let mut response = Vec::with_capacity(question_end + 16);
+    response.extend_from_slice(&request[0..2]); // transaction ID
+    response.extend_from_slice(&[0x81, 0x80]); // flags: standard response, recursion available
+    response.extend_from_slice(&request[4..6]); // QDCOUNT
+    response.extend_from_slice(&[0x00, 0x01]); // ANCOUNT = 1
+    response.extend_from_slice(&[0x00, 0x00]); // NSCOUNT
+    response.extend_from_slice(&[0x00, 0x00]); // ARCOUNT
+    response.extend_from_slice(&request[12..question_end]); // original question
+
+    if qtype == 1 {
+        response.extend_from_slice(&[0xc0, 0x0c]); // name: pointer to question
+        response.extend_from_slice(&[0x00, 0x01]); // TYPE A
+        response.extend_from_slice(&[0x00, 0x01]); // CLASS IN
+        response.extend_from_slice(&60u32.to_be_bytes()); // TTL
+        response.extend_from_slice(&[0x00, 0x04]); // RDLENGTH
+        response.extend_from_slice(&answer_ip.octets());
+    } else {
+        // No AAAA records to offer; respond with zero answers for v6 queries.
+        response[6] = 0x00;
+        response[7] = 0x00;
+    }
+
+    Some(response)
+}
+
+/// Spins up a UDP DNS responder on port 53 that answers every A-record query
+/// with `answer_ip`, so any client that joins the provisioning AP is steered
+/// to the config page regardless of which hostname it tries to resolve.
+fn spawn_captive_portal_dns(answer_ip: Ipv4Addr, provisioning: ProvisioningState) {
+    thread::Builder::new()
+        .name("captive-dns".into())
+        .stack_size(8 * 1024)
+        .spawn(move || {
+            let socket = match UdpSocket::bind(("0.0.0.0", 53)) {
+                Ok(socket) => socket,
+                Err(err) => {
+                    warn!("failed to bind captive portal DNS socket: {err}");
+                    return;
+                }
+            };
+
+            let mut buf = [0u8; 512];
+            loop {
+                match socket.recv_from(&mut buf) {
+                    Ok((len, src)) => {
+                        provisioning
+                            .connected_clients
+                            .store(1, Ordering::Relaxed);
+                        if let Some(response) = build_dns_redirect_response(&buf[..len], answer_ip)
+                        {
+                            if let Err(err) = socket.send_to(&response, src) {
+                                warn!("failed to send captive portal DNS response: {err}");
+                            }
+                        }
+                    }
+                    Err(err) => warn!("captive portal DNS recv error: {err}"),
+                }
+            }
+        })
+        .expect("failed to spawn captive portal DNS thread");
+}
+
+fn start_provisioning_ap(wifi: &mut BlockingWifi<&mut EspWifi<'static>>) -> anyhow::Result<()> {
+    wifi.set_configuration(&Configuration::AccessPoint(AccessPointConfiguration {
+        ssid: PROVISIONING_AP_SSID
+            .try_into()
             .map_err(|_| anyhow!("provisioning AP SSID too long"))?,
         password: PROVISIONING_AP_PASSWORD
             .try_into()
@@ -1349,11 +2584,33 @@ fn start_provisioning_ap(wifi: &mut BlockingWifi<&mut EspWifi<'static>>) -> anyh
 
 fn create_mqtt_client(
     network: &thermostat_common::config::NetworkConfig,
+    nvs_store: &NvsStore,
 ) -> anyhow::Result<(EspMqttClient<'static>, EspMqttConnection)> {
-    let url = format!("mqtt://{}:{}", network.mqtt_host, network.mqtt_port);
+    let scheme = if network.mqtt_tls { "mqtts" } else { "mqtt" };
+    let url = format!("{scheme}://{}:{}", network.mqtt_host, network.mqtt_port);
+
+    let lwt = LwtConfiguration {
+        topic: TOPIC_CONTROLLER_AVAILABILITY,
+        payload: b"offline",
+        qos: QoS::AtLeastOnce,
+        retain: true,
+    };
+
+    let ca_cert_cstring = if network.mqtt_tls {
+        let pem = nvs_store.load_mqtt_ca_cert().unwrap_or_else(|err| {
+            warn!("failed to load custom MQTT CA cert from NVS: {err:#}");
+            None
+        });
+        pem.map(std::ffi::CString::new)
+            .transpose()
+            .map_err(|_| anyhow!("mqtt CA cert contains interior NUL bytes"))?
+    } else {
+        None
+    };
 
-    let conf = MqttClientConfiguration {
-        client_id: Some("thermostat-controller"),
+    let mut conf = MqttClientConfiguration {
+        client_id: Some(HA_DEVICE_ID),
+        lwt: Some(lwt),
         username: if network.mqtt_user.is_empty() {
             None
         } else {
@@ -1367,18 +2624,30 @@ fn create_mqtt_client(
         ..Default::default()
     };
 
+    if network.mqtt_tls {
+        match ca_cert_cstring.as_deref() {
+            Some(cert) => {
+                conf.server_certificate = Some(X509::pem_until_nul(cert.to_bytes_with_nul()));
+            }
+            None => conf.crt_bundle_attach = Some(esp_idf_svc::sys::esp_crt_bundle_attach),
+        }
+    }
+
     Ok(EspMqttClient::new(url.as_str(), &conf)?)
 }
 
 fn subscribe_topics(mqtt: &Arc<Mutex<EspMqttClient<'static>>>) -> anyhow::Result<()> {
     let topics = [
-        TOPIC_SENSOR_TEMP,
-        TOPIC_SENSOR_HUMIDITY,
+        TOPIC_SENSOR_TEMP_WILDCARD,
+        TOPIC_SENSOR_HUMIDITY_WILDCARD,
         TOPIC_CMD_POWER,
         TOPIC_CMD_TARGET,
         TOPIC_CMD_MODE,
         TOPIC_CMD_HOLD,
         TOPIC_CMD_SCHEDULE,
+        TOPIC_CMD_OVERRIDE,
+        TOPIC_CMD_DATE_EXCEPTIONS,
+        TOPIC_CMD,
     ];
 
     let mut mqtt = mqtt.lock().unwrap();
@@ -1389,21 +2658,231 @@ fn subscribe_topics(mqtt: &Arc<Mutex<EspMqttClient<'static>>>) -> anyhow::Result
     Ok(())
 }
 
+#[derive(Debug, Serialize)]
+struct HaDevice {
+    identifiers: Vec<String>,
+    name: String,
+    manufacturer: String,
+    sw_version: String,
+}
+
+fn ha_device() -> HaDevice {
+    HaDevice {
+        identifiers: vec![HA_DEVICE_ID.to_string()],
+        name: "Smart Thermostat".to_string(),
+        manufacturer: "nkocher".to_string(),
+        sw_version: FIRMWARE_VERSION.to_string(),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct HaClimateDiscovery {
+    name: String,
+    unique_id: String,
+    availability_topic: String,
+    mode_command_topic: String,
+    mode_state_topic: String,
+    mode_state_template: String,
+    modes: Vec<String>,
+    temperature_command_topic: String,
+    temperature_state_topic: String,
+    temperature_state_template: String,
+    current_temperature_topic: String,
+    current_temperature_template: String,
+    current_humidity_topic: String,
+    current_humidity_template: String,
+    power_command_topic: String,
+    preset_modes: Vec<String>,
+    preset_mode_command_topic: String,
+    preset_mode_state_topic: String,
+    preset_mode_value_template: String,
+    min_temp: f32,
+    max_temp: f32,
+    temp_step: f32,
+    temperature_unit: String,
+    device: HaDevice,
+}
+
+#[derive(Debug, Serialize)]
+struct HaSensorDiscovery {
+    name: String,
+    unique_id: String,
+    availability_topic: String,
+    state_topic: String,
+    value_template: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unit_of_measurement: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    device_class: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entity_category: Option<String>,
+    device: HaDevice,
+}
+
+/// Publishes a retained discovery config for one entity under
+/// `homeassistant/<component>/<object_id>/config`, the topic layout Home
+/// Assistant's MQTT integration polls on startup (and whenever a retained
+/// message lands there) to add or update an entity.
+fn publish_ha_discovery_config<T: Serialize>(
+    mqtt: &Arc<Mutex<EspMqttClient<'static>>>,
+    component: &str,
+    object_id: &str,
+    config: &T,
+) -> anyhow::Result<()> {
+    let topic = format!("homeassistant/{component}/{object_id}/config");
+    let payload = serde_json::to_vec(config)?;
+    let mut client = mqtt.lock().unwrap();
+    client.publish(&topic, QoS::AtLeastOnce, true, &payload)?;
+    Ok(())
+}
+
+/// Publishes Home Assistant MQTT discovery configs for the thermostat's
+/// climate entity plus standalone temperature/humidity sensors, all
+/// referencing the command/state topics this firmware already publishes and
+/// subscribes to. Safe to call repeatedly; every config is retained, so HA
+/// simply re-applies the same entity definition.
+fn publish_ha_discovery(mqtt: &Arc<Mutex<EspMqttClient<'static>>>) -> anyhow::Result<()> {
+    let climate = HaClimateDiscovery {
+        name: "Thermostat".to_string(),
+        unique_id: HA_DEVICE_ID.to_string(),
+        availability_topic: TOPIC_CONTROLLER_AVAILABILITY.to_string(),
+        mode_command_topic: TOPIC_CMD_MODE.to_string(),
+        mode_state_topic: TOPIC_CONTROLLER_STATE.to_string(),
+        mode_state_template: "{{ value_json.mode | lower }}".to_string(),
+        modes: vec![
+            "off".to_string(),
+            "heat".to_string(),
+            "cool".to_string(),
+            "auto".to_string(),
+        ],
+        temperature_command_topic: TOPIC_CMD_TARGET.to_string(),
+        temperature_state_topic: TOPIC_CONTROLLER_STATE.to_string(),
+        temperature_state_template: "{{ value_json.target }}".to_string(),
+        current_temperature_topic: TOPIC_CONTROLLER_STATE.to_string(),
+        current_temperature_template: "{{ value_json.temp }}".to_string(),
+        current_humidity_topic: TOPIC_CONTROLLER_STATE.to_string(),
+        current_humidity_template: "{{ value_json.humidity }}".to_string(),
+        power_command_topic: TOPIC_CMD_POWER.to_string(),
+        preset_modes: vec!["none".to_string(), "hold".to_string()],
+        preset_mode_command_topic: TOPIC_CMD_HOLD.to_string(),
+        preset_mode_state_topic: TOPIC_CONTROLLER_STATE.to_string(),
+        preset_mode_value_template: "{{ 'hold' if value_json.holdActive else 'none' }}"
+            .to_string(),
+        min_temp: 60.0,
+        max_temp: 84.0,
+        temp_step: 1.0,
+        temperature_unit: "F".to_string(),
+        device: ha_device(),
+    };
+    publish_ha_discovery_config(mqtt, "climate", HA_DEVICE_ID, &climate)?;
+
+    let temperature_sensor = HaSensorDiscovery {
+        name: "Thermostat Temperature".to_string(),
+        unique_id: format!("{HA_DEVICE_ID}-temperature"),
+        availability_topic: TOPIC_CONTROLLER_AVAILABILITY.to_string(),
+        state_topic: TOPIC_CONTROLLER_STATE.to_string(),
+        value_template: "{{ value_json.temp }}".to_string(),
+        unit_of_measurement: Some("°F".to_string()),
+        device_class: Some("temperature".to_string()),
+        entity_category: None,
+        device: ha_device(),
+    };
+    publish_ha_discovery_config(
+        mqtt,
+        "sensor",
+        &format!("{HA_DEVICE_ID}-temperature"),
+        &temperature_sensor,
+    )?;
+
+    let humidity_sensor = HaSensorDiscovery {
+        name: "Thermostat Humidity".to_string(),
+        unique_id: format!("{HA_DEVICE_ID}-humidity"),
+        availability_topic: TOPIC_CONTROLLER_AVAILABILITY.to_string(),
+        state_topic: TOPIC_CONTROLLER_STATE.to_string(),
+        value_template: "{{ value_json.humidity }}".to_string(),
+        unit_of_measurement: Some("%".to_string()),
+        device_class: Some("humidity".to_string()),
+        entity_category: None,
+        device: ha_device(),
+    };
+    publish_ha_discovery_config(
+        mqtt,
+        "sensor",
+        &format!("{HA_DEVICE_ID}-humidity"),
+        &humidity_sensor,
+    )?;
+
+    let ota_status_sensor = HaSensorDiscovery {
+        name: "Thermostat OTA Status".to_string(),
+        unique_id: format!("{HA_DEVICE_ID}-ota-status"),
+        availability_topic: TOPIC_CONTROLLER_AVAILABILITY.to_string(),
+        state_topic: TOPIC_CONTROLLER_OTA_STATE.to_string(),
+        value_template: "{{ 'pending verify' if value_json.pendingVerify else 'confirmed' }}"
+            .to_string(),
+        unit_of_measurement: None,
+        device_class: None,
+        entity_category: Some("diagnostic".to_string()),
+        device: ha_device(),
+    };
+    publish_ha_discovery_config(
+        mqtt,
+        "sensor",
+        &format!("{HA_DEVICE_ID}-ota-status"),
+        &ota_status_sensor,
+    )?;
+
+    Ok(())
+}
+
+/// How long the MQTT receiver may go between polls of `conn.next()` before
+/// the watchdog supervisor considers it stalled. Generous relative to the
+/// broker's keepalive interval, since a healthy connection with no inbound
+/// traffic still wakes this loop periodically for keepalive pings.
+const MQTT_RECEIVER_WATCHDOG_INTERVAL_MS: u64 = 60_000;
+
 fn spawn_mqtt_receiver(
     state: SharedState,
     nvs_store: NvsStore,
     mut conn: EspMqttConnection,
     mqtt: Arc<Mutex<EspMqttClient<'static>>>,
+    watchdog: WatchdogRegistry,
 ) {
     thread::Builder::new()
         .name("mqtt-rx".into())
         .stack_size(12 * 1024)
         .spawn(move || {
             loop {
+                watchdog.heartbeat("mqtt-rx", MQTT_RECEIVER_WATCHDOG_INTERVAL_MS, monotonic_ms());
                 match conn.next() {
                     Ok(event) => {
                         state.mqtt_connected.store(true, Ordering::Relaxed);
 
+                        if let EventPayload::Connected(_) = event.payload() {
+                            if let Err(err) = {
+                                let mut client = mqtt.lock().unwrap();
+                                client.publish(
+                                    TOPIC_CONTROLLER_AVAILABILITY,
+                                    QoS::AtLeastOnce,
+                                    true,
+                                    b"online",
+                                )
+                            } {
+                                warn!("failed to publish availability birth message: {err:#}");
+                            }
+
+                            let ha_discovery_enabled = nvs_store
+                                .load_runtime_config()
+                                .map(|runtime| runtime.network.ha_discovery_enabled)
+                                .unwrap_or(true);
+                            if ha_discovery_enabled {
+                                if let Err(err) = publish_ha_discovery(&mqtt) {
+                                    warn!(
+                                        "failed to publish Home Assistant discovery config: {err:#}"
+                                    );
+                                }
+                            }
+                        }
+
                         if let EventPayload::Received {
                             topic: Some(topic),
                             data,
@@ -1427,7 +2906,7 @@ fn spawn_mqtt_receiver(
 
                             if let Ok(message) = core::str::from_utf8(data) {
                                 if let Err(err) =
-                                    handle_mqtt_message(&state, &nvs_store, topic, message)
+                                    handle_mqtt_message(&state, &nvs_store, &mqtt, topic, message)
                                 {
                                     warn!("mqtt message handling failed: {err:#}");
                                 }
@@ -1448,65 +2927,161 @@ fn spawn_mqtt_receiver(
         .expect("failed to spawn mqtt receiver thread");
 }
 
+/// The control loop ticks every 200ms; allow a generous multiple of that
+/// before the watchdog supervisor considers it stalled.
+const CONTROL_LOOP_WATCHDOG_INTERVAL_MS: u64 = 5_000;
+
 fn spawn_control_loop(
     state: SharedState,
     nvs_store: NvsStore,
     mqtt: Arc<Mutex<EspMqttClient<'static>>>,
-    mut status_led: Option<StatusLed>,
+    mut status_led: Option<status_led::StatusLed>,
+    mut connectivity_manager: Option<ConnectivityManager>,
+    sntp: EspSntp<'static>,
+    watchdog: WatchdogRegistry,
 ) {
     thread::Builder::new()
         .name("control-loop".into())
         .stack_size(12 * 1024)
         .spawn(move || {
-            if let Err(err) = add_current_task_to_watchdog() {
-                warn!("failed to register control loop with watchdog: {err:#}");
-            }
+            // Keeps the SNTP service running for the lifetime of the control
+            // loop; dropping it would stop the periodic resync.
+            let sntp = sntp;
 
             let mut last_state_publish_ms = 0_u64;
             let mut wifi_disconnected_since_ms: Option<u64> = None;
+            let mut ppp_failover_started_ms: Option<u64> = None;
 
             loop {
-                feed_watchdog();
                 let now_ms = monotonic_ms();
+                watchdog.heartbeat("control-loop", CONTROL_LOOP_WATCHDOG_INTERVAL_MS, now_ms);
                 let wifi_connected = is_wifi_station_connected();
                 let mqtt_connected = state.mqtt_connected.load(Ordering::Relaxed);
 
                 state
                     .wifi_connected
                     .store(wifi_connected, Ordering::Relaxed);
-                update_status_led(&mut status_led, wifi_connected, mqtt_connected, now_ms);
+
+                if !state.time_synced.load(Ordering::Relaxed)
+                    && sntp.get_sync_status() == SyncStatus::Completed
+                {
+                    state.time_synced.store(true, Ordering::Relaxed);
+                    *state.last_sync_ms.lock().unwrap() = Some(now_ms);
+                    info!("SNTP time sync completed");
+                }
+                let time_synced = state.time_synced.load(Ordering::Relaxed);
+
+                if let Some(led) = status_led.as_mut() {
+                    let thermostat_state = state.engine.lock().unwrap().state();
+                    let led_state = status_led::resolve_led_state(
+                        wifi_connected,
+                        time_synced,
+                        mqtt_connected,
+                        thermostat_state,
+                    );
+                    led.update(led_state, now_ms);
+                }
+                check_ota_health(&state, &nvs_store, wifi_connected, mqtt_connected, now_ms);
+                apply_deferred_ota_if_due(&state, &nvs_store);
 
                 if wifi_connected {
                     wifi_disconnected_since_ms = None;
+                    if ppp_failover_started_ms.take().is_some() {
+                        if let Some(manager) = connectivity_manager.as_mut() {
+                            manager.disconnect();
+                        }
+                    }
+                    *state.transport.lock().unwrap() = Transport::Wifi;
                 } else if let Some(disconnected_since_ms) = wifi_disconnected_since_ms {
-                    if now_ms.saturating_sub(disconnected_since_ms) >= WIFI_RESTART_GRACE_MS {
-                        warn!(
-                            "wifi disconnected for {}s; restarting device for recovery",
-                            WIFI_RESTART_GRACE_MS / 1000
-                        );
-                        thread::sleep(Duration::from_millis(100));
-                        unsafe { esp_idf_svc::sys::esp_restart() };
+                    let down_for_ms = now_ms.saturating_sub(disconnected_since_ms);
+                    if down_for_ms >= WIFI_RESTART_GRACE_MS {
+                        // `try_connect` is synchronous (each AT exchange has its own
+                        // `AT_COMMAND_TIMEOUT_MS` bound), so it either flips `transport`
+                        // to `Ppp` or fails within this same tick - there's no separate
+                        // dial-in grace window to wait out here, unlike the WiFi-loss
+                        // window above. Until `start_ppp_netif` actually attaches an
+                        // esp_netif PPP interface, this attempt always fails and the
+                        // device restarts on the same schedule as it did before PPP
+                        // failover existed.
+                        if let Some(manager) = connectivity_manager.as_mut() {
+                            if ppp_failover_started_ms.is_none() {
+                                warn!(
+                                    "wifi disconnected for {}s; attempting PPP failover over the \
+                                     cellular modem",
+                                    WIFI_RESTART_GRACE_MS / 1000
+                                );
+                                ppp_failover_started_ms = Some(now_ms);
+                                if let Err(err) = manager.try_connect() {
+                                    warn!("PPP failover attempt failed: {err:#}");
+                                }
+                            }
+                        }
+
+                        let transport_is_ppp = connectivity_manager.is_some()
+                            && *state.transport.lock().unwrap() == Transport::Ppp;
+
+                        if !transport_is_ppp {
+                            warn!(
+                                "wifi and PPP failover both unavailable after {}s; restarting \
+                                 device for recovery",
+                                down_for_ms / 1000
+                            );
+                            thread::sleep(Duration::from_millis(100));
+                            unsafe { esp_idf_svc::sys::esp_restart() };
+                        }
                     }
                 } else {
                     wifi_disconnected_since_ms = Some(now_ms);
                 }
 
                 let timezone = state.timezone.lock().unwrap().clone();
-                let now_in_tz = now_in_timezone(&timezone);
-                state
-                    .time_synced
-                    .store(now_in_tz.is_some(), Ordering::Relaxed);
+                let now_in_tz = time_synced.then(|| now_in_timezone(&timezone)).flatten();
 
                 if let Some(now) = now_in_tz {
-                    let schedule_action = {
+                    let current_temp_f = { state.engine.lock().unwrap().current_temp_f() };
+                    let (schedule_action, heat_transition, override_active) = {
                         let schedule = state.schedule.lock().unwrap();
-                        schedule.current_action(now)
+                        (
+                            schedule.effective_action(now, current_temp_f),
+                            schedule.next_heat_transition(now),
+                            schedule.override_action.is_some(),
+                        )
+                    };
+
+                    // An explicit override represents the user's current intent, so
+                    // it always suppresses anticipatory pre-heat. Otherwise, if the
+                    // upcoming transition is a heat-up and we're not already headed
+                    // there, ask the engine whether it's time to start early.
+                    let wants_preheat = !override_active
+                        && schedule_action
+                            .map(|action| action.mode != ThermostatMode::Heat)
+                            .unwrap_or(true);
+
+                    let resolved_action = if wants_preheat {
+                        if let Some((transition_epoch, target_temp_f)) = heat_transition {
+                            let should_preheat = {
+                                let engine = state.engine.lock().unwrap();
+                                engine.should_preheat(target_temp_f, transition_epoch, now.timestamp())
+                            };
+                            if should_preheat {
+                                Some(ScheduleAction {
+                                    mode: ThermostatMode::Heat,
+                                    target_temp_f,
+                                })
+                            } else {
+                                schedule_action
+                            }
+                        } else {
+                            schedule_action
+                        }
+                    } else {
+                        schedule_action
                     };
 
                     if let Some(ScheduleAction {
                         mode,
                         target_temp_f,
-                    }) = schedule_action
+                    }) = resolved_action
                     {
                         let mut engine = state.engine.lock().unwrap();
                         let (_, schedule_actions) =
@@ -1568,28 +3143,51 @@ fn publish_state(
         )?;
     }
 
+    let ota_payload = serde_json::to_vec(&build_ota_status_response(state))?;
+
+    {
+        let mut client = mqtt.lock().unwrap();
+        client.publish(TOPIC_CONTROLLER_OTA_STATE, QoS::AtLeastOnce, true, &ota_payload)?;
+    }
+
     Ok(())
 }
 
 fn handle_mqtt_message(
     state: &SharedState,
     nvs_store: &NvsStore,
+    mqtt: &Arc<Mutex<EspMqttClient<'static>>>,
     topic: &str,
     message: &str,
 ) -> anyhow::Result<()> {
     let now_ms = monotonic_ms();
 
     match topic {
-        TOPIC_SENSOR_TEMP => {
+        _ if topic.ends_with(TOPIC_SENSOR_TEMP) => {
             if let Ok(temp) = message.parse::<f32>() {
                 if temp.is_finite() && (-40.0..=150.0).contains(&temp) {
-                    let mut engine = state.engine.lock().unwrap();
-                    let humidity = engine.current_humidity();
-                    engine.update_sensor_data(temp, humidity, now_ms);
+                    let correction = {
+                        let mut engine = state.engine.lock().unwrap();
+                        let humidity = engine.current_humidity();
+                        engine.update_sensor_data(temp, humidity, now_ms);
+
+                        if engine.is_fireplace_on() {
+                            let setpoint =
+                                engine.settings().target_temp_f + engine.settings().fireplace_offset_f as f32;
+                            let mut transmitter = state.ir_sender.lock().unwrap();
+                            transmitter.sample_room_temp(setpoint, temp)
+                        } else {
+                            None
+                        }
+                    };
+
+                    if let Some(action) = correction {
+                        execute_engine_actions(state, vec![action]);
+                    }
                 }
             }
         }
-        TOPIC_SENSOR_HUMIDITY => {
+        _ if topic.ends_with(TOPIC_SENSOR_HUMIDITY) => {
             if let Ok(humidity) = message.parse::<f32>() {
                 if humidity.is_finite() && (0.0..=100.0).contains(&humidity) {
                     let mut engine = state.engine.lock().unwrap();
@@ -1628,6 +3226,14 @@ fn handle_mqtt_message(
                     let (changed, actions) =
                         engine.set_mode_with_actions(ThermostatMode::Heat, now_ms);
                     (changed, actions, debounce_ms)
+                } else if message.eq_ignore_ascii_case("COOL") {
+                    let (changed, actions) =
+                        engine.set_mode_with_actions(ThermostatMode::Cool, now_ms);
+                    (changed, actions, debounce_ms)
+                } else if message.eq_ignore_ascii_case("AUTO") {
+                    let (changed, actions) =
+                        engine.set_mode_with_actions(ThermostatMode::Auto, now_ms);
+                    (changed, actions, debounce_ms)
                 } else if message.eq_ignore_ascii_case("OFF") {
                     let (changed, actions) =
                         engine.set_mode_with_actions(ThermostatMode::Off, now_ms);
@@ -1643,9 +3249,18 @@ fn handle_mqtt_message(
         }
         TOPIC_CMD_HOLD => {
             let mut engine = state.engine.lock().unwrap();
-            if message.eq_ignore_ascii_case("on") || message.eq_ignore_ascii_case("enter") {
+            // "hold"/"none" are the literal preset names Home Assistant's climate
+            // preset_mode_command_topic sends, alongside the pre-existing on/off
+            // and enter/exit spellings used by other MQTT clients.
+            if message.eq_ignore_ascii_case("on")
+                || message.eq_ignore_ascii_case("enter")
+                || message.eq_ignore_ascii_case("hold")
+            {
                 engine.enter_hold(None, now_ms);
-            } else if message.eq_ignore_ascii_case("off") || message.eq_ignore_ascii_case("exit") {
+            } else if message.eq_ignore_ascii_case("off")
+                || message.eq_ignore_ascii_case("exit")
+                || message.eq_ignore_ascii_case("none")
+            {
                 engine.exit_hold();
             } else if let Ok(minutes) = message.parse::<u64>() {
                 if minutes > 0 && minutes <= engine.config.max_hold_minutes as u64 {
@@ -1663,12 +3278,264 @@ fn handle_mqtt_message(
                 nvs_store.save_schedule(&schedule)?;
             }
         }
+        TOPIC_CMD_OVERRIDE => {
+            let lower = message.trim().to_ascii_lowercase();
+            let schedule = if lower == "off" || lower == "cancel" || lower == "clear" {
+                let mut current = state.schedule.lock().unwrap();
+                current.override_action = None;
+                current.clone()
+            } else {
+                match parse_schedule_override(message) {
+                    Ok(over) => {
+                        let mut current = state.schedule.lock().unwrap();
+                        current.override_action = Some(over);
+                        current.clone()
+                    }
+                    Err(err) => {
+                        warn!("invalid override command: {err}");
+                        return Ok(());
+                    }
+                }
+            };
+            nvs_store.save_schedule(&schedule)?;
+        }
+        TOPIC_CMD_DATE_EXCEPTIONS => {
+            let lower = message.trim().to_ascii_lowercase();
+            let schedule = if lower == "off" || lower == "cancel" || lower == "clear" {
+                let mut current = state.schedule.lock().unwrap();
+                current.date_exceptions.clear();
+                current.clone()
+            } else {
+                match serde_json::from_str::<Vec<DateException>>(message) {
+                    Ok(mut exceptions) => {
+                        let mut current = state.schedule.lock().unwrap();
+                        current.date_exceptions.clear();
+                        current.date_exceptions.append(&mut exceptions);
+                        current.normalize();
+                        current.clone()
+                    }
+                    Err(err) => {
+                        warn!("invalid date exceptions command: {err}");
+                        return Ok(());
+                    }
+                }
+            };
+            nvs_store.save_schedule(&schedule)?;
+        }
+        TOPIC_CMD => {
+            let ack = handle_scpi_command(state, message);
+            let payload = serde_json::to_vec(&ack)?;
+            let mut client = mqtt.lock().unwrap();
+            client.publish(TOPIC_CMD_RESULT, QoS::AtLeastOnce, false, &payload)?;
+        }
         _ => {}
     }
 
     Ok(())
 }
 
+/// Stable, machine-readable error codes for `TOPIC_CMD` acks, so a client
+/// can branch on the failure mode instead of parsing human-readable text.
+#[derive(Debug, Clone, Copy, Serialize)]
+enum CmdErrorCode {
+    #[serde(rename = "E_RANGE")]
+    ERange,
+    #[serde(rename = "E_UNKNOWN")]
+    EUnknown,
+    #[serde(rename = "E_PARSE")]
+    EParse,
+    #[serde(rename = "E_LOCKED")]
+    ELocked,
+}
+
+/// Ack published to `TOPIC_CMD_RESULT` for every command line received on
+/// `TOPIC_CMD`.
+#[derive(Debug, Serialize)]
+struct CmdAck {
+    cmd: String,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<CmdErrorCode>,
+}
+
+impl CmdAck {
+    fn ok(cmd: &str, value: Option<serde_json::Value>) -> Self {
+        Self {
+            cmd: cmd.to_string(),
+            ok: true,
+            value,
+            error: None,
+        }
+    }
+
+    fn err(cmd: &str, error: CmdErrorCode) -> Self {
+        Self {
+            cmd: cmd.to_string(),
+            ok: false,
+            value: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// Parses a single SCPI-style command line: `VERB`, `VERB ARG`, or `VERB?`
+/// for a query. The verb is case-insensitive; a trailing `?` with no
+/// preceding space marks a query rather than a set.
+fn parse_scpi_command(line: &str) -> (String, Option<&str>, bool) {
+    let line = line.trim();
+    let (head, arg) = match line.split_once(char::is_whitespace) {
+        Some((head, rest)) => (head, Some(rest.trim())),
+        None => (line, None),
+    };
+    let is_query = head.ends_with('?');
+    let verb = head.trim_end_matches('?').to_ascii_uppercase();
+    (verb, arg, is_query)
+}
+
+/// Dispatches a single `TOPIC_CMD` line against `state.engine`, reusing the
+/// same engine calls and actions as the legacy per-topic handlers above.
+/// `TARG` is stricter than the legacy target handler: a value outside
+/// 60..=84 is rejected with `E_RANGE` instead of being clamped.
+/// Mutating verbs are rejected with `E_LOCKED` while a latched fault is
+/// active, since the fault requires its own explicit recovery before
+/// normal setpoint/mode control should resume; queries are always allowed.
+fn handle_scpi_command(state: &SharedState, line: &str) -> CmdAck {
+    let now_ms = monotonic_ms();
+    let (verb, arg, is_query) = parse_scpi_command(line);
+
+    match verb.as_str() {
+        "MODE" => {
+            if is_query {
+                let mode = state.engine.lock().unwrap().settings().mode.as_str();
+                return CmdAck::ok(line, Some(serde_json::json!(mode)));
+            }
+            if state.engine.lock().unwrap().is_fault_active() {
+                return CmdAck::err(line, CmdErrorCode::ELocked);
+            }
+            let Some(arg) = arg else {
+                return CmdAck::err(line, CmdErrorCode::EParse);
+            };
+            let mode = match arg.to_ascii_uppercase().as_str() {
+                "OFF" => ThermostatMode::Off,
+                "HEAT" => ThermostatMode::Heat,
+                "COOL" => ThermostatMode::Cool,
+                "AUTO" => ThermostatMode::Auto,
+                _ => return CmdAck::err(line, CmdErrorCode::EParse),
+            };
+            let (changed, actions, debounce_ms) = {
+                let mut engine = state.engine.lock().unwrap();
+                let (changed, actions) = engine.set_mode_with_actions(mode, now_ms);
+                (changed, actions, engine.config.settings_save_debounce_ms)
+            };
+            execute_engine_actions(state, actions);
+            if changed {
+                queue_settings_save(state, now_ms, debounce_ms);
+            }
+            CmdAck::ok(line, Some(serde_json::json!(mode.as_str())))
+        }
+        "TARG" => {
+            if is_query {
+                let target = state.engine.lock().unwrap().settings().target_temp_f;
+                return CmdAck::ok(line, Some(serde_json::json!(target)));
+            }
+            if state.engine.lock().unwrap().is_fault_active() {
+                return CmdAck::err(line, CmdErrorCode::ELocked);
+            }
+            let Some(Ok(target)) = arg.map(str::parse::<f32>) else {
+                return CmdAck::err(line, CmdErrorCode::EParse);
+            };
+            if !target.is_finite() || !(60.0..=84.0).contains(&target) {
+                return CmdAck::err(line, CmdErrorCode::ERange);
+            }
+            let (changed, debounce_ms) = {
+                let mut engine = state.engine.lock().unwrap();
+                let changed = engine.set_target_temp(target);
+                (changed, engine.config.settings_save_debounce_ms)
+            };
+            if changed {
+                queue_settings_save(state, now_ms, debounce_ms);
+            }
+            CmdAck::ok(line, Some(serde_json::json!(target)))
+        }
+        "HOLD" => {
+            if is_query {
+                let (in_hold, remaining_ms) = {
+                    let engine = state.engine.lock().unwrap();
+                    (engine.is_in_hold(), engine.hold_remaining_ms(now_ms))
+                };
+                let remaining_minutes = if in_hold { remaining_ms / 60_000 } else { 0 };
+                return CmdAck::ok(line, Some(serde_json::json!(remaining_minutes)));
+            }
+            if state.engine.lock().unwrap().is_fault_active() {
+                return CmdAck::err(line, CmdErrorCode::ELocked);
+            }
+            let Some(arg) = arg else {
+                return CmdAck::err(line, CmdErrorCode::EParse);
+            };
+            let lower = arg.to_ascii_lowercase();
+            if lower == "on" || lower == "enter" {
+                state.engine.lock().unwrap().enter_hold(None, now_ms);
+                return CmdAck::ok(line, None);
+            }
+            if lower == "off" || lower == "exit" {
+                state.engine.lock().unwrap().exit_hold();
+                return CmdAck::ok(line, None);
+            }
+            let Ok(minutes) = lower.parse::<u64>() else {
+                return CmdAck::err(line, CmdErrorCode::EParse);
+            };
+            let max_hold_minutes = state.engine.lock().unwrap().config.max_hold_minutes as u64;
+            if minutes == 0 || minutes > max_hold_minutes {
+                return CmdAck::err(line, CmdErrorCode::ERange);
+            }
+            state
+                .engine
+                .lock()
+                .unwrap()
+                .enter_hold(Some(minutes * 60_000), now_ms);
+            CmdAck::ok(line, Some(serde_json::json!(minutes)))
+        }
+        _ => CmdAck::err(line, CmdErrorCode::EUnknown),
+    }
+}
+
+/// Payload accepted on `TOPIC_CMD_OVERRIDE`: `untilEpoch` takes precedence
+/// if present, otherwise `durationMinutes` is resolved against wall-clock
+/// time when the command is received.
+#[derive(Debug, Deserialize)]
+struct ScheduleOverrideCommand {
+    mode: ThermostatMode,
+    #[serde(rename = "targetTemp")]
+    target_temp_f: f32,
+    #[serde(rename = "untilEpoch")]
+    until_epoch: Option<i64>,
+    #[serde(rename = "durationMinutes")]
+    duration_minutes: Option<i64>,
+}
+
+fn parse_schedule_override(value: &str) -> Result<ScheduleOverride, String> {
+    let command = serde_json::from_str::<ScheduleOverrideCommand>(value)
+        .map_err(|err| format!("invalid override: {err}"))?;
+
+    let until_epoch = match (command.until_epoch, command.duration_minutes) {
+        (Some(epoch), _) => epoch,
+        (None, Some(minutes)) if minutes > 0 => Utc::now().timestamp() + minutes * 60,
+        _ => return Err("expected 'untilEpoch' or a positive 'durationMinutes'".to_string()),
+    };
+
+    if until_epoch <= Utc::now().timestamp() {
+        return Err("override expiry must be in the future".to_string());
+    }
+
+    Ok(ScheduleOverride {
+        mode: command.mode,
+        target_temp_f: command.target_temp_f,
+        until_epoch,
+    })
+}
+
 fn execute_engine_actions(state: &SharedState, actions: Vec<EngineAction>) {
     for action in actions {
         if let EngineAction::Delay(ms) = action {
@@ -1676,12 +3543,29 @@ fn execute_engine_actions(state: &SharedState, actions: Vec<EngineAction>) {
             continue;
         }
 
+        let mutates_ir_state = matches!(
+            action,
+            EngineAction::PowerOn
+                | EngineAction::TempUp
+                | EngineAction::TempDown
+                | EngineAction::SetTemp(_)
+                | EngineAction::LightToggle
+                | EngineAction::TimerToggle
+        );
+
         let mut transmitter = state.ir_sender.lock().unwrap();
         let description = format!("{action:?}");
         if let Err(err) = transmitter.execute_action(action) {
             warn!("engine action failed [{description}]: {err:#}");
         } else {
             info!("engine action sent [{description}]");
+            if mutates_ir_state {
+                let snapshot = transmitter.state_snapshot();
+                drop(transmitter);
+                if let Err(err) = state.nvs_store.save_ir_runtime_state(&snapshot) {
+                    warn!("failed to persist IR runtime state: {err:#}");
+                }
+            }
         }
     }
 }
@@ -1691,9 +3575,11 @@ fn build_status(state: &SharedState) -> thermostat_common::ControllerStatus {
     let timezone = state.timezone.lock().unwrap().clone();
     let time_synced = state.time_synced.load(Ordering::Relaxed);
 
-    let next_schedule_event_epoch = {
+    let next_schedule_event_epoch = if time_synced {
         let schedule = state.schedule.lock().unwrap();
         now_in_timezone(&timezone).and_then(|now| schedule.next_event_epoch(now))
+    } else {
+        None
     };
 
     let schedule_enabled = state.schedule.lock().unwrap().enabled;
@@ -1751,50 +3637,221 @@ fn flush_pending_settings_save(nvs_store: &NvsStore, state: &SharedState, now_ms
     }
 }
 
-fn validate_network_update(update: &NetworkConfigUpdate) -> Result<(), &'static str> {
-    if update.wifi_ssid.trim().is_empty() {
-        return Err("wifiSsid cannot be empty");
-    }
-    if update.mqtt_host.trim().is_empty() {
-        return Err("mqttHost cannot be empty");
+fn parse_ipv4(text: &str) -> Result<[u8; 4], &'static str> {
+    let mut octets = [0_u8; 4];
+    let mut parts = text.split('.');
+
+    for slot in octets.iter_mut() {
+        let part = parts.next().ok_or("must be a dotted-decimal IPv4 address")?;
+        *slot = part
+            .parse::<u8>()
+            .map_err(|_| "must be a dotted-decimal IPv4 address")?;
     }
-    if update.mqtt_port == 0 {
-        return Err("mqttPort must be between 1 and 65535");
+    if parts.next().is_some() {
+        return Err("must be a dotted-decimal IPv4 address");
     }
-    if update.use_static_ip
-        && (update.static_ip.is_none() || update.gateway.is_none() || update.subnet.is_none())
-    {
-        return Err("staticIp, gateway, and subnet are required when useStaticIp is true");
+
+    Ok(octets)
+}
+
+fn subnet_mask_from_prefix(prefix_len: u8) -> Result<[u8; 4], &'static str> {
+    if prefix_len > 32 {
+        return Err("CIDR prefix length must be between 0 and 32");
     }
+    let mask = if prefix_len == 0 {
+        0_u32
+    } else {
+        u32::MAX << (32 - prefix_len)
+    };
+    Ok(mask.to_be_bytes())
+}
 
-    Ok(())
+/// Returns the prefix length of `mask` if it's a valid contiguous netmask
+/// (all set bits leading, all clear bits trailing), `None` otherwise.
+fn prefix_len_from_mask(mask: [u8; 4]) -> Option<u32> {
+    let value = u32::from_be_bytes(mask);
+    let ones = value.count_ones();
+    let is_contiguous = if ones == 0 {
+        value == 0
+    } else {
+        value == (u32::MAX << (32 - ones))
+    };
+    is_contiguous.then_some(ones)
 }
 
-fn apply_network_update(
-    nvs_store: &NvsStore,
-    update: NetworkConfigUpdate,
-) -> anyhow::Result<NetworkUpdateResponse> {
-    let mut runtime = nvs_store.load_runtime_config().unwrap_or_default();
-    let previous = runtime.network.clone();
+/// Parses a `staticIp` field that's either a plain dotted-decimal address
+/// (`"192.168.1.50"`) or CIDR notation (`"192.168.1.50/24"`). CIDR input also
+/// yields the subnet mask derived from the prefix length.
+fn parse_static_ip(text: &str) -> Result<([u8; 4], Option<[u8; 4]>), &'static str> {
+    match text.split_once('/') {
+        Some((ip_part, prefix_part)) => {
+            let ip = parse_ipv4(ip_part)?;
+            let prefix_len: u8 = prefix_part
+                .trim()
+                .parse()
+                .map_err(|_| "CIDR prefix length must be a number")?;
+            Ok((ip, Some(subnet_mask_from_prefix(prefix_len)?)))
+        }
+        None => Ok((parse_ipv4(text)?, None)),
+    }
+}
 
-    runtime.network.wifi_ssid = update.wifi_ssid;
-    if let Some(pass) = update.wifi_pass {
-        runtime.network.wifi_pass = pass;
+fn validate_static_ip_config(
+    ip: [u8; 4],
+    gateway: [u8; 4],
+    subnet: [u8; 4],
+) -> Result<(), &'static str> {
+    let prefix_len =
+        prefix_len_from_mask(subnet).ok_or("subnet is not a valid contiguous netmask")?;
+    let ip_bits = u32::from_be_bytes(ip);
+    let gateway_bits = u32::from_be_bytes(gateway);
+    let mask_bits = u32::from_be_bytes(subnet);
+
+    if ip_bits & mask_bits != gateway_bits & mask_bits {
+        return Err("staticIp and gateway must be on the same subnet");
     }
-    runtime.network.mqtt_host = update.mqtt_host;
-    runtime.network.mqtt_port = update.mqtt_port;
-    runtime.network.mqtt_user = update.mqtt_user;
-    if let Some(pass) = update.mqtt_pass {
-        runtime.network.mqtt_pass = pass;
+
+    if prefix_len < 31 {
+        let network = ip_bits & mask_bits;
+        let broadcast = network | !mask_bits;
+        if ip_bits == network {
+            return Err("staticIp cannot be the network address");
+        }
+        if ip_bits == broadcast {
+            return Err("staticIp cannot be the broadcast address");
+        }
     }
+
+    Ok(())
+}
+
+/// Resolves and validates the static-IP fields of a network update, deriving
+/// the subnet mask from CIDR notation when `staticIp` carries one. Returns
+/// `None` when `useStaticIp` is false.
+fn resolve_static_network(
+    update: &NetworkConfigUpdate,
+) -> Result<Option<([u8; 4], [u8; 4], [u8; 4])>, &'static str> {
+    if !update.use_static_ip {
+        return Ok(None);
+    }
+
+    let static_ip_text = update
+        .static_ip
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .ok_or("staticIp is required when useStaticIp is true")?;
+    let gateway = update
+        .gateway
+        .ok_or("gateway is required when useStaticIp is true")?;
+
+    let (static_ip, cidr_subnet) = parse_static_ip(static_ip_text)?;
+    let subnet = cidr_subnet
+        .or(update.subnet)
+        .ok_or("subnet is required when staticIp is not CIDR notation")?;
+
+    validate_static_ip_config(static_ip, gateway, subnet)?;
+
+    Ok(Some((static_ip, gateway, subnet)))
+}
+
+/// Parses the `wifiAuth` wire value into the corresponding `WifiAuthMode`.
+fn parse_wifi_auth(text: &str) -> Result<WifiAuthMode, &'static str> {
+    match text {
+        "open" => Ok(WifiAuthMode::Open),
+        "wpa2" => Ok(WifiAuthMode::Wpa2),
+        "wpa2wpa3" => Ok(WifiAuthMode::Wpa2Wpa3),
+        "wpa3" => Ok(WifiAuthMode::Wpa3),
+        "wpa2-enterprise" => Ok(WifiAuthMode::Wpa2Enterprise),
+        _ => Err("wifiAuth must be one of: open, wpa2, wpa2wpa3, wpa3, wpa2-enterprise"),
+    }
+}
+
+fn validate_network_update(update: &NetworkConfigUpdate) -> Result<(), &'static str> {
+    if update.wifi_ssid.trim().is_empty() {
+        return Err("wifiSsid cannot be empty");
+    }
+    if update.mqtt_host.trim().is_empty() {
+        return Err("mqttHost cannot be empty");
+    }
+    if update.mqtt_port == 0 {
+        return Err("mqttPort must be between 1 and 65535");
+    }
+
+    resolve_static_network(update)?;
+
+    if let Some(wifi_auth) = update.wifi_auth.as_deref() {
+        let mode = parse_wifi_auth(wifi_auth)?;
+        if mode == WifiAuthMode::Wpa2Enterprise {
+            let identity_set = update
+                .wifi_identity
+                .as_deref()
+                .is_some_and(|v| !v.trim().is_empty());
+            let username_set = update
+                .wifi_username
+                .as_deref()
+                .is_some_and(|v| !v.trim().is_empty());
+            if !identity_set || !username_set {
+                return Err(
+                    "wifiIdentity and wifiUsername are required when wifiAuth is wpa2-enterprise",
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn apply_network_update(
+    nvs_store: &NvsStore,
+    update: NetworkConfigUpdate,
+) -> anyhow::Result<NetworkUpdateResponse> {
+    let static_network =
+        resolve_static_network(&update).map_err(|err| anyhow!("invalid network update: {err}"))?;
+
+    let mut runtime = nvs_store.load_runtime_config().unwrap_or_default();
+    let previous = runtime.network.clone();
+
+    runtime.network.wifi_ssid = update.wifi_ssid;
+    if let Some(pass) = update.wifi_pass {
+        runtime.network.wifi_pass = pass;
+    }
+    runtime.network.mqtt_host = update.mqtt_host;
+    runtime.network.mqtt_port = update.mqtt_port;
+    runtime.network.mqtt_user = update.mqtt_user;
+    if let Some(pass) = update.mqtt_pass {
+        runtime.network.mqtt_pass = pass;
+    }
+    runtime.network.mqtt_tls = update.mqtt_tls;
     if let Some(pass) = update.ota_password {
         runtime.network.ota_password = pass;
     }
     runtime.network.use_static_ip = update.use_static_ip;
-    runtime.network.static_ip = update.static_ip;
-    runtime.network.gateway = update.gateway;
-    runtime.network.subnet = update.subnet;
+    match static_network {
+        Some((static_ip, gateway, subnet)) => {
+            runtime.network.static_ip = Some(static_ip);
+            runtime.network.gateway = Some(gateway);
+            runtime.network.subnet = Some(subnet);
+        }
+        None => {
+            runtime.network.static_ip = None;
+            runtime.network.gateway = None;
+            runtime.network.subnet = None;
+        }
+    }
     runtime.network.dns = update.dns;
+    runtime.network.secondary_dns = update.secondary_dns;
+    if let Some(wifi_auth) = update.wifi_auth {
+        let mode =
+            parse_wifi_auth(&wifi_auth).map_err(|err| anyhow!("invalid network update: {err}"))?;
+        runtime.network.wifi_auth = Some(mode);
+    }
+    if let Some(identity) = update.wifi_identity {
+        runtime.network.wifi_identity = Some(identity);
+    }
+    if let Some(username) = update.wifi_username {
+        runtime.network.wifi_username = Some(username);
+    }
 
     nvs_store.save_runtime_config(&runtime)?;
 
@@ -1814,6 +3871,14 @@ fn validate_ir_update(update: &IrConfigUpdate) -> Result<(), &'static str> {
     if !(10..=100).contains(&update.carrier_khz) {
         return Err("carrierKHz must be between 10 and 100");
     }
+    if let Some(rx_pin) = update.rx_pin {
+        if rx_pin < 0 {
+            return Err("rxPin must be >= 0");
+        }
+    }
+    if !(10..=2_000).contains(&update.learn_glitch_floor_us) {
+        return Err("learnGlitchFloorUs must be between 10 and 2000");
+    }
 
     Ok(())
 }
@@ -1828,6 +3893,9 @@ fn apply_ir_update(
     runtime.ir.tx_pin = update.tx_pin;
     runtime.ir.rmt_channel = update.rmt_channel;
     runtime.ir.carrier_khz = update.carrier_khz;
+    runtime.ir.protocol = update.protocol;
+    runtime.ir.rx_pin = update.rx_pin;
+    runtime.ir.learn_glitch_floor_us = update.learn_glitch_floor_us;
     runtime.ir.sanitize();
 
     nvs_store.save_runtime_config(&runtime)?;
@@ -1854,6 +3922,17 @@ fn validate_ota_apply_request(update: &OtaApplyRequest) -> Result<(), &'static s
         }
     }
 
+    let signature = update
+        .signature
+        .as_ref()
+        .ok_or("signature is required to verify image authenticity")?;
+    let decoded = BASE64_STANDARD
+        .decode(signature.trim())
+        .map_err(|_| "signature must be valid base64")?;
+    if decoded.len() != 64 {
+        return Err("signature must decode to 64 bytes");
+    }
+
     Ok(())
 }
 
@@ -1886,6 +3965,7 @@ fn apply_ota_update(
     }
 
     let ota_state = state.ota.clone();
+    let nvs_store = nvs_store.clone();
     let spawn_result = thread::Builder::new()
         .name("ota-apply".into())
         .stack_size(16 * 1024)
@@ -1895,7 +3975,14 @@ fn apply_ota_update(
                 .sha256
                 .as_ref()
                 .map(|v| v.trim().to_ascii_lowercase());
-            let result = download_and_apply_ota(&ota_state, &update.url, expected_sha.as_deref());
+            let signature = update.signature.clone().unwrap_or_default();
+            let result = download_and_apply_ota(
+                &ota_state,
+                &nvs_store,
+                &update.url,
+                expected_sha.as_deref(),
+                &signature,
+            );
 
             match result {
                 Ok((bytes_written, digest_hex)) => {
@@ -1907,6 +3994,11 @@ fn apply_ota_update(
                         ota.last_error = None;
                         ota.last_sha256 = Some(digest_hex);
                         ota.last_completed_epoch = Some(Utc::now().timestamp());
+                        ota.pending_verify = true;
+                    }
+
+                    if let Err(err) = nvs_store.save_ota_pending(true) {
+                        warn!("failed to persist OTA pending-verify flag in NVS: {err:#}");
                     }
 
                     info!("OTA apply completed successfully ({} bytes)", bytes_written);
@@ -1942,32 +4034,16 @@ fn apply_ota_update(
 
 fn download_and_apply_ota(
     ota_state: &Arc<Mutex<OtaRuntimeState>>,
+    nvs_store: &NvsStore,
     url: &str,
     expected_sha256: Option<&str>,
+    expected_signature: &str,
 ) -> anyhow::Result<(u64, String)> {
     let http_conf = HttpClientConfiguration {
         timeout: Some(Duration::from_secs(30)),
         crt_bundle_attach: Some(esp_idf_svc::sys::esp_crt_bundle_attach),
         ..Default::default()
     };
-    let mut client = HttpClient::wrap(EspHttpConnection::new(&http_conf)?);
-    let request = client.request(Method::Get, url, &[])?;
-    let mut response = request.submit().map_err(|e| anyhow!("{e:?}"))?;
-
-    let status = response.status();
-    if !(200..300).contains(&status) {
-        return Err(anyhow!("OTA download failed with HTTP {status}"));
-    }
-
-    let content_length = response
-        .header("content-length")
-        .or_else(|| response.header("Content-Length"))
-        .and_then(|value| value.parse::<u64>().ok());
-
-    {
-        let mut ota = ota_state.lock().unwrap();
-        ota.total_bytes = content_length;
-    }
 
     let mut ota = EspOta::new().map_err(|err| anyhow!("failed to acquire OTA: {err:?}"))?;
     let mut update = ota
@@ -1977,24 +4053,102 @@ fn download_and_apply_ota(
     let mut hasher = Sha256::new();
     let mut total_written = 0_u64;
     let mut chunk = [0_u8; OTA_CHUNK_SIZE];
+    let mut retries = 0_u32;
+
+    'download: loop {
+        let resume_from = total_written;
+        let mut client = HttpClient::wrap(EspHttpConnection::new(&http_conf)?);
+        let range_header = format!("bytes={resume_from}-");
+        let headers: &[(&str, &str)] = if resume_from > 0 {
+            &[("Range", range_header.as_str())]
+        } else {
+            &[]
+        };
+        let request = client.request(Method::Get, url, headers)?;
+        let mut response = request.submit().map_err(|e| anyhow!("{e:?}"))?;
 
-    loop {
-        let read = response.read(&mut chunk).map_err(|e| anyhow!("{e:?}"))?;
-        if read == 0 {
-            break;
+        let status = response.status();
+        let resumed = if resume_from == 0 {
+            if !(200..300).contains(&status) {
+                return Err(anyhow!("OTA download failed with HTTP {status}"));
+            }
+            false
+        } else {
+            match status {
+                206 => {
+                    let content_range = response
+                        .header("content-range")
+                        .or_else(|| response.header("Content-Range"))
+                        .unwrap_or_default();
+                    let expected_prefix = format!("bytes {resume_from}-");
+                    if !content_range.starts_with(&expected_prefix) {
+                        return Err(anyhow!(
+                            "OTA resume got unexpected Content-Range `{content_range}` for \
+                             offset {resume_from}"
+                        ));
+                    }
+                    true
+                }
+                200 => false,
+                _ => return Err(anyhow!("OTA resume request failed with HTTP {status}")),
+            }
+        };
+
+        if !resumed && resume_from > 0 {
+            warn!(
+                "OTA server did not honor resume at byte {resume_from}; restarting the update \
+                 from scratch"
+            );
+            drop(update);
+            update = ota
+                .initiate_update()
+                .map_err(|err| anyhow!("failed to re-initiate OTA update: {err:?}"))?;
+            hasher = Sha256::new();
+            total_written = 0;
+        }
+
+        if total_written == 0 {
+            let content_length = response
+                .header("content-length")
+                .or_else(|| response.header("Content-Length"))
+                .and_then(|value| value.parse::<u64>().ok());
+            let mut state = ota_state.lock().unwrap();
+            state.total_bytes = content_length;
         }
 
-        update
-            .write(&chunk[..read])
-            .map_err(|err| anyhow!("failed writing OTA data: {err:?}"))?;
-        hasher.update(&chunk[..read]);
-        total_written = total_written.saturating_add(read as u64);
+        loop {
+            let read = match response.read(&mut chunk) {
+                Ok(read) => read,
+                Err(err) => {
+                    retries += 1;
+                    if retries > OTA_DOWNLOAD_MAX_RETRIES {
+                        return Err(anyhow!(
+                            "OTA download failed after {OTA_DOWNLOAD_MAX_RETRIES} retries: {err:?}"
+                        ));
+                    }
+                    warn!(
+                        "OTA download interrupted at byte {total_written} ({err:?}); resuming \
+                         attempt {retries}/{OTA_DOWNLOAD_MAX_RETRIES}"
+                    );
+                    continue 'download;
+                }
+            };
+            if read == 0 {
+                break 'download;
+            }
 
-        let mut state = ota_state.lock().unwrap();
-        state.bytes_written = total_written;
-        if let Some(total) = state.total_bytes.filter(|value| *value > 0) {
-            let pct = (total_written.saturating_mul(100) / total).min(100);
-            state.progress_pct = Some(pct as u8);
+            update
+                .write(&chunk[..read])
+                .map_err(|err| anyhow!("failed writing OTA data: {err:?}"))?;
+            hasher.update(&chunk[..read]);
+            total_written = total_written.saturating_add(read as u64);
+
+            let mut state = ota_state.lock().unwrap();
+            state.bytes_written = total_written;
+            if let Some(total) = state.total_bytes.filter(|value| *value > 0) {
+                let pct = (total_written.saturating_mul(100) / total).min(100);
+                state.progress_pct = Some(pct as u8);
+            }
         }
     }
 
@@ -2018,6 +4172,24 @@ fn download_and_apply_ota(
         }
     }
 
+    let signature_bytes = BASE64_STANDARD
+        .decode(expected_signature.trim())
+        .map_err(|err| anyhow!("signature is not valid base64: {err}"))?;
+    let signature = Signature::from_slice(&signature_bytes)
+        .map_err(|err| anyhow!("malformed Ed25519 signature: {err}"))?;
+    let signing_key = nvs_store
+        .load_ota_signing_public_key()
+        .unwrap_or_else(|err| {
+            warn!("failed to load OTA signing key from NVS, using dev fallback: {err:#}");
+            None
+        })
+        .unwrap_or(OTA_SIGNING_PUBLIC_KEY);
+    let verifying_key = VerifyingKey::from_bytes(&signing_key)
+        .map_err(|err| anyhow!("invalid OTA signing public key: {err}"))?;
+    verifying_key
+        .verify(digest.as_slice(), &signature)
+        .map_err(|_| anyhow!("OTA image signature verification failed"))?;
+
     update
         .complete()
         .map_err(|err| anyhow!("failed finalizing OTA image: {err:?}"))?;
@@ -2029,6 +4201,11 @@ fn download_and_apply_ota(
 fn build_ota_status_response(state: &SharedState) -> OtaStatusResponse {
     let ota = state.ota.lock().unwrap();
 
+    let health_check_deadline_epoch = ota.verify_deadline_ms.map(|deadline_ms| {
+        let now_ms = monotonic_ms();
+        Utc::now().timestamp() + (deadline_ms.saturating_sub(now_ms) / 1000) as i64
+    });
+
     OtaStatusResponse {
         supported: true,
         in_progress: ota.in_progress,
@@ -2042,6 +4219,300 @@ fn build_ota_status_response(state: &SharedState) -> OtaStatusResponse {
         running_slot: ota_slot_label(SlotQuery::Running),
         boot_slot: ota_slot_label(SlotQuery::Boot),
         update_slot: ota_slot_label(SlotQuery::Update),
+        pending_verify: ota.pending_verify,
+        last_result: ota.last_result,
+        health_check_deadline_epoch,
+        available_version: ota.available_version.clone(),
+        deferred_until_epoch: ota.deferred_until_epoch,
+    }
+}
+
+fn build_connectivity_status(state: &SharedState) -> ConnectivityStatusResponse {
+    ConnectivityStatusResponse {
+        transport: *state.transport.lock().unwrap(),
+    }
+}
+
+fn parse_version(text: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = text.trim().split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().unwrap_or("0").parse().ok()?;
+    let patch = parts.next().unwrap_or("0").parse().ok()?;
+    Some((major, minor, patch))
+}
+
+fn version_is_newer(candidate: &str, current: &str) -> bool {
+    match (parse_version(candidate), parse_version(current)) {
+        (Some(candidate), Some(current)) => candidate > current,
+        _ => false,
+    }
+}
+
+fn fetch_ota_manifest(url: &str) -> anyhow::Result<OtaManifest> {
+    let http_conf = HttpClientConfiguration {
+        timeout: Some(Duration::from_secs(15)),
+        crt_bundle_attach: Some(esp_idf_svc::sys::esp_crt_bundle_attach),
+        ..Default::default()
+    };
+    let mut client = HttpClient::wrap(EspHttpConnection::new(&http_conf)?);
+    let request = client.request(Method::Get, url, &[])?;
+    let mut response = request.submit().map_err(|e| anyhow!("{e:?}"))?;
+
+    let status = response.status();
+    if !(200..300).contains(&status) {
+        return Err(anyhow!("manifest fetch failed with HTTP {status}"));
+    }
+
+    let mut body = Vec::new();
+    let mut chunk = [0_u8; 512];
+    loop {
+        let read = response.read(&mut chunk).map_err(|e| anyhow!("{e:?}"))?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..read]);
+        if body.len() > MAX_HTTP_BODY {
+            return Err(anyhow!("OTA manifest exceeds {MAX_HTTP_BODY} bytes"));
+        }
+    }
+
+    serde_json::from_slice(&body).context("invalid OTA manifest JSON")
+}
+
+/// Fetches and evaluates an OTA manifest: checks it's actually newer than the
+/// running firmware and that the device has enough free heap, then either
+/// applies it immediately or queues it for the control loop to apply once
+/// the manifest's `notBefore` window has passed.
+fn check_ota_manifest(
+    state: &SharedState,
+    nvs_store: &NvsStore,
+    request: &OtaCheckRequest,
+) -> anyhow::Result<OtaCheckResponse> {
+    let runtime = nvs_store.load_runtime_config().unwrap_or_default();
+    if !runtime.network.ota_password.is_empty() {
+        let supplied = request.password.as_deref().unwrap_or_default();
+        if supplied != runtime.network.ota_password {
+            return Err(anyhow!("invalid OTA password"));
+        }
+    }
+
+    let manifest = fetch_ota_manifest(&request.manifest_url)?;
+
+    if !version_is_newer(&manifest.version, FIRMWARE_VERSION) {
+        return Ok(OtaCheckResponse {
+            accepted: false,
+            reason: Some(format!(
+                "manifest version {} is not newer than running version {FIRMWARE_VERSION}",
+                manifest.version
+            )),
+            available_version: Some(manifest.version),
+            deferred_until_epoch: None,
+        });
+    }
+
+    if let Some(min_free_heap) = manifest.min_free_heap {
+        let free_heap = unsafe { esp_idf_svc::sys::esp_get_free_heap_size() };
+        if free_heap < min_free_heap {
+            return Ok(OtaCheckResponse {
+                accepted: false,
+                reason: Some(format!(
+                    "free heap {free_heap} bytes is below the manifest's required \
+                     {min_free_heap} bytes"
+                )),
+                available_version: Some(manifest.version),
+                deferred_until_epoch: None,
+            });
+        }
+    }
+
+    let queued = QueuedOtaUpdate {
+        url: manifest.url,
+        sha256: manifest.sha256,
+        signature: manifest.signature,
+        password: request.password.clone(),
+    };
+
+    let probe_request = OtaApplyRequest {
+        url: queued.url.clone(),
+        sha256: queued.sha256.clone(),
+        signature: queued.signature.clone(),
+        password: queued.password.clone(),
+        reboot: Some(true),
+    };
+    if let Err(message) = validate_ota_apply_request(&probe_request) {
+        return Err(anyhow!("manifest produced an invalid OTA request: {message}"));
+    }
+
+    let deferred_until_epoch = manifest
+        .not_before
+        .filter(|not_before| *not_before > Utc::now().timestamp());
+
+    {
+        let mut ota = state.ota.lock().unwrap();
+        ota.available_version = Some(manifest.version.clone());
+        ota.deferred_until_epoch = deferred_until_epoch;
+        ota.queued_update = Some(queued);
+    }
+
+    if deferred_until_epoch.is_none() {
+        apply_ota_update(state, nvs_store, probe_request)?;
+    }
+
+    Ok(OtaCheckResponse {
+        accepted: true,
+        reason: None,
+        available_version: Some(manifest.version),
+        deferred_until_epoch,
+    })
+}
+
+/// Applies a queued manifest update once its `notBefore` deferral window has
+/// passed and no other OTA is mid-flight.
+fn apply_deferred_ota_if_due(state: &SharedState, nvs_store: &NvsStore) {
+    let due_update = {
+        let mut ota = state.ota.lock().unwrap();
+        if ota.in_progress {
+            return;
+        }
+        let Some(deadline_epoch) = ota.deferred_until_epoch else {
+            return;
+        };
+        if Utc::now().timestamp() < deadline_epoch {
+            return;
+        }
+
+        ota.deferred_until_epoch = None;
+        let Some(queued) = ota.queued_update.take() else {
+            return;
+        };
+        queued
+    };
+
+    info!("applying deferred OTA update now that its notBefore window has passed");
+    let apply_request = OtaApplyRequest {
+        url: due_update.url,
+        sha256: due_update.sha256,
+        signature: due_update.signature,
+        password: due_update.password,
+        reboot: Some(true),
+    };
+    if let Err(err) = apply_ota_update(state, nvs_store, apply_request) {
+        warn!("deferred OTA apply failed: {err:#}");
+    }
+}
+
+/// Manually confirms a pending OTA update, marking the running slot valid and
+/// cancelling the automatic rollback so it survives future reboots. Exposed
+/// over HTTP so a client that has verified the new firmware (e.g. after
+/// checking its own UI loads correctly) doesn't have to wait out the
+/// `check_ota_health` grace window. A no-op if nothing is pending.
+fn confirm_ota_update(
+    state: &SharedState,
+    nvs_store: &NvsStore,
+) -> anyhow::Result<OtaStatusResponse> {
+    let mut ota = state.ota.lock().unwrap();
+    if ota.pending_verify {
+        EspOta::new()
+            .and_then(|mut slot| slot.mark_running_slot_valid())
+            .map_err(|err| anyhow!("failed to mark running OTA slot valid: {err:?}"))?;
+        ota.pending_verify = false;
+        ota.verify_deadline_ms = None;
+        ota.last_result = Some(OtaHealthState::Confirmed);
+        nvs_store.save_ota_pending(false)?;
+        nvs_store.save_ota_last_result(OtaHealthState::Confirmed)?;
+        info!("OTA update confirmed via /api/ota/confirm");
+    }
+    drop(ota);
+
+    Ok(build_ota_status_response(state))
+}
+
+/// Forces an immediate rollback to the previous OTA slot and reboots,
+/// regardless of whether the `check_ota_health` grace window has elapsed.
+/// Exposed over HTTP for a client that has detected the new firmware
+/// misbehaving through a channel this device can't see itself (e.g. a
+/// reverse proxy tracking error rates). A no-op if nothing is pending.
+fn revert_ota_update(state: &SharedState, nvs_store: &NvsStore) -> anyhow::Result<()> {
+    let mut ota = state.ota.lock().unwrap();
+    if !ota.pending_verify {
+        return Ok(());
+    }
+    ota.pending_verify = false;
+    ota.last_result = Some(OtaHealthState::RolledBack);
+    drop(ota);
+
+    nvs_store.save_ota_pending(false)?;
+    nvs_store.save_ota_last_result(OtaHealthState::RolledBack)?;
+    info!("OTA update reverted via /api/ota/revert; rolling back to the previous slot");
+
+    thread::Builder::new()
+        .name("ota-revert".into())
+        .spawn(|| {
+            thread::sleep(Duration::from_millis(500));
+            let rollback =
+                EspOta::new().and_then(|mut slot| slot.mark_running_slot_invalid_and_reboot());
+            if let Err(err) = rollback {
+                warn!("failed to mark running OTA slot invalid, restarting anyway: {err:?}");
+                unsafe { esp_idf_svc::sys::esp_restart() };
+            }
+        })
+        .expect("failed to spawn OTA revert thread");
+
+    Ok(())
+}
+
+fn check_ota_health(
+    state: &SharedState,
+    nvs_store: &NvsStore,
+    wifi_connected: bool,
+    mqtt_connected: bool,
+    now_ms: u64,
+) {
+    let mut ota = state.ota.lock().unwrap();
+    if !ota.pending_verify {
+        return;
+    }
+
+    if wifi_connected && mqtt_connected {
+        match EspOta::new().and_then(|mut slot| slot.mark_running_slot_valid()) {
+            Ok(()) => {
+                info!("OTA health check passed; running slot marked valid");
+                ota.pending_verify = false;
+                ota.verify_deadline_ms = None;
+                ota.last_result = Some(OtaHealthState::Confirmed);
+                if let Err(err) = nvs_store.save_ota_pending(false) {
+                    warn!("failed to clear OTA pending-verify flag in NVS: {err:#}");
+                }
+                if let Err(err) = nvs_store.save_ota_last_result(OtaHealthState::Confirmed) {
+                    warn!("failed to persist OTA health check result in NVS: {err:#}");
+                }
+            }
+            Err(err) => warn!("failed to mark running OTA slot valid: {err:?}"),
+        }
+        return;
+    }
+
+    let Some(deadline_ms) = ota.verify_deadline_ms else {
+        return;
+    };
+
+    if now_ms >= deadline_ms {
+        warn!(
+            "OTA health check did not pass within the grace window; marking the running slot \
+             invalid and rolling back to the previous slot"
+        );
+        ota.last_result = Some(OtaHealthState::RolledBack);
+        drop(ota);
+        if let Err(err) = nvs_store.save_ota_last_result(OtaHealthState::RolledBack) {
+            warn!("failed to persist OTA health check result in NVS: {err:#}");
+        }
+        thread::sleep(Duration::from_millis(100));
+        let rollback =
+            EspOta::new().and_then(|mut slot| slot.mark_running_slot_invalid_and_reboot());
+        if let Err(err) = rollback {
+            warn!("failed to mark running OTA slot invalid, restarting anyway: {err:?}");
+            unsafe { esp_idf_svc::sys::esp_restart() };
+        }
     }
 }
 
@@ -2066,6 +4537,9 @@ fn build_ir_config_view(ir: &IrHardwareConfig) -> IrConfigView {
         tx_pin: ir.tx_pin,
         rmt_channel: ir.rmt_channel,
         carrier_khz: ir.carrier_khz,
+        protocol: ir.protocol,
+        rx_pin: ir.rx_pin,
+        learn_glitch_floor_us: ir.learn_glitch_floor_us,
     }
 }
 
@@ -2073,6 +4547,10 @@ fn ir_restart_required(previous: &IrHardwareConfig, current: &IrHardwareConfig)
     previous != current
 }
 
+fn validate_ir_learn_rx_pin(ir: &IrHardwareConfig) -> Result<i32, &'static str> {
+    ir.rx_pin.ok_or("rxPin is not configured; set ir.rxPin before learning commands")
+}
+
 fn is_supported_rmt_channel(channel: u8) -> bool {
     match channel {
         0 | 1 | 2 | 3 => true,
@@ -2090,12 +4568,17 @@ fn build_network_config_view(network: &NetworkConfig) -> NetworkConfigView {
         mqtt_port: network.mqtt_port,
         mqtt_user: network.mqtt_user.clone(),
         mqtt_pass_set: !network.mqtt_pass.is_empty(),
+        mqtt_tls: network.mqtt_tls,
         ota_password_set: !network.ota_password.is_empty(),
         use_static_ip: network.use_static_ip,
         static_ip: network.static_ip,
         gateway: network.gateway,
         subnet: network.subnet,
         dns: network.dns,
+        secondary_dns: network.secondary_dns,
+        wifi_auth: network.wifi_auth,
+        wifi_identity: network.wifi_identity.clone(),
+        wifi_username: network.wifi_username.clone(),
     }
 }
 
@@ -2107,48 +4590,308 @@ fn network_restart_required(previous: &NetworkConfig, current: &NetworkConfig) -
         || previous.gateway != current.gateway
         || previous.subnet != current.subnet
         || previous.dns != current.dns
+        || previous.secondary_dns != current.secondary_dns
         || previous.mqtt_host != current.mqtt_host
         || previous.mqtt_port != current.mqtt_port
         || previous.mqtt_user != current.mqtt_user
         || previous.mqtt_pass != current.mqtt_pass
+        || previous.mqtt_tls != current.mqtt_tls
+        || previous.wifi_auth != current.wifi_auth
+        || previous.wifi_identity != current.wifi_identity
+        || previous.wifi_username != current.wifi_username
 }
 
 impl NvsStore {
-    fn load_runtime_config(&self) -> anyhow::Result<RuntimeConfig> {
+    /// Reads a versioned, chunked payload previously written by
+    /// [`NvsStore::save_versioned`]: concatenates its chunks back into one
+    /// JSON string, walks it forward through `migrations[stored..current]`,
+    /// then deserializes the result into `T`. Returns
+    /// [`NvsLoadError::Missing`] rather than an error when nothing has been
+    /// written yet, so callers can tell "first boot" apart from corruption.
+    fn load_versioned<T: serde::de::DeserializeOwned>(
+        &self,
+        version_key: &str,
+        chunks_key: &str,
+        chunk_prefix: &str,
+        current_version: u8,
+        migrations: &[Migration],
+    ) -> Result<T, NvsLoadError> {
+        let _guard = self.lock.lock().unwrap();
+        let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)
+            .map_err(|err| NvsLoadError::Corrupt(err.to_string()))?;
+
+        let Some(stored_version) = nvs
+            .get_u8(version_key)
+            .map_err(|err| NvsLoadError::Corrupt(err.to_string()))?
+        else {
+            return Err(NvsLoadError::Missing);
+        };
+        if stored_version > current_version {
+            return Err(NvsLoadError::UnsupportedVersion(stored_version));
+        }
+
+        let chunk_count = nvs
+            .get_u8(chunks_key)
+            .map_err(|err| NvsLoadError::Corrupt(err.to_string()))?
+            .ok_or_else(|| NvsLoadError::Corrupt("version present but chunks missing".into()))?;
+
+        let mut json = String::new();
+        let mut buffer = vec![0_u8; NVS_CHUNK_MAX_BYTES + 64];
+        for i in 0..chunk_count {
+            let key = format!("{chunk_prefix}{i}");
+            let chunk = nvs
+                .get_str(&key, &mut buffer)
+                .map_err(|err| NvsLoadError::Corrupt(err.to_string()))?
+                .ok_or_else(|| NvsLoadError::Corrupt(format!("missing chunk {i}")))?;
+            json.push_str(chunk);
+        }
+
+        let mut value: serde_json::Value =
+            serde_json::from_str(&json).map_err(|err| NvsLoadError::Corrupt(err.to_string()))?;
+        for migration in &migrations[stored_version as usize..current_version as usize] {
+            value = migration(value).map_err(|err| NvsLoadError::Corrupt(err.to_string()))?;
+        }
+
+        serde_json::from_value(value).map_err(|err| NvsLoadError::Corrupt(err.to_string()))
+    }
+
+    /// Serializes `value`, splits it into chunks under `NVS_CHUNK_MAX_BYTES`,
+    /// and writes them under `chunk_prefix`, then stamps `chunks_key` and
+    /// finally `version_key` (written last, so on a first save a reader never
+    /// observes a version with an incompletely-written chunk set behind it,
+    /// e.g. after a power loss mid-save). A re-save overwrites the chunks in
+    /// place while the previous `version_key` and `chunks_key` are still
+    /// stored, so an interrupted re-save can leave a mix of old and new
+    /// chunks behind the old version.
+    fn save_versioned<T: Serialize>(
+        &self,
+        version_key: &str,
+        chunks_key: &str,
+        chunk_prefix: &str,
+        version: u8,
+        value: &T,
+    ) -> anyhow::Result<()> {
+        let json = serde_json::to_string(value)?;
+        let chunks = chunk_str(&json, NVS_CHUNK_MAX_BYTES);
+
+        let _guard = self.lock.lock().unwrap();
+        let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            nvs.set_str(&format!("{chunk_prefix}{i}"), chunk)?;
+        }
+        nvs.set_u8(chunks_key, chunks.len() as u8)?;
+        nvs.set_u8(version_key, version)?;
+        Ok(())
+    }
+
+    /// Reads a key written by the fixed-size, unversioned storage this
+    /// versioned scheme replaces, so a payload saved by older firmware can
+    /// still be read back once on upgrade.
+    fn load_legacy_str(&self, key: &str) -> anyhow::Result<Option<String>> {
         let _guard = self.lock.lock().unwrap();
         let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
         let mut buffer = vec![0_u8; 4096];
+        Ok(nvs.get_str(key, &mut buffer)?.map(str::to_string))
+    }
+
+    fn load_runtime_config(&self) -> anyhow::Result<RuntimeConfig> {
+        match self.load_versioned(
+            NVS_RUNTIME_VERSION_KEY,
+            NVS_RUNTIME_CHUNKS_KEY,
+            NVS_RUNTIME_CHUNK_PREFIX,
+            RUNTIME_SCHEMA_VERSION,
+            RUNTIME_MIGRATIONS,
+        ) {
+            Ok(runtime) => return Ok(runtime),
+            Err(NvsLoadError::Missing) => {}
+            Err(err) => return Err(err.into()),
+        }
 
-        match nvs.get_str(NVS_RUNTIME_KEY, &mut buffer)? {
-            Some(value) => Ok(serde_json::from_str::<RuntimeConfig>(value)?),
+        // Nothing under the versioned keys yet - check for a blob written by
+        // older firmware before this scheme existed, and upgrade it so this
+        // is the last time this device takes the legacy path.
+        match self.load_legacy_str(NVS_RUNTIME_KEY)? {
+            Some(value) => {
+                let runtime = serde_json::from_str::<RuntimeConfig>(&value)?;
+                self.save_runtime_config(&runtime)?;
+                Ok(runtime)
+            }
             None => Ok(RuntimeConfig::default()),
         }
     }
 
     fn save_runtime_config(&self, runtime: &RuntimeConfig) -> anyhow::Result<()> {
+        self.save_versioned(
+            NVS_RUNTIME_VERSION_KEY,
+            NVS_RUNTIME_CHUNKS_KEY,
+            NVS_RUNTIME_CHUNK_PREFIX,
+            RUNTIME_SCHEMA_VERSION,
+            runtime,
+        )
+    }
+
+    fn load_schedule(&self) -> anyhow::Result<Schedule> {
+        match self.load_versioned(
+            NVS_SCHEDULE_VERSION_KEY,
+            NVS_SCHEDULE_CHUNKS_KEY,
+            NVS_SCHEDULE_CHUNK_PREFIX,
+            SCHEDULE_SCHEMA_VERSION,
+            SCHEDULE_MIGRATIONS,
+        ) {
+            Ok(schedule) => return Ok(schedule),
+            Err(NvsLoadError::Missing) => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        match self.load_legacy_str(NVS_SCHEDULE_KEY)? {
+            Some(value) => {
+                let schedule = serde_json::from_str::<Schedule>(&value)?;
+                self.save_schedule(&schedule)?;
+                Ok(schedule)
+            }
+            None => Ok(Schedule::default()),
+        }
+    }
+
+    fn save_schedule(&self, schedule: &Schedule) -> anyhow::Result<()> {
+        self.save_versioned(
+            NVS_SCHEDULE_VERSION_KEY,
+            NVS_SCHEDULE_CHUNKS_KEY,
+            NVS_SCHEDULE_CHUNK_PREFIX,
+            SCHEDULE_SCHEMA_VERSION,
+            schedule,
+        )
+    }
+
+    fn load_learned_ir_commands(&self) -> anyhow::Result<HashMap<String, Vec<u16>>> {
         let _guard = self.lock.lock().unwrap();
         let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
-        let payload = serde_json::to_string(runtime)?;
-        nvs.set_str(NVS_RUNTIME_KEY, &payload)?;
+        let mut buffer = vec![0_u8; 4096];
+
+        match nvs.get_str(NVS_IR_LEARNED_KEY, &mut buffer)? {
+            Some(value) => Ok(serde_json::from_str::<HashMap<String, Vec<u16>>>(value)?),
+            None => Ok(HashMap::new()),
+        }
+    }
+
+    fn save_learned_ir_command(&self, key: &str, timings: &[u16]) -> anyhow::Result<()> {
+        let mut commands = self.load_learned_ir_commands()?;
+        commands.insert(key.to_string(), timings.to_vec());
+
+        let _guard = self.lock.lock().unwrap();
+        let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
+        let payload = serde_json::to_string(&commands)?;
+        nvs.set_str(NVS_IR_LEARNED_KEY, &payload)?;
         Ok(())
     }
 
-    fn load_schedule(&self) -> anyhow::Result<Schedule> {
+    /// Returns `None` when nothing has been persisted yet, distinct from
+    /// `IrRuntimeState::default()`, so the caller knows whether the assumed
+    /// state is a genuine restore or just an untested guess.
+    fn load_ir_runtime_state(&self) -> anyhow::Result<Option<IrRuntimeState>> {
         let _guard = self.lock.lock().unwrap();
         let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
-        let mut buffer = vec![0_u8; 4096];
+        let mut buffer = vec![0_u8; 256];
 
-        match nvs.get_str(NVS_SCHEDULE_KEY, &mut buffer)? {
-            Some(value) => Ok(serde_json::from_str::<Schedule>(value)?),
-            None => Ok(Schedule::default()),
+        match nvs.get_str(NVS_IR_STATE_KEY, &mut buffer)? {
+            Some(value) => Ok(Some(serde_json::from_str::<IrRuntimeState>(value)?)),
+            None => Ok(None),
         }
     }
 
-    fn save_schedule(&self, schedule: &Schedule) -> anyhow::Result<()> {
+    fn save_ir_runtime_state(&self, state: &IrRuntimeState) -> anyhow::Result<()> {
         let _guard = self.lock.lock().unwrap();
         let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
-        let payload = serde_json::to_string(schedule)?;
-        nvs.set_str(NVS_SCHEDULE_KEY, &payload)?;
+        let payload = serde_json::to_string(state)?;
+        nvs.set_str(NVS_IR_STATE_KEY, &payload)?;
+        Ok(())
+    }
+
+    /// Whether the currently running slot was just flashed by an OTA apply
+    /// and is still waiting on the post-reboot health check.
+    fn load_ota_pending(&self) -> anyhow::Result<bool> {
+        let _guard = self.lock.lock().unwrap();
+        let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
+        Ok(nvs.get_u8(NVS_OTA_PENDING_KEY)?.unwrap_or(0) != 0)
+    }
+
+    fn save_ota_pending(&self, pending: bool) -> anyhow::Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
+        nvs.set_u8(NVS_OTA_PENDING_KEY, u8::from(pending))?;
+        Ok(())
+    }
+
+    /// The outcome of the most recently completed OTA health check. `None`
+    /// if no OTA has ever completed a health check on this device.
+    fn load_ota_last_result(&self) -> anyhow::Result<Option<OtaHealthState>> {
+        let _guard = self.lock.lock().unwrap();
+        let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
+        Ok(match nvs.get_u8(NVS_OTA_LAST_RESULT_KEY)? {
+            Some(1) => Some(OtaHealthState::Confirmed),
+            Some(2) => Some(OtaHealthState::RolledBack),
+            _ => None,
+        })
+    }
+
+    fn save_ota_last_result(&self, result: OtaHealthState) -> anyhow::Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
+        let value = match result {
+            OtaHealthState::Confirmed => 1,
+            OtaHealthState::RolledBack => 2,
+        };
+        nvs.set_u8(NVS_OTA_LAST_RESULT_KEY, value)?;
+        Ok(())
+    }
+
+    /// A user-uploaded PEM bundle for validating the MQTT broker's TLS
+    /// certificate. `None` when no custom CA has been uploaded, in which case
+    /// the broker is validated against the device's bundled ESP-IDF CA store.
+    fn load_mqtt_ca_cert(&self) -> anyhow::Result<Option<String>> {
+        let _guard = self.lock.lock().unwrap();
+        let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
+        let mut buffer = vec![0_u8; 4096];
+
+        Ok(nvs
+            .get_str(NVS_MQTT_CA_CERT_KEY, &mut buffer)?
+            .map(str::to_string))
+    }
+
+    fn save_mqtt_ca_cert(&self, pem: &str) -> anyhow::Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
+        nvs.set_str(NVS_MQTT_CA_CERT_KEY, pem)?;
+        Ok(())
+    }
+
+    /// Per-fleet Ed25519 public key to verify OTA image signatures against,
+    /// pushed via `/api/ota/signing-key`. `None` when no real key has been
+    /// provisioned on this device, in which case the caller falls back to
+    /// the dev-only `OTA_SIGNING_PUBLIC_KEY` placeholder, which can never
+    /// verify a real signature.
+    fn load_ota_signing_public_key(&self) -> anyhow::Result<Option<[u8; 32]>> {
+        let _guard = self.lock.lock().unwrap();
+        let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
+        let mut buffer = vec![0_u8; 64];
+
+        let Some(encoded) = nvs.get_str(NVS_OTA_SIGNING_KEY_KEY, &mut buffer)? else {
+            return Ok(None);
+        };
+        let decoded = BASE64_STANDARD
+            .decode(encoded)
+            .map_err(|err| anyhow!("corrupt OTA signing key in NVS: {err}"))?;
+        let key_bytes: [u8; 32] = decoded
+            .try_into()
+            .map_err(|_| anyhow!("corrupt OTA signing key in NVS: expected 32 bytes"))?;
+        Ok(Some(key_bytes))
+    }
+
+    fn save_ota_signing_public_key(&self, key: &[u8; 32]) -> anyhow::Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
+        nvs.set_str(NVS_OTA_SIGNING_KEY_KEY, &BASE64_STANDARD.encode(key))?;
         Ok(())
     }
 }
@@ -2178,6 +4921,101 @@ fn feed_watchdog() {
     let _ = unsafe { esp_idf_svc::sys::esp_task_wdt_reset() };
 }
 
+/// How often the watchdog supervisor checks every registered task's last
+/// heartbeat and decides whether to feed the hardware watchdog.
+const WATCHDOG_SUPERVISOR_INTERVAL_MS: u64 = 1_000;
+
+struct WatchdogTaskState {
+    max_interval_ms: u64,
+    last_heartbeat_ms: u64,
+}
+
+/// Tracks a heartbeat per long-lived thread (control loop, MQTT receiver,
+/// ...), each with its own allowed interval, so the single thread actually
+/// registered with the ESP-IDF hardware watchdog
+/// ([`spawn_watchdog_supervisor`]) can refuse to feed it when *any*
+/// registered task has stalled, rather than only noticing a stall in
+/// whichever task happens to call `feed_watchdog` itself.
+#[derive(Clone, Default)]
+struct WatchdogRegistry {
+    tasks: Arc<Mutex<HashMap<&'static str, WatchdogTaskState>>>,
+}
+
+impl WatchdogRegistry {
+    /// Records that `name` is alive as of `now_ms`, and is expected to call
+    /// this again within `max_interval_ms`.
+    fn heartbeat(&self, name: &'static str, max_interval_ms: u64, now_ms: u64) {
+        self.tasks.lock().unwrap().insert(
+            name,
+            WatchdogTaskState {
+                max_interval_ms,
+                last_heartbeat_ms: now_ms,
+            },
+        );
+    }
+
+    /// Returns whether every registered task is within its allowed interval,
+    /// and the name/elapsed time of the slowest one (for logging), if any
+    /// task has registered yet.
+    fn check(&self, now_ms: u64) -> (bool, Option<(&'static str, u64)>) {
+        let tasks = self.tasks.lock().unwrap();
+        let mut all_healthy = true;
+        let mut slowest: Option<(&'static str, u64)> = None;
+
+        for (&name, state) in tasks.iter() {
+            let elapsed_ms = now_ms.saturating_sub(state.last_heartbeat_ms);
+            if elapsed_ms > state.max_interval_ms {
+                all_healthy = false;
+            }
+            let is_slowest_so_far = match slowest {
+                Some((_, slowest_elapsed)) => elapsed_ms > slowest_elapsed,
+                None => true,
+            };
+            if is_slowest_so_far {
+                slowest = Some((name, elapsed_ms));
+            }
+        }
+
+        (all_healthy, slowest)
+    }
+}
+
+/// The only task registered with the ESP-IDF hardware watchdog. Periodically
+/// checks every task in `registry` and only calls `feed_watchdog` when all
+/// of them are within their allowed interval, so a stalled MQTT-receiver or
+/// control-loop thread eventually trips the hardware watchdog's configured
+/// `trigger_panic` reboot instead of going unnoticed.
+fn spawn_watchdog_supervisor(registry: WatchdogRegistry) {
+    thread::Builder::new()
+        .name("wdt-supervisor".into())
+        .stack_size(4 * 1024)
+        .spawn(move || {
+            if let Err(err) = add_current_task_to_watchdog() {
+                warn!("failed to register watchdog supervisor: {err:#}");
+            }
+
+            loop {
+                thread::sleep(Duration::from_millis(WATCHDOG_SUPERVISOR_INTERVAL_MS));
+                let now_ms = monotonic_ms();
+                let (all_healthy, slowest) = registry.check(now_ms);
+
+                if let Some((name, elapsed_ms)) = slowest {
+                    debug!("watchdog: slowest task `{name}` last fed {elapsed_ms}ms ago");
+                }
+
+                if all_healthy {
+                    feed_watchdog();
+                } else {
+                    warn!(
+                        "watchdog: a registered task has stalled past its allowed interval, \
+                         withholding feed"
+                    );
+                }
+            }
+        })
+        .expect("failed to spawn watchdog supervisor thread");
+}
+
 fn disable_wifi_power_save() {
     let rc = unsafe { esp_idf_svc::sys::esp_wifi_set_ps(0) };
     if rc == esp_idf_svc::sys::ESP_OK {
@@ -2193,55 +5031,241 @@ fn is_wifi_station_connected() -> bool {
     rc == esp_idf_svc::sys::ESP_OK
 }
 
-fn init_status_led(pin: i32) -> Option<StatusLed> {
-    let driver = unsafe { PinDriver::output(AnyOutputPin::new(pin)) };
-    match driver {
-        Ok(mut pin) => {
-            let _ = pin.set_low();
-            Some(StatusLed { pin, lit: false })
-        }
+/// Brings up the UART link to the cellular modem used for PPP failover.
+/// Returns `None` (rather than failing startup) when the UART can't be
+/// claimed, since the modem is a fallback path and its absence shouldn't
+/// block WiFi-only operation.
+fn init_connectivity_manager(
+    uart1: esp_idf_hal::uart::UART1,
+    transport: Arc<Mutex<Transport>>,
+) -> Option<ConnectivityManager> {
+    let config = UartConfig::new().baudrate(MODEM_UART_BAUD_RATE.into());
+    let uart = unsafe {
+        UartDriver::new(
+            uart1,
+            AnyIOPin::new(MODEM_UART_TX_PIN),
+            AnyIOPin::new(MODEM_UART_RX_PIN),
+            Option::<AnyIOPin>::None,
+            Option::<AnyIOPin>::None,
+            &config,
+        )
+    };
+    match uart {
+        Ok(uart) => Some(ConnectivityManager::new(uart, transport)),
         Err(err) => {
-            warn!("status LED unavailable on GPIO{pin}: {err}");
+            warn!(
+                "modem UART unavailable on GPIO{MODEM_UART_TX_PIN}/GPIO{MODEM_UART_RX_PIN}, \
+                 PPP failover disabled: {err}"
+            );
             None
         }
     }
 }
 
-fn update_status_led(
-    status_led: &mut Option<StatusLed>,
-    wifi_connected: bool,
-    mqtt_connected: bool,
-    now_ms: u64,
-) {
-    let desired_on = if !wifi_connected {
-        ((now_ms / LED_FAST_BLINK_MS) % 2) == 0
-    } else if !mqtt_connected {
-        ((now_ms / LED_SLOW_BLINK_MS) % 2) == 0
-    } else {
-        true
+/// Builds the IR transmitter and (if configured) the status LED, both of
+/// which may need to come off the same `RMT` peripheral. The mono-GPIO LED
+/// backend doesn't touch RMT at all, so it's initialized independently of
+/// IR on that path; the RGB backend shares the peripheral with IR and so
+/// must be paired up in a single RMT-channel match, via
+/// [`init_ir_and_rgb_led`], before any channel field is moved out of `rmt`.
+fn init_ir_and_status_led(
+    rmt: RMT,
+    ir: &IrHardwareConfig,
+    led: &StatusLedConfig,
+    learned_commands: HashMap<String, Vec<u16>>,
+    restored_state: Option<IrRuntimeState>,
+) -> (IrTransmitter, Option<status_led::StatusLed>) {
+    let rgb_channels_valid = led.backend == StatusLedBackend::Rgb
+        && ir.rmt_channel <= 3
+        && led.rmt_channel <= 3
+        && ir.rmt_channel != led.rmt_channel;
+
+    if led.backend == StatusLedBackend::Rgb && rgb_channels_valid {
+        return match init_ir_and_rgb_led(rmt, ir, led, learned_commands, restored_state) {
+            Ok((transmitter, rgb_led)) => {
+                info!(
+                    "IR transmitter initialized on RMT channel{} / GPIO{} @ {}kHz, protocol={:?}; \
+                     RGB status LED on RMT channel{} / GPIO{}",
+                    ir.rmt_channel,
+                    ir.tx_pin,
+                    ir.carrier_khz,
+                    ir.protocol,
+                    led.rmt_channel,
+                    led.pin,
+                );
+                (transmitter, Some(status_led::StatusLed::Rgb(rgb_led)))
+            }
+            Err(err) => {
+                warn!("failed to initialize IR + RGB status LED on shared RMT: {err:#}");
+                (IrTransmitter::disabled(), None)
+            }
+        };
+    }
+
+    if led.backend == StatusLedBackend::Rgb {
+        warn!(
+            "RGB status LED requires a distinct RMT channel 0-3 from IR (IR={}, LED={}); \
+             status LED disabled",
+            ir.rmt_channel, led.rmt_channel
+        );
+    }
+
+    let transmitter = match init_ir_transmitter(rmt, ir, learned_commands, restored_state) {
+        Ok(transmitter) => {
+            info!(
+                "IR transmitter initialized on RMT channel{} / GPIO{} @ {}kHz, protocol={:?}, state={:?}",
+                ir.rmt_channel,
+                ir.tx_pin,
+                ir.carrier_khz,
+                ir.protocol,
+                transmitter.state_origin(),
+            );
+            transmitter
+        }
+        Err(err) => {
+            warn!("failed to initialize IR transmitter, running disabled: {err:#}");
+            IrTransmitter::disabled()
+        }
     };
 
-    let Some(led) = status_led.as_mut() else {
-        return;
+    let status_led = if led.backend == StatusLedBackend::Mono {
+        init_mono_status_led(led.pin).map(status_led::StatusLed::Mono)
+    } else {
+        None
     };
 
-    if desired_on == led.lit {
-        return;
+    (transmitter, status_led)
+}
+
+fn init_mono_status_led(pin: i32) -> Option<status_led::MonoStatusLed<AnyOutputPin>> {
+    if pin < 0 {
+        warn!("invalid status LED pin: {pin}");
+        return None;
     }
 
-    let result = if desired_on {
-        led.pin.set_high()
-    } else {
-        led.pin.set_low()
-    };
+    match status_led::MonoStatusLed::new(unsafe { AnyOutputPin::new(pin) }) {
+        Ok(led) => Some(led),
+        Err(err) => {
+            warn!("status LED unavailable on GPIO{pin}: {err:#}");
+            None
+        }
+    }
+}
 
-    if let Err(err) = result {
-        warn!("failed to drive status LED: {err}");
-    } else {
-        led.lit = desired_on;
+/// Pairs an IR RMT channel with an RGB status LED RMT channel off the same
+/// `RMT` peripheral. Both channels must be distinct and in `0..=3` (present
+/// on every ESP32 variant); callers are expected to have checked that
+/// already. Rust's move-checker can't verify that two independently
+/// runtime-selected fields of `rmt` are disjoint across two separate
+/// top-level matches, so both channels are claimed together here in one
+/// match on the `(ir_channel, led_channel)` pair.
+fn init_ir_and_rgb_led(
+    rmt: RMT,
+    ir: &IrHardwareConfig,
+    led: &StatusLedConfig,
+    learned_commands: HashMap<String, Vec<u16>>,
+    restored_state: Option<IrRuntimeState>,
+) -> anyhow::Result<(IrTransmitter, status_led::RgbStatusLed<'static>)> {
+    if ir.tx_pin < 0 {
+        return Err(anyhow!("invalid IR tx pin: {}", ir.tx_pin));
+    }
+    if led.pin < 0 {
+        return Err(anyhow!("invalid status LED pin: {}", led.pin));
+    }
+
+    let ir_pin = ir.tx_pin;
+    let carrier_khz = ir.carrier_khz;
+    let protocol = ir.protocol;
+    let led_pin = led.pin;
+
+    match (ir.rmt_channel, led.rmt_channel) {
+        (0, 1) => combine_ir_and_rgb_led(
+            rmt.channel0, rmt.channel1, ir_pin, carrier_khz, protocol, led_pin,
+            learned_commands, restored_state,
+        ),
+        (0, 2) => combine_ir_and_rgb_led(
+            rmt.channel0, rmt.channel2, ir_pin, carrier_khz, protocol, led_pin,
+            learned_commands, restored_state,
+        ),
+        (0, 3) => combine_ir_and_rgb_led(
+            rmt.channel0, rmt.channel3, ir_pin, carrier_khz, protocol, led_pin,
+            learned_commands, restored_state,
+        ),
+        (1, 0) => combine_ir_and_rgb_led(
+            rmt.channel1, rmt.channel0, ir_pin, carrier_khz, protocol, led_pin,
+            learned_commands, restored_state,
+        ),
+        (1, 2) => combine_ir_and_rgb_led(
+            rmt.channel1, rmt.channel2, ir_pin, carrier_khz, protocol, led_pin,
+            learned_commands, restored_state,
+        ),
+        (1, 3) => combine_ir_and_rgb_led(
+            rmt.channel1, rmt.channel3, ir_pin, carrier_khz, protocol, led_pin,
+            learned_commands, restored_state,
+        ),
+        (2, 0) => combine_ir_and_rgb_led(
+            rmt.channel2, rmt.channel0, ir_pin, carrier_khz, protocol, led_pin,
+            learned_commands, restored_state,
+        ),
+        (2, 1) => combine_ir_and_rgb_led(
+            rmt.channel2, rmt.channel1, ir_pin, carrier_khz, protocol, led_pin,
+            learned_commands, restored_state,
+        ),
+        (2, 3) => combine_ir_and_rgb_led(
+            rmt.channel2, rmt.channel3, ir_pin, carrier_khz, protocol, led_pin,
+            learned_commands, restored_state,
+        ),
+        (3, 0) => combine_ir_and_rgb_led(
+            rmt.channel3, rmt.channel0, ir_pin, carrier_khz, protocol, led_pin,
+            learned_commands, restored_state,
+        ),
+        (3, 1) => combine_ir_and_rgb_led(
+            rmt.channel3, rmt.channel1, ir_pin, carrier_khz, protocol, led_pin,
+            learned_commands, restored_state,
+        ),
+        (3, 2) => combine_ir_and_rgb_led(
+            rmt.channel3, rmt.channel2, ir_pin, carrier_khz, protocol, led_pin,
+            learned_commands, restored_state,
+        ),
+        (ir_channel, led_channel) => Err(anyhow!(
+            "unsupported IR/RGB-LED RMT channel pairing: IR={ir_channel}, LED={led_channel}"
+        )),
     }
 }
 
+/// One arm's worth of work for [`init_ir_and_rgb_led`], factored out since
+/// every `(ir_channel, led_channel)` pair does the same two-step
+/// construction with two distinctly-typed `RMT` channel fields.
+#[allow(clippy::too_many_arguments)]
+fn combine_ir_and_rgb_led<IrC, LedC>(
+    ir_channel: IrC,
+    led_channel: LedC,
+    ir_pin: i32,
+    carrier_khz: u32,
+    protocol: IrProtocol,
+    led_pin: i32,
+    learned_commands: HashMap<String, Vec<u16>>,
+    restored_state: Option<IrRuntimeState>,
+) -> anyhow::Result<(IrTransmitter, status_led::RgbStatusLed<'static>)>
+where
+    IrC: esp_idf_hal::rmt::RmtChannel,
+    LedC: esp_idf_hal::rmt::RmtChannel,
+{
+    let ir_tx = unsafe {
+        IrTransmitter::new_with_carrier(
+            ir_channel,
+            AnyOutputPin::new(ir_pin),
+            carrier_khz,
+            protocol,
+            learned_commands,
+            restored_state,
+        )
+    }?;
+    let rgb_led =
+        unsafe { status_led::RgbStatusLed::new(led_channel, AnyOutputPin::new(led_pin)) }?;
+    Ok((ir_tx, rgb_led))
+}
+
 fn now_in_timezone(timezone: &str) -> Option<chrono::DateTime<chrono::FixedOffset>> {
     let tz: Tz = timezone.parse().ok()?;
     let local = Utc::now().with_timezone(&tz);
@@ -2258,6 +5282,13 @@ fn monotonic_ms() -> u64 {
         .unwrap_or(u64::MAX)
 }
 
+/// Converts a `monotonic_ms()`-space timestamp into a wall-clock epoch for
+/// HTTP responses, same idea as the OTA health-check deadline conversion.
+fn monotonic_ms_to_epoch(monotonic_ms_value: u64) -> i64 {
+    let now_ms = monotonic_ms();
+    Utc::now().timestamp() - (now_ms.saturating_sub(monotonic_ms_value) / 1000) as i64
+}
+
 #[allow(dead_code)]
 fn _schedule_examples() -> Vec<ScheduleEntry> {
     vec![
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     io::ErrorKind,
     net::SocketAddr,
     path::PathBuf,
@@ -7,30 +7,37 @@ use std::{
         atomic::{AtomicBool, Ordering},
         Arc, OnceLock,
     },
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::Context;
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
-    routing::{get, post, put},
+    routing::{delete, get, post, put},
     Json, Router,
 };
 use chrono::{Offset, Utc};
 use chrono_tz::Tz;
-use rumqttc::{AsyncClient, Event, Incoming, MqttOptions, QoS};
+use futures_util::StreamExt;
+use hmac::{Hmac, Mac};
+use rumqttc::{AsyncClient, Event, Incoming, LastWill, MqttOptions, QoS};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tokio::{net::TcpListener, sync::Mutex};
 use tower_http::services::ServeDir;
 use tracing::{info, warn};
 
 use thermostat_common::{
-    config::{IrHardwareConfig, NetworkConfig},
-    DayOfWeek, EngineAction, RuntimeConfig, Schedule, ScheduleAction, ScheduleEntry,
-    ThermostatEngine, ThermostatMode, TOPIC_CMD_HOLD, TOPIC_CMD_MODE, TOPIC_CMD_POWER,
-    TOPIC_CMD_SCHEDULE, TOPIC_CMD_TARGET, TOPIC_CONTROLLER_SCHEDULE_STATE, TOPIC_CONTROLLER_STATE,
+    config::{IrHardwareConfig, IrProtocol, NetworkConfig},
+    ControlStrategy, ControllerStatePayload, ControllerStatus, DateException, DayOfWeek,
+    EngineAction, InfluxConfig, PidParameters, RuntimeConfig, Schedule, ScheduleAction, ScheduleEntry,
+    ScheduleOverride, TelemetryConfig, Temperature, TemperatureUnit, ThermostatEngine,
+    ThermostatMode, ThermostatState, UploaderConfig,
+    TOPIC_CMD_DATE_EXCEPTIONS, TOPIC_CMD_HOLD, TOPIC_CMD_MODE, TOPIC_CMD_OVERRIDE,
+    TOPIC_CMD_POWER, TOPIC_CMD_SCHEDULE, TOPIC_CMD_TARGET, TOPIC_CMD_TELEMETRY_ONESHOT,
+    TOPIC_CONTROLLER_AVAILABILITY, TOPIC_CONTROLLER_SCHEDULE_STATE, TOPIC_CONTROLLER_STATE,
     TOPIC_SENSOR_HUMIDITY, TOPIC_SENSOR_TEMP,
 };
 
@@ -40,17 +47,77 @@ struct AppState {
     schedule: Arc<Mutex<Schedule>>,
     timezone: Arc<Mutex<String>>,
     time_synced: Arc<AtomicBool>,
-    mqtt: AsyncClient,
+    mqtt: Arc<Mutex<AsyncClient>>,
+    mqtt_loop_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
     store: AppStore,
+    ha_discovery_enabled: bool,
+    telemetry: Arc<Mutex<TelemetryConfig>>,
+    telemetry_oneshot: Arc<tokio::sync::Notify>,
+    influx: Arc<Mutex<InfluxConfig>>,
+    /// Points not yet accepted by InfluxDB, oldest first, capped at
+    /// `INFLUX_BUFFER_CAPACITY` so a prolonged outage trims history instead
+    /// of growing without bound.
+    influx_buffer: Arc<Mutex<VecDeque<String>>>,
+    uploader: Arc<Mutex<UploaderConfig>>,
+    /// Signed snapshot JSON objects not yet accepted by the uploader
+    /// endpoint, oldest first, capped at `UPLOADER_BUFFER_CAPACITY` the same
+    /// way `influx_buffer` is.
+    uploader_buffer: Arc<Mutex<VecDeque<String>>>,
+    /// Monotonically increasing counter included in every uploaded
+    /// snapshot, so the remote endpoint can detect drops/reordering and
+    /// reject replays. Never persisted - restarting the controller resets
+    /// it, which is fine since the server only needs it to increase within
+    /// one continuous run.
+    uploader_seq: Arc<Mutex<u64>>,
+    ota: Arc<Mutex<OtaProgress>>,
+    /// Reload state for the `config.toml` overlay loaded/applied by
+    /// `load_and_apply_file_config`; see `FileConfigState`.
+    file_config: Arc<Mutex<FileConfigState>>,
+    status_tx: tokio::sync::broadcast::Sender<ControllerStatus>,
 }
 
+type HmacSha256 = Hmac<Sha256>;
+
 #[derive(Clone)]
 struct AppStore {
     runtime_path: Arc<PathBuf>,
     schedule_path: Arc<PathBuf>,
+    schedule_history_path: Arc<PathBuf>,
     lock: Arc<Mutex<()>>,
 }
 
+const SCHEDULE_HISTORY_CAP: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ScheduleHistoryEntry {
+    id: u64,
+    #[serde(rename = "timestampEpoch")]
+    timestamp_epoch: i64,
+    note: String,
+    /// `None` marks this entry as a tombstone for a deleted schedule.
+    schedule: Option<Schedule>,
+}
+
+#[derive(Debug, Serialize)]
+struct ScheduleHistoryListEntry {
+    id: u64,
+    #[serde(rename = "timestampEpoch")]
+    timestamp_epoch: i64,
+    note: String,
+    deleted: bool,
+}
+
+impl From<&ScheduleHistoryEntry> for ScheduleHistoryListEntry {
+    fn from(entry: &ScheduleHistoryEntry) -> Self {
+        Self {
+            id: entry.id,
+            timestamp_epoch: entry.timestamp_epoch,
+            note: entry.note.clone(),
+            deleted: entry.schedule.is_none(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct ErrorBody {
     error: String,
@@ -129,6 +196,13 @@ struct NetworkUpdateResponse {
 
 const MAX_MQTT_PAYLOAD_BYTES: usize = 512;
 
+/// Cap on buffered-but-unsent InfluxDB points. A push is attempted every
+/// `push_interval_secs`, so this bounds how much history an outage can
+/// accumulate before the oldest points start getting dropped to make room
+/// for newer ones.
+const INFLUX_BUFFER_CAPACITY: usize = 500;
+const UPLOADER_BUFFER_CAPACITY: usize = 200;
+
 #[derive(Debug, Serialize)]
 struct IrConfigView {
     #[serde(rename = "txPin")]
@@ -137,6 +211,9 @@ struct IrConfigView {
     rmt_channel: u8,
     #[serde(rename = "carrierKHz")]
     carrier_khz: u32,
+    protocol: IrProtocol,
+    #[serde(rename = "rxPin")]
+    rx_pin: Option<i32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -147,6 +224,10 @@ struct IrConfigUpdate {
     rmt_channel: u8,
     #[serde(rename = "carrierKHz")]
     carrier_khz: u32,
+    #[serde(default)]
+    protocol: IrProtocol,
+    #[serde(rename = "rxPin", default)]
+    rx_pin: Option<i32>,
 }
 
 #[derive(Debug, Serialize)]
@@ -156,9 +237,340 @@ struct IrConfigUpdateResponse {
     ir: IrConfigView,
 }
 
+#[derive(Debug, Serialize)]
+struct PidConfigView {
+    kp: f32,
+    ki: f32,
+    kd: f32,
+    #[serde(rename = "outputMin")]
+    output_min: f32,
+    #[serde(rename = "outputMax")]
+    output_max: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct PidConfigUpdate {
+    kp: f32,
+    ki: f32,
+    kd: f32,
+    #[serde(rename = "outputMin")]
+    output_min: f32,
+    #[serde(rename = "outputMax")]
+    output_max: f32,
+}
+
+fn build_pid_config_view(pid: &PidParameters) -> PidConfigView {
+    PidConfigView {
+        kp: pid.kp,
+        ki: pid.ki,
+        kd: pid.kd,
+        output_min: pid.output_min,
+        output_max: pid.output_max,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct IrLearnStartRequest {
+    command: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SaveProfileRequest {
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+struct IrLearnStartResponse {
+    accepted: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct IrLearnResultView {
+    status: &'static str,
+    timings: Option<Vec<u16>>,
+}
+
+#[derive(Debug, Serialize)]
+struct TelemetryConfigView {
+    #[serde(rename = "stateIntervalSecs")]
+    state_interval_secs: u64,
+    #[serde(rename = "scheduleIntervalSecs")]
+    schedule_interval_secs: u64,
+    #[serde(rename = "stateEnabled")]
+    state_enabled: bool,
+    #[serde(rename = "scheduleEnabled")]
+    schedule_enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct TelemetryConfigUpdate {
+    #[serde(rename = "stateIntervalSecs")]
+    state_interval_secs: u64,
+    #[serde(rename = "scheduleIntervalSecs")]
+    schedule_interval_secs: u64,
+    #[serde(rename = "stateEnabled")]
+    state_enabled: bool,
+    #[serde(rename = "scheduleEnabled")]
+    schedule_enabled: bool,
+}
+
+fn build_telemetry_config_view(telemetry: &TelemetryConfig) -> TelemetryConfigView {
+    TelemetryConfigView {
+        state_interval_secs: telemetry.state_interval_secs,
+        schedule_interval_secs: telemetry.schedule_interval_secs,
+        state_enabled: telemetry.state_enabled,
+        schedule_enabled: telemetry.schedule_enabled,
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct InfluxConfigView {
+    enabled: bool,
+    url: String,
+    org: String,
+    bucket: String,
+    #[serde(rename = "tokenSet")]
+    token_set: bool,
+    #[serde(rename = "pushIntervalSecs")]
+    push_interval_secs: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct InfluxConfigUpdate {
+    enabled: bool,
+    url: String,
+    org: String,
+    bucket: String,
+    #[serde(default)]
+    token: Option<String>,
+    #[serde(rename = "pushIntervalSecs")]
+    push_interval_secs: u64,
+}
+
+fn build_influx_config_view(influx: &InfluxConfig) -> InfluxConfigView {
+    InfluxConfigView {
+        enabled: influx.enabled,
+        url: influx.url.clone(),
+        org: influx.org.clone(),
+        bucket: influx.bucket.clone(),
+        token_set: !influx.token.is_empty(),
+        push_interval_secs: influx.push_interval_secs,
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct UploaderConfigView {
+    enabled: bool,
+    #[serde(rename = "serverUrl")]
+    server_url: String,
+    #[serde(rename = "hmacKeySet")]
+    hmac_key_set: bool,
+    #[serde(rename = "pushIntervalSecs")]
+    push_interval_secs: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct UploaderConfigUpdate {
+    enabled: bool,
+    #[serde(rename = "serverUrl")]
+    server_url: String,
+    #[serde(default, rename = "hmacKey")]
+    hmac_key: Option<String>,
+    #[serde(rename = "pushIntervalSecs")]
+    push_interval_secs: u64,
+}
+
+fn build_uploader_config_view(uploader: &UploaderConfig) -> UploaderConfigView {
+    UploaderConfigView {
+        enabled: uploader.enabled,
+        server_url: uploader.server_url.clone(),
+        hmac_key_set: !uploader.hmac_key.is_empty(),
+        push_interval_secs: uploader.push_interval_secs,
+    }
+}
+
+/// How often `spawn_file_config_watch_loop` checks `config.toml`'s mtime
+/// for an on-disk edit.
+const FILE_CONFIG_POLL_SECS: u64 = 5;
+
+/// Tracks the reload state of the `config.toml` overlay so `build_status`
+/// can echo which version/path is live. `version` starts at `0` (no file
+/// config applied yet this run) and is bumped once per successful reload;
+/// `last_modified` is the mtime `spawn_file_config_watch_loop` last applied,
+/// so it only reloads on an actual change instead of every poll tick.
+#[derive(Debug, Default)]
+struct FileConfigState {
+    version: u32,
+    last_modified: Option<SystemTime>,
+}
+
+/// Hot-reloadable subset of the daemon's tunables, loaded from
+/// `config.toml` (see `AppStore::file_config_path`) so an operator can edit
+/// setpoints/limits on disk without restarting the daemon or scripting
+/// individual `/api/*` calls. Every field is optional - an absent key
+/// leaves whatever is already running untouched rather than resetting it to
+/// a hardcoded default.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct FileConfig {
+    #[serde(default)]
+    hysteresis_f: Option<f32>,
+    #[serde(default)]
+    fireplace_offset_f: Option<i32>,
+    #[serde(default)]
+    target_temp_f: Option<f32>,
+    #[serde(default)]
+    timezone: Option<String>,
+    #[serde(default)]
+    schedule_enabled: Option<bool>,
+    /// Temp-monitor safety band, applied together as
+    /// `ThermostatEngine::set_alarm_limits` (`ThermostatConfig::alarm_low_f`/
+    /// `alarm_high_f`) - the independent hard freeze/over-temp latch, not a
+    /// setpoint. Only applied when both are present in the same reload; a
+    /// lone `lower_limit_f`/`upper_limit_f` is left for the next edit that
+    /// supplies the pair.
+    #[serde(default)]
+    lower_limit_f: Option<f32>,
+    #[serde(default)]
+    upper_limit_f: Option<f32>,
+}
+
+impl FileConfig {
+    /// Checked against the whole file before any of it is applied, so one
+    /// bad field rejects the edit atomically instead of half-applying it.
+    fn validate(&self) -> Result<(), String> {
+        if let Some(hysteresis) = self.hysteresis_f {
+            if hysteresis <= 0.0 {
+                return Err(format!("hysteresis_f must be > 0, got {hysteresis}"));
+            }
+        }
+        if let (Some(lower), Some(upper)) = (self.lower_limit_f, self.upper_limit_f) {
+            if lower >= upper {
+                return Err(format!(
+                    "lower_limit_f ({lower}) must be less than upper_limit_f ({upper})"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads, validates, and applies `config.toml` as one atomic unit - a file
+/// that fails `FileConfig::validate` (or doesn't parse) is rejected in full
+/// and nothing currently running changes. A missing file is not an error:
+/// it just means no overlay is active. On success, bumps
+/// `file_config.version` and persists the applied settings the same way
+/// the `/api/*` setters do, so they survive a restart.
+async fn load_and_apply_file_config(state: &AppState, path: &PathBuf) -> anyhow::Result<()> {
+    let raw = match tokio::fs::read_to_string(path).await {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let config: FileConfig =
+        toml::from_str(&raw).with_context(|| format!("failed to parse {}", path.display()))?;
+    config.validate().map_err(|err| anyhow::anyhow!(err))?;
+
+    let alarm_limits = config.lower_limit_f.zip(config.upper_limit_f);
+    {
+        let mut engine = state.engine.lock().await;
+        if let Some(hysteresis) = config.hysteresis_f {
+            engine.set_hysteresis(hysteresis);
+        }
+        if let Some(offset) = config.fireplace_offset_f {
+            engine.set_fireplace_offset(offset);
+        }
+        if let Some(target) = config.target_temp_f {
+            engine.set_target_temp(target);
+        }
+        if let Some((lower, upper)) = alarm_limits {
+            // `validate` already checked `lower < upper`, so this can't fail.
+            engine.set_alarm_limits(lower, upper);
+        }
+    }
+    if let Some(timezone) = config.timezone {
+        *state.timezone.lock().await = timezone;
+    }
+    if let Some(enabled) = config.schedule_enabled {
+        state.schedule.lock().await.enabled = enabled;
+    }
+
+    persist_runtime_from_state(state).await?;
+
+    if let Some((lower, upper)) = alarm_limits {
+        let mut runtime = state.store.load_runtime_config().await?;
+        runtime.thermostat.alarm_low_f = lower;
+        runtime.thermostat.alarm_high_f = upper;
+        state.store.save_runtime_config(&runtime).await?;
+    }
+
+    state.file_config.lock().await.version += 1;
+    Ok(())
+}
+
+/// Polls `config.toml`'s mtime every `FILE_CONFIG_POLL_SECS` and re-applies
+/// it whenever it changes, so an operator's on-disk edit takes effect
+/// without needing to hit `/api/config/reload` themselves.
+fn spawn_file_config_watch_loop(app_state: AppState) {
+    tokio::spawn(async move {
+        let path = app_state.store.file_config_path();
+        loop {
+            tokio::time::sleep(Duration::from_secs(FILE_CONFIG_POLL_SECS)).await;
+
+            let modified = tokio::fs::metadata(&path)
+                .await
+                .ok()
+                .and_then(|meta| meta.modified().ok());
+            let changed = {
+                let file_config = app_state.file_config.lock().await;
+                modified.is_some() && modified != file_config.last_modified
+            };
+            if !changed {
+                continue;
+            }
+
+            match load_and_apply_file_config(&app_state, &path).await {
+                Ok(()) => app_state.file_config.lock().await.last_modified = modified,
+                Err(err) => warn!("rejected {} reload: {err:#}", path.display()),
+            }
+        }
+    });
+}
+
+/// `POST /api/config/reload` - applies `config.toml` right now instead of
+/// waiting for `spawn_file_config_watch_loop`'s next poll tick.
+async fn handle_post_config_reload(State(state): State<AppState>) -> impl IntoResponse {
+    let path = state.store.file_config_path();
+    match load_and_apply_file_config(&state, &path).await {
+        Ok(()) => {
+            let modified = tokio::fs::metadata(&path)
+                .await
+                .ok()
+                .and_then(|meta| meta.modified().ok());
+            state.file_config.lock().await.last_modified = modified;
+            handle_get_status(State(state)).await.into_response()
+        }
+        Err(err) => {
+            warn!("rejected {} reload: {err:#}", path.display());
+            error_response(
+                StatusCode::BAD_REQUEST,
+                "Config reload rejected; see controller logs",
+            )
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct IrCommandStatView {
+    command: String,
+    sent: u64,
+    failed: u64,
+}
+
 #[derive(Debug, Serialize)]
 struct IrDiagnosticsView {
     enabled: bool,
+    protocol: IrProtocol,
     #[serde(rename = "txPin")]
     tx_pin: i32,
     #[serde(rename = "rmtChannel")]
@@ -171,6 +583,8 @@ struct IrDiagnosticsView {
     failed_actions: u64,
     #[serde(rename = "lastError")]
     last_error: Option<String>,
+    #[serde(rename = "commandStats")]
+    command_stats: Vec<IrCommandStatView>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -180,11 +594,45 @@ struct OtaApplyRequest {
     sha256: Option<String>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum OtaState {
+    Idle,
+    Downloading,
+    Verifying,
+    PendingReboot,
+    Failed,
+}
+
+#[derive(Debug, Clone)]
+struct OtaProgress {
+    state: OtaState,
+    bytes_written: u64,
+    total_bytes: Option<u64>,
+    last_error: Option<String>,
+}
+
+impl Default for OtaProgress {
+    fn default() -> Self {
+        Self {
+            state: OtaState::Idle,
+            bytes_written: 0,
+            total_bytes: None,
+            last_error: None,
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct OtaStatusResponse {
     supported: bool,
+    state: OtaState,
     #[serde(rename = "inProgress")]
     in_progress: bool,
+    #[serde(rename = "bytesWritten")]
+    bytes_written: u64,
+    #[serde(rename = "totalBytes")]
+    total_bytes: Option<u64>,
     #[serde(rename = "lastError")]
     last_error: Option<String>,
 }
@@ -221,29 +669,85 @@ pub async fn run() -> anyhow::Result<()> {
     if !mqtt_user.is_empty() {
         mqtt_options.set_credentials(mqtt_user, mqtt_pass);
     }
+    mqtt_options.set_last_will(LastWill::new(
+        TOPIC_CONTROLLER_AVAILABILITY,
+        "offline",
+        QoS::AtLeastOnce,
+        true,
+    ));
 
     let (mqtt, eventloop) = AsyncClient::new(mqtt_options, 64);
 
+    let ha_discovery_enabled = std::env::var("HA_DISCOVERY_ENABLED")
+        .ok()
+        .and_then(|value| value.parse::<bool>().ok())
+        .unwrap_or(runtime.network.ha_discovery_enabled);
+
     let app_state = AppState {
         engine: Arc::new(Mutex::new(engine)),
         schedule: Arc::new(Mutex::new(schedule)),
         timezone: Arc::new(Mutex::new(runtime.timezone)),
         time_synced: Arc::new(AtomicBool::new(false)),
-        mqtt,
+        mqtt: Arc::new(Mutex::new(mqtt)),
+        mqtt_loop_handle: Arc::new(Mutex::new(None)),
         store,
+        ha_discovery_enabled,
+        telemetry: Arc::new(Mutex::new(runtime.telemetry)),
+        telemetry_oneshot: Arc::new(tokio::sync::Notify::new()),
+        influx: Arc::new(Mutex::new(runtime.influx)),
+        influx_buffer: Arc::new(Mutex::new(VecDeque::new())),
+        uploader: Arc::new(Mutex::new(runtime.uploader)),
+        uploader_buffer: Arc::new(Mutex::new(VecDeque::new())),
+        uploader_seq: Arc::new(Mutex::new(0)),
+        ota: Arc::new(Mutex::new(OtaProgress::default())),
+        file_config: Arc::new(Mutex::new(FileConfigState::default())),
+        status_tx: tokio::sync::broadcast::channel(16).0,
     };
 
-    subscribe_topics(&app_state.mqtt).await?;
-    spawn_mqtt_loop(app_state.clone(), eventloop);
-    spawn_control_loop(app_state.clone());
+    let watchdog_interval_secs = std::env::var("WATCHDOG_USEC")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(|usec| (usec / 2 / 1_000_000).max(1));
+
+    subscribe_topics(&*app_state.mqtt.lock().await).await?;
+    let initial_mqtt_loop = spawn_mqtt_loop(app_state.clone(), eventloop);
+    *app_state.mqtt_loop_handle.lock().await = Some(initial_mqtt_loop);
+    spawn_control_loop(app_state.clone(), watchdog_interval_secs);
     spawn_state_publish_loop(app_state.clone());
+    spawn_influx_push_loop(app_state.clone());
+    spawn_uploader_push_loop(app_state.clone());
+
+    let config_path = app_state.store.file_config_path();
+    match load_and_apply_file_config(&app_state, &config_path).await {
+        Ok(()) => {
+            let modified = tokio::fs::metadata(&config_path)
+                .await
+                .ok()
+                .and_then(|meta| meta.modified().ok());
+            app_state.file_config.lock().await.last_modified = modified;
+        }
+        Err(err) => warn!("rejected {} at startup: {err:#}", config_path.display()),
+    }
+    spawn_file_config_watch_loop(app_state.clone());
 
     let web_root = format!("{}/web", env!("CARGO_MANIFEST_DIR"));
     let app = Router::new()
         .route("/api/status", get(handle_get_status))
+        .route("/tstat", get(handle_get_tstat).post(handle_post_tstat))
+        .route("/api/settings/summary", get(handle_get_settings_summary))
+        .route("/api/ws", get(handle_ws))
         .route("/api/target", post(handle_set_target))
+        .route("/api/auto_cool_target", post(handle_set_auto_cool_target))
         .route("/api/mode", post(handle_set_mode))
         .route("/api/hysteresis", post(handle_set_hysteresis))
+        .route("/api/humidity-target", post(handle_set_humidity_target))
+        .route(
+            "/api/humidity-hysteresis",
+            post(handle_set_humidity_hysteresis),
+        )
+        .route("/api/control-strategy", post(handle_set_control_strategy))
+        .route("/api/display-unit", post(handle_set_display_unit))
+        .route("/api/pid", get(handle_get_pid).put(handle_put_pid))
         .route("/api/offset", post(handle_set_offset))
         .route("/api/ir/on", post(handle_ir_on))
         .route("/api/ir/off", post(handle_ir_off))
@@ -258,12 +762,32 @@ pub async fn run() -> anyhow::Result<()> {
             get(handle_get_ir_config).put(handle_put_ir_config),
         )
         .route("/api/ir/diagnostics", get(handle_get_ir_diagnostics))
+        .route("/api/ir/learn/start", post(handle_post_ir_learn_start))
+        .route("/api/ir/learn/result", get(handle_get_ir_learn_result))
         .route("/api/hold/enter", post(handle_hold_enter))
         .route("/api/hold/exit", post(handle_hold_exit))
         .route("/api/safety/reset", post(handle_safety_reset))
+        .route("/api/alarm/clear", post(handle_alarm_clear))
+        .route(
+            "/api/profiles",
+            get(handle_get_profiles).post(handle_post_save_profile),
+        )
+        .route("/api/profiles/{id}", delete(handle_delete_profile))
+        .route("/api/profiles/{id}/apply", post(handle_post_apply_profile))
         .route(
             "/api/schedule",
-            get(handle_get_schedule).put(handle_put_schedule),
+            get(handle_get_schedule)
+                .put(handle_put_schedule)
+                .delete(handle_delete_schedule),
+        )
+        .route("/api/schedule/history", get(handle_get_schedule_history))
+        .route(
+            "/api/schedule/history/{id}",
+            get(handle_get_schedule_history_entry),
+        )
+        .route(
+            "/api/schedule/rollback/{id}",
+            post(handle_post_schedule_rollback),
         )
         .route("/api/time", get(handle_get_time))
         .route("/api/timezone", put(handle_put_timezone))
@@ -273,6 +797,22 @@ pub async fn run() -> anyhow::Result<()> {
         )
         .route("/api/ota/status", get(handle_get_ota_status))
         .route("/api/ota/apply", post(handle_post_ota_apply))
+        .route(
+            "/api/telemetry",
+            get(handle_get_telemetry).put(handle_put_telemetry),
+        )
+        .route("/api/telemetry/oneshot", post(handle_post_telemetry_oneshot))
+        .route("/api/influx", get(handle_get_influx).put(handle_put_influx))
+        .route(
+            "/api/uploader",
+            get(handle_get_uploader).put(handle_put_uploader),
+        )
+        .route("/api/config/reload", post(handle_post_config_reload))
+        .route("/api/provision/state", get(handle_get_provision_state))
+        .route(
+            "/api/provision/complete",
+            post(handle_post_provision_complete),
+        )
         .fallback_service(ServeDir::new(web_root))
         .with_state(app_state);
 
@@ -286,7 +826,25 @@ pub async fn run() -> anyhow::Result<()> {
         .with_context(|| format!("failed to bind controller server at {addr}"))?;
 
     info!("controller listening on http://{addr}");
-    axum::serve(listener, app).await?;
+    sd_notify("READY=1");
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+
+    sd_notify("STOPPING=1");
+    let mqtt = app_state.mqtt.lock().await.clone();
+    if let Err(err) = mqtt
+        .publish(
+            TOPIC_CONTROLLER_AVAILABILITY,
+            QoS::AtLeastOnce,
+            true,
+            "offline",
+        )
+        .await
+    {
+        warn!("failed to publish offline availability message on shutdown: {err:#}");
+    }
+    mqtt.disconnect().await.ok();
     Ok(())
 }
 
@@ -299,6 +857,9 @@ async fn subscribe_topics(mqtt: &AsyncClient) -> anyhow::Result<()> {
         TOPIC_CMD_MODE,
         TOPIC_CMD_HOLD,
         TOPIC_CMD_SCHEDULE,
+        TOPIC_CMD_OVERRIDE,
+        TOPIC_CMD_DATE_EXCEPTIONS,
+        TOPIC_CMD_TELEMETRY_ONESHOT,
     ];
 
     for topic in topics {
@@ -307,39 +868,184 @@ async fn subscribe_topics(mqtt: &AsyncClient) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn spawn_mqtt_loop(app_state: AppState, mut eventloop: rumqttc::EventLoop) {
-    tokio::spawn(async move {
-        loop {
-            match eventloop.poll().await {
-                Ok(Event::Incoming(Incoming::Publish(message))) => {
-                    if let Err(err) =
-                        handle_mqtt_message(&app_state, message.topic, message.payload.to_vec())
-                            .await
-                    {
-                        warn!("mqtt message handling error: {err:#}");
-                    }
+const HA_DEVICE_ID: &str = "thermostat-controller";
+
+#[derive(Debug, Serialize)]
+struct HaDevice {
+    identifiers: Vec<String>,
+    name: String,
+    manufacturer: String,
+    sw_version: String,
+}
+
+#[derive(Debug, Serialize)]
+struct HaClimateDiscovery {
+    name: String,
+    unique_id: String,
+    availability_topic: String,
+    mode_command_topic: String,
+    mode_state_topic: String,
+    mode_state_template: String,
+    modes: Vec<String>,
+    temperature_command_topic: String,
+    temperature_state_topic: String,
+    temperature_state_template: String,
+    current_temperature_topic: String,
+    current_temperature_template: String,
+    current_humidity_topic: String,
+    current_humidity_template: String,
+    power_command_topic: String,
+    min_temp: f32,
+    max_temp: f32,
+    temp_step: f32,
+    temperature_unit: String,
+    device: HaDevice,
+}
+
+async fn publish_ha_discovery(mqtt: &AsyncClient) -> anyhow::Result<()> {
+    let discovery = HaClimateDiscovery {
+        name: "Thermostat".to_string(),
+        unique_id: HA_DEVICE_ID.to_string(),
+        availability_topic: TOPIC_CONTROLLER_AVAILABILITY.to_string(),
+        mode_command_topic: TOPIC_CMD_MODE.to_string(),
+        mode_state_topic: TOPIC_CONTROLLER_STATE.to_string(),
+        mode_state_template: "{{ value_json.mode | lower }}".to_string(),
+        modes: vec!["off".to_string(), "heat".to_string()],
+        temperature_command_topic: TOPIC_CMD_TARGET.to_string(),
+        temperature_state_topic: TOPIC_CONTROLLER_STATE.to_string(),
+        temperature_state_template: "{{ value_json.target }}".to_string(),
+        current_temperature_topic: TOPIC_CONTROLLER_STATE.to_string(),
+        current_temperature_template: "{{ value_json.temp }}".to_string(),
+        current_humidity_topic: TOPIC_CONTROLLER_STATE.to_string(),
+        current_humidity_template: "{{ value_json.humidity }}".to_string(),
+        power_command_topic: TOPIC_CMD_POWER.to_string(),
+        min_temp: 60.0,
+        max_temp: 84.0,
+        temp_step: 1.0,
+        temperature_unit: "F".to_string(),
+        device: HaDevice {
+            identifiers: vec![HA_DEVICE_ID.to_string()],
+            name: "Smart Thermostat".to_string(),
+            manufacturer: "nkocher".to_string(),
+            sw_version: env!("CARGO_PKG_VERSION").to_string(),
+        },
+    };
+
+    let topic = format!("homeassistant/climate/{HA_DEVICE_ID}/config");
+    let payload = serde_json::to_vec(&discovery)?;
+    mqtt.publish(topic, QoS::AtLeastOnce, true, payload).await?;
+    Ok(())
+}
+
+fn spawn_mqtt_loop(app_state: AppState, eventloop: rumqttc::EventLoop) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(mqtt_event_loop(app_state, eventloop))
+}
+
+async fn mqtt_event_loop(app_state: AppState, mut eventloop: rumqttc::EventLoop) {
+    loop {
+        match eventloop.poll().await {
+            Ok(Event::Incoming(Incoming::Publish(message))) => {
+                if let Err(err) =
+                    handle_mqtt_message(&app_state, message.topic, message.payload.to_vec()).await
+                {
+                    warn!("mqtt message handling error: {err:#}");
                 }
-                Ok(Event::Incoming(Incoming::ConnAck(_))) => {
-                    info!("mqtt connected");
+            }
+            Ok(Event::Incoming(Incoming::ConnAck(_))) => {
+                info!("mqtt connected");
+                let client = app_state.mqtt.lock().await.clone();
+                if let Err(err) = client
+                    .publish(
+                        TOPIC_CONTROLLER_AVAILABILITY,
+                        QoS::AtLeastOnce,
+                        true,
+                        "online",
+                    )
+                    .await
+                {
+                    warn!("failed to publish availability birth message: {err:#}");
                 }
-                Ok(_) => {}
-                Err(err) => {
-                    warn!("mqtt poll error: {err}");
-                    tokio::time::sleep(Duration::from_secs(2)).await;
+                if app_state.ha_discovery_enabled {
+                    if let Err(err) = publish_ha_discovery(&client).await {
+                        warn!("failed to publish Home Assistant discovery config: {err:#}");
+                    }
                 }
             }
+            Ok(_) => {}
+            Err(err) => {
+                warn!("mqtt poll error: {err}");
+                tokio::time::sleep(Duration::from_secs(2)).await;
+            }
         }
-    });
+    }
+}
+
+/// Rebuilds the MQTT client with fresh broker credentials and swaps it into
+/// `app_state` so an in-flight `handle_put_network` update takes effect without
+/// a process restart. The previous event loop task is aborted and replaced.
+async fn reload_mqtt_client(app_state: &AppState, network: &NetworkConfig) {
+    let mqtt_host = std::env::var("MQTT_HOST").unwrap_or(network.mqtt_host.clone());
+    let mqtt_port = std::env::var("MQTT_PORT")
+        .ok()
+        .and_then(|value| value.parse::<u16>().ok())
+        .unwrap_or(network.mqtt_port);
+
+    let mut mqtt_options = MqttOptions::new("thermostat-controller-rust", mqtt_host, mqtt_port);
+    if !network.mqtt_user.is_empty() {
+        mqtt_options.set_credentials(network.mqtt_user.clone(), network.mqtt_pass.clone());
+    }
+    mqtt_options.set_last_will(LastWill::new(
+        TOPIC_CONTROLLER_AVAILABILITY,
+        "offline",
+        QoS::AtLeastOnce,
+        true,
+    ));
+
+    let (new_client, new_eventloop) = AsyncClient::new(mqtt_options, 64);
+    if let Err(err) = subscribe_topics(&new_client).await {
+        warn!("failed to subscribe reloaded mqtt client: {err:#}");
+    }
+
+    *app_state.mqtt.lock().await = new_client;
+
+    let new_handle = spawn_mqtt_loop(app_state.clone(), new_eventloop);
+    if let Some(old_handle) = app_state.mqtt_loop_handle.lock().await.replace(new_handle) {
+        old_handle.abort();
+    }
+
+    info!("reloaded mqtt client with updated broker credentials");
 }
 
-fn spawn_control_loop(app_state: AppState) {
+fn spawn_control_loop(app_state: AppState, watchdog_interval_secs: Option<u64>) {
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(Duration::from_secs(1));
+        let mut ticks_since_watchdog_ping = 0u64;
+        let mut last_watchdog_ms = monotonic_ms();
+        let mut last_reported_state: Option<ThermostatState> = None;
 
         loop {
             interval.tick().await;
             let now_ms = monotonic_ms();
 
+            if let Some(interval_secs) = watchdog_interval_secs {
+                ticks_since_watchdog_ping += 1;
+                if ticks_since_watchdog_ping >= interval_secs.max(1) {
+                    ticks_since_watchdog_ping = 0;
+                    let engine_responsive = now_ms > last_watchdog_ms
+                        && app_state
+                            .engine
+                            .try_lock()
+                            .map(|engine| engine.state())
+                            .is_ok();
+                    if engine_responsive {
+                        sd_notify("WATCHDOG=1");
+                    } else {
+                        warn!("skipping watchdog ping: engine loop appears unresponsive");
+                    }
+                    last_watchdog_ms = now_ms;
+                }
+            }
+
             let timezone = { app_state.timezone.lock().await.clone() };
             let now_in_tz = now_in_timezone(&timezone);
             app_state
@@ -347,15 +1053,50 @@ fn spawn_control_loop(app_state: AppState) {
                 .store(now_in_tz.is_some(), Ordering::Relaxed);
 
             if let Some(now) = now_in_tz {
-                let schedule_action = {
+                let current_temp_f = { app_state.engine.lock().await.current_temp_f() };
+                let (schedule_action, heat_transition, override_active) = {
                     let schedule = app_state.schedule.lock().await;
-                    schedule.current_action(now)
+                    (
+                        schedule.effective_action(now, current_temp_f),
+                        schedule.next_heat_transition(now),
+                        schedule.override_action.is_some(),
+                    )
+                };
+
+                // An explicit override represents the user's current intent, so
+                // it always suppresses anticipatory pre-heat. Otherwise, if the
+                // upcoming transition is a heat-up and we're not already headed
+                // there, ask the engine whether it's time to start early.
+                let wants_preheat = !override_active
+                    && schedule_action
+                        .map(|action| action.mode != ThermostatMode::Heat)
+                        .unwrap_or(true);
+
+                let resolved_action = if wants_preheat {
+                    if let Some((transition_epoch, target_temp_f)) = heat_transition {
+                        let should_preheat = {
+                            let engine = app_state.engine.lock().await;
+                            engine.should_preheat(target_temp_f, transition_epoch, now.timestamp())
+                        };
+                        if should_preheat {
+                            Some(ScheduleAction {
+                                mode: ThermostatMode::Heat,
+                                target_temp_f,
+                            })
+                        } else {
+                            schedule_action
+                        }
+                    } else {
+                        schedule_action
+                    }
+                } else {
+                    schedule_action
                 };
 
                 if let Some(ScheduleAction {
                     mode,
                     target_temp_f,
-                }) = schedule_action
+                }) = resolved_action
                 {
                     let schedule_actions = {
                         let mut engine = app_state.engine.lock().await;
@@ -365,83 +1106,459 @@ fn spawn_control_loop(app_state: AppState) {
                     };
 
                     if !schedule_actions.is_empty() {
-                        execute_engine_actions(schedule_actions).await;
+                        execute_engine_actions(&app_state, schedule_actions).await;
                     }
                 }
             }
 
-            let actions = {
+            let (actions, status_line) = {
                 let mut engine = app_state.engine.lock().await;
-                engine.tick(now_ms)
+                let actions = engine.tick(now_ms);
+                let current_state = engine.state();
+                let status_line = if last_reported_state != Some(current_state) {
+                    last_reported_state = Some(current_state);
+                    Some(engine_status_line(&engine, now_ms))
+                } else {
+                    None
+                };
+                (actions, status_line)
             };
 
+            if let Some(status_line) = status_line {
+                sd_notify(&format!("STATUS={status_line}"));
+            }
+
             if !actions.is_empty() {
-                execute_engine_actions(actions).await;
+                execute_engine_actions(&app_state, actions).await;
             }
         }
     });
 }
 
-fn spawn_state_publish_loop(app_state: AppState) {
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(Duration::from_secs(10));
-        loop {
-            interval.tick().await;
+async fn publish_controller_state(app_state: &AppState) {
+    let now_ms = monotonic_ms();
+    let payload = {
+        let engine = app_state.engine.lock().await;
+        serde_json::to_vec(&engine.state_payload(now_ms))
+    };
 
-            let now_ms = monotonic_ms();
-            let payload = {
-                let engine = app_state.engine.lock().await;
-                serde_json::to_vec(&engine.state_payload(now_ms))
-            };
+    match payload {
+        Ok(body) => {
+            let mqtt = app_state.mqtt.lock().await.clone();
+            if let Err(err) = mqtt
+                .publish(TOPIC_CONTROLLER_STATE, QoS::AtLeastOnce, true, body)
+                .await
+            {
+                warn!("controller state publish failed: {err}");
+            }
+        }
+        Err(err) => warn!("controller state serialization failed: {err}"),
+    }
+}
 
-            match payload {
-                Ok(body) => {
-                    if let Err(err) = app_state
-                        .mqtt
-                        .publish(TOPIC_CONTROLLER_STATE, QoS::AtLeastOnce, true, body)
-                        .await
-                    {
-                        warn!("controller state publish failed: {err}");
-                    }
-                }
-                Err(err) => warn!("controller state serialization failed: {err}"),
+async fn publish_schedule_state(app_state: &AppState) {
+    let schedule_payload = {
+        let schedule = app_state.schedule.lock().await;
+        serde_json::to_vec(&*schedule)
+    };
+
+    match schedule_payload {
+        Ok(body) => {
+            let mqtt = app_state.mqtt.lock().await.clone();
+            if let Err(err) = mqtt
+                .publish(
+                    TOPIC_CONTROLLER_SCHEDULE_STATE,
+                    QoS::AtLeastOnce,
+                    true,
+                    body,
+                )
+                .await
+            {
+                warn!("schedule state publish failed: {err}");
             }
+        }
+        Err(err) => warn!("schedule serialization failed: {err}"),
+    }
+}
 
-            let schedule_payload = {
-                let schedule = app_state.schedule.lock().await;
-                serde_json::to_vec(&*schedule)
-            };
+/// Drives the periodic controller-state/schedule-state telemetry publishes at their
+/// configured (and independently enable-able) cadences, and publishes an immediate
+/// controller-state update whenever a one-shot publish is requested out of band.
+fn spawn_state_publish_loop(app_state: AppState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        let mut ticks_since_state = 0u64;
+        let mut ticks_since_schedule = 0u64;
 
-            match schedule_payload {
-                Ok(body) => {
-                    if let Err(err) = app_state
-                        .mqtt
-                        .publish(
-                            TOPIC_CONTROLLER_SCHEDULE_STATE,
-                            QoS::AtLeastOnce,
-                            true,
-                            body,
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    ticks_since_state += 1;
+                    ticks_since_schedule += 1;
+
+                    let (state_enabled, state_interval, schedule_enabled, schedule_interval) = {
+                        let telemetry = app_state.telemetry.lock().await;
+                        (
+                            telemetry.state_enabled,
+                            telemetry.state_interval_secs,
+                            telemetry.schedule_enabled,
+                            telemetry.schedule_interval_secs,
                         )
-                        .await
-                    {
-                        warn!("schedule state publish failed: {err}");
+                    };
+
+                    if state_enabled && ticks_since_state >= state_interval {
+                        ticks_since_state = 0;
+                        publish_controller_state(&app_state).await;
+                    }
+                    if schedule_enabled && ticks_since_schedule >= schedule_interval {
+                        ticks_since_schedule = 0;
+                        publish_schedule_state(&app_state).await;
                     }
                 }
-                Err(err) => warn!("schedule serialization failed: {err}"),
+                _ = app_state.telemetry_oneshot.notified() => {
+                    publish_controller_state(&app_state).await;
+                }
             }
         }
     });
 }
 
-async fn execute_engine_actions(actions: Vec<EngineAction>) {
-    for action in actions {
-        if let EngineAction::Delay(ms) = action {
-            tokio::time::sleep(Duration::from_millis(ms)).await;
-            continue;
+/// Builds one InfluxDB line-protocol point from the current controller
+/// state: `thermostat,device_id=<id> temp=<f>,setpoint=<f>,relay=<0|1>i
+/// <unix_nanos>`. `relay` mirrors `fireplace` - the only heat-output signal
+/// this controller has - so the measurement can be graphed as a duty cycle
+/// alongside temp and setpoint.
+fn build_influx_line(payload: &ControllerStatePayload, timestamp_ns: u128) -> String {
+    let relay = i32::from(payload.fireplace);
+    format!(
+        "thermostat,device_id={HA_DEVICE_ID} temp={:.2},setpoint={:.2},relay={relay}i {timestamp_ns}",
+        payload.temp, payload.target,
+    )
+}
+
+/// Pushes everything currently buffered to InfluxDB in one batched write
+/// request. On success the buffer is drained; on any failure (including
+/// the feed being disabled or misconfigured) the points stay buffered for
+/// the next tick, so a brief WiFi/HTTP outage doesn't drop data - it just
+/// arrives late once `INFLUX_BUFFER_CAPACITY` isn't exceeded first.
+async fn push_influx_points(app_state: &AppState) {
+    let influx = app_state.influx.lock().await.clone();
+    if !influx.enabled {
+        return;
+    }
+    if influx.url.trim().is_empty() || influx.org.trim().is_empty() || influx.bucket.trim().is_empty()
+    {
+        warn!("influx export enabled but url/org/bucket incomplete; skipping push");
+        return;
+    }
+
+    let body = {
+        let buffer = app_state.influx_buffer.lock().await;
+        if buffer.is_empty() {
+            return;
         }
+        buffer.iter().cloned().collect::<Vec<_>>().join("\n")
+    };
 
-        // This preserves behavior sequencing in one place; ESP32 IR transport hooks in here.
-        info!("engine action: {action:?}");
+    let url = format!(
+        "{}/api/v2/write?org={}&bucket={}&precision=ns",
+        influx.url.trim_end_matches('/'),
+        influx.org,
+        influx.bucket
+    );
+
+    let result = reqwest::Client::new()
+        .post(&url)
+        .header("Authorization", format!("Token {}", influx.token))
+        .header("Content-Type", "text/plain; charset=utf-8")
+        .body(body)
+        .send()
+        .await;
+
+    match result {
+        Ok(response) if response.status().is_success() => {
+            app_state.influx_buffer.lock().await.clear();
+        }
+        Ok(response) => warn!("influx write rejected with status {}", response.status()),
+        Err(err) => warn!("influx write failed: {err:#}"),
+    }
+}
+
+/// Samples the controller state onto the InfluxDB ring buffer and attempts
+/// a batched push every `influx.push_interval_secs`, independent of the
+/// MQTT state/schedule telemetry cadence in `spawn_state_publish_loop`.
+fn spawn_influx_push_loop(app_state: AppState) {
+    tokio::spawn(async move {
+        loop {
+            let push_interval_secs = app_state.influx.lock().await.push_interval_secs;
+            tokio::time::sleep(Duration::from_secs(push_interval_secs.max(1))).await;
+
+            if !app_state.influx.lock().await.enabled {
+                continue;
+            }
+
+            let now_ms = monotonic_ms();
+            let payload = app_state.engine.lock().await.state_payload(now_ms);
+            let timestamp_ns = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos();
+            let line = build_influx_line(&payload, timestamp_ns);
+
+            {
+                let mut buffer = app_state.influx_buffer.lock().await;
+                if buffer.len() >= INFLUX_BUFFER_CAPACITY {
+                    buffer.pop_front();
+                }
+                buffer.push_back(line);
+            }
+
+            push_influx_points(&app_state).await;
+        }
+    });
+}
+
+/// One signed snapshot queued for upload. `seq` lets the remote endpoint
+/// detect drops/reordering and reject replays; `device_time_ms` is this
+/// device's own monotonic clock (not the server's receipt time), alongside
+/// `status` for drift/staleness detection independent of the signature.
+#[derive(Debug, Serialize)]
+struct UploaderSnapshot {
+    seq: u64,
+    #[serde(rename = "deviceTimeMs")]
+    device_time_ms: u64,
+    status: ControllerStatus,
+}
+
+/// Pushes everything currently buffered to the configured uploader endpoint
+/// in one request, body `[snapshot, snapshot, ...]`, signed as a whole with
+/// HMAC-SHA256 over the exact bytes sent so the server can verify nothing
+/// in transit - including every buffered `seq` - was altered. On success
+/// the buffer is drained; on any failure (including the feed being
+/// disabled or misconfigured) the snapshots stay buffered for the next
+/// tick, same recovery behavior as `push_influx_points`.
+async fn push_uploader_snapshots(app_state: &AppState) {
+    let uploader = app_state.uploader.lock().await.clone();
+    if !uploader.enabled {
+        return;
+    }
+    if uploader.server_url.trim().is_empty() || uploader.hmac_key.is_empty() {
+        warn!("status uploader enabled but serverUrl/hmacKey incomplete; skipping push");
+        return;
+    }
+
+    let body = {
+        let buffer = app_state.uploader_buffer.lock().await;
+        if buffer.is_empty() {
+            return;
+        }
+        format!("[{}]", buffer.iter().cloned().collect::<Vec<_>>().join(","))
+    };
+
+    let Ok(mut mac) = HmacSha256::new_from_slice(uploader.hmac_key.as_bytes()) else {
+        warn!("status uploader hmac key rejected by HMAC-SHA256; skipping push");
+        return;
+    };
+    mac.update(body.as_bytes());
+    let signature = hex_encode(&mac.finalize().into_bytes());
+
+    let result = reqwest::Client::new()
+        .post(&uploader.server_url)
+        .header("Content-Type", "application/json")
+        .header("X-Signature-Sha256", signature)
+        .body(body)
+        .send()
+        .await;
+
+    match result {
+        Ok(response) if response.status().is_success() => {
+            app_state.uploader_buffer.lock().await.clear();
+        }
+        Ok(response) => warn!("status upload rejected with status {}", response.status()),
+        Err(err) => warn!("status upload failed: {err:#}"),
+    }
+}
+
+/// Samples the current `ControllerStatus` onto the uploader ring buffer and
+/// attempts a signed batched push every `uploader.push_interval_secs`,
+/// independent of the MQTT state/schedule telemetry cadence and the
+/// InfluxDB push cadence.
+fn spawn_uploader_push_loop(app_state: AppState) {
+    tokio::spawn(async move {
+        loop {
+            let push_interval_secs = app_state.uploader.lock().await.push_interval_secs;
+            tokio::time::sleep(Duration::from_secs(push_interval_secs.max(1))).await;
+
+            if !app_state.uploader.lock().await.enabled {
+                continue;
+            }
+
+            let now_ms = monotonic_ms();
+            let status = build_status(&app_state).await;
+
+            let seq = {
+                let mut seq = app_state.uploader_seq.lock().await;
+                *seq += 1;
+                *seq
+            };
+
+            let snapshot = UploaderSnapshot {
+                seq,
+                device_time_ms: now_ms,
+                status,
+            };
+            match serde_json::to_string(&snapshot) {
+                Ok(json) => {
+                    let mut buffer = app_state.uploader_buffer.lock().await;
+                    if buffer.len() >= UPLOADER_BUFFER_CAPACITY {
+                        buffer.pop_front();
+                    }
+                    buffer.push_back(json);
+                }
+                Err(err) => warn!("failed to serialize uploader snapshot: {err:#}"),
+            }
+
+            push_uploader_snapshots(&app_state).await;
+        }
+    });
+}
+
+async fn execute_engine_actions(app_state: &AppState, actions: Vec<EngineAction>) {
+    for action in actions {
+        if let EngineAction::Delay(ms) = action {
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+            continue;
+        }
+
+        // This preserves behavior sequencing in one place; ESP32 IR transport hooks in here.
+        info!("engine action: {action:?}");
+    }
+
+    publish_status_update(app_state).await;
+}
+
+/// Builds the current status snapshot and pushes it to any subscribed
+/// `/api/ws` clients. A no-op if nobody is currently connected.
+async fn publish_status_update(app_state: &AppState) {
+    let status = build_status(app_state).await;
+
+    // `send` only errors when there are no subscribers; that's fine.
+    let _ = app_state.status_tx.send(status);
+}
+
+/// Assembles the live `ControllerStatus` snapshot: the engine's own
+/// reporting (temps, mode, state, ...) plus the daemon-level context it
+/// doesn't track itself (schedule/timezone/time-sync state, and which
+/// `config.toml` version is currently applied).
+async fn build_status(state: &AppState) -> ControllerStatus {
+    let now_ms = monotonic_ms();
+    let timezone = state.timezone.lock().await.clone();
+    let next_schedule = {
+        let schedule = state.schedule.lock().await;
+        now_in_timezone(&timezone).and_then(|now| schedule.next_event_epoch(now))
+    };
+    let schedule_enabled = state.schedule.lock().await.enabled;
+    let time_synced = state.time_synced.load(Ordering::Relaxed);
+
+    let mut status = {
+        let engine = state.engine.lock().await;
+        engine.status(
+            now_ms,
+            schedule_enabled,
+            next_schedule,
+            time_synced,
+            &timezone,
+        )
+    };
+
+    let file_config = state.file_config.lock().await;
+    status.config_version = file_config.version;
+    status.config_path = (file_config.version > 0)
+        .then(|| state.store.file_config_path().display().to_string());
+    status
+}
+
+#[derive(Debug, Deserialize)]
+struct CommandEnvelope {
+    id: Option<String>,
+    value: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+struct CommandResponse {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    applied: Option<serde_json::Value>,
+}
+
+/// Accepts either `{ "id": "...", "value": ... }` or a bare scalar payload
+/// (for backward compatibility with pre-existing fire-and-forget commands).
+fn parse_command_payload(message: &str) -> (Option<String>, String) {
+    if let Ok(envelope) = serde_json::from_str::<CommandEnvelope>(message) {
+        let value = match envelope.value {
+            serde_json::Value::String(s) => s,
+            other => other.to_string(),
+        };
+        (envelope.id, value)
+    } else {
+        (None, message.to_string())
+    }
+}
+
+/// Payload accepted on `TOPIC_CMD_OVERRIDE`: `untilEpoch` takes precedence
+/// if present, otherwise `durationMinutes` is resolved against wall-clock
+/// time when the command is received.
+#[derive(Debug, Deserialize)]
+struct ScheduleOverrideCommand {
+    mode: ThermostatMode,
+    #[serde(rename = "targetTemp")]
+    target_temp_f: f32,
+    #[serde(rename = "untilEpoch")]
+    until_epoch: Option<i64>,
+    #[serde(rename = "durationMinutes")]
+    duration_minutes: Option<i64>,
+}
+
+fn parse_schedule_override(value: &str) -> Result<ScheduleOverride, String> {
+    let command = serde_json::from_str::<ScheduleOverrideCommand>(value)
+        .map_err(|err| format!("invalid override: {err}"))?;
+
+    let until_epoch = match (command.until_epoch, command.duration_minutes) {
+        (Some(epoch), _) => epoch,
+        (None, Some(minutes)) if minutes > 0 => Utc::now().timestamp() + minutes * 60,
+        _ => return Err("expected 'untilEpoch' or a positive 'durationMinutes'".to_string()),
+    };
+
+    if until_epoch <= Utc::now().timestamp() {
+        return Err("override expiry must be in the future".to_string());
+    }
+
+    Ok(ScheduleOverride {
+        mode: command.mode,
+        target_temp_f: command.target_temp_f,
+        until_epoch,
+    })
+}
+
+async fn publish_command_response(
+    app_state: &AppState,
+    id: &str,
+    ok: bool,
+    error: Option<String>,
+    applied: Option<serde_json::Value>,
+) {
+    let response = CommandResponse { ok, error, applied };
+    match serde_json::to_vec(&response) {
+        Ok(payload) => {
+            let topic = format!("thermostat/controller/response/{id}");
+            let mqtt = app_state.mqtt.lock().await.clone();
+            if let Err(err) = mqtt.publish(topic, QoS::AtLeastOnce, false, payload).await {
+                warn!("failed to publish command response: {err:#}");
+            }
+        }
+        Err(err) => warn!("failed to encode command response: {err:#}"),
     }
 }
 
@@ -482,70 +1599,194 @@ async fn handle_mqtt_message(
             }
         }
         TOPIC_CMD_POWER => {
-            let lower = message.to_ascii_lowercase();
-            let actions = {
+            let (id, value) = parse_command_payload(&message);
+            let lower = value.to_ascii_lowercase();
+            let (ok, actions) = {
                 let mut engine = app_state.engine.lock().await;
                 if lower == "on" {
-                    engine.manual_on(now_ms)
+                    (true, engine.manual_on(now_ms))
                 } else if lower == "off" {
-                    engine.manual_off(now_ms)
+                    (true, engine.manual_off(now_ms))
                 } else {
-                    Vec::new()
+                    (false, Vec::new())
                 }
             };
-            execute_engine_actions(actions).await;
+            execute_engine_actions(app_state, actions).await;
+            if let Some(id) = id {
+                let error = (!ok).then(|| "expected 'on' or 'off'".to_string());
+                let applied = ok.then(|| serde_json::Value::String(lower));
+                publish_command_response(app_state, &id, ok, error, applied).await;
+            }
         }
         TOPIC_CMD_TARGET => {
-            if let Ok(target) = message.parse::<f32>() {
-                let changed = {
-                    let mut engine = app_state.engine.lock().await;
-                    engine.set_target_temp(target)
-                };
-                if changed {
-                    persist_runtime_from_state(app_state).await?;
+            let (id, value) = parse_command_payload(&message);
+            let parsed = value.parse::<f32>();
+            let (ok, error, applied) = match parsed {
+                Ok(target) => {
+                    let changed = {
+                        let mut engine = app_state.engine.lock().await;
+                        engine.set_target_temp(target)
+                    };
+                    if changed {
+                        persist_runtime_from_state(app_state).await?;
+                    }
+                    let applied = { app_state.engine.lock().await.settings().target_temp_f };
+                    (true, None, Some(serde_json::json!(applied)))
                 }
+                Err(_) => (false, Some("invalid temperature value".to_string()), None),
+            };
+            if let Some(id) = id {
+                publish_command_response(app_state, &id, ok, error, applied).await;
             }
         }
         TOPIC_CMD_MODE => {
-            let upper = message.to_ascii_uppercase();
-            let (changed, actions) = {
+            let (id, value) = parse_command_payload(&message);
+            let upper = value.to_ascii_uppercase();
+            let (ok, changed, actions) = {
                 let mut engine = app_state.engine.lock().await;
                 if upper == "HEAT" {
-                    engine.set_mode_with_actions(ThermostatMode::Heat, now_ms)
+                    let (changed, actions) =
+                        engine.set_mode_with_actions(ThermostatMode::Heat, now_ms);
+                    (true, changed, actions)
+                } else if upper == "COOL" {
+                    let (changed, actions) =
+                        engine.set_mode_with_actions(ThermostatMode::Cool, now_ms);
+                    (true, changed, actions)
+                } else if upper == "AUTO" {
+                    let (changed, actions) =
+                        engine.set_mode_with_actions(ThermostatMode::Auto, now_ms);
+                    (true, changed, actions)
                 } else if upper == "OFF" {
-                    engine.set_mode_with_actions(ThermostatMode::Off, now_ms)
+                    let (changed, actions) =
+                        engine.set_mode_with_actions(ThermostatMode::Off, now_ms);
+                    (true, changed, actions)
                 } else {
-                    (false, Vec::new())
+                    (false, false, Vec::new())
                 }
             };
             if !actions.is_empty() {
-                execute_engine_actions(actions).await;
+                execute_engine_actions(app_state, actions).await;
             }
             if changed {
                 persist_runtime_from_state(app_state).await?;
             }
+            if let Some(id) = id {
+                let error = (!ok).then(|| "expected 'HEAT', 'COOL', 'AUTO', or 'OFF'".to_string());
+                let applied = ok.then(|| serde_json::Value::String(upper));
+                publish_command_response(app_state, &id, ok, error, applied).await;
+            }
         }
         TOPIC_CMD_HOLD => {
-            let lower = message.to_ascii_lowercase();
-            let mut engine = app_state.engine.lock().await;
-            if lower == "on" || lower == "enter" {
-                engine.enter_hold(None, now_ms);
-            } else if lower == "off" || lower == "exit" {
-                engine.exit_hold();
-            } else if let Ok(minutes) = lower.parse::<u64>() {
-                if minutes > 0 && minutes <= engine.config.max_hold_minutes as u64 {
-                    engine.enter_hold(Some(minutes * 60_000), now_ms);
+            let (id, value) = parse_command_payload(&message);
+            let lower = value.to_ascii_lowercase();
+            let (ok, error) = {
+                let mut engine = app_state.engine.lock().await;
+                if lower == "on" || lower == "enter" {
+                    engine.enter_hold(None, now_ms);
+                    (true, None)
+                } else if lower == "off" || lower == "exit" {
+                    engine.exit_hold();
+                    (true, None)
+                } else if let Ok(minutes) = lower.parse::<u64>() {
+                    if minutes > 0 && minutes <= engine.config.max_hold_minutes as u64 {
+                        engine.enter_hold(Some(minutes * 60_000), now_ms);
+                        (true, None)
+                    } else {
+                        (false, Some("hold minutes out of range".to_string()))
+                    }
+                } else {
+                    (false, Some("expected 'on', 'off', or minutes".to_string()))
                 }
+            };
+            if let Some(id) = id {
+                let applied = ok.then(|| serde_json::Value::String(lower));
+                publish_command_response(app_state, &id, ok, error, applied).await;
+            }
+        }
+        TOPIC_CMD_TELEMETRY_ONESHOT => {
+            let (id, _) = parse_command_payload(&message);
+            app_state.telemetry_oneshot.notify_one();
+            if let Some(id) = id {
+                publish_command_response(app_state, &id, true, None, None).await;
             }
         }
         TOPIC_CMD_SCHEDULE => {
-            if let Ok(mut schedule) = serde_json::from_str::<Schedule>(&message) {
-                schedule.normalize();
-                {
+            let (id, value) = parse_command_payload(&message);
+            let parsed = serde_json::from_str::<Schedule>(&value);
+            let (ok, error) = match parsed {
+                Ok(mut schedule) => {
+                    schedule.normalize();
+                    {
+                        let mut active = app_state.schedule.lock().await;
+                        *active = schedule.clone();
+                    }
+                    app_state.store.save_schedule(&schedule).await?;
+                    (true, None)
+                }
+                Err(err) => (false, Some(format!("invalid schedule: {err}"))),
+            };
+            if let Some(id) = id {
+                publish_command_response(app_state, &id, ok, error, None).await;
+            }
+        }
+        TOPIC_CMD_OVERRIDE => {
+            let (id, value) = parse_command_payload(&message);
+            let lower = value.trim().to_ascii_lowercase();
+            let (ok, error) = if lower == "off" || lower == "cancel" || lower == "clear" {
+                let schedule = {
                     let mut active = app_state.schedule.lock().await;
-                    *active = schedule.clone();
+                    active.override_action = None;
+                    active.clone()
+                };
+                app_state.store.save_schedule(&schedule).await?;
+                (true, None)
+            } else {
+                match parse_schedule_override(&value) {
+                    Ok(over) => {
+                        let schedule = {
+                            let mut active = app_state.schedule.lock().await;
+                            active.override_action = Some(over);
+                            active.clone()
+                        };
+                        app_state.store.save_schedule(&schedule).await?;
+                        (true, None)
+                    }
+                    Err(err) => (false, Some(err)),
                 }
+            };
+            if let Some(id) = id {
+                publish_command_response(app_state, &id, ok, error, None).await;
+            }
+        }
+        TOPIC_CMD_DATE_EXCEPTIONS => {
+            let (id, value) = parse_command_payload(&message);
+            let lower = value.trim().to_ascii_lowercase();
+            let (ok, error) = if lower == "off" || lower == "cancel" || lower == "clear" {
+                let schedule = {
+                    let mut active = app_state.schedule.lock().await;
+                    active.date_exceptions.clear();
+                    active.clone()
+                };
                 app_state.store.save_schedule(&schedule).await?;
+                (true, None)
+            } else {
+                match serde_json::from_str::<Vec<DateException>>(&value) {
+                    Ok(mut exceptions) => {
+                        let schedule = {
+                            let mut active = app_state.schedule.lock().await;
+                            active.date_exceptions.clear();
+                            active.date_exceptions.append(&mut exceptions);
+                            active.normalize();
+                            active.clone()
+                        };
+                        app_state.store.save_schedule(&schedule).await?;
+                        (true, None)
+                    }
+                    Err(err) => (false, Some(format!("invalid date exceptions: {err}"))),
+                }
+            };
+            if let Some(id) = id {
+                publish_command_response(app_state, &id, ok, error, None).await;
             }
         }
         _ => {}
@@ -555,29 +1796,59 @@ async fn handle_mqtt_message(
 }
 
 async fn handle_get_status(State(state): State<AppState>) -> impl IntoResponse {
-    let now_ms = monotonic_ms();
-    let timezone = state.timezone.lock().await.clone();
+    Json(build_status(&state).await)
+}
 
-    let next_schedule = {
-        let schedule = state.schedule.lock().await;
-        now_in_timezone(&timezone).and_then(|now| schedule.next_event_epoch(now))
-    };
+async fn handle_get_settings_summary(State(state): State<AppState>) -> impl IntoResponse {
+    let summary = state.engine.lock().await.settings_summary();
+    Json(summary)
+}
 
-    let schedule_enabled = state.schedule.lock().await.enabled;
-    let time_synced = state.time_synced.load(Ordering::Relaxed);
+async fn handle_ws(
+    State(state): State<AppState>,
+    ws: axum::extract::ws::WebSocketUpgrade,
+) -> impl IntoResponse {
+    let mut status_rx = state.status_tx.subscribe();
+    ws.on_upgrade(move |socket| async move {
+        ws_status_stream(socket, state, &mut status_rx).await;
+    })
+}
 
-    let status = {
-        let engine = state.engine.lock().await;
-        engine.status(
-            now_ms,
-            schedule_enabled,
-            next_schedule,
-            time_synced,
-            &timezone,
-        )
-    };
+async fn ws_status_stream(
+    mut socket: axum::extract::ws::WebSocket,
+    state: AppState,
+    status_rx: &mut tokio::sync::broadcast::Receiver<ControllerStatus>,
+) {
+    let initial = build_status(&state).await;
+
+    if send_status_frame(&mut socket, &initial).await.is_err() {
+        return;
+    }
+
+    loop {
+        match status_rx.recv().await {
+            Ok(status) => {
+                if send_status_frame(&mut socket, &status).await.is_err() {
+                    return;
+                }
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("websocket status receiver lagged, dropped {skipped} updates");
+                continue;
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
+
+async fn send_status_frame(
+    socket: &mut axum::extract::ws::WebSocket,
+    status: &ControllerStatus,
+) -> Result<(), axum::Error> {
+    use axum::extract::ws::Message;
 
-    Json(status)
+    let payload = serde_json::to_string(status).unwrap_or_default();
+    socket.send(Message::Text(payload.into())).await
 }
 
 async fn handle_set_target(
@@ -609,6 +1880,39 @@ async fn handle_set_target(
     handle_get_status(State(state)).await.into_response()
 }
 
+/// `POST /api/auto_cool_target?value=<f32>` - sets the cooling setpoint
+/// `ThermostatMode::Auto` uses, independent of `target_temp_f`'s heating
+/// setpoint. Has no effect on `Heat`/`Cool`, which both still run off
+/// `target_temp_f` alone.
+async fn handle_set_auto_cool_target(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let Some(value) = params.get("value") else {
+        return error_response(StatusCode::BAD_REQUEST, "Missing 'value' parameter");
+    };
+    let Ok(target) = value.parse::<f32>() else {
+        return error_response(StatusCode::BAD_REQUEST, "Invalid temperature value");
+    };
+
+    let changed = {
+        let mut engine = state.engine.lock().await;
+        engine.set_auto_cool_setpoint(target)
+    };
+
+    if changed {
+        if let Err(err) = persist_runtime_from_state(&state).await {
+            warn!("failed to persist auto cool target update: {err:#}");
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to persist runtime settings",
+            );
+        }
+    }
+
+    handle_get_status(State(state)).await.into_response()
+}
+
 async fn handle_set_mode(
     State(state): State<AppState>,
     Query(params): Query<HashMap<String, String>>,
@@ -619,8 +1923,15 @@ async fn handle_set_mode(
 
     let mode = match value.to_ascii_uppercase().as_str() {
         "HEAT" => ThermostatMode::Heat,
+        "COOL" => ThermostatMode::Cool,
+        "AUTO" => ThermostatMode::Auto,
         "OFF" => ThermostatMode::Off,
-        _ => return error_response(StatusCode::BAD_REQUEST, "Invalid mode. Use 'HEAT' or 'OFF'"),
+        _ => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "Invalid mode. Use 'HEAT', 'COOL', 'AUTO', or 'OFF'",
+            )
+        }
     };
 
     let now_ms = monotonic_ms();
@@ -629,7 +1940,7 @@ async fn handle_set_mode(
         engine.set_mode_with_actions(mode, now_ms)
     };
     if !actions.is_empty() {
-        execute_engine_actions(actions).await;
+        execute_engine_actions(&state, actions).await;
     }
 
     if changed {
@@ -645,6 +1956,193 @@ async fn handle_set_mode(
     handle_get_status(State(state)).await.into_response()
 }
 
+/// Which unit a `/tstat` request/response is in. Everything internal
+/// (`PersistedSettings`, the engine, schedules) is stored in Fahrenheit, so
+/// this only ever affects what crosses the wire; conversion itself is
+/// delegated to the shared `Temperature` newtype rather than a bare-float
+/// helper local to this endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TstatUnit {
+    Fahrenheit,
+    Celsius,
+}
+
+impl TstatUnit {
+    fn from_query(params: &HashMap<String, String>) -> Self {
+        match params.get("unit").map(|value| value.to_ascii_uppercase()) {
+            Some(value) if value == "C" => Self::Celsius,
+            _ => Self::Fahrenheit,
+        }
+    }
+
+    fn from_temp_f(self, temp_f: f32) -> f32 {
+        let reading = Temperature::from_fahrenheit(temp_f);
+        match self {
+            Self::Fahrenheit => reading.as_fahrenheit(),
+            Self::Celsius => reading.as_celsius(),
+        }
+    }
+
+    fn to_temp_f(self, value: f32) -> f32 {
+        match self {
+            Self::Fahrenheit => Temperature::from_fahrenheit(value).as_fahrenheit(),
+            Self::Celsius => Temperature::from_celsius(value).as_fahrenheit(),
+        }
+    }
+}
+
+fn tmode_to_thermostat_mode(tmode: u8) -> Option<ThermostatMode> {
+    match tmode {
+        0 => Some(ThermostatMode::Off),
+        1 => Some(ThermostatMode::Heat),
+        2 => Some(ThermostatMode::Cool),
+        3 => Some(ThermostatMode::Auto),
+        _ => None,
+    }
+}
+
+fn thermostat_mode_to_tmode(mode: ThermostatMode) -> u8 {
+    match mode {
+        ThermostatMode::Off => 0,
+        ThermostatMode::Heat => 1,
+        ThermostatMode::Cool => 2,
+        ThermostatMode::Auto => 3,
+    }
+}
+
+/// Wire shape of the classic Radio Thermostat (CT50/CT80) `/tstat`
+/// endpoint, kept in its native snake_case field names rather than this
+/// API's usual camelCase since it's a compatibility shim for existing
+/// home-automation clients, not part of our own API surface. `t_heat`/
+/// `t_cool` mirror whichever of them apply to the current `tmode`
+/// (`Auto` reports both: `t_heat` is `target_temp_f` and `t_cool` is
+/// `auto_cool_setpoint_f`); `fmode` always reports 0 because this
+/// unit has no fan output to report.
+#[derive(Debug, Serialize)]
+struct TstatResponse {
+    temp: f32,
+    tmode: u8,
+    fmode: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    t_heat: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    t_cool: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TstatUpdateRequest {
+    #[serde(default)]
+    tmode: Option<u8>,
+    #[serde(default)]
+    t_heat: Option<f32>,
+    #[serde(default)]
+    t_cool: Option<f32>,
+    #[serde(default)]
+    fmode: Option<u8>,
+}
+
+/// `GET /tstat` - a Radio-Thermostat-compatible read of current temp,
+/// setpoint, and mode, for home-automation controllers that already speak
+/// the classic CT50/CT80 JSON API instead of MQTT. Pass `?unit=C` for
+/// Celsius; Fahrenheit otherwise.
+async fn handle_get_tstat(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let unit = TstatUnit::from_query(&params);
+
+    let engine = state.engine.lock().await;
+    let mode = engine.settings().mode;
+    let target_f = engine.settings().target_temp_f;
+    let temp_f = engine.current_temp_f();
+
+    let (t_heat, t_cool) = match mode {
+        ThermostatMode::Heat => (Some(unit.from_temp_f(target_f)), None),
+        ThermostatMode::Cool => (None, Some(unit.from_temp_f(target_f))),
+        ThermostatMode::Auto => (
+            Some(unit.from_temp_f(target_f)),
+            Some(unit.from_temp_f(engine.settings().auto_cool_setpoint_f)),
+        ),
+        ThermostatMode::Off => (None, None),
+    };
+
+    Json(TstatResponse {
+        temp: unit.from_temp_f(temp_f),
+        tmode: thermostat_mode_to_tmode(mode),
+        fmode: 0,
+        t_heat,
+        t_cool,
+    })
+}
+
+/// `POST /tstat` - accepts the same `tmode`/`t_heat`/`t_cool`/`fmode` fields
+/// the classic Radio Thermostat API does, and integrates them through the
+/// same `ThermostatEngine` mutation and persistence path every other
+/// `/api/*` setter uses, so a tstat-driven override is indistinguishable
+/// from one made through the web UI or MQTT. `t_cool` sets
+/// `auto_cool_setpoint_f` while the mode is `Auto` and `target_temp_f`
+/// otherwise. `fmode` is accepted but ignored; this unit has no fan to drive.
+async fn handle_post_tstat(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+    Json(update): Json<TstatUpdateRequest>,
+) -> impl IntoResponse {
+    let unit = TstatUnit::from_query(&params);
+
+    if let Some(fmode) = update.fmode {
+        if fmode != 0 {
+            warn!("tstat fmode={fmode} requested but this unit has no fan output; ignoring");
+        }
+    }
+
+    let mut changed = false;
+
+    if let Some(tmode) = update.tmode {
+        let Some(mode) = tmode_to_thermostat_mode(tmode) else {
+            return error_response(StatusCode::BAD_REQUEST, "Invalid tmode; use 0-3");
+        };
+        let now_ms = monotonic_ms();
+        let actions = {
+            let mut engine = state.engine.lock().await;
+            let (mode_changed, actions) = engine.set_mode_with_actions(mode, now_ms);
+            changed |= mode_changed;
+            actions
+        };
+        if !actions.is_empty() {
+            execute_engine_actions(&state, actions).await;
+        }
+    }
+
+    if let Some(t_heat) = update.t_heat {
+        let mut engine = state.engine.lock().await;
+        changed |= engine.set_target_temp(unit.to_temp_f(t_heat));
+    }
+
+    if let Some(t_cool) = update.t_cool {
+        let mut engine = state.engine.lock().await;
+        let t_cool_f = unit.to_temp_f(t_cool);
+        changed |= if engine.settings().mode == ThermostatMode::Auto {
+            engine.set_auto_cool_setpoint(t_cool_f)
+        } else {
+            engine.set_target_temp(t_cool_f)
+        };
+    }
+
+    if changed {
+        if let Err(err) = persist_runtime_from_state(&state).await {
+            warn!("failed to persist tstat update: {err:#}");
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to persist runtime settings",
+            );
+        }
+    }
+
+    handle_get_tstat(State(state), Query(params))
+        .await
+        .into_response()
+}
+
 async fn handle_set_hysteresis(
     State(state): State<AppState>,
     Query(params): Query<HashMap<String, String>>,
@@ -684,6 +2182,171 @@ async fn handle_set_hysteresis(
     handle_get_status(State(state)).await.into_response()
 }
 
+async fn handle_set_humidity_target(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let Some(value) = params.get("value") else {
+        return error_response(StatusCode::BAD_REQUEST, "Missing 'value' parameter");
+    };
+
+    // An empty value disables humidity-aware control.
+    let target = if value.is_empty() {
+        None
+    } else {
+        let Ok(parsed) = value.parse::<f32>() else {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "Invalid humidity target value (20-80, or empty to disable)",
+            );
+        };
+        if !(20.0..=80.0).contains(&parsed) {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "Invalid humidity target value (20-80, or empty to disable)",
+            );
+        }
+        Some(parsed)
+    };
+
+    let changed = {
+        let mut engine = state.engine.lock().await;
+        engine.set_humidity_target(target)
+    };
+
+    if changed {
+        if let Err(err) = persist_runtime_from_state(&state).await {
+            warn!("failed to persist humidity target update: {err:#}");
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to persist runtime settings",
+            );
+        }
+    }
+
+    handle_get_status(State(state)).await.into_response()
+}
+
+async fn handle_set_humidity_hysteresis(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let Some(value) = params.get("value") else {
+        return error_response(StatusCode::BAD_REQUEST, "Missing 'value' parameter");
+    };
+    let Ok(humidity_hysteresis) = value.parse::<f32>() else {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "Invalid humidity hysteresis value (1.0-20.0)",
+        );
+    };
+
+    if !(1.0..=20.0).contains(&humidity_hysteresis) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "Invalid humidity hysteresis value (1.0-20.0)",
+        );
+    }
+
+    let changed = {
+        let mut engine = state.engine.lock().await;
+        engine.set_humidity_hysteresis(humidity_hysteresis)
+    };
+
+    if changed {
+        if let Err(err) = persist_runtime_from_state(&state).await {
+            warn!("failed to persist humidity hysteresis update: {err:#}");
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to persist runtime settings",
+            );
+        }
+    }
+
+    handle_get_status(State(state)).await.into_response()
+}
+
+async fn handle_set_control_strategy(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let Some(value) = params.get("value") else {
+        return error_response(StatusCode::BAD_REQUEST, "Missing 'value' parameter");
+    };
+
+    let strategy = match value.to_ascii_uppercase().as_str() {
+        "HYSTERESIS" => ControlStrategy::Hysteresis,
+        "PID" => ControlStrategy::Pid,
+        "SETPOINT_PID" => ControlStrategy::SetpointPid,
+        "TIME_PROPORTIONAL" => ControlStrategy::TimeProportional,
+        _ => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "Invalid control strategy. Use 'HYSTERESIS', 'PID', 'SETPOINT_PID', or 'TIME_PROPORTIONAL'",
+            )
+        }
+    };
+
+    let changed = {
+        let mut engine = state.engine.lock().await;
+        engine.set_control_strategy(strategy)
+    };
+
+    if changed {
+        if let Err(err) = persist_runtime_from_state(&state).await {
+            warn!("failed to persist control strategy update: {err:#}");
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to persist runtime settings",
+            );
+        }
+    }
+
+    handle_get_status(State(state)).await.into_response()
+}
+
+/// Sets the unit outward-facing readings/logs are additionally rendered in.
+/// Purely a display preference: `target_temp`, `currentTemp`, and every
+/// other `*Temp` field in `ControllerStatus` stay Fahrenheit no matter what
+/// this is set to (see `thermostat_common::Temperature`).
+async fn handle_set_display_unit(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let Some(value) = params.get("value") else {
+        return error_response(StatusCode::BAD_REQUEST, "Missing 'value' parameter");
+    };
+
+    let unit = match value.to_ascii_uppercase().as_str() {
+        "CELSIUS" => TemperatureUnit::Celsius,
+        "FAHRENHEIT" => TemperatureUnit::Fahrenheit,
+        "KELVIN" => TemperatureUnit::Kelvin,
+        _ => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "Invalid display unit. Use 'CELSIUS', 'FAHRENHEIT', or 'KELVIN'",
+            )
+        }
+    };
+
+    let changed = {
+        let mut engine = state.engine.lock().await;
+        engine.set_display_unit(unit)
+    };
+
+    if changed {
+        if let Err(err) = persist_runtime_from_state(&state).await {
+            warn!("failed to persist display unit update: {err:#}");
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to persist runtime settings",
+            );
+        }
+    }
+
+    handle_get_status(State(state)).await.into_response()
+}
+
 async fn handle_set_offset(
     State(state): State<AppState>,
     Query(params): Query<HashMap<String, String>>,
@@ -728,7 +2391,7 @@ async fn handle_ir_on(State(state): State<AppState>) -> impl IntoResponse {
         let mut engine = state.engine.lock().await;
         engine.manual_on(monotonic_ms())
     };
-    execute_engine_actions(actions).await;
+    execute_engine_actions(&state, actions).await;
     handle_get_status(State(state)).await.into_response()
 }
 
@@ -737,7 +2400,7 @@ async fn handle_ir_off(State(state): State<AppState>) -> impl IntoResponse {
         let mut engine = state.engine.lock().await;
         engine.manual_off(monotonic_ms())
     };
-    execute_engine_actions(actions).await;
+    execute_engine_actions(&state, actions).await;
     handle_get_status(State(state)).await.into_response()
 }
 
@@ -746,7 +2409,7 @@ async fn handle_ir_heat_on(State(state): State<AppState>) -> impl IntoResponse {
         let mut engine = state.engine.lock().await;
         engine.manual_heat_on(monotonic_ms())
     };
-    execute_engine_actions(actions).await;
+    execute_engine_actions(&state, actions).await;
     handle_get_status(State(state)).await.into_response()
 }
 
@@ -755,7 +2418,7 @@ async fn handle_ir_heat_off(State(state): State<AppState>) -> impl IntoResponse
         let mut engine = state.engine.lock().await;
         engine.manual_heat_off(monotonic_ms())
     };
-    execute_engine_actions(actions).await;
+    execute_engine_actions(&state, actions).await;
     handle_get_status(State(state)).await.into_response()
 }
 
@@ -764,7 +2427,7 @@ async fn handle_ir_heat_up(State(state): State<AppState>) -> impl IntoResponse {
         let mut engine = state.engine.lock().await;
         engine.manual_heat_up()
     };
-    execute_engine_actions(actions).await;
+    execute_engine_actions(&state, actions).await;
     handle_get_status(State(state)).await.into_response()
 }
 
@@ -773,7 +2436,7 @@ async fn handle_ir_heat_down(State(state): State<AppState>) -> impl IntoResponse
         let mut engine = state.engine.lock().await;
         engine.manual_heat_down()
     };
-    execute_engine_actions(actions).await;
+    execute_engine_actions(&state, actions).await;
     handle_get_status(State(state)).await.into_response()
 }
 
@@ -782,7 +2445,7 @@ async fn handle_ir_light_toggle(State(state): State<AppState>) -> impl IntoRespo
         let mut engine = state.engine.lock().await;
         engine.manual_light_toggle()
     };
-    execute_engine_actions(actions).await;
+    execute_engine_actions(&state, actions).await;
     handle_get_status(State(state)).await.into_response()
 }
 
@@ -791,7 +2454,7 @@ async fn handle_ir_timer_toggle(State(state): State<AppState>) -> impl IntoRespo
         let mut engine = state.engine.lock().await;
         engine.manual_timer_toggle()
     };
-    execute_engine_actions(actions).await;
+    execute_engine_actions(&state, actions).await;
     handle_get_status(State(state)).await.into_response()
 }
 
@@ -818,37 +2481,202 @@ async fn handle_hold_exit(State(state): State<AppState>) -> impl IntoResponse {
         let mut engine = state.engine.lock().await;
         engine.exit_hold();
     }
-    handle_get_status(State(state)).await.into_response()
+    handle_get_status(State(state)).await.into_response()
+}
+
+async fn handle_safety_reset(State(state): State<AppState>) -> impl IntoResponse {
+    {
+        let mut engine = state.engine.lock().await;
+        engine.reset_safety(monotonic_ms());
+    }
+    handle_get_status(State(state)).await.into_response()
+}
+
+/// Clears a latched over/under-temperature or setpoint-deviation alarm, but
+/// only if `ThermostatEngine::clear_alarm` finds the reading is actually
+/// back in the safe range - unlike `/api/safety/reset`, a still-tripped
+/// alarm can't be dismissed away.
+async fn handle_alarm_clear(State(state): State<AppState>) -> impl IntoResponse {
+    {
+        let mut engine = state.engine.lock().await;
+        engine.clear_alarm(monotonic_ms());
+    }
+    handle_get_status(State(state)).await.into_response()
+}
+
+async fn handle_get_profiles(State(state): State<AppState>) -> impl IntoResponse {
+    let profiles = state.engine.lock().await.list_profiles().to_vec();
+    Json(profiles)
+}
+
+async fn handle_post_save_profile(
+    State(state): State<AppState>,
+    Json(request): Json<SaveProfileRequest>,
+) -> impl IntoResponse {
+    if request.name.trim().is_empty() {
+        return error_response(StatusCode::BAD_REQUEST, "Missing 'name'");
+    }
+
+    let mut engine = state.engine.lock().await;
+    engine.save_profile(request.name);
+    Json(engine.list_profiles().to_vec()).into_response()
+}
+
+async fn handle_post_apply_profile(
+    State(state): State<AppState>,
+    Path(id): Path<u32>,
+) -> impl IntoResponse {
+    let actions = {
+        let mut engine = state.engine.lock().await;
+        let (_, actions) = engine.apply_profile(id, monotonic_ms());
+        actions
+    };
+    execute_engine_actions(&state, actions).await;
+
+    if let Err(err) = persist_runtime_from_state(&state).await {
+        warn!("failed to persist applied profile: {err:#}");
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to persist runtime settings",
+        );
+    }
+
+    handle_get_status(State(state)).await.into_response()
+}
+
+async fn handle_delete_profile(
+    State(state): State<AppState>,
+    Path(id): Path<u32>,
+) -> impl IntoResponse {
+    let removed = state.engine.lock().await.delete_profile(id);
+    if !removed {
+        return error_response(StatusCode::NOT_FOUND, "Profile not found");
+    }
+
+    handle_get_profiles(State(state)).await.into_response()
+}
+
+async fn handle_get_schedule(State(state): State<AppState>) -> impl IntoResponse {
+    let schedule = state.schedule.lock().await.clone();
+    Json(schedule)
+}
+
+async fn handle_put_schedule(
+    State(state): State<AppState>,
+    Json(mut schedule): Json<Schedule>,
+) -> impl IntoResponse {
+    schedule.normalize();
+    {
+        let mut active = state.schedule.lock().await;
+        *active = schedule.clone();
+    }
+
+    if let Err(err) = state.store.save_schedule(&schedule).await {
+        warn!("failed to persist schedule update: {err:#}");
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to persist schedule",
+        );
+    }
+
+    handle_get_schedule(State(state)).await.into_response()
+}
+
+async fn handle_delete_schedule(State(state): State<AppState>) -> impl IntoResponse {
+    if let Err(err) = state.store.delete_schedule().await {
+        warn!("failed to persist schedule deletion: {err:#}");
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to delete schedule",
+        );
+    }
+
+    {
+        let mut active = state.schedule.lock().await;
+        *active = Schedule::default();
+    }
+
+    handle_get_schedule(State(state)).await.into_response()
 }
 
-async fn handle_safety_reset(State(state): State<AppState>) -> impl IntoResponse {
-    {
-        let mut engine = state.engine.lock().await;
-        engine.reset_safety();
+async fn handle_get_schedule_history(State(state): State<AppState>) -> impl IntoResponse {
+    match state.store.load_schedule_history().await {
+        Ok(history) => {
+            let view: Vec<ScheduleHistoryListEntry> = history.iter().map(Into::into).collect();
+            Json(view).into_response()
+        }
+        Err(err) => {
+            warn!("failed to load schedule history: {err:#}");
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to load schedule history",
+            )
+        }
     }
-    handle_get_status(State(state)).await.into_response()
 }
 
-async fn handle_get_schedule(State(state): State<AppState>) -> impl IntoResponse {
-    let schedule = state.schedule.lock().await.clone();
-    Json(schedule)
+async fn handle_get_schedule_history_entry(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> impl IntoResponse {
+    let history = match state.store.load_schedule_history().await {
+        Ok(history) => history,
+        Err(err) => {
+            warn!("failed to load schedule history: {err:#}");
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to load schedule history",
+            );
+        }
+    };
+
+    match history.into_iter().find(|entry| entry.id == id) {
+        Some(entry) => match entry.schedule {
+            Some(schedule) => Json(schedule).into_response(),
+            None => error_response(StatusCode::GONE, "Schedule version was deleted"),
+        },
+        None => error_response(StatusCode::NOT_FOUND, "Schedule version not found"),
+    }
 }
 
-async fn handle_put_schedule(
+async fn handle_post_schedule_rollback(
     State(state): State<AppState>,
-    Json(mut schedule): Json<Schedule>,
+    Path(id): Path<u64>,
 ) -> impl IntoResponse {
+    let history = match state.store.load_schedule_history().await {
+        Ok(history) => history,
+        Err(err) => {
+            warn!("failed to load schedule history: {err:#}");
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to load schedule history",
+            );
+        }
+    };
+
+    let mut schedule = match history.into_iter().find(|entry| entry.id == id) {
+        Some(entry) => match entry.schedule {
+            Some(schedule) => schedule,
+            None => return error_response(StatusCode::GONE, "Schedule version was deleted"),
+        },
+        None => return error_response(StatusCode::NOT_FOUND, "Schedule version not found"),
+    };
     schedule.normalize();
+
     {
         let mut active = state.schedule.lock().await;
         *active = schedule.clone();
     }
 
-    if let Err(err) = state.store.save_schedule(&schedule).await {
-        warn!("failed to persist schedule update: {err:#}");
+    if let Err(err) = state
+        .store
+        .save_schedule_with_note(&schedule, &format!("rollback to version {id}"))
+        .await
+    {
+        warn!("failed to persist schedule rollback: {err:#}");
         return error_response(
             StatusCode::INTERNAL_SERVER_ERROR,
-            "Failed to persist schedule",
+            "Failed to persist schedule rollback",
         );
     }
 
@@ -962,6 +2790,10 @@ async fn handle_put_network(
         );
     }
 
+    if mqtt_credentials_changed(&previous, &runtime.network) {
+        reload_mqtt_client(&state, &runtime.network).await;
+    }
+
     let payload = NetworkUpdateResponse {
         restart_required: network_restart_required(&previous, &runtime.network),
         network: build_network_config_view(&runtime.network),
@@ -1002,6 +2834,8 @@ async fn handle_put_ir_config(
     runtime.ir.tx_pin = update.tx_pin;
     runtime.ir.rmt_channel = update.rmt_channel;
     runtime.ir.carrier_khz = update.carrier_khz;
+    runtime.ir.protocol = update.protocol;
+    runtime.ir.rx_pin = update.rx_pin;
     runtime.ir.sanitize();
 
     if let Err(err) = state.store.save_runtime_config(&runtime).await {
@@ -1031,30 +2865,321 @@ async fn handle_get_ir_diagnostics(State(state): State<AppState>) -> impl IntoRe
 
     let payload = IrDiagnosticsView {
         enabled: false,
+        protocol: runtime.ir.protocol,
         tx_pin: runtime.ir.tx_pin,
         rmt_channel: runtime.ir.rmt_channel,
         carrier_khz: runtime.ir.carrier_khz,
         sent_frames: 0,
         failed_actions: 0,
         last_error: Some("IR transmission is only available in ESP32 builds".to_string()),
+        command_stats: Vec::new(),
     };
     Json(payload)
 }
 
-async fn handle_get_ota_status() -> impl IntoResponse {
+async fn handle_post_ir_learn_start(
+    State(_state): State<AppState>,
+    Json(_request): Json<IrLearnStartRequest>,
+) -> impl IntoResponse {
+    Json(IrLearnStartResponse { accepted: false })
+}
+
+async fn handle_get_ir_learn_result() -> impl IntoResponse {
+    Json(IrLearnResultView {
+        status: "FAILED",
+        timings: None,
+    })
+}
+
+async fn handle_get_telemetry(State(state): State<AppState>) -> impl IntoResponse {
+    let telemetry = state.telemetry.lock().await.clone();
+    Json(build_telemetry_config_view(&telemetry))
+}
+
+async fn handle_put_telemetry(
+    State(state): State<AppState>,
+    Json(update): Json<TelemetryConfigUpdate>,
+) -> impl IntoResponse {
+    let mut telemetry = TelemetryConfig {
+        state_interval_secs: update.state_interval_secs,
+        schedule_interval_secs: update.schedule_interval_secs,
+        state_enabled: update.state_enabled,
+        schedule_enabled: update.schedule_enabled,
+    };
+    telemetry.sanitize();
+
+    {
+        *state.telemetry.lock().await = telemetry.clone();
+    }
+
+    let mut runtime = state
+        .store
+        .load_runtime_config()
+        .await
+        .unwrap_or_else(|err| {
+            warn!("failed to load existing runtime config for telemetry update: {err:#}");
+            RuntimeConfig::default()
+        });
+    runtime.telemetry = telemetry.clone();
+    if let Err(err) = state.store.save_runtime_config(&runtime).await {
+        warn!("failed to persist telemetry config update: {err:#}");
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to persist telemetry settings",
+        );
+    }
+
+    Json(build_telemetry_config_view(&telemetry)).into_response()
+}
+
+async fn handle_post_telemetry_oneshot(State(state): State<AppState>) -> impl IntoResponse {
+    state.telemetry_oneshot.notify_one();
+    Json(serde_json::json!({ "ok": true }))
+}
+
+async fn handle_get_influx(State(state): State<AppState>) -> impl IntoResponse {
+    let influx = state.influx.lock().await.clone();
+    Json(build_influx_config_view(&influx))
+}
+
+async fn handle_put_influx(
+    State(state): State<AppState>,
+    Json(update): Json<InfluxConfigUpdate>,
+) -> impl IntoResponse {
+    if update.enabled && update.url.trim().is_empty() {
+        return error_response(StatusCode::BAD_REQUEST, "url cannot be empty when enabled");
+    }
+
+    let mut runtime = state
+        .store
+        .load_runtime_config()
+        .await
+        .unwrap_or_else(|err| {
+            warn!("failed to load existing runtime config for influx update: {err:#}");
+            RuntimeConfig::default()
+        });
+
+    let mut influx = InfluxConfig {
+        enabled: update.enabled,
+        url: update.url,
+        org: update.org,
+        bucket: update.bucket,
+        token: update.token.unwrap_or(runtime.influx.token),
+        push_interval_secs: update.push_interval_secs,
+    };
+    influx.sanitize();
+
+    runtime.influx = influx.clone();
+    if let Err(err) = state.store.save_runtime_config(&runtime).await {
+        warn!("failed to persist influx config update: {err:#}");
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to persist influx settings",
+        );
+    }
+
+    *state.influx.lock().await = influx.clone();
+
+    Json(build_influx_config_view(&influx)).into_response()
+}
+
+async fn handle_get_uploader(State(state): State<AppState>) -> impl IntoResponse {
+    let uploader = state.uploader.lock().await.clone();
+    Json(build_uploader_config_view(&uploader))
+}
+
+async fn handle_put_uploader(
+    State(state): State<AppState>,
+    Json(update): Json<UploaderConfigUpdate>,
+) -> impl IntoResponse {
+    if update.enabled && update.server_url.trim().is_empty() {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "serverUrl cannot be empty when enabled",
+        );
+    }
+
+    let mut runtime = state
+        .store
+        .load_runtime_config()
+        .await
+        .unwrap_or_else(|err| {
+            warn!("failed to load existing runtime config for uploader update: {err:#}");
+            RuntimeConfig::default()
+        });
+
+    let mut uploader = UploaderConfig {
+        enabled: update.enabled,
+        server_url: update.server_url,
+        hmac_key: update.hmac_key.unwrap_or(runtime.uploader.hmac_key),
+        push_interval_secs: update.push_interval_secs,
+    };
+    uploader.sanitize();
+
+    runtime.uploader = uploader.clone();
+    if let Err(err) = state.store.save_runtime_config(&runtime).await {
+        warn!("failed to persist uploader config update: {err:#}");
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to persist uploader settings",
+        );
+    }
+
+    *state.uploader.lock().await = uploader.clone();
+
+    Json(build_uploader_config_view(&uploader)).into_response()
+}
+
+/// The Linux dev build never enters SoftAP/captive-portal mode (it assumes it's
+/// already reachable on a LAN), so this simulates the provisioning state machine
+/// as permanently "already provisioned" rather than driving real WiFi/DNS.
+async fn handle_get_provision_state() -> impl IntoResponse {
+    Json(serde_json::json!({
+        "apModeActive": false,
+        "connectedClients": 0,
+    }))
+}
+
+async fn handle_post_provision_complete(State(state): State<AppState>) -> impl IntoResponse {
+    let runtime = state
+        .store
+        .load_runtime_config()
+        .await
+        .unwrap_or_else(|err| {
+            warn!("failed to reload network config on provision complete: {err:#}");
+            RuntimeConfig::default()
+        });
+    info!(
+        "provisioning complete requested (simulated); network config ssid=`{}`",
+        runtime.network.wifi_ssid
+    );
+    Json(serde_json::json!({ "restarting": false }))
+}
+
+async fn handle_get_ota_status(State(state): State<AppState>) -> impl IntoResponse {
+    let ota = state.ota.lock().await.clone();
     Json(OtaStatusResponse {
-        supported: false,
-        in_progress: false,
-        last_error: Some("OTA apply is only available in ESP32 builds".to_string()),
+        supported: true,
+        state: ota.state,
+        in_progress: matches!(ota.state, OtaState::Downloading | OtaState::Verifying),
+        bytes_written: ota.bytes_written,
+        total_bytes: ota.total_bytes,
+        last_error: ota.last_error,
     })
 }
 
-async fn handle_post_ota_apply(Json(request): Json<OtaApplyRequest>) -> impl IntoResponse {
-    let _ = (request.url.as_str(), request.sha256.as_deref());
-    error_response(
-        StatusCode::NOT_IMPLEMENTED,
-        "OTA apply is only available in ESP32 builds",
-    )
+async fn handle_post_ota_apply(
+    State(state): State<AppState>,
+    Json(request): Json<OtaApplyRequest>,
+) -> impl IntoResponse {
+    {
+        let ota = state.ota.lock().await;
+        if matches!(ota.state, OtaState::Downloading | OtaState::Verifying) {
+            return error_response(StatusCode::CONFLICT, "OTA update already in progress");
+        }
+    }
+
+    {
+        let mut ota = state.ota.lock().await;
+        *ota = OtaProgress {
+            state: OtaState::Downloading,
+            bytes_written: 0,
+            total_bytes: None,
+            last_error: None,
+        };
+    }
+
+    tokio::spawn(run_ota_update(state.clone(), request));
+
+    Json(serde_json::json!({ "accepted": true })).into_response()
+}
+
+/// Streams the firmware image from `request.url` in fixed-size chunks,
+/// hashing as it writes to a staging file so memory stays bounded. The
+/// hash is checked only when `request.sha256` is given. On success the
+/// image is left at `ota_staging_path` and the process notifies systemd
+/// it is stopping and exits with status 0; nothing here moves the staged
+/// image over the running binary. On any failure the staged file is removed
+/// and the previous binary keeps running untouched (the Linux-build
+/// equivalent of leaving the inactive A/B slot rolled back).
+async fn run_ota_update(state: AppState, request: OtaApplyRequest) {
+    let staging_path = state.store.ota_staging_path();
+
+    let result = download_and_verify_ota(&state, &request, &staging_path).await;
+
+    match result {
+        Ok(()) => {
+            {
+                let mut ota = state.ota.lock().await;
+                ota.state = OtaState::PendingReboot;
+            }
+            info!("OTA update staged at {staging_path:?}; requesting restart to apply it");
+            sd_notify("STOPPING=1");
+            std::process::exit(0);
+        }
+        Err(err) => {
+            warn!("OTA update failed: {err:#}");
+            tokio::fs::remove_file(&staging_path).await.ok();
+            let mut ota = state.ota.lock().await;
+            ota.state = OtaState::Failed;
+            ota.last_error = Some(err.to_string());
+        }
+    }
+}
+
+async fn download_and_verify_ota(
+    state: &AppState,
+    request: &OtaApplyRequest,
+    staging_path: &std::path::Path,
+) -> anyhow::Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    if let Some(parent) = staging_path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    let response = reqwest::get(&request.url)
+        .await
+        .context("failed to start OTA download")?;
+    let total_bytes = response.content_length();
+    {
+        let mut ota = state.ota.lock().await;
+        ota.total_bytes = total_bytes;
+    }
+
+    let mut file = tokio::fs::File::create(staging_path).await?;
+    let mut hasher = Sha256::new();
+    let mut bytes_written: u64 = 0;
+    let mut stream = response.bytes_stream();
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.context("OTA download stream error")?;
+        hasher.update(&chunk);
+        file.write_all(&chunk).await?;
+        bytes_written += chunk.len() as u64;
+
+        let mut ota = state.ota.lock().await;
+        ota.bytes_written = bytes_written;
+    }
+    file.flush().await?;
+
+    {
+        let mut ota = state.ota.lock().await;
+        ota.state = OtaState::Verifying;
+    }
+
+    if let Some(expected) = &request.sha256 {
+        let computed = hex_encode(&hasher.finalize());
+        if !computed.eq_ignore_ascii_case(expected) {
+            anyhow::bail!("sha256 mismatch: expected {expected}, computed {computed}");
+        }
+    }
+
+    Ok(())
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
 }
 
 impl AppStore {
@@ -1066,10 +3191,31 @@ impl AppStore {
         Self {
             runtime_path: Arc::new(data_dir.join("runtime.json")),
             schedule_path: Arc::new(data_dir.join("schedule.json")),
+            schedule_history_path: Arc::new(data_dir.join("schedule_history.json")),
             lock: Arc::new(Mutex::new(())),
         }
     }
 
+    fn ota_staging_path(&self) -> PathBuf {
+        self.runtime_path
+            .parent()
+            .map(|dir| dir.join("ota-staged.bin"))
+            .unwrap_or_else(|| PathBuf::from("ota-staged.bin"))
+    }
+
+    /// Overridable with `THERMOSTAT_CONFIG_PATH`; otherwise a sibling of
+    /// `runtime_path`, same convention as `ota_staging_path`.
+    fn file_config_path(&self) -> PathBuf {
+        std::env::var("THERMOSTAT_CONFIG_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                self.runtime_path
+                    .parent()
+                    .map(|dir| dir.join("config.toml"))
+                    .unwrap_or_else(|| PathBuf::from("config.toml"))
+            })
+    }
+
     async fn load_runtime_config(&self) -> anyhow::Result<RuntimeConfig> {
         let _guard = self.lock.lock().await;
         match tokio::fs::read(self.runtime_path.as_ref()).await {
@@ -1100,6 +3246,10 @@ impl AppStore {
     }
 
     async fn save_schedule(&self, schedule: &Schedule) -> anyhow::Result<()> {
+        self.save_schedule_with_note(schedule, "schedule update").await
+    }
+
+    async fn save_schedule_with_note(&self, schedule: &Schedule, note: &str) -> anyhow::Result<()> {
         let _guard = self.lock.lock().await;
         let path = self.schedule_path.as_ref().clone();
         if let Some(parent) = path.parent() {
@@ -1107,6 +3257,62 @@ impl AppStore {
         }
         let payload = serde_json::to_vec_pretty(schedule)?;
         tokio::fs::write(path, payload).await?;
+        self.append_schedule_history_locked(Some(schedule.clone()), note)
+            .await
+    }
+
+    /// Writes a tombstone entry rather than removing any schedule history,
+    /// and resets the active schedule to the default (empty) schedule.
+    async fn delete_schedule(&self) -> anyhow::Result<()> {
+        let _guard = self.lock.lock().await;
+        let path = self.schedule_path.as_ref().clone();
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let payload = serde_json::to_vec_pretty(&Schedule::default())?;
+        tokio::fs::write(path, payload).await?;
+        self.append_schedule_history_locked(None, "schedule deleted")
+            .await
+    }
+
+    async fn load_schedule_history(&self) -> anyhow::Result<Vec<ScheduleHistoryEntry>> {
+        let _guard = self.lock.lock().await;
+        self.read_schedule_history().await
+    }
+
+    async fn read_schedule_history(&self) -> anyhow::Result<Vec<ScheduleHistoryEntry>> {
+        match tokio::fs::read(self.schedule_history_path.as_ref()).await {
+            Ok(raw) => Ok(serde_json::from_slice(&raw)?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Appends a new immutable history entry, evicting the oldest once the
+    /// cap is exceeded. Must be called with `self.lock` already held.
+    async fn append_schedule_history_locked(
+        &self,
+        schedule: Option<Schedule>,
+        note: &str,
+    ) -> anyhow::Result<()> {
+        let mut history = self.read_schedule_history().await?;
+        let next_id = history.last().map(|entry| entry.id + 1).unwrap_or(1);
+        history.push(ScheduleHistoryEntry {
+            id: next_id,
+            timestamp_epoch: chrono::Utc::now().timestamp(),
+            note: note.to_string(),
+            schedule,
+        });
+        while history.len() > SCHEDULE_HISTORY_CAP {
+            history.remove(0);
+        }
+
+        let path = self.schedule_history_path.as_ref().clone();
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let payload = serde_json::to_vec_pretty(&history)?;
+        tokio::fs::write(path, payload).await?;
         Ok(())
     }
 }
@@ -1121,6 +3327,55 @@ async fn persist_runtime_from_state(state: &AppState) -> anyhow::Result<()> {
     state.store.save_runtime_config(&runtime).await
 }
 
+/// `GET /api/pid` - the PID gains and output clamp used by
+/// `ControlStrategy::Pid`, read from the live engine rather than
+/// `RuntimeConfig` since the two can drift until the next `PUT` persists
+/// them.
+async fn handle_get_pid(State(state): State<AppState>) -> impl IntoResponse {
+    let pid = state.engine.lock().await.pid_parameters();
+    Json(build_pid_config_view(&pid))
+}
+
+/// `PUT /api/pid` - tunes the PID gains/output clamp and persists them into
+/// `RuntimeConfig.thermostat.pid`, so they survive a restart the same way
+/// `PersistedSettings` does.
+async fn handle_put_pid(
+    State(state): State<AppState>,
+    Json(update): Json<PidConfigUpdate>,
+) -> impl IntoResponse {
+    {
+        let mut engine = state.engine.lock().await;
+        engine.set_kp(update.kp);
+        engine.set_ki(update.ki);
+        engine.set_kd(update.kd);
+        if !engine.set_pid_output_limits(update.output_min, update.output_max) {
+            return error_response(StatusCode::BAD_REQUEST, "outputMin must be < outputMax");
+        }
+    }
+
+    let pid = state.engine.lock().await.pid_parameters();
+    let mut runtime = match state.store.load_runtime_config().await {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            warn!("failed to load existing runtime config for pid update: {err:#}");
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to load runtime settings",
+            );
+        }
+    };
+    runtime.thermostat.pid = pid;
+    if let Err(err) = state.store.save_runtime_config(&runtime).await {
+        warn!("failed to persist pid update: {err:#}");
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to persist runtime settings",
+        );
+    }
+
+    Json(build_pid_config_view(&pid)).into_response()
+}
+
 fn build_network_config_view(network: &NetworkConfig) -> NetworkConfigView {
     NetworkConfigView {
         wifi_ssid: network.wifi_ssid.clone(),
@@ -1143,6 +3398,8 @@ fn build_ir_config_view(ir: &IrHardwareConfig) -> IrConfigView {
         tx_pin: ir.tx_pin,
         rmt_channel: ir.rmt_channel,
         carrier_khz: ir.carrier_khz,
+        protocol: ir.protocol,
+        rx_pin: ir.rx_pin,
     }
 }
 
@@ -1156,6 +3413,11 @@ fn validate_ir_update(update: &IrConfigUpdate) -> Result<(), &'static str> {
     if !(10..=100).contains(&update.carrier_khz) {
         return Err("carrierKHz must be between 10 and 100");
     }
+    if let Some(rx_pin) = update.rx_pin {
+        if rx_pin < 0 {
+            return Err("rxPin must be >= 0");
+        }
+    }
     Ok(())
 }
 
@@ -1175,7 +3437,10 @@ fn network_restart_required(previous: &NetworkConfig, current: &NetworkConfig) -
         || previous.gateway != current.gateway
         || previous.subnet != current.subnet
         || previous.dns != current.dns
-        || previous.mqtt_host != current.mqtt_host
+}
+
+fn mqtt_credentials_changed(previous: &NetworkConfig, current: &NetworkConfig) -> bool {
+    previous.mqtt_host != current.mqtt_host
         || previous.mqtt_port != current.mqtt_port
         || previous.mqtt_user != current.mqtt_user
         || previous.mqtt_pass != current.mqtt_pass
@@ -1197,6 +3462,68 @@ fn error_response(status: StatusCode, message: &str) -> axum::response::Response
         .into_response()
 }
 
+/// Builds a human-readable `STATUS=` line describing the engine's current state,
+/// suitable for `systemctl status` output.
+fn engine_status_line(engine: &ThermostatEngine, now_ms: u64) -> String {
+    let target = engine.settings().target_temp_f;
+    match engine.state() {
+        ThermostatState::Idle => "idle".to_string(),
+        ThermostatState::Heating => format!("heating, target {target:.0}°F"),
+        ThermostatState::Cooling => {
+            let settings = engine.settings();
+            let cool_target = if settings.mode == ThermostatMode::Auto {
+                settings.auto_cool_setpoint_f
+            } else {
+                target
+            };
+            format!("cooling, target {cool_target:.0}°F")
+        }
+        ThermostatState::Satisfied => format!("satisfied, target {target:.0}°F"),
+        ThermostatState::Hold => {
+            let remaining_min = engine.hold_remaining_ms(now_ms) / 60_000;
+            format!("hold active {remaining_min}m left")
+        }
+        ThermostatState::Cooldown => {
+            let remaining_min = engine.cooldown_remaining_ms(now_ms) / 60_000;
+            format!("cooldown, {remaining_min}m remaining")
+        }
+        ThermostatState::Fault => "fault latched, awaiting clear_alarm".to_string(),
+    }
+}
+
+/// Sends a message to the systemd notify socket named by `NOTIFY_SOCKET`, if set.
+/// No-op when the service isn't running under a systemd `Type=notify` unit.
+fn sd_notify(message: &str) {
+    let Ok(mut socket_path) = std::env::var("NOTIFY_SOCKET") else {
+        return;
+    };
+    if socket_path.starts_with('@') {
+        socket_path.replace_range(0..1, "\0");
+    }
+
+    match std::os::unix::net::UnixDatagram::unbound() {
+        Ok(socket) => {
+            if let Err(err) = socket.send_to(message.as_bytes(), &socket_path) {
+                warn!("failed to send sd_notify message: {err}");
+            }
+        }
+        Err(err) => warn!("failed to create sd_notify socket: {err}"),
+    }
+}
+
+async fn shutdown_signal() {
+    let mut sigterm =
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler");
+    let mut sigint = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt())
+        .expect("failed to install SIGINT handler");
+
+    tokio::select! {
+        _ = sigterm.recv() => info!("received SIGTERM, shutting down"),
+        _ = sigint.recv() => info!("received SIGINT, shutting down"),
+    }
+}
+
 fn monotonic_ms() -> u64 {
     static START: OnceLock<Instant> = OnceLock::new();
     START
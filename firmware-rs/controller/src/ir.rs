@@ -1,13 +1,14 @@
 use core::convert::TryInto;
 use std::{
-    sync::OnceLock,
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
     thread,
     time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, Context};
 use esp_idf_hal::{
-    gpio::OutputPin,
+    gpio::{InputPin, OutputPin, PinDriver},
     peripheral::Peripheral,
     rmt::{
         config::{CarrierConfig, DutyPercent, TransmitConfig},
@@ -16,9 +17,9 @@ use esp_idf_hal::{
     units::FromValueType,
 };
 use log::{info, warn};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use thermostat_common::EngineAction;
+use thermostat_common::{ir_protocol, EngineAction, IrProtocol};
 
 use crate::ir_codes;
 
@@ -28,8 +29,189 @@ const IR_REPEAT_COUNT: usize = 3;
 const IR_REPEAT_GAP_MS: u64 = 50;
 const MIN_SEND_INTERVAL_MS: u64 = 300;
 
-#[derive(Debug, Clone)]
-struct IrRuntimeState {
+/// EMA smoothing factor applied to raw room-temperature samples before
+/// they reach the PI loop below.
+const TEMP_CONTROL_ALPHA: f32 = 0.1;
+const TEMP_CONTROL_KP: f32 = 0.8;
+const TEMP_CONTROL_KI: f32 = 0.05;
+/// Anti-windup clamp on the accumulated integral term.
+const TEMP_CONTROL_INTEGRAL_MIN: f32 = -10.0;
+const TEMP_CONTROL_INTEGRAL_MAX: f32 = 10.0;
+/// `output` must clear this magnitude before a correction step is issued,
+/// so the loop doesn't chase single-degree sensor jitter.
+const TEMP_CONTROL_OUTPUT_DEADBAND: f32 = 1.0;
+
+/// A logical remote-control action, independent of the protocol used to
+/// encode it on the wire. `temp_*` actions carry the engine's current
+/// absolute temperature since several codecs (and all learned commands)
+/// key their raw tables off it rather than an increment.
+#[derive(Debug, Clone, Copy)]
+pub enum IrCommand {
+    PowerOn,
+    PowerOff,
+    HeatOn,
+    HeatOff,
+    TempUp { from_temp_f: i32 },
+    TempDown { from_temp_f: i32 },
+    LightToggle { from_level: u8 },
+    TimerToggle { from_state: u8 },
+}
+
+impl IrCommand {
+    /// Stable identifier used both as the learned-command lookup key and as
+    /// the per-command diagnostics counter key.
+    pub fn key(&self) -> String {
+        match self {
+            IrCommand::PowerOn => "power_on".to_string(),
+            IrCommand::PowerOff => "power_off".to_string(),
+            IrCommand::HeatOn => "heat_on".to_string(),
+            IrCommand::HeatOff => "heat_off".to_string(),
+            IrCommand::TempUp { from_temp_f } => format!("temp_up_{from_temp_f}"),
+            IrCommand::TempDown { from_temp_f } => format!("temp_down_{from_temp_f}"),
+            IrCommand::LightToggle { from_level } => format!("light_from_{from_level}"),
+            IrCommand::TimerToggle { from_state } => format!("timer_from_{from_state}"),
+        }
+    }
+
+    /// A single-byte command code for procedural codecs (NEC/RC5), chosen so
+    /// each logical command maps to a stable, non-colliding slot.
+    fn procedural_code(&self) -> u8 {
+        match self {
+            IrCommand::PowerOn => 0x01,
+            IrCommand::PowerOff => 0x02,
+            IrCommand::HeatOn => 0x03,
+            IrCommand::HeatOff => 0x04,
+            IrCommand::TempUp { from_temp_f } => 0x10 + ((from_temp_f - 60) / 2).clamp(0, 15) as u8,
+            IrCommand::TempDown { from_temp_f } => {
+                0x30 + ((from_temp_f - 60) / 2).clamp(0, 15) as u8
+            }
+            IrCommand::LightToggle { from_level } => 0x50 + (*from_level).min(15),
+            IrCommand::TimerToggle { from_state } => 0x60 + (*from_state).min(15),
+        }
+    }
+}
+
+/// Encodes a logical [`IrCommand`] into a raw pulse/space timing table (in
+/// RMT ticks), the way the transmit driver already represents every frame.
+/// Implemented once per supported protocol and selected via
+/// `IrHardwareConfig::protocol`, mirroring how this codebase feature-gates
+/// hardware modules behind a single enum switch rather than trait objects
+/// sprinkled through the call sites.
+pub trait IrCodec: Send {
+    fn encode(&self, command: IrCommand) -> Option<Vec<u16>>;
+
+    /// Inserts a freshly captured raw timing table under `key`. A no-op for
+    /// every codec except [`LearnedCodec`], which is the only one whose
+    /// table can grow at runtime.
+    fn learn(&mut self, _key: String, _timings: Vec<u16>) {}
+}
+
+/// Replays the factory-preconfigured raw timing tables in `ir_codes`. This
+/// is the historical, hard-wired behavior and remains the default.
+struct RawTableCodec;
+
+impl IrCodec for RawTableCodec {
+    fn encode(&self, command: IrCommand) -> Option<Vec<u16>> {
+        let raw: &[u16] = match command {
+            IrCommand::PowerOn => ir_codes::IR_RAW_POWER_ON,
+            IrCommand::PowerOff => ir_codes::IR_RAW_POWER_OFF,
+            IrCommand::HeatOn => ir_codes::IR_RAW_HEAT_ON,
+            IrCommand::HeatOff => ir_codes::IR_RAW_HEAT_OFF,
+            IrCommand::TempUp { from_temp_f } => temp_up_code(from_temp_f)?,
+            IrCommand::TempDown { from_temp_f } => temp_down_code(from_temp_f)?,
+            IrCommand::LightToggle { from_level } => match from_level {
+                0 => ir_codes::IR_RAW_LIGHT_FROM_OFF,
+                4 => ir_codes::IR_RAW_LIGHT_FROM_4,
+                3 => ir_codes::IR_RAW_LIGHT_FROM_3,
+                2 => ir_codes::IR_RAW_LIGHT_FROM_2,
+                1 => ir_codes::IR_RAW_LIGHT_FROM_1,
+                _ => return None,
+            },
+            IrCommand::TimerToggle { from_state } => match from_state {
+                0 => ir_codes::IR_RAW_TIMER_FROM_OFF,
+                1 => ir_codes::IR_RAW_TIMER_FROM_0_5,
+                2 => ir_codes::IR_RAW_TIMER_FROM_1,
+                3 => ir_codes::IR_RAW_TIMER_FROM_2,
+                4 => ir_codes::IR_RAW_TIMER_FROM_3,
+                5 => ir_codes::IR_RAW_TIMER_FROM_4,
+                6 => ir_codes::IR_RAW_TIMER_FROM_5,
+                7 => ir_codes::IR_RAW_TIMER_FROM_6,
+                8 => ir_codes::IR_RAW_TIMER_FROM_7,
+                9 => ir_codes::IR_RAW_TIMER_FROM_8,
+                10 => ir_codes::IR_RAW_TIMER_FROM_9,
+                _ => return None,
+            },
+        };
+        Some(raw.to_vec())
+    }
+}
+
+/// Replays commands captured through the learn-and-replay flow
+/// (`POST /api/ir/learn/start` + `GET /api/ir/learn/result`), keyed by
+/// [`IrCommand::key`].
+struct LearnedCodec {
+    commands: HashMap<String, Vec<u16>>,
+}
+
+impl IrCodec for LearnedCodec {
+    fn encode(&self, command: IrCommand) -> Option<Vec<u16>> {
+        self.commands.get(&command.key()).cloned()
+    }
+
+    fn learn(&mut self, key: String, timings: Vec<u16>) {
+        self.commands.insert(key, timings);
+    }
+}
+
+/// Synthesizes frames for a procedural protocol whose bit timing lives in
+/// `thermostat_common::ir_protocol`, keyed off this thermostat's own
+/// `IrCommand`s via `procedural_code()` as the wire "command" and a fixed
+/// per-device/remote "address". Shared by every procedural protocol this
+/// transmitter supports; only the wire protocol and address width differ.
+struct ProceduralCodec {
+    protocol: ir_protocol::IrProtocol,
+    address: u16,
+}
+
+impl IrCodec for ProceduralCodec {
+    fn encode(&self, command: IrCommand) -> Option<Vec<u16>> {
+        Some(ir_protocol::encode(
+            self.protocol,
+            self.address,
+            command.procedural_code() as u16,
+        ))
+    }
+}
+
+fn build_codec(protocol: IrProtocol, learned: HashMap<String, Vec<u16>>) -> Box<dyn IrCodec> {
+    match protocol {
+        IrProtocol::Pronto => Box::new(RawTableCodec),
+        IrProtocol::Nec => Box::new(ProceduralCodec {
+            protocol: ir_protocol::IrProtocol::Nec,
+            address: 0x00,
+        }),
+        IrProtocol::Rc5 => Box::new(ProceduralCodec {
+            protocol: ir_protocol::IrProtocol::Rc5,
+            address: 0x00,
+        }),
+        IrProtocol::Rc6 => Box::new(ProceduralCodec {
+            protocol: ir_protocol::IrProtocol::Rc6,
+            address: 0x00,
+        }),
+        IrProtocol::Sirc => Box::new(ProceduralCodec {
+            protocol: ir_protocol::IrProtocol::Sirc,
+            address: 0x00,
+        }),
+        IrProtocol::Learned => Box::new(LearnedCodec { commands: learned }),
+    }
+}
+
+/// Mirrors what the codec assumes the physical AC unit is doing. Persisted
+/// to NVS and restored on boot (see [`IrTransmitter::new_with_carrier`])
+/// since the unit keeps its real state across a thermostat reboot even
+/// though this struct would otherwise reset to `Default`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct IrRuntimeState {
     current_temp_f: i32,
     light_level: u8,
     timer_state: u8,
@@ -45,24 +227,128 @@ impl Default for IrRuntimeState {
     }
 }
 
+/// Where `IrTransmitter::state` came from, surfaced in [`IrDiagnostics`] so
+/// it's visible whether the assumed state is still just a guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum IrStateOrigin {
+    /// No persisted state was found; `IrRuntimeState::default()` is a guess.
+    Default,
+    /// Loaded from NVS, so it should match hardware as of the last save.
+    Restored,
+    /// Forced to a known state via [`IrTransmitter::resync`], so it's
+    /// provably correct as of the resync rather than merely assumed.
+    Resynced,
+}
+
+/// Closed-loop correction driven by actual room-temperature samples rather
+/// than the open-loop step counting `IrRuntimeState` otherwise relies on:
+/// an EMA filter kills sensor noise, then a discrete PI loop against the
+/// room setpoint decides whether the dial needs to move. `sample` is the
+/// only entry point; it returns the single-step action needed to walk the
+/// assumed state toward the setpoint, or `None` once it's close enough.
+#[derive(Debug, Default)]
+struct TempControlLoop {
+    filtered_temp_f: Option<f32>,
+    integral: f32,
+    last_sample_ms: Option<u64>,
+}
+
+impl TempControlLoop {
+    fn sample(&mut self, setpoint_f: f32, sample_f: f32, now_ms: u64) -> Option<EngineAction> {
+        let filtered = match self.filtered_temp_f {
+            Some(previous) => previous + TEMP_CONTROL_ALPHA * (sample_f - previous),
+            None => sample_f,
+        };
+        self.filtered_temp_f = Some(filtered);
+
+        let dt_s = self
+            .last_sample_ms
+            .map_or(0.0, |last| now_ms.saturating_sub(last) as f32 / 1000.0);
+        self.last_sample_ms = Some(now_ms);
+
+        let error = setpoint_f - filtered;
+        self.integral = (self.integral + error * dt_s)
+            .clamp(TEMP_CONTROL_INTEGRAL_MIN, TEMP_CONTROL_INTEGRAL_MAX);
+
+        let output = TEMP_CONTROL_KP * error + TEMP_CONTROL_KI * self.integral;
+        if output >= TEMP_CONTROL_OUTPUT_DEADBAND {
+            Some(EngineAction::TempUp)
+        } else if output <= -TEMP_CONTROL_OUTPUT_DEADBAND {
+            Some(EngineAction::TempDown)
+        } else {
+            None
+        }
+    }
+}
+
 enum IrBackend {
     Rmt(TxRmtDriver<'static>),
     Disabled,
 }
 
+/// Per-unit timing/repeat/carrier tuning, previously hard-coded as consts.
+/// `tick_divider` and `carrier_khz` are baked into the RMT driver at
+/// construction, so changing them takes a fresh `new_with_profile` call;
+/// `repeat_count`, `repeat_gap_ms`, and `min_send_interval_ms` are read
+/// fresh by `send_raw`/`rate_limit` on every send, so [`IrTransmitter::set_profile`]
+/// can retune those live without recreating the transmitter. Lets one
+/// board drive a second remote (different carrier, different repeat
+/// behavior) without a rebuild.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct IrProfile {
+    pub tick_divider: u8,
+    #[serde(rename = "carrierKHz")]
+    pub carrier_khz: u32,
+    pub repeat_count: usize,
+    pub repeat_gap_ms: u64,
+    pub min_send_interval_ms: u64,
+}
+
+impl Default for IrProfile {
+    fn default() -> Self {
+        Self {
+            tick_divider: IR_TICK_DIVIDER,
+            carrier_khz: IR_CARRIER_FREQ_KHZ,
+            repeat_count: IR_REPEAT_COUNT,
+            repeat_gap_ms: IR_REPEAT_GAP_MS,
+            min_send_interval_ms: MIN_SEND_INTERVAL_MS,
+        }
+    }
+}
+
 pub struct IrTransmitter {
     backend: IrBackend,
+    codec: Box<dyn IrCodec>,
+    protocol: IrProtocol,
     state: IrRuntimeState,
+    state_origin: IrStateOrigin,
     last_send_ms: Option<u64>,
-    carrier_khz: u32,
+    profile: IrProfile,
     sent_frames: u64,
     failed_actions: u64,
     last_error: Option<String>,
+    command_stats: HashMap<String, CommandStat>,
+    control_loop: TempControlLoop,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+struct CommandStat {
+    sent: u64,
+    failed: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IrCommandStat {
+    command: String,
+    sent: u64,
+    failed: u64,
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct IrDiagnostics {
     pub enabled: bool,
+    pub protocol: IrProtocol,
     #[serde(rename = "carrierKHz")]
     pub carrier_khz: u32,
     #[serde(rename = "repeatCount")]
@@ -85,79 +371,168 @@ pub struct IrDiagnostics {
     pub runtime_light_level: u8,
     #[serde(rename = "runtimeTimerState")]
     pub runtime_timer_state: u8,
+    #[serde(rename = "stateOrigin")]
+    pub state_origin: IrStateOrigin,
+    #[serde(rename = "controlKp")]
+    pub control_kp: f32,
+    #[serde(rename = "controlKi")]
+    pub control_ki: f32,
+    #[serde(rename = "controlIntegral")]
+    pub control_integral: f32,
+    #[serde(rename = "filteredTempF")]
+    pub filtered_temp_f: Option<f32>,
+    #[serde(rename = "commandStats")]
+    pub command_stats: Vec<IrCommandStat>,
 }
 
 impl IrTransmitter {
     pub fn new<C, P>(
         channel: impl Peripheral<P = C> + 'static,
         pin: impl Peripheral<P = P> + 'static,
+        protocol: IrProtocol,
+        learned_commands: HashMap<String, Vec<u16>>,
+        restored_state: Option<IrRuntimeState>,
     ) -> anyhow::Result<Self>
     where
         C: RmtChannel,
         P: OutputPin,
     {
-        Self::new_with_carrier(channel, pin, IR_CARRIER_FREQ_KHZ)
+        Self::new_with_carrier(
+            channel,
+            pin,
+            IR_CARRIER_FREQ_KHZ,
+            protocol,
+            learned_commands,
+            restored_state,
+        )
     }
 
     pub fn new_with_carrier<C, P>(
         channel: impl Peripheral<P = C> + 'static,
         pin: impl Peripheral<P = P> + 'static,
         carrier_khz: u32,
+        protocol: IrProtocol,
+        learned_commands: HashMap<String, Vec<u16>>,
+        restored_state: Option<IrRuntimeState>,
+    ) -> anyhow::Result<Self>
+    where
+        C: RmtChannel,
+        P: OutputPin,
+    {
+        Self::new_with_profile(
+            channel,
+            pin,
+            IrProfile {
+                carrier_khz,
+                ..IrProfile::default()
+            },
+            protocol,
+            learned_commands,
+            restored_state,
+        )
+    }
+
+    pub fn new_with_profile<C, P>(
+        channel: impl Peripheral<P = C> + 'static,
+        pin: impl Peripheral<P = P> + 'static,
+        profile: IrProfile,
+        protocol: IrProtocol,
+        learned_commands: HashMap<String, Vec<u16>>,
+        restored_state: Option<IrRuntimeState>,
     ) -> anyhow::Result<Self>
     where
         C: RmtChannel,
         P: OutputPin,
     {
         let carrier = CarrierConfig::new()
-            .frequency(carrier_khz.kHz().into())
+            .frequency(profile.carrier_khz.kHz().into())
             .carrier_level(PinState::High)
             .duty_percent(DutyPercent::new(33)?);
 
         let config = TransmitConfig::new()
-            .clock_divider(IR_TICK_DIVIDER)
+            .clock_divider(profile.tick_divider)
             .carrier(Some(carrier))
             .idle(Some(PinState::Low));
 
         let tx = TxRmtDriver::new(channel, pin, &config).context("failed to init RMT IR driver")?;
 
+        let (state, state_origin) = match restored_state {
+            Some(state) => (state, IrStateOrigin::Restored),
+            None => (IrRuntimeState::default(), IrStateOrigin::Default),
+        };
+
         Ok(Self {
             backend: IrBackend::Rmt(tx),
-            state: IrRuntimeState::default(),
+            codec: build_codec(protocol, learned_commands),
+            protocol,
+            state,
+            state_origin,
             last_send_ms: None,
-            carrier_khz,
+            profile,
             sent_frames: 0,
             failed_actions: 0,
             last_error: None,
+            command_stats: HashMap::new(),
+            control_loop: TempControlLoop::default(),
         })
     }
 
     pub fn disabled() -> Self {
         Self {
             backend: IrBackend::Disabled,
+            codec: build_codec(IrProtocol::default(), HashMap::new()),
+            protocol: IrProtocol::default(),
             state: IrRuntimeState::default(),
+            state_origin: IrStateOrigin::Default,
             last_send_ms: None,
-            carrier_khz: IR_CARRIER_FREQ_KHZ,
+            profile: IrProfile::default(),
             sent_frames: 0,
             failed_actions: 0,
             last_error: None,
+            command_stats: HashMap::new(),
+            control_loop: TempControlLoop::default(),
         }
     }
 
+    /// Swaps the active timing/repeat/carrier profile. Takes effect on the
+    /// next send for `repeat_count`/`repeat_gap_ms`/`min_send_interval_ms`;
+    /// `carrier_khz` only shows up in diagnostics and `tick_divider` is only
+    /// stored here, since the RMT driver itself isn't recreated.
+    pub fn set_profile(&mut self, profile: IrProfile) {
+        self.profile = profile;
+    }
+
+    pub fn profile(&self) -> IrProfile {
+        self.profile
+    }
+
+    /// Inserts a newly learned raw timing table keyed by [`IrCommand::key`]
+    /// into the active codec, so a capture under `IrProtocol::Learned` takes
+    /// effect immediately without a restart.
+    pub fn learn_command(&mut self, key: String, timings: Vec<u16>) {
+        self.codec.learn(key, timings);
+    }
+
     pub fn execute_action(&mut self, action: EngineAction) -> anyhow::Result<()> {
         let result = (|| -> anyhow::Result<()> {
             match action {
                 EngineAction::PowerOn => {
-                    self.send_raw(ir_codes::IR_RAW_POWER_ON)?;
+                    self.send_command(IrCommand::PowerOn)?;
                     self.state.light_level = 4;
                 }
                 EngineAction::PowerOff => {
-                    self.send_raw(ir_codes::IR_RAW_POWER_OFF)?;
+                    self.send_command(IrCommand::PowerOff)?;
                 }
                 EngineAction::HeatOn => {
-                    self.send_raw(ir_codes::IR_RAW_HEAT_ON)?;
+                    self.send_command(IrCommand::HeatOn)?;
                 }
                 EngineAction::HeatOff => {
-                    self.send_raw(ir_codes::IR_RAW_HEAT_OFF)?;
+                    self.send_command(IrCommand::HeatOff)?;
+                }
+                EngineAction::CoolOn | EngineAction::CoolOff => {
+                    return Err(anyhow!(
+                        "this fireplace remote has no cooling function; {action:?} is not supported"
+                    ));
                 }
                 EngineAction::TempUp => {
                     if !self.send_temp_up_transition()? {
@@ -179,6 +554,17 @@ impl IrTransmitter {
                 EngineAction::TimerToggle => {
                     self.send_timer_toggle()?;
                 }
+                EngineAction::ThrottleHeat => {
+                    info!("thermal load throttling heat call (no IR command to send)");
+                }
+                EngineAction::EmergencyLockout => {
+                    info!(
+                        "thermal load emergency lockout engaged, heat call refused until margin clears"
+                    );
+                }
+                EngineAction::AutotuneComplete { kp, ki, kd } => {
+                    info!("autotune complete: kp={kp:.4} ki={ki:.4} kd={kd:.4}");
+                }
             }
             Ok(())
         })();
@@ -194,12 +580,24 @@ impl IrTransmitter {
     }
 
     pub fn diagnostics(&self) -> IrDiagnostics {
+        let mut command_stats: Vec<IrCommandStat> = self
+            .command_stats
+            .iter()
+            .map(|(command, stat)| IrCommandStat {
+                command: command.clone(),
+                sent: stat.sent,
+                failed: stat.failed,
+            })
+            .collect();
+        command_stats.sort_by(|a, b| a.command.cmp(&b.command));
+
         IrDiagnostics {
             enabled: matches!(self.backend, IrBackend::Rmt(_)),
-            carrier_khz: self.carrier_khz,
-            repeat_count: IR_REPEAT_COUNT,
-            repeat_gap_ms: IR_REPEAT_GAP_MS,
-            min_send_interval_ms: MIN_SEND_INTERVAL_MS,
+            protocol: self.protocol,
+            carrier_khz: self.profile.carrier_khz,
+            repeat_count: self.profile.repeat_count,
+            repeat_gap_ms: self.profile.repeat_gap_ms,
+            min_send_interval_ms: self.profile.min_send_interval_ms,
             last_send_ms: self.last_send_ms,
             sent_frames: self.sent_frames,
             failed_actions: self.failed_actions,
@@ -207,9 +605,50 @@ impl IrTransmitter {
             runtime_temp_f: self.state.current_temp_f,
             runtime_light_level: self.state.light_level,
             runtime_timer_state: self.state.timer_state,
+            state_origin: self.state_origin,
+            control_kp: TEMP_CONTROL_KP,
+            control_ki: TEMP_CONTROL_KI,
+            control_integral: self.control_loop.integral,
+            filtered_temp_f: self.control_loop.filtered_temp_f,
+            command_stats,
         }
     }
 
+    /// Returns the assumed runtime state, for NVS persistence after an
+    /// action that mutates it.
+    pub(crate) fn state_snapshot(&self) -> IrRuntimeState {
+        self.state.clone()
+    }
+
+    pub fn state_origin(&self) -> IrStateOrigin {
+        self.state_origin
+    }
+
+    /// Feeds a fresh room-temperature sample through the closed-loop PI
+    /// controller and returns the single-step correction it calls for, if
+    /// any. The caller is expected to execute the returned action the same
+    /// way as any other `EngineAction` (e.g. via `execute_action`), so it
+    /// goes through the usual rate limiting and state persistence.
+    pub fn sample_room_temp(&mut self, setpoint_f: f32, sample_f: f32) -> Option<EngineAction> {
+        self.control_loop.sample(setpoint_f, sample_f, monotonic_ms())
+    }
+
+    /// Forces the unit into a deterministic known state instead of trusting
+    /// whatever `self.state` currently claims: power off, power on (which
+    /// sets `light_level = 4`), then step the temperature down to the 60°F
+    /// floor. Run this when there's no persisted state to restore, so the
+    /// assumed state provably matches hardware rather than just guessing.
+    pub fn resync(&mut self) -> anyhow::Result<()> {
+        self.send_command(IrCommand::PowerOff)?;
+        self.send_command(IrCommand::PowerOn)?;
+        self.state.light_level = 4;
+
+        while self.send_temp_down_transition()? {}
+
+        self.state_origin = IrStateOrigin::Resynced;
+        Ok(())
+    }
+
     fn set_temp(&mut self, target_temp_f: i32) -> anyhow::Result<()> {
         let target = normalize_temp(target_temp_f);
 
@@ -233,9 +672,8 @@ impl IrTransmitter {
             return Ok(false);
         }
 
-        let code = temp_up_code(self.state.current_temp_f)
-            .ok_or_else(|| anyhow!("missing temp-up code for {}", self.state.current_temp_f))?;
-        self.send_raw(code)?;
+        let from_temp_f = self.state.current_temp_f;
+        self.send_command(IrCommand::TempUp { from_temp_f })?;
         self.state.current_temp_f += 2;
         Ok(true)
     }
@@ -245,24 +683,15 @@ impl IrTransmitter {
             return Ok(false);
         }
 
-        let code = temp_down_code(self.state.current_temp_f)
-            .ok_or_else(|| anyhow!("missing temp-down code for {}", self.state.current_temp_f))?;
-        self.send_raw(code)?;
+        let from_temp_f = self.state.current_temp_f;
+        self.send_command(IrCommand::TempDown { from_temp_f })?;
         self.state.current_temp_f -= 2;
         Ok(true)
     }
 
     fn send_light_toggle(&mut self) -> anyhow::Result<()> {
-        let code = match self.state.light_level {
-            0 => ir_codes::IR_RAW_LIGHT_FROM_OFF,
-            4 => ir_codes::IR_RAW_LIGHT_FROM_4,
-            3 => ir_codes::IR_RAW_LIGHT_FROM_3,
-            2 => ir_codes::IR_RAW_LIGHT_FROM_2,
-            1 => ir_codes::IR_RAW_LIGHT_FROM_1,
-            level => return Err(anyhow!("invalid light level state: {level}")),
-        };
-
-        self.send_raw(code)?;
+        let from_level = self.state.light_level;
+        self.send_command(IrCommand::LightToggle { from_level })?;
         self.state.light_level = if self.state.light_level == 0 {
             4
         } else {
@@ -273,27 +702,35 @@ impl IrTransmitter {
     }
 
     fn send_timer_toggle(&mut self) -> anyhow::Result<()> {
-        let code = match self.state.timer_state {
-            0 => ir_codes::IR_RAW_TIMER_FROM_OFF,
-            1 => ir_codes::IR_RAW_TIMER_FROM_0_5,
-            2 => ir_codes::IR_RAW_TIMER_FROM_1,
-            3 => ir_codes::IR_RAW_TIMER_FROM_2,
-            4 => ir_codes::IR_RAW_TIMER_FROM_3,
-            5 => ir_codes::IR_RAW_TIMER_FROM_4,
-            6 => ir_codes::IR_RAW_TIMER_FROM_5,
-            7 => ir_codes::IR_RAW_TIMER_FROM_6,
-            8 => ir_codes::IR_RAW_TIMER_FROM_7,
-            9 => ir_codes::IR_RAW_TIMER_FROM_8,
-            10 => ir_codes::IR_RAW_TIMER_FROM_9,
-            state => return Err(anyhow!("invalid timer state: {state}")),
-        };
-
-        self.send_raw(code)?;
+        let from_state = self.state.timer_state;
+        self.send_command(IrCommand::TimerToggle { from_state })?;
         self.state.timer_state = (self.state.timer_state + 1) % 11;
 
         Ok(())
     }
 
+    /// Encodes `command` via the active codec and transmits it, tracking
+    /// per-command send/fail counters for diagnostics.
+    fn send_command(&mut self, command: IrCommand) -> anyhow::Result<()> {
+        let key = command.key();
+        let result = (|| -> anyhow::Result<()> {
+            let raw = self
+                .codec
+                .encode(command)
+                .ok_or_else(|| anyhow!("no code for command {key} under {:?} protocol", self.protocol))?;
+            self.send_raw(&raw)
+        })();
+
+        let stat = self.command_stats.entry(key).or_default();
+        if result.is_ok() {
+            stat.sent = stat.sent.saturating_add(1);
+        } else {
+            stat.failed = stat.failed.saturating_add(1);
+        }
+
+        result
+    }
+
     fn send_raw(&mut self, raw: &[u16]) -> anyhow::Result<()> {
         if raw.is_empty() {
             return Ok(());
@@ -327,11 +764,11 @@ impl IrTransmitter {
             .context("failed to convert IR timings to RMT signal")?;
 
         if let IrBackend::Rmt(tx) = &mut self.backend {
-            for repeat in 0..IR_REPEAT_COUNT {
+            for repeat in 0..self.profile.repeat_count {
                 tx.start_blocking(&signal)
                     .context("failed to transmit IR frame over RMT")?;
-                if repeat + 1 < IR_REPEAT_COUNT {
-                    thread::sleep(Duration::from_millis(IR_REPEAT_GAP_MS));
+                if repeat + 1 < self.profile.repeat_count {
+                    thread::sleep(Duration::from_millis(self.profile.repeat_gap_ms));
                 }
             }
         }
@@ -345,8 +782,10 @@ impl IrTransmitter {
         let now = monotonic_ms();
         if let Some(last) = self.last_send_ms {
             let elapsed = now.saturating_sub(last);
-            if elapsed < MIN_SEND_INTERVAL_MS {
-                thread::sleep(Duration::from_millis(MIN_SEND_INTERVAL_MS - elapsed));
+            if elapsed < self.profile.min_send_interval_ms {
+                thread::sleep(Duration::from_millis(
+                    self.profile.min_send_interval_ms - elapsed,
+                ));
             }
         }
     }
@@ -392,6 +831,230 @@ fn normalize_temp(temp_f: i32) -> i32 {
     normalized
 }
 
+const LEARN_CAPTURE_TIMEOUT_MS: u64 = 5_000;
+const LEARN_IDLE_GAP_MS: u64 = 8;
+const LEARN_MAX_TRANSITIONS: usize = 400;
+const LEARN_POLL_INTERVAL_US: u64 = 20;
+const LEARN_RESULT_POLL_INTERVAL_MS: u64 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LearnStatus {
+    Idle,
+    Capturing,
+    Done,
+    TimedOut,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LearnResult {
+    pub status: LearnStatus,
+    pub timings: Option<Vec<u16>>,
+}
+
+struct LearnState {
+    status: LearnStatus,
+    timings: Option<Vec<u16>>,
+}
+
+/// Captures a raw pulse/space sequence from an IR receiver pin so it can be
+/// replayed later under `IrProtocol::Learned`. Runs the capture on a
+/// dedicated polling thread (mirroring how the captive-portal DNS responder
+/// owns its own thread) rather than blocking the HTTP handler that starts it.
+pub struct IrLearner {
+    state: Arc<Mutex<LearnState>>,
+}
+
+impl IrLearner {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(LearnState {
+                status: LearnStatus::Idle,
+                timings: None,
+            })),
+        }
+    }
+
+    /// Starts a capture on `pin`, returning an error if one is already in
+    /// progress. The capture runs to completion in the background; poll
+    /// [`IrLearner::result`] for the outcome. `glitch_floor_us` is read
+    /// fresh at each call (typically from `IrHardwareConfig::learn_glitch_floor_us`)
+    /// rather than cached, the same way callers re-resolve `rx_pin` per
+    /// capture instead of pinning it at `IrLearner` construction time.
+    pub fn start<P>(
+        &self,
+        pin: impl Peripheral<P = P> + Send + 'static,
+        glitch_floor_us: u16,
+    ) -> anyhow::Result<()>
+    where
+        P: InputPin,
+    {
+        {
+            let mut guard = self.state.lock().unwrap();
+            if guard.status == LearnStatus::Capturing {
+                return Err(anyhow!("IR learn capture already in progress"));
+            }
+            guard.status = LearnStatus::Capturing;
+            guard.timings = None;
+        }
+
+        let state = Arc::clone(&self.state);
+        thread::Builder::new()
+            .name("ir-learn".to_string())
+            .spawn(move || {
+                let outcome = capture_timings(pin, glitch_floor_us);
+                let mut guard = state.lock().unwrap();
+                match outcome {
+                    Ok(Some(timings)) => {
+                        guard.timings = Some(timings);
+                        guard.status = LearnStatus::Done;
+                    }
+                    Ok(None) => guard.status = LearnStatus::TimedOut,
+                    Err(err) => {
+                        warn!("IR learn capture failed: {err:#}");
+                        guard.status = LearnStatus::Failed;
+                    }
+                }
+            })
+            .context("failed to spawn IR learn capture thread")?;
+
+        Ok(())
+    }
+
+    pub fn result(&self) -> LearnResult {
+        let guard = self.state.lock().unwrap();
+        LearnResult {
+            status: guard.status,
+            timings: guard.timings.clone(),
+        }
+    }
+
+    /// Blocking convenience wrapper around [`IrLearner::start`] +
+    /// [`IrLearner::result`] for callers that don't need the async
+    /// start/poll split the HTTP learn endpoints use (e.g. tooling scripts).
+    /// Returns the filtered timing table, or an error if the capture timed
+    /// out, failed, or `timeout` elapsed first.
+    pub fn learn<P>(
+        &self,
+        pin: impl Peripheral<P = P> + Send + 'static,
+        glitch_floor_us: u16,
+        timeout: Duration,
+    ) -> anyhow::Result<Vec<u16>>
+    where
+        P: InputPin,
+    {
+        self.start(pin, glitch_floor_us)?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let result = self.result();
+            match result.status {
+                LearnStatus::Done => {
+                    return result
+                        .timings
+                        .ok_or_else(|| anyhow!("IR learn reported done with no timings"))
+                }
+                LearnStatus::TimedOut => return Err(anyhow!("IR learn capture timed out")),
+                LearnStatus::Failed => return Err(anyhow!("IR learn capture failed")),
+                LearnStatus::Idle | LearnStatus::Capturing => {}
+            }
+
+            if Instant::now() > deadline {
+                return Err(anyhow!("IR learn capture did not finish within {timeout:?}"));
+            }
+
+            thread::sleep(Duration::from_millis(LEARN_RESULT_POLL_INTERVAL_MS));
+        }
+    }
+}
+
+impl Default for IrLearner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Polls `pin` for level transitions until `LEARN_IDLE_GAP_MS` passes with no
+/// change (end of frame) or `LEARN_CAPTURE_TIMEOUT_MS` elapses with nothing
+/// captured at all. Returns the inter-edge durations in microseconds, in the
+/// same mark-first alternating form `IrTransmitter::send_raw` expects, after
+/// running them through [`filter_glitches`].
+fn capture_timings<P>(
+    pin: impl Peripheral<P = P> + 'static,
+    glitch_floor_us: u16,
+) -> anyhow::Result<Option<Vec<u16>>>
+where
+    P: InputPin,
+{
+    let driver = PinDriver::input(pin).context("failed to init IR learn input pin")?;
+
+    let deadline = Instant::now() + Duration::from_millis(LEARN_CAPTURE_TIMEOUT_MS);
+    let mut last_level = driver.is_high();
+    let mut last_edge = Instant::now();
+    // The wait before the first edge is idle line, not part of the frame.
+    let mut seen_edge = false;
+    let mut levels: Vec<(bool, u16)> = Vec::new();
+
+    loop {
+        if Instant::now() > deadline {
+            return Ok(None);
+        }
+
+        let level = driver.is_high();
+        if level != last_level {
+            let gap_us = last_edge.elapsed().as_micros().min(u16::MAX as u128) as u16;
+            if seen_edge {
+                levels.push((last_level, gap_us));
+            }
+            seen_edge = true;
+            last_level = level;
+            last_edge = Instant::now();
+
+            if levels.len() >= LEARN_MAX_TRANSITIONS {
+                return Ok(Some(filter_glitches(levels, glitch_floor_us)));
+            }
+        } else if !levels.is_empty()
+            && last_edge.elapsed() > Duration::from_millis(LEARN_IDLE_GAP_MS)
+        {
+            return Ok(Some(filter_glitches(levels, glitch_floor_us)));
+        }
+
+        thread::sleep(Duration::from_micros(LEARN_POLL_INTERVAL_US));
+    }
+}
+
+/// Discards pulses shorter than `floor_us` and merges same-level runs,
+/// since a demodulated receiver chatters for a few microseconds at mark/
+/// space edges. A dropped pulse's duration folds into whichever run is
+/// currently open rather than being thrown away outright, so the brief
+/// spurious flip it represented effectively never happened and its real
+/// neighbors (which share that run's level) merge back into one pulse.
+/// Returns the same mark-first alternating `&[u16]` form `send_raw` expects.
+fn filter_glitches(levels: Vec<(bool, u16)>, floor_us: u16) -> Vec<u16> {
+    let mut runs: Vec<(bool, u32)> = Vec::with_capacity(levels.len());
+
+    for (level, duration) in levels {
+        if duration < floor_us {
+            if let Some((_, run_duration)) = runs.last_mut() {
+                *run_duration += duration as u32;
+            }
+            continue;
+        }
+
+        match runs.last_mut() {
+            Some((run_level, run_duration)) if *run_level == level => {
+                *run_duration += duration as u32;
+            }
+            _ => runs.push((level, duration as u32)),
+        }
+    }
+
+    runs.into_iter()
+        .map(|(_, duration)| duration.min(u16::MAX as u32) as u16)
+        .collect()
+}
+
 fn monotonic_ms() -> u64 {
     static START: OnceLock<Instant> = OnceLock::new();
     START
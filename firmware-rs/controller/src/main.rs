@@ -3,9 +3,13 @@ mod esp;
 #[cfg(not(feature = "esp32"))]
 mod host;
 #[cfg(feature = "esp32")]
+mod connectivity;
+#[cfg(feature = "esp32")]
 mod ir;
 #[cfg(feature = "esp32")]
 mod ir_codes;
+#[cfg(feature = "esp32")]
+mod status_led;
 
 #[cfg(not(feature = "esp32"))]
 #[tokio::main]
@@ -0,0 +1,234 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use esp_idf_hal::{
+    gpio::{Output, OutputPin, PinDriver},
+    peripheral::Peripheral,
+    rmt::{
+        config::TransmitConfig, PinState, Pulse, PulseTicks, RmtChannel, TxRmtDriver,
+        VariableLengthSignal,
+    },
+};
+use log::warn;
+
+use thermostat_common::ThermostatState;
+
+/// How fast the RMT clock is divided down before driving WS2812 bit timing.
+/// Paired with the tick counts in [`ws2812_pulses`], which are sized in
+/// ~100ns units at this divider.
+const WS2812_TICK_DIVIDER: u8 = 8;
+
+const LED_FAST_BLINK_MS: u64 = 200;
+const LED_SLOW_BLINK_MS: u64 = 900;
+/// Blink rate for "WiFi is up but SNTP hasn't completed its first sync yet" -
+/// distinct from both the WiFi-down fast blink and the MQTT-down slow blink
+/// so the two causes of a non-solid LED aren't confused with each other.
+const LED_SYNC_BLINK_MS: u64 = 450;
+/// Fastest blink of the bunch - a latched thermostat fault is the one
+/// condition that wants to look urgent rather than just "not fully up yet".
+const LED_FAULT_BLINK_MS: u64 = 100;
+/// Slow single-color blink `CoolActive` uses on a mono LED, distinct from
+/// `MqttDown`'s rate since the two can't otherwise be told apart.
+const LED_COOL_BLINK_MS: u64 = 1_500;
+
+/// Semantic status the LED should convey, resolved once per control-loop
+/// tick from connectivity state and [`ThermostatState`]. Each backend
+/// ([`MonoStatusLed`], [`RgbStatusLed`]) renders this the way its hardware
+/// allows: a mono LED can only vary blink rate, an RGB LED can also vary
+/// color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedState {
+    WifiDown,
+    ClockUnsynced,
+    MqttDown,
+    Fault,
+    HeatActive,
+    CoolActive,
+    AllGood,
+}
+
+/// Resolves the connectivity/thermostat priority chain into a single
+/// [`LedState`]. Connectivity problems take priority over thermostat
+/// activity since a device that can't reach MQTT or NTP needs attention
+/// regardless of what the fireplace is doing.
+pub fn resolve_led_state(
+    wifi_connected: bool,
+    time_synced: bool,
+    mqtt_connected: bool,
+    thermostat_state: ThermostatState,
+) -> LedState {
+    if !wifi_connected {
+        return LedState::WifiDown;
+    }
+    if !time_synced {
+        return LedState::ClockUnsynced;
+    }
+    if !mqtt_connected {
+        return LedState::MqttDown;
+    }
+
+    match thermostat_state {
+        ThermostatState::Fault => LedState::Fault,
+        ThermostatState::Heating => LedState::HeatActive,
+        ThermostatState::Cooling => LedState::CoolActive,
+        ThermostatState::Idle
+        | ThermostatState::Satisfied
+        | ThermostatState::Hold
+        | ThermostatState::Cooldown => LedState::AllGood,
+    }
+}
+
+/// A single GPIO status LED, blinked at a rate that depends on `LedState`.
+/// Can't show color, so `HeatActive` (solid on), `CoolActive` (blinking) and
+/// `AllGood` (off) are told apart by pattern, replacing the solid-on-when-healthy behavior this LED
+/// used to have before thermostat activity was folded into `LedState`.
+pub struct MonoStatusLed<P: OutputPin> {
+    pin: PinDriver<'static, P, Output>,
+    lit: bool,
+}
+
+impl<P: OutputPin> MonoStatusLed<P> {
+    pub fn new(pin: impl Peripheral<P = P> + 'static) -> anyhow::Result<Self> {
+        let mut driver = PinDriver::output(pin).context("failed to init status LED GPIO")?;
+        let _ = driver.set_low();
+        Ok(Self {
+            pin: driver,
+            lit: false,
+        })
+    }
+
+    pub fn update(&mut self, state: LedState, now_ms: u64) {
+        let desired_on = match state {
+            LedState::WifiDown => blink(now_ms, LED_FAST_BLINK_MS),
+            LedState::ClockUnsynced => blink(now_ms, LED_SYNC_BLINK_MS),
+            LedState::MqttDown => blink(now_ms, LED_SLOW_BLINK_MS),
+            LedState::Fault => blink(now_ms, LED_FAULT_BLINK_MS),
+            LedState::HeatActive => true,
+            LedState::CoolActive => blink(now_ms, LED_COOL_BLINK_MS),
+            LedState::AllGood => false,
+        };
+
+        if desired_on == self.lit {
+            return;
+        }
+
+        let result = if desired_on {
+            self.pin.set_high()
+        } else {
+            self.pin.set_low()
+        };
+
+        match result {
+            Ok(()) => self.lit = desired_on,
+            Err(err) => warn!("failed to drive status LED: {err}"),
+        }
+    }
+}
+
+fn blink(now_ms: u64, period_ms: u64) -> bool {
+    ((now_ms / period_ms) % 2) == 0
+}
+
+/// A WS2812/NeoPixel addressable LED driven over its own RMT channel, one
+/// pixel. Renders `LedState` as a color, blinking it on/off for the same
+/// connectivity states `MonoStatusLed` blinks, so a single photo of either
+/// backend tells the same story.
+pub struct RgbStatusLed<'d> {
+    tx: TxRmtDriver<'d>,
+    lit: bool,
+}
+
+impl<'d> RgbStatusLed<'d> {
+    pub fn new<C: RmtChannel, P: OutputPin>(
+        channel: impl Peripheral<P = C> + 'd,
+        pin: impl Peripheral<P = P> + 'd,
+    ) -> anyhow::Result<Self> {
+        let config = TransmitConfig::new()
+            .clock_divider(WS2812_TICK_DIVIDER)
+            .idle(Some(PinState::Low));
+        let mut led = Self {
+            tx: TxRmtDriver::new(channel, pin, &config)
+                .context("failed to init RMT status LED driver")?,
+            lit: false,
+        };
+        led.send((0, 0, 0))?;
+        Ok(led)
+    }
+
+    pub fn update(&mut self, state: LedState, now_ms: u64) {
+        let (color, desired_on) = match state {
+            LedState::WifiDown => ((255, 0, 0), blink(now_ms, LED_FAST_BLINK_MS)),
+            LedState::ClockUnsynced => ((255, 120, 0), blink(now_ms, LED_SYNC_BLINK_MS)),
+            LedState::MqttDown => ((255, 0, 255), blink(now_ms, LED_SLOW_BLINK_MS)),
+            LedState::Fault => ((255, 0, 0), blink(now_ms, LED_FAULT_BLINK_MS)),
+            LedState::HeatActive => ((255, 40, 0), true),
+            LedState::CoolActive => ((0, 80, 255), true),
+            LedState::AllGood => ((0, 255, 0), true),
+        };
+
+        if desired_on == self.lit {
+            return;
+        }
+
+        let rgb = if desired_on { color } else { (0, 0, 0) };
+        if let Err(err) = self.send(rgb) {
+            warn!("failed to drive RGB status LED: {err}");
+            return;
+        }
+        self.lit = desired_on;
+    }
+
+    fn send(&mut self, (r, g, b): (u8, u8, u8)) -> anyhow::Result<()> {
+        let pulses = ws2812_pulses((g, r, b))?;
+        let pulse_refs: Vec<&Pulse> = pulses.iter().collect();
+        let mut signal = VariableLengthSignal::with_capacity(pulses.len());
+        signal
+            .push(pulse_refs)
+            .context("failed to convert status LED color to RMT signal")?;
+        self.tx
+            .start_blocking(&signal)
+            .context("failed to transmit status LED color over RMT")?;
+        // WS2812's latch/reset gap, so the next frame isn't read as more bits.
+        std::thread::sleep(Duration::from_micros(60));
+        Ok(())
+    }
+}
+
+/// Encodes one WS2812 frame, `bytes` already in on-the-wire order (GRB), MSB
+/// first per byte. Timings are 400/800/700/600ns (T0H/T0L/T1H/T1L),
+/// which WS2812 tolerates well within its spec's +/-150ns margin.
+fn ws2812_pulses((byte0, byte1, byte2): (u8, u8, u8)) -> anyhow::Result<Vec<Pulse>> {
+    let mut pulses = Vec::with_capacity(24);
+    for byte in [byte0, byte1, byte2] {
+        for bit in (0..8).rev() {
+            let is_one = (byte >> bit) & 1 == 1;
+            let (high_ticks, low_ticks) = if is_one { (7, 6) } else { (4, 8) };
+            pulses.push(Pulse::new(
+                PinState::High,
+                PulseTicks::new(high_ticks).context("invalid status LED pulse duration")?,
+            ));
+            pulses.push(Pulse::new(
+                PinState::Low,
+                PulseTicks::new(low_ticks).context("invalid status LED pulse duration")?,
+            ));
+        }
+    }
+    Ok(pulses)
+}
+
+/// Dispatches to whichever backend `StatusLedConfig` selected, matching the
+/// `IrBackend`/`WifiStartup` convention elsewhere in this crate of an enum
+/// over variant structs rather than a trait object.
+pub enum StatusLed {
+    Mono(MonoStatusLed<esp_idf_hal::gpio::AnyOutputPin>),
+    Rgb(RgbStatusLed<'static>),
+}
+
+impl StatusLed {
+    pub fn update(&mut self, state: LedState, now_ms: u64) {
+        match self {
+            StatusLed::Mono(led) => led.update(state, now_ms),
+            StatusLed::Rgb(led) => led.update(state, now_ms),
+        }
+    }
+}
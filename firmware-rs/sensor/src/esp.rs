@@ -1,6 +1,6 @@
 use core::convert::TryInto;
 use std::{
-    net::Ipv4Addr,
+    net::{Ipv4Addr, UdpSocket},
     sync::{Arc, Mutex},
     thread,
     time::{Duration, Instant},
@@ -12,7 +12,7 @@ use ds18b20::{Ds18b20, Resolution};
 use embedded_svc::{
     http::{client::Client as HttpClient, Headers, Method, Status},
     io::{Read, Write},
-    mqtt::client::QoS,
+    mqtt::client::{Details, EventPayload, QoS},
     wifi::{AccessPointConfiguration, AuthMethod, ClientConfiguration, Configuration},
 };
 use esp_idf_hal::{
@@ -31,11 +31,12 @@ use esp_idf_svc::{
         Configuration as IpConfiguration, Mask, Subnet,
     },
     log::EspLogger,
-    mqtt::client::{EspMqttClient, MqttClientConfiguration},
+    mqtt::client::{EspMqttClient, LwtConfiguration, MqttClientConfiguration},
     netif::{EspNetif, NetifConfiguration},
     nvs::{EspDefaultNvsPartition, EspNvs},
     ota::EspOta,
-    wifi::{BlockingWifi, EspWifi},
+    tls::X509,
+    wifi::{BlockingWifi, EspWifi, WifiDeviceId},
 };
 use log::{info, warn};
 use one_wire_bus::{Address, OneWire};
@@ -43,12 +44,22 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
 use thermostat_common::{
-    config::NetworkConfig, RuntimeConfig, TOPIC_SENSOR_HUMIDITY, TOPIC_SENSOR_STATUS,
-    TOPIC_SENSOR_TEMP,
+    config::{Ipv6Mode, NetworkConfig, WifiAuthMode},
+    RuntimeConfig, Temperature, TemperatureUnit, TOPIC_CMD_SENSOR_OTA_APPLY,
+    TOPIC_CMD_SENSOR_SETTINGS, TOPIC_SENSOR_HUMIDITY, TOPIC_SENSOR_OTA_STATUS,
+    TOPIC_SENSOR_SETTINGS_RESULT, TOPIC_SENSOR_STATUS, TOPIC_SENSOR_TEMP,
 };
 
 const NVS_NAMESPACE: &str = "thermostat";
 const NVS_RUNTIME_KEY: &str = "runtime_json";
+const NVS_OTA_PENDING_KEY: &str = "ota_pending";
+const NVS_MQTT_CA_CERT_KEY: &str = "mqtt_ca_pem";
+const NVS_RECOVERY_MODE_KEY: &str = "recovery_mode";
+
+/// Largest inbound settings-command payload accepted on
+/// `TOPIC_CMD_SENSOR_SETTINGS`, mirroring the controller's guard against an
+/// oversized or corrupt MQTT message wedging the poll loop.
+const MAX_MQTT_PAYLOAD_BYTES: usize = 512;
 
 const DS18B20_PIN: i32 = 4;
 const DHT11_PIN: i32 = 16;
@@ -61,6 +72,32 @@ const WATCHDOG_TIMEOUT_SEC: u32 = 90;
 const WIFI_RESTART_GRACE_MS: u64 = 300_000;
 const WIFI_CONNECT_ATTEMPTS: u32 = 5;
 const WIFI_RETRY_DELAY_MS: u64 = 3_000;
+/// Reconnect backoff schedule used by `maintain_wifi_health`: 1s, 2s, 4s,
+/// 8s, ... doubling per retry, capped here.
+const WIFI_RECONNECT_BASE_DELAY_MS: u64 = 1_000;
+const WIFI_RECONNECT_MAX_DELAY_MS: u64 = 60_000;
+/// Retries attempted per `Reconnecting` burst before `maintain_wifi_health`
+/// falls back to `Cooldown`. Combined with `WIFI_RESTART_GRACE_MS`, a
+/// restart only happens once *both* this many retries have been burned
+/// *and* the device has been down for the full grace window.
+const WIFI_RECONNECT_MAX_RETRIES: u32 = 8;
+/// How long the radio stays stopped during `Cooldown` before a fresh
+/// `Reconnecting` burst starts, giving a struggling or rebooting AP a clean
+/// window to come back instead of getting hammered with reassociations.
+const WIFI_COOLDOWN_MS: u64 = 30_000;
+/// How long after boot `detect_reset_button_double_press` watches GPIO0 (the
+/// devkit's BOOT button, active low) for a second press before giving up and
+/// letting the device boot `Normal` as usual.
+const RESET_BUTTON_WINDOW_MS: u64 = 2_000;
+const DNS_PORT: u16 = 53;
+const DNS_ANSWER_TTL_SECS: u32 = 60;
+/// How long after boot a freshly applied OTA image has to prove itself
+/// (WiFi connected, MQTT status published, one sensor reading published)
+/// before `check_ota_health` gives up and rolls back to the previous slot.
+/// WiFi is already up by the time this clock starts ticking, so this mostly
+/// bounds how long a broken MQTT/sensor path gets before the device reboots
+/// itself out of it.
+const OTA_CONFIRM_TIMEOUT_SECS: u64 = 120;
 
 const SENSOR_PORTAL_HTML: &str = r#"<!doctype html>
 <html lang="en">
@@ -83,10 +120,14 @@ const SENSOR_PORTAL_HTML: &str = r#"<!doctype html>
   <h1>Thermostat Sensor Setup</h1>
   <p class="muted">Configure WiFi/MQTT, then optionally apply an OTA image.</p>
   <p class="muted">Provisioning AP password: <code>ThermostatSetup</code></p>
+  <p class="muted">Device ID: <code id="deviceId">--</code></p>
 
   <div class="card">
     <h2>Network</h2>
-    <label>WiFi SSID</label><input id="wifiSsid" type="text">
+    <label>WiFi SSID</label>
+    <input id="wifiSsid" type="text" list="ssidList"><datalist id="ssidList"></datalist>
+    <button id="wifiScan" type="button">Scan</button>
+    <p class="muted" id="wifiScanStatus"></p>
     <label>WiFi Password (leave blank to keep current)</label><input id="wifiPass" type="password">
     <div class="row">
       <div><label>MQTT Host</label><input id="mqttHost" type="text"></div>
@@ -103,6 +144,15 @@ const SENSOR_PORTAL_HTML: &str = r#"<!doctype html>
       <div><label>Subnet Mask</label><input id="subnet" type="text" placeholder="255.255.255.0"></div>
       <div><label>DNS</label><input id="dns" type="text" placeholder="192.168.1.1"></div>
     </div>
+    <label><input id="useStaticIpv6" type="checkbox"> Use static IPv6 (otherwise SLAAC)</label>
+    <div class="row">
+      <div><label>IPv6 Address</label><input id="ipv6Address" type="text" placeholder="2001:db8::51"></div>
+      <div><label>Prefix Length</label><input id="ipv6PrefixLen" type="number" min="1" max="128"></div>
+    </div>
+    <div class="row">
+      <div><label>IPv6 Gateway</label><input id="ipv6Gateway" type="text" placeholder="2001:db8::1"></div>
+      <div><label>IPv6 DNS</label><input id="ipv6Dns" type="text" placeholder="2001:db8::1"></div>
+    </div>
     <button id="save">Save Configuration</button>
     <button id="restart">Restart Device</button>
   </div>
@@ -128,6 +178,26 @@ const SENSOR_PORTAL_HTML: &str = r#"<!doctype html>
     const q=(id)=>document.getElementById(id);
     const toStr=(arr)=>Array.isArray(arr)?arr.join('.'):'';
     const toArr=(value)=>{if(!value.trim())return null;const p=value.trim().split('.').map(Number);if(p.length!==4||p.some(n=>!Number.isInteger(n)||n<0||n>255))throw new Error('Invalid IPv4: '+value);return p;};
+    const toStr6=(arr)=>Array.isArray(arr)?(()=>{const g=[];for(let i=0;i<16;i+=2)g.push(((arr[i]<<8)|arr[i+1]).toString(16));return g.join(':');})():'';
+    const toArr6=(value)=>{
+      value=value.trim(); if(!value)return null;
+      const halves=value.split('::');
+      if(halves.length>2)throw new Error('Invalid IPv6: '+value);
+      const parseGroups=(s)=>s===''?[]:s.split(':').map(g=>{if(!/^[0-9a-fA-F]{1,4}$/.test(g))throw new Error('Invalid IPv6: '+value);return parseInt(g,16);});
+      let groups;
+      if(halves.length===2){
+        const head=parseGroups(halves[0]), tail=parseGroups(halves[1]);
+        const missing=8-head.length-tail.length;
+        if(missing<0)throw new Error('Invalid IPv6: '+value);
+        groups=[...head,...Array(missing).fill(0),...tail];
+      }else{
+        groups=parseGroups(value);
+        if(groups.length!==8)throw new Error('Invalid IPv6: '+value);
+      }
+      const bytes=[];
+      for(const g of groups){bytes.push((g>>8)&0xff);bytes.push(g&0xff);}
+      return bytes;
+    };
 
     async function api(path,opt){
       const r=await fetch(path,opt);let b={};
@@ -138,6 +208,7 @@ const SENSOR_PORTAL_HTML: &str = r#"<!doctype html>
 
     async function loadNetwork(){
       const n=await api('/api/network');
+      q('deviceId').textContent=n.deviceId||'--';
       q('wifiSsid').value=n.wifiSsid||'';
       q('mqttHost').value=n.mqttHost||'';
       q('mqttPort').value=n.mqttPort||1883;
@@ -147,6 +218,11 @@ const SENSOR_PORTAL_HTML: &str = r#"<!doctype html>
       q('gateway').value=toStr(n.gateway);
       q('subnet').value=toStr(n.subnet);
       q('dns').value=toStr(n.dns);
+      q('useStaticIpv6').checked=!!n.useStaticIpv6;
+      q('ipv6Address').value=toStr6(n.ipv6Address);
+      q('ipv6PrefixLen').value=n.ipv6PrefixLen||'';
+      q('ipv6Gateway').value=toStr6(n.ipv6Gateway);
+      q('ipv6Dns').value=toStr6(n.ipv6Dns);
     }
 
     async function loadOta(){
@@ -173,6 +249,11 @@ const SENSOR_PORTAL_HTML: &str = r#"<!doctype html>
           gateway:toArr(q('gateway').value),
           subnet:toArr(q('subnet').value),
           dns:toArr(q('dns').value),
+          useStaticIpv6:q('useStaticIpv6').checked,
+          ipv6Address:toArr6(q('ipv6Address').value),
+          ipv6PrefixLen:q('ipv6PrefixLen').value?Number(q('ipv6PrefixLen').value):null,
+          ipv6Gateway:toArr6(q('ipv6Gateway').value),
+          ipv6Dns:toArr6(q('ipv6Dns').value),
         };
         const res=await api('/api/network',{method:'PUT',headers:{'content-type':'application/json'},body:JSON.stringify(payload)});
         q('status').className='ok'; q('status').textContent='Saved. restartRequired='+String(!!res.restartRequired);
@@ -180,6 +261,20 @@ const SENSOR_PORTAL_HTML: &str = r#"<!doctype html>
       }catch(err){q('status').className='err'; q('status').textContent=err.message;}
     });
 
+    q('wifiScan').addEventListener('click', async ()=>{
+      q('wifiScanStatus').textContent='Scanning...';
+      try{
+        const aps=await api('/api/wifi/scan');
+        const list=q('ssidList'); list.innerHTML='';
+        for(const ap of aps){
+          const opt=document.createElement('option');
+          opt.value=ap.ssid; opt.label=ap.ssid+' ('+ap.rssi+' dBm, '+ap.authMethod+')';
+          list.appendChild(opt);
+        }
+        q('wifiScanStatus').textContent=aps.length+' network(s) found.';
+      }catch(err){q('wifiScanStatus').textContent=err.message;}
+    });
+
     q('restart').addEventListener('click', async ()=>{
       q('status').className='muted'; q('status').textContent='Restarting...';
       try{await api('/api/restart',{method:'POST'});q('status').className='ok';q('status').textContent='Restart requested.';}
@@ -216,6 +311,20 @@ enum WifiStartup {
     Provisioning(EspWifi<'static>),
 }
 
+/// Whether this boot runs the normal sensor/MQTT loop or the field-config
+/// captive portal. Entered either on-demand (a double-press of the reset
+/// button within `RESET_BUTTON_WINDOW_MS` of boot, acted on in the same boot)
+/// or automatically once `maintain_wifi_health` exhausts its reconnection
+/// retries against credentials or an SSID that are never coming back (by
+/// persisting the flag in NVS and rebooting into it). Both reuse the same
+/// `WifiStartup::Provisioning` path `connect_wifi` already falls back to
+/// when station credentials are missing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WorkingMode {
+    Normal,
+    Recovery,
+}
+
 struct SensorReadings {
     temperature_f: Option<f32>,
     humidity: Option<f32>,
@@ -234,8 +343,21 @@ struct NvsStore {
     lock: Arc<Mutex<()>>,
 }
 
+/// One access point seen by a `GET /api/wifi/scan` scan, de-duplicated by
+/// SSID (keeping the strongest signal) and sorted strongest-first.
+#[derive(Debug, Clone, Serialize)]
+struct WifiScanResult {
+    ssid: String,
+    rssi: i8,
+    #[serde(rename = "authMethod")]
+    auth_method: String,
+    channel: u8,
+}
+
 #[derive(Debug, Serialize)]
 struct NetworkConfigView {
+    #[serde(rename = "deviceId")]
+    device_id: String,
     #[serde(rename = "wifiSsid")]
     wifi_ssid: String,
     #[serde(rename = "wifiPassSet")]
@@ -248,6 +370,8 @@ struct NetworkConfigView {
     mqtt_user: String,
     #[serde(rename = "mqttPassSet")]
     mqtt_pass_set: bool,
+    #[serde(rename = "mqttTls")]
+    mqtt_tls: bool,
     #[serde(rename = "otaPasswordSet")]
     ota_password_set: bool,
     #[serde(rename = "useStaticIp")]
@@ -257,6 +381,22 @@ struct NetworkConfigView {
     gateway: Option<[u8; 4]>,
     subnet: Option<[u8; 4]>,
     dns: Option<[u8; 4]>,
+    #[serde(rename = "useStaticIpv6")]
+    use_static_ipv6: bool,
+    #[serde(rename = "ipv6Address")]
+    ipv6_address: Option<[u8; 16]>,
+    #[serde(rename = "ipv6PrefixLen")]
+    ipv6_prefix_len: Option<u8>,
+    #[serde(rename = "ipv6Gateway")]
+    ipv6_gateway: Option<[u8; 16]>,
+    #[serde(rename = "ipv6Dns")]
+    ipv6_dns: Option<[u8; 16]>,
+    #[serde(rename = "wifiAuth")]
+    wifi_auth: Option<WifiAuthMode>,
+    #[serde(rename = "wifiIdentity")]
+    wifi_identity: Option<String>,
+    #[serde(rename = "wifiUsername")]
+    wifi_username: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -273,6 +413,8 @@ struct NetworkConfigUpdate {
     mqtt_user: String,
     #[serde(rename = "mqttPass", default)]
     mqtt_pass: Option<String>,
+    #[serde(rename = "mqttTls", default)]
+    mqtt_tls: bool,
     #[serde(rename = "otaPassword", default)]
     ota_password: Option<String>,
     #[serde(rename = "useStaticIp")]
@@ -282,6 +424,22 @@ struct NetworkConfigUpdate {
     gateway: Option<[u8; 4]>,
     subnet: Option<[u8; 4]>,
     dns: Option<[u8; 4]>,
+    #[serde(rename = "useStaticIpv6")]
+    use_static_ipv6: bool,
+    #[serde(rename = "ipv6Address")]
+    ipv6_address: Option<[u8; 16]>,
+    #[serde(rename = "ipv6PrefixLen")]
+    ipv6_prefix_len: Option<u8>,
+    #[serde(rename = "ipv6Gateway")]
+    ipv6_gateway: Option<[u8; 16]>,
+    #[serde(rename = "ipv6Dns")]
+    ipv6_dns: Option<[u8; 16]>,
+    #[serde(rename = "wifiAuth", default)]
+    wifi_auth: Option<String>,
+    #[serde(rename = "wifiIdentity", default)]
+    wifi_identity: Option<String>,
+    #[serde(rename = "wifiUsername", default)]
+    wifi_username: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -291,6 +449,14 @@ struct NetworkUpdateResponse {
     network: NetworkConfigView,
 }
 
+/// A PEM-encoded CA certificate (or chain) to validate the MQTT broker
+/// against when `mqtt_tls` is enabled, overriding the bundled ESP-IDF CA
+/// store.
+#[derive(Debug, Deserialize)]
+struct MqttCaCertUpdate {
+    pem: String,
+}
+
 #[derive(Debug, Default)]
 struct OtaRuntimeState {
     in_progress: bool,
@@ -301,6 +467,19 @@ struct OtaRuntimeState {
     last_sha256: Option<String>,
     last_source_url: Option<String>,
     last_completed_epoch: Option<i64>,
+    pending_verify: bool,
+    last_result: Option<OtaHealthState>,
+}
+
+/// Outcome of the most recently completed OTA health check (confirm or
+/// rollback). `None` on `OtaStatusResponse` until the first health check
+/// resolves one way or the other; doesn't need a "pending" variant since
+/// that's already covered by `OtaStatusResponse::pending_verify`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum OtaHealthState {
+    Confirmed,
+    RolledBack,
 }
 
 #[derive(Debug, Deserialize)]
@@ -323,6 +502,8 @@ struct OtaApplyResponse {
 
 #[derive(Debug, Serialize)]
 struct OtaStatusResponse {
+    #[serde(rename = "deviceId")]
+    device_id: String,
     supported: bool,
     #[serde(rename = "inProgress")]
     in_progress: bool,
@@ -346,6 +527,10 @@ struct OtaStatusResponse {
     boot_slot: Option<String>,
     #[serde(rename = "updateSlot")]
     update_slot: Option<String>,
+    #[serde(rename = "pendingVerify")]
+    pending_verify: bool,
+    #[serde(rename = "lastResult")]
+    last_result: Option<OtaHealthState>,
 }
 
 impl SensorSuite {
@@ -440,12 +625,13 @@ impl SensorSuite {
 
         match sensor.read_data(&mut self.one_wire, &mut self.delay) {
             Ok(data) => {
-                let temp_f = celsius_to_fahrenheit(data.temperature);
+                let reading = Temperature::from_celsius(data.temperature);
                 info!(
-                    "[DS18B20] Temperature: {:.1}°F ({:.1}°C)",
-                    temp_f, data.temperature
+                    "[DS18B20] Temperature: {} ({})",
+                    reading.format(TemperatureUnit::Fahrenheit),
+                    reading.format(TemperatureUnit::Celsius)
                 );
-                Some(temp_f)
+                Some(reading.as_fahrenheit())
             }
             Err(err) => {
                 warn!("failed to read DS18B20 data: {err:?}");
@@ -481,6 +667,7 @@ impl SensorSuite {
 pub fn run() -> anyhow::Result<()> {
     esp_idf_svc::sys::link_patches();
     EspLogger::initialize_default();
+    log_reset_reason();
 
     let sys_loop = EspSystemEventLoop::take()?;
     let nvs_partition = EspDefaultNvsPartition::take()?;
@@ -498,11 +685,36 @@ pub fn run() -> anyhow::Result<()> {
 
     let Peripherals { modem, pins, .. } = Peripherals::take()?;
 
+    let reset_button_pin = pins.gpio0.downgrade();
     let mut sensors = SensorSuite::new(pins.gpio4.downgrade(), pins.gpio16.downgrade())
         .context("failed to initialize sensor suite")?;
 
-    let wifi = match connect_wifi(modem, sys_loop.clone(), nvs_partition, &runtime.network)
-        .context("wifi startup failed")?
+    let recovery_requested = nvs_store.load_recovery_mode().unwrap_or_else(|err| {
+        warn!("failed to load recovery-mode flag from NVS: {err:#}");
+        false
+    });
+    if let Err(err) = nvs_store.save_recovery_mode(false) {
+        warn!("failed to clear recovery-mode flag in NVS: {err:#}");
+    }
+
+    let working_mode = if recovery_requested {
+        info!("recovery-mode flag set in NVS; booting straight into the provisioning portal");
+        WorkingMode::Recovery
+    } else if detect_reset_button_double_press(reset_button_pin) {
+        info!("reset button double-pressed at boot; entering recovery mode on demand");
+        WorkingMode::Recovery
+    } else {
+        WorkingMode::Normal
+    };
+
+    let wifi = match connect_wifi(
+        modem,
+        sys_loop.clone(),
+        nvs_partition,
+        &runtime.network,
+        working_mode == WorkingMode::Recovery,
+    )
+    .context("wifi startup failed")?
     {
         WifiStartup::Connected(wifi) => {
             info!("wifi connected");
@@ -513,7 +725,26 @@ pub fn run() -> anyhow::Result<()> {
                 "wifi station connection unavailable; starting provisioning AP `{}`",
                 PROVISIONING_AP_SSID
             );
-            let server = create_provisioning_http_server(nvs_store.clone())?;
+            let device_id = station_mac_hex(&wifi).unwrap_or_else(|err| {
+                warn!("failed to read station MAC for device id: {err:#}");
+                "unknown".to_string()
+            });
+            let ap_gateway_ip = wifi
+                .ap_netif()
+                .get_ip_info()
+                .map(|info| info.ip)
+                .unwrap_or_else(|err| {
+                    warn!("failed to read provisioning AP gateway IP: {err:?}");
+                    Ipv4Addr::new(192, 168, 71, 1)
+                });
+            spawn_captive_dns_responder(ap_gateway_ip);
+            let wifi = Arc::new(Mutex::new(wifi));
+            let server = create_provisioning_http_server(
+                nvs_store.clone(),
+                wifi.clone(),
+                sys_loop.clone(),
+                device_id,
+            )?;
 
             let _wifi = wifi;
             let _server = server;
@@ -524,7 +755,24 @@ pub fn run() -> anyhow::Result<()> {
     };
     disable_wifi_power_save();
 
-    if let Ok(mut ota) = EspOta::new() {
+    let device_id = station_mac_hex(&wifi).unwrap_or_else(|err| {
+        warn!("failed to read station MAC for device id: {err:#}");
+        "unknown".to_string()
+    });
+
+    let boot_instant = Instant::now();
+
+    let ota_pending_verify = nvs_store.load_ota_pending().unwrap_or_else(|err| {
+        warn!("failed to load OTA pending-verify flag from NVS: {err:#}");
+        false
+    });
+
+    if ota_pending_verify {
+        warn!(
+            "booted into a newly applied OTA image; deferring slot validation until the \
+             post-boot health check passes"
+        );
+    } else if let Ok(mut ota) = EspOta::new() {
         if let Err(err) = ota.mark_running_slot_valid() {
             warn!("failed to mark running OTA slot valid: {err:?}");
         }
@@ -533,19 +781,90 @@ pub fn run() -> anyhow::Result<()> {
     init_watchdog(WATCHDOG_TIMEOUT_SEC)?;
     add_current_task_to_watchdog()?;
 
-    let ota_state = Arc::new(Mutex::new(OtaRuntimeState::default()));
-    let server = create_http_server(nvs_store.clone(), ota_state.clone())?;
+    let ota_state = Arc::new(Mutex::new(OtaRuntimeState {
+        pending_verify: ota_pending_verify,
+        ..Default::default()
+    }));
 
-    let (mut mqtt, mut conn) = create_mqtt_client(&runtime)?;
+    let (mqtt, mut conn) = create_mqtt_client(&runtime, &nvs_store, &device_id)?;
+    let mqtt = Arc::new(Mutex::new(mqtt));
+    {
+        let mut client = mqtt.lock().unwrap();
+        if let Err(err) = client.subscribe(TOPIC_CMD_SENSOR_SETTINGS, QoS::AtMostOnce) {
+            warn!("failed to subscribe to sensor settings topic: {err:?}");
+        }
+        if let Err(err) = client.subscribe(TOPIC_CMD_SENSOR_OTA_APPLY, QoS::AtMostOnce) {
+            warn!("failed to subscribe to sensor OTA apply topic: {err:?}");
+        }
+    }
 
+    let server = create_http_server(
+        nvs_store.clone(),
+        ota_state.clone(),
+        mqtt.clone(),
+        device_id.clone(),
+    )?;
+
+    let mqtt_poll = mqtt.clone();
+    let mqtt_nvs_store = nvs_store.clone();
+    let mqtt_ota_state = ota_state.clone();
+    let mqtt_device_id = device_id.clone();
     thread::Builder::new()
         .name("mqtt-poll".to_string())
         .stack_size(8192)
         .spawn(move || {
             loop {
                 match conn.next() {
-                    Ok(_event) => {
-                        // Sensor node currently has no command subscriptions.
+                    Ok(event) => {
+                        if let EventPayload::Received {
+                            topic: Some(topic),
+                            data,
+                            details,
+                            ..
+                        } = event.payload()
+                        {
+                            // We only process full MQTT payloads.
+                            if !matches!(details, Details::Complete) {
+                                continue;
+                            }
+                            if data.len() > MAX_MQTT_PAYLOAD_BYTES {
+                                warn!(
+                                    "dropping oversized MQTT payload on topic {} ({} bytes)",
+                                    topic,
+                                    data.len()
+                                );
+                                continue;
+                            }
+
+                            if topic == TOPIC_CMD_SENSOR_SETTINGS {
+                                if let Some(ack) = handle_settings_command(&mqtt_nvs_store, data) {
+                                    let payload = match serde_json::to_vec(&ack) {
+                                        Ok(payload) => payload,
+                                        Err(err) => {
+                                            warn!("failed to encode settings ack: {err}");
+                                            continue;
+                                        }
+                                    };
+                                    let mut client = mqtt_poll.lock().unwrap();
+                                    if let Err(err) = client.publish(
+                                        TOPIC_SENSOR_SETTINGS_RESULT,
+                                        QoS::AtLeastOnce,
+                                        false,
+                                        &payload,
+                                    ) {
+                                        warn!("failed to publish settings ack: {err:?}");
+                                    }
+                                }
+                            } else if topic == TOPIC_CMD_SENSOR_OTA_APPLY {
+                                handle_ota_apply_command(
+                                    &mqtt_ota_state,
+                                    &mqtt_nvs_store,
+                                    &mqtt_poll,
+                                    &mqtt_device_id,
+                                    data,
+                                );
+                            }
+                        }
                     }
                     Err(err) => {
                         warn!("sensor mqtt poll error: {err:?}");
@@ -556,48 +875,70 @@ pub fn run() -> anyhow::Result<()> {
         })
         .expect("failed to spawn mqtt thread");
 
-    if let Err(err) = mqtt.publish(TOPIC_SENSOR_STATUS, QoS::AtLeastOnce, true, b"online") {
-        warn!("failed to publish sensor online status: {err:?}");
-    }
+    let status_topic = device_topic(&device_id, TOPIC_SENSOR_STATUS);
+    let status_publish_ok = match mqtt
+        .lock()
+        .unwrap()
+        .publish(&status_topic, QoS::AtLeastOnce, true, b"online")
+    {
+        Ok(()) => true,
+        Err(err) => {
+            warn!("failed to publish sensor online status: {err:?}");
+            false
+        }
+    };
 
-    // Keep services alive for the program lifetime.
-    let _wifi = wifi;
+    // Keep the server alive for the program lifetime; `wifi` stays mutable
+    // so `maintain_wifi_health` can reconnect it in place.
+    let mut wifi = wifi;
     let _server = server;
-    let mut wifi_disconnected_since: Option<Instant> = None;
+    let mut wifi_recovery = WifiRecoveryState::default();
 
     loop {
         feed_watchdog();
-        maintain_wifi_health(&mut wifi_disconnected_since);
+        maintain_wifi_health(&mut wifi_recovery, &mut wifi, &sys_loop, &nvs_store);
 
         let readings = sensors.read();
+        let mut sensor_publish_ok = false;
 
         if let Some(temp_f) = readings.temperature_f {
+            let temp_topic = device_topic(&device_id, TOPIC_SENSOR_TEMP);
             let temp_payload = format!("{temp_f:.1}");
-            if let Err(err) = mqtt.publish(
-                TOPIC_SENSOR_TEMP,
+            match mqtt.lock().unwrap().publish(
+                &temp_topic,
                 QoS::AtLeastOnce,
                 true,
                 temp_payload.as_bytes(),
             ) {
-                warn!("failed to publish temperature: {err:?}");
+                Ok(()) => sensor_publish_ok = true,
+                Err(err) => warn!("failed to publish temperature: {err:?}"),
             }
         }
 
         if let Some(humidity) = readings.humidity {
+            let humidity_topic = device_topic(&device_id, TOPIC_SENSOR_HUMIDITY);
             let humidity_payload = format!("{humidity:.1}");
-            if let Err(err) = mqtt.publish(
-                TOPIC_SENSOR_HUMIDITY,
+            match mqtt.lock().unwrap().publish(
+                &humidity_topic,
                 QoS::AtLeastOnce,
                 true,
                 humidity_payload.as_bytes(),
             ) {
-                warn!("failed to publish humidity: {err:?}");
+                Ok(()) => sensor_publish_ok = true,
+                Err(err) => warn!("failed to publish humidity: {err:?}"),
             }
         }
 
+        check_ota_health(
+            &ota_state,
+            &nvs_store,
+            status_publish_ok && sensor_publish_ok,
+            boot_instant,
+        );
+
         for _ in 0..30 {
             feed_watchdog();
-            maintain_wifi_health(&mut wifi_disconnected_since);
+            maintain_wifi_health(&mut wifi_recovery, &mut wifi, &sys_loop, &nvs_store);
             thread::sleep(Duration::from_secs(1));
         }
     }
@@ -606,6 +947,8 @@ pub fn run() -> anyhow::Result<()> {
 fn create_http_server(
     nvs_store: NvsStore,
     ota_state: Arc<Mutex<OtaRuntimeState>>,
+    mqtt: Arc<Mutex<EspMqttClient<'static>>>,
+    device_id: String,
 ) -> anyhow::Result<EspHttpServer<'static>> {
     let conf = HttpConfiguration {
         stack_size: 16 * 1024,
@@ -622,15 +965,17 @@ fn create_http_server(
 
     {
         let nvs_store = nvs_store.clone();
+        let device_id = device_id.clone();
         server.fn_handler("/api/network", Method::Get, move |req| {
             let runtime = nvs_store.load_runtime_config().unwrap_or_default();
-            let payload = build_network_config_view(&runtime.network);
+            let payload = build_network_config_view(&device_id, &runtime.network);
             write_json(req, &payload)
         })?;
     }
 
     {
         let nvs_store = nvs_store.clone();
+        let device_id = device_id.clone();
         server.fn_handler::<anyhow::Error, _>("/api/network", Method::Put, move |mut req| {
             let body = read_request_body(&mut req)?;
             let update: NetworkConfigUpdate =
@@ -640,15 +985,32 @@ fn create_http_server(
                 return write_error(req, 400, message);
             }
 
-            let payload = apply_network_update(&nvs_store, update)?;
+            let payload = apply_network_update(&nvs_store, &device_id, update)?;
             write_json(req, &payload)
         })?;
     }
 
+    {
+        let nvs_store = nvs_store.clone();
+        server.fn_handler::<anyhow::Error, _>("/api/network/mqtt-ca", Method::Put, move |mut req| {
+            let body = read_request_body(&mut req)?;
+            let update: MqttCaCertUpdate =
+                serde_json::from_slice(&body).context("invalid mqtt CA payload")?;
+
+            if update.pem.trim().is_empty() {
+                return write_error(req, 400, "pem must not be empty");
+            }
+
+            nvs_store.save_mqtt_ca_cert(&update.pem)?;
+            write_json(req, &serde_json::json!({ "ok": true }))
+        })?;
+    }
+
     {
         let ota_state = ota_state.clone();
+        let device_id = device_id.clone();
         server.fn_handler("/api/ota/status", Method::Get, move |req| {
-            let payload = build_ota_status_response(&ota_state);
+            let payload = build_ota_status_response(&device_id, &ota_state);
             write_json(req, &payload)
         })?;
     }
@@ -656,6 +1018,8 @@ fn create_http_server(
     {
         let ota_state = ota_state.clone();
         let nvs_store = nvs_store.clone();
+        let mqtt = mqtt.clone();
+        let device_id = device_id.clone();
         server.fn_handler::<anyhow::Error, _>("/api/ota/apply", Method::Post, move |mut req| {
             let body = read_request_body(&mut req)?;
             let update: OtaApplyRequest =
@@ -665,7 +1029,8 @@ fn create_http_server(
                 return write_error(req, 400, message);
             }
 
-            match apply_ota_update(&ota_state, &nvs_store, update) {
+            match apply_ota_update(&ota_state, &nvs_store, mqtt.clone(), device_id.clone(), update)
+            {
                 Ok(payload) => write_json(req, &payload),
                 Err(err) => {
                     let message = err.to_string();
@@ -697,7 +1062,12 @@ fn create_http_server(
     Ok(server)
 }
 
-fn create_provisioning_http_server(nvs_store: NvsStore) -> anyhow::Result<EspHttpServer<'static>> {
+fn create_provisioning_http_server(
+    nvs_store: NvsStore,
+    wifi: Arc<Mutex<EspWifi<'static>>>,
+    sys_loop: EspSystemEventLoop,
+    device_id: String,
+) -> anyhow::Result<EspHttpServer<'static>> {
     let conf = HttpConfiguration {
         stack_size: 16 * 1024,
         ..Default::default()
@@ -723,15 +1093,17 @@ fn create_provisioning_http_server(nvs_store: NvsStore) -> anyhow::Result<EspHtt
 
     {
         let nvs_store = nvs_store.clone();
+        let device_id = device_id.clone();
         server.fn_handler("/api/network", Method::Get, move |req| {
             let runtime = nvs_store.load_runtime_config().unwrap_or_default();
-            let payload = build_network_config_view(&runtime.network);
+            let payload = build_network_config_view(&device_id, &runtime.network);
             write_json(req, &payload)
         })?;
     }
 
     {
         let nvs_store = nvs_store.clone();
+        let device_id = device_id.clone();
         server.fn_handler::<anyhow::Error, _>("/api/network", Method::Put, move |mut req| {
             let body = read_request_body(&mut req)?;
             let update: NetworkConfigUpdate =
@@ -741,30 +1113,63 @@ fn create_provisioning_http_server(nvs_store: NvsStore) -> anyhow::Result<EspHtt
                 return write_error(req, 400, message);
             }
 
-            let payload = apply_network_update(&nvs_store, update)?;
+            let payload = apply_network_update(&nvs_store, &device_id, update)?;
             write_json(req, &payload)
         })?;
     }
 
-    server.fn_handler("/api/ota/status", Method::Get, move |req| {
-        let payload = OtaStatusResponse {
-            supported: false,
-            in_progress: false,
-            bytes_written: 0,
-            total_bytes: None,
-            progress_pct: None,
-            last_error: Some(
-                "Sensor is in provisioning mode; OTA apply is unavailable".to_string(),
-            ),
-            last_sha256: None,
-            last_source_url: None,
-            last_completed_epoch: None,
-            running_slot: None,
-            boot_slot: None,
-            update_slot: None,
-        };
-        write_json(req, &payload)
-    })?;
+    {
+        let nvs_store = nvs_store.clone();
+        server.fn_handler::<anyhow::Error, _>("/api/network/mqtt-ca", Method::Put, move |mut req| {
+            let body = read_request_body(&mut req)?;
+            let update: MqttCaCertUpdate =
+                serde_json::from_slice(&body).context("invalid mqtt CA payload")?;
+
+            if update.pem.trim().is_empty() {
+                return write_error(req, 400, "pem must not be empty");
+            }
+
+            nvs_store.save_mqtt_ca_cert(&update.pem)?;
+            write_json(req, &serde_json::json!({ "ok": true }))
+        })?;
+    }
+
+    {
+        // Dedup-by-strongest-RSSI and descending-signal sort already live in
+        // `scan_wifi_networks`, covering the full `/api/wifi/scan` contract.
+        let wifi = wifi.clone();
+        let sys_loop = sys_loop.clone();
+        server.fn_handler::<anyhow::Error, _>("/api/wifi/scan", Method::Get, move |req| {
+            let payload = scan_wifi_networks(&wifi, sys_loop.clone())?;
+            write_json(req, &payload)
+        })?;
+    }
+
+    {
+        let device_id = device_id.clone();
+        server.fn_handler("/api/ota/status", Method::Get, move |req| {
+            let payload = OtaStatusResponse {
+                device_id: device_id.clone(),
+                supported: false,
+                in_progress: false,
+                bytes_written: 0,
+                total_bytes: None,
+                progress_pct: None,
+                last_error: Some(
+                    "Sensor is in provisioning mode; OTA apply is unavailable".to_string(),
+                ),
+                last_sha256: None,
+                last_source_url: None,
+                last_completed_epoch: None,
+                running_slot: None,
+                boot_slot: None,
+                update_slot: None,
+                pending_verify: false,
+                last_result: None,
+            };
+            write_json(req, &payload)
+        })?;
+    }
 
     server.fn_handler("/api/ota/apply", Method::Post, move |req| {
         write_error(req, 409, "Connect station WiFi before applying OTA updates")
@@ -856,6 +1261,30 @@ fn ipv4_from_octets(ip: [u8; 4]) -> Ipv4Addr {
     Ipv4Addr::new(ip[0], ip[1], ip[2], ip[3])
 }
 
+/// Stable per-device id derived from the station MAC (hex, no separators),
+/// so every sensor node gets a distinct id without any provisioning step.
+/// Readable as soon as the WiFi driver is up, whether or not the station
+/// ends up connected, so both `WifiStartup::Connected` and `::Provisioning`
+/// can use it.
+fn station_mac_hex(wifi: &EspWifi<'static>) -> anyhow::Result<String> {
+    let mac = wifi.get_mac(WifiDeviceId::Sta)?;
+    Ok(mac.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// Namespaces a sensor topic suffix (`TOPIC_SENSOR_TEMP` and friends) under
+/// this device's id, so multiple sensor nodes publishing to one broker land
+/// on distinct topics instead of colliding.
+fn device_topic(device_id: &str, suffix: &str) -> String {
+    format!("{device_id}/{suffix}")
+}
+
+/// Builds the IPv4 static netif config. `esp_idf_svc::ipv4::Configuration`
+/// has no IPv6 counterpart to assign a fixed address through - ESP-IDF's
+/// netif only ever autoconfigures v6 (link-local always, global via SLAAC
+/// once a router advertisement arrives) - so `network.ipv6_mode` is
+/// persisted and exposed to the portal/settings API for forward
+/// compatibility, but doesn't change what actually comes up on this
+/// interface yet.
 fn build_static_ip_config(network: &NetworkConfig) -> anyhow::Result<Option<NetifConfiguration>> {
     if !network.use_static_ip {
         return Ok(None);
@@ -891,11 +1320,76 @@ fn build_static_ip_config(network: &NetworkConfig) -> anyhow::Result<Option<Neti
     Ok(Some(conf))
 }
 
+/// Maps `network.wifi_auth` onto the matching `AuthMethod`. When unset, keeps
+/// the historical auto-detect behavior: open if `wifi_pass` is empty,
+/// WPA/WPA2-Personal otherwise.
+fn resolve_wifi_auth_method(network: &NetworkConfig) -> AuthMethod {
+    match network.wifi_auth {
+        Some(WifiAuthMode::Open) => AuthMethod::None,
+        Some(WifiAuthMode::Wpa2) => AuthMethod::WPA2Personal,
+        Some(WifiAuthMode::Wpa2Wpa3) => AuthMethod::WPA2WPA3Personal,
+        Some(WifiAuthMode::Wpa3) => AuthMethod::WPA3Personal,
+        Some(WifiAuthMode::Wpa2Enterprise) => AuthMethod::WPA2Enterprise,
+        None if network.wifi_pass.is_empty() => AuthMethod::None,
+        None => AuthMethod::WPAWPA2Personal,
+    }
+}
+
+/// Configures the ESP-IDF WPA2-Enterprise (802.1X) supplicant with the
+/// identity/username/password needed to join a corporate or campus network.
+/// Must run before `wifi.set_configuration`/`wifi.connect()` for
+/// `AuthMethod::WPA2Enterprise` networks; `network.wifi_pass` doubles as the
+/// EAP password.
+fn configure_wifi_enterprise_credentials(network: &NetworkConfig) -> anyhow::Result<()> {
+    let identity = network
+        .wifi_identity
+        .as_deref()
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| anyhow!("wifi_identity is required for WPA2-Enterprise"))?;
+    let username = network
+        .wifi_username
+        .as_deref()
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| anyhow!("wifi_username is required for WPA2-Enterprise"))?;
+
+    let rc = unsafe {
+        esp_idf_svc::sys::esp_eap_client_set_identity(identity.as_ptr(), identity.len() as i32)
+    };
+    if rc != esp_idf_svc::sys::ESP_OK {
+        return Err(anyhow!("esp_eap_client_set_identity failed with code {rc}"));
+    }
+
+    let rc = unsafe {
+        esp_idf_svc::sys::esp_eap_client_set_username(username.as_ptr(), username.len() as i32)
+    };
+    if rc != esp_idf_svc::sys::ESP_OK {
+        return Err(anyhow!("esp_eap_client_set_username failed with code {rc}"));
+    }
+
+    let rc = unsafe {
+        esp_idf_svc::sys::esp_eap_client_set_password(
+            network.wifi_pass.as_ptr(),
+            network.wifi_pass.len() as i32,
+        )
+    };
+    if rc != esp_idf_svc::sys::ESP_OK {
+        return Err(anyhow!("esp_eap_client_set_password failed with code {rc}"));
+    }
+
+    let rc = unsafe { esp_idf_svc::sys::esp_wifi_sta_enterprise_enable() };
+    if rc != esp_idf_svc::sys::ESP_OK {
+        return Err(anyhow!("esp_wifi_sta_enterprise_enable failed with code {rc}"));
+    }
+
+    Ok(())
+}
+
 fn connect_wifi(
     modem: Modem,
     sys_loop: EspSystemEventLoop,
     nvs_partition: EspDefaultNvsPartition,
     network: &NetworkConfig,
+    force_recovery: bool,
 ) -> anyhow::Result<WifiStartup> {
     let mut esp_wifi = EspWifi::new(modem, sys_loop.clone(), Some(nvs_partition))?;
 
@@ -915,6 +1409,12 @@ fn connect_wifi(
 
     let mut wifi = BlockingWifi::wrap(&mut esp_wifi, sys_loop)?;
 
+    if force_recovery {
+        warn!("recovery mode requested; entering provisioning AP mode");
+        start_provisioning_ap(&mut wifi)?;
+        return Ok(WifiStartup::Provisioning(esp_wifi));
+    }
+
     if let Some(err) = static_ip_error {
         warn!("invalid static IP configuration ({err:#}); entering provisioning mode");
         start_provisioning_ap(&mut wifi)?;
@@ -927,11 +1427,10 @@ fn connect_wifi(
         return Ok(WifiStartup::Provisioning(esp_wifi));
     }
 
-    let auth_method = if network.wifi_pass.is_empty() {
-        AuthMethod::None
-    } else {
-        AuthMethod::WPAWPA2Personal
-    };
+    let auth_method = resolve_wifi_auth_method(network);
+    if matches!(auth_method, AuthMethod::WPA2Enterprise) {
+        configure_wifi_enterprise_credentials(network)?;
+    }
 
     wifi.set_configuration(&Configuration::Client(ClientConfiguration {
         ssid: network
@@ -990,6 +1489,42 @@ fn connect_wifi(
     }
 }
 
+/// Watches GPIO0 (the devkit's BOOT button, active low) for
+/// `RESET_BUTTON_WINDOW_MS` after boot, returning `true` as soon as a second
+/// press lands inside that window. Lets a technician force recovery mode on
+/// demand without needing the MQTT/HTTP settings paths those protocols
+/// normally go through - handy exactly when WiFi is the thing that's broken.
+fn detect_reset_button_double_press(pin: AnyIOPin) -> bool {
+    let mut button = match PinDriver::input(pin) {
+        Ok(driver) => driver,
+        Err(err) => {
+            warn!("failed to initialize reset button pin: {err:?}");
+            return false;
+        }
+    };
+    if let Err(err) = button.set_pull(Pull::Up) {
+        warn!("failed to enable pull-up on reset button pin: {err:?}");
+    }
+
+    let deadline = Instant::now() + Duration::from_millis(RESET_BUTTON_WINDOW_MS);
+    let mut press_count = 0;
+    let mut was_pressed = false;
+
+    while Instant::now() < deadline {
+        let pressed = button.is_low();
+        if pressed && !was_pressed {
+            press_count += 1;
+            if press_count >= 2 {
+                return true;
+            }
+        }
+        was_pressed = pressed;
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    false
+}
+
 fn start_provisioning_ap(wifi: &mut BlockingWifi<&mut EspWifi<'static>>) -> anyhow::Result<()> {
     wifi.set_configuration(&Configuration::AccessPoint(AccessPointConfiguration {
         ssid: PROVISIONING_AP_SSID
@@ -1011,6 +1546,161 @@ fn start_provisioning_ap(wifi: &mut BlockingWifi<&mut EspWifi<'static>>) -> anyh
     Ok(())
 }
 
+/// Spawns a wildcard DNS responder on the provisioning AP so a connecting
+/// phone/laptop's captive-portal probe (which resolves a hostname before
+/// hitting the well-known paths `create_provisioning_http_server` serves)
+/// gets an answer at all. Every A-record query is answered with
+/// `gateway_ip` regardless of the name asked for; everything else is
+/// dropped. Runs for the life of the process - there's currently no path
+/// back out of `WifiStartup::Provisioning` short of a reboot, so there's
+/// nothing to tear this thread down for.
+fn spawn_captive_dns_responder(gateway_ip: Ipv4Addr) {
+    let spawn_result = thread::Builder::new()
+        .name("captive-dns".into())
+        .stack_size(4096)
+        .spawn(move || {
+            let socket = match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, DNS_PORT)) {
+                Ok(socket) => socket,
+                Err(err) => {
+                    warn!("failed to bind captive-portal DNS socket: {err}");
+                    return;
+                }
+            };
+
+            let mut buf = [0_u8; 512];
+            loop {
+                feed_watchdog();
+                match socket.recv_from(&mut buf) {
+                    Ok((len, from)) => {
+                        if let Some(response) = build_dns_a_response(&buf[..len], gateway_ip) {
+                            if let Err(err) = socket.send_to(&response, from) {
+                                warn!("failed to send captive-portal DNS reply: {err}");
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        warn!("captive-portal DNS recv error: {err}");
+                        thread::sleep(Duration::from_millis(500));
+                    }
+                }
+            }
+        });
+
+    if let Err(err) = spawn_result {
+        warn!("failed to spawn captive-portal DNS thread: {err}");
+    }
+}
+
+/// Builds an authoritative A-record reply to `query`, answering with
+/// `answer_ip` no matter what name was asked for. Returns `None` for
+/// anything that isn't a single-question A-record query (malformed
+/// packet, AAAA/other record types), which the caller silently drops.
+fn build_dns_a_response(query: &[u8], answer_ip: Ipv4Addr) -> Option<Vec<u8>> {
+    const DNS_TYPE_A: u16 = 1;
+    const DNS_CLASS_IN: u16 = 1;
+
+    if query.len() < 12 {
+        return None;
+    }
+
+    let qdcount = u16::from_be_bytes([query[4], query[5]]);
+    if qdcount != 1 {
+        return None;
+    }
+
+    let mut pos = 12;
+    while pos < query.len() && query[pos] != 0 {
+        pos += 1 + query[pos] as usize;
+    }
+    if pos >= query.len() {
+        return None;
+    }
+    let question_end = pos + 1 + 4; // terminating zero label + QTYPE + QCLASS
+    if question_end > query.len() {
+        return None;
+    }
+    let qtype = u16::from_be_bytes([query[pos + 1], query[pos + 2]]);
+    if qtype != DNS_TYPE_A {
+        return None;
+    }
+
+    let mut response = Vec::with_capacity(question_end + 16);
+    response.extend_from_slice(&query[..12]);
+    response[2] |= 0x80; // QR: this is a response
+    response[2] |= 0x04; // AA: authoritative answer
+    response[6..8].copy_from_slice(&1_u16.to_be_bytes()); // ANCOUNT = 1
+    response.extend_from_slice(&query[12..question_end]); // echoed question
+
+    response.extend_from_slice(&0xC00C_u16.to_be_bytes()); // name: pointer to offset 12
+    response.extend_from_slice(&DNS_TYPE_A.to_be_bytes());
+    response.extend_from_slice(&DNS_CLASS_IN.to_be_bytes());
+    response.extend_from_slice(&DNS_ANSWER_TTL_SECS.to_be_bytes());
+    response.extend_from_slice(&4_u16.to_be_bytes()); // RDLENGTH
+    response.extend_from_slice(&answer_ip.octets());
+
+    Some(response)
+}
+
+/// Runs a synchronous scan over `wifi` (valid while the station or the
+/// provisioning AP is up, since `EspWifi` drives both) and returns the
+/// results sorted strongest-first, de-duplicated by SSID so a repeated
+/// beacon from the same AP only shows up once.
+fn scan_wifi_networks(
+    wifi: &Arc<Mutex<EspWifi<'static>>>,
+    sys_loop: EspSystemEventLoop,
+) -> anyhow::Result<Vec<WifiScanResult>> {
+    let mut esp_wifi = wifi.lock().unwrap();
+    let mut blocking = BlockingWifi::wrap(&mut *esp_wifi, sys_loop)?;
+    let access_points = blocking.scan().context("wifi scan failed")?;
+
+    let mut strongest_by_ssid: std::collections::HashMap<String, WifiScanResult> =
+        std::collections::HashMap::new();
+    for ap in access_points {
+        let ssid = ap.ssid.as_str().to_string();
+        if ssid.is_empty() {
+            continue;
+        }
+
+        let result = WifiScanResult {
+            ssid: ssid.clone(),
+            rssi: ap.signal_strength,
+            auth_method: auth_method_label(ap.auth_method),
+            channel: ap.channel,
+        };
+        strongest_by_ssid
+            .entry(ssid)
+            .and_modify(|existing| {
+                if result.rssi > existing.rssi {
+                    *existing = result.clone();
+                }
+            })
+            .or_insert(result);
+    }
+
+    let mut results: Vec<WifiScanResult> = strongest_by_ssid.into_values().collect();
+    results.sort_by(|a, b| b.rssi.cmp(&a.rssi));
+    Ok(results)
+}
+
+fn auth_method_label(auth_method: Option<AuthMethod>) -> String {
+    match auth_method {
+        None | Some(AuthMethod::None) => "open".to_string(),
+        Some(other) => format!("{other:?}"),
+    }
+}
+
+/// Parses the `wifiAuth` wire value into the corresponding `WifiAuthMode`.
+fn parse_wifi_auth(text: &str) -> Result<WifiAuthMode, &'static str> {
+    match text {
+        "open" => Ok(WifiAuthMode::Open),
+        "wpa2" => Ok(WifiAuthMode::Wpa2),
+        "wpa2wpa3" => Ok(WifiAuthMode::Wpa2Wpa3),
+        "wpa3" => Ok(WifiAuthMode::Wpa3),
+        "wpa2-enterprise" => Ok(WifiAuthMode::Wpa2Enterprise),
+        _ => Err("wifiAuth must be one of: open, wpa2, wpa2wpa3, wpa3, wpa2-enterprise"),
+    }
+}
+
 fn validate_network_update(update: &NetworkConfigUpdate) -> Result<(), &'static str> {
     if update.wifi_ssid.trim().is_empty() {
         return Err("wifiSsid cannot be empty");
@@ -1026,12 +1716,41 @@ fn validate_network_update(update: &NetworkConfigUpdate) -> Result<(), &'static
     {
         return Err("staticIp, gateway, and subnet are required when useStaticIp is true");
     }
+    if update.use_static_ipv6 {
+        if update.ipv6_address.is_none() || update.ipv6_gateway.is_none() {
+            return Err("ipv6Address and ipv6Gateway are required when useStaticIpv6 is true");
+        }
+        match update.ipv6_prefix_len {
+            Some(1..=128) => {}
+            _ => return Err("ipv6PrefixLen must be between 1 and 128"),
+        }
+    }
+
+    if let Some(wifi_auth) = update.wifi_auth.as_deref() {
+        let mode = parse_wifi_auth(wifi_auth)?;
+        if mode == WifiAuthMode::Wpa2Enterprise {
+            let identity_set = update
+                .wifi_identity
+                .as_deref()
+                .is_some_and(|v| !v.trim().is_empty());
+            let username_set = update
+                .wifi_username
+                .as_deref()
+                .is_some_and(|v| !v.trim().is_empty());
+            if !identity_set || !username_set {
+                return Err(
+                    "wifiIdentity and wifiUsername are required when wifiAuth is wpa2-enterprise",
+                );
+            }
+        }
+    }
 
     Ok(())
 }
 
 fn apply_network_update(
     nvs_store: &NvsStore,
+    device_id: &str,
     update: NetworkConfigUpdate,
 ) -> anyhow::Result<NetworkUpdateResponse> {
     let mut runtime = nvs_store.load_runtime_config().unwrap_or_default();
@@ -1047,6 +1766,7 @@ fn apply_network_update(
     if let Some(pass) = update.mqtt_pass {
         runtime.network.mqtt_pass = pass;
     }
+    runtime.network.mqtt_tls = update.mqtt_tls;
     if let Some(pass) = update.ota_password {
         runtime.network.ota_password = pass;
     }
@@ -1055,29 +1775,59 @@ fn apply_network_update(
     runtime.network.gateway = update.gateway;
     runtime.network.subnet = update.subnet;
     runtime.network.dns = update.dns;
+    runtime.network.ipv6_mode = if update.use_static_ipv6 {
+        Ipv6Mode::Static
+    } else {
+        Ipv6Mode::Slaac
+    };
+    runtime.network.ipv6_address = update.ipv6_address;
+    runtime.network.ipv6_prefix_len = update.ipv6_prefix_len;
+    runtime.network.ipv6_gateway = update.ipv6_gateway;
+    runtime.network.ipv6_dns = update.ipv6_dns;
+    if let Some(wifi_auth) = update.wifi_auth {
+        let mode =
+            parse_wifi_auth(&wifi_auth).map_err(|err| anyhow!("invalid network update: {err}"))?;
+        runtime.network.wifi_auth = Some(mode);
+    }
+    if let Some(identity) = update.wifi_identity {
+        runtime.network.wifi_identity = Some(identity);
+    }
+    if let Some(username) = update.wifi_username {
+        runtime.network.wifi_username = Some(username);
+    }
 
     nvs_store.save_runtime_config(&runtime)?;
 
     Ok(NetworkUpdateResponse {
         restart_required: network_restart_required(&previous, &runtime.network),
-        network: build_network_config_view(&runtime.network),
+        network: build_network_config_view(device_id, &runtime.network),
     })
 }
 
-fn build_network_config_view(network: &NetworkConfig) -> NetworkConfigView {
+fn build_network_config_view(device_id: &str, network: &NetworkConfig) -> NetworkConfigView {
     NetworkConfigView {
+        device_id: device_id.to_string(),
         wifi_ssid: network.wifi_ssid.clone(),
         wifi_pass_set: !network.wifi_pass.is_empty(),
         mqtt_host: network.mqtt_host.clone(),
         mqtt_port: network.mqtt_port,
         mqtt_user: network.mqtt_user.clone(),
         mqtt_pass_set: !network.mqtt_pass.is_empty(),
+        mqtt_tls: network.mqtt_tls,
         ota_password_set: !network.ota_password.is_empty(),
         use_static_ip: network.use_static_ip,
         static_ip: network.static_ip,
         gateway: network.gateway,
         subnet: network.subnet,
         dns: network.dns,
+        use_static_ipv6: network.ipv6_mode == Ipv6Mode::Static,
+        ipv6_address: network.ipv6_address,
+        ipv6_prefix_len: network.ipv6_prefix_len,
+        ipv6_gateway: network.ipv6_gateway,
+        ipv6_dns: network.ipv6_dns,
+        wifi_auth: network.wifi_auth,
+        wifi_identity: network.wifi_identity.clone(),
+        wifi_username: network.wifi_username.clone(),
     }
 }
 
@@ -1089,10 +1839,136 @@ fn network_restart_required(previous: &NetworkConfig, current: &NetworkConfig) -
         || previous.gateway != current.gateway
         || previous.subnet != current.subnet
         || previous.dns != current.dns
+        || previous.ipv6_mode != current.ipv6_mode
+        || previous.ipv6_address != current.ipv6_address
+        || previous.ipv6_prefix_len != current.ipv6_prefix_len
+        || previous.ipv6_gateway != current.ipv6_gateway
+        || previous.ipv6_dns != current.ipv6_dns
         || previous.mqtt_host != current.mqtt_host
         || previous.mqtt_port != current.mqtt_port
         || previous.mqtt_user != current.mqtt_user
         || previous.mqtt_pass != current.mqtt_pass
+        || previous.mqtt_tls != current.mqtt_tls
+        || previous.wifi_auth != current.wifi_auth
+        || previous.wifi_identity != current.wifi_identity
+        || previous.wifi_username != current.wifi_username
+}
+
+/// Inbound command on `TOPIC_CMD_SENSOR_SETTINGS`: a single leaf write
+/// against `RuntimeConfig`, addressed by a `/`-delimited `path` matching the
+/// struct's own (plain snake_case) serde field names, e.g. `network/mqtt_host`
+/// or `telemetry/state_interval_secs`. `id` is an opaque correlation token
+/// the caller chooses and gets back unchanged on `TOPIC_SENSOR_SETTINGS_RESULT`,
+/// the simple JSON-envelope fallback the request allows in place of MQTT5
+/// `response_topic`/`correlation_data` properties, which this crate's MQTT
+/// client stack has no support for.
+#[derive(Debug, Deserialize)]
+struct SettingsCommand {
+    id: String,
+    path: String,
+    value: serde_json::Value,
+}
+
+/// Ack published to `TOPIC_SENSOR_SETTINGS_RESULT` for every
+/// [`SettingsCommand`] received.
+#[derive(Debug, Serialize)]
+struct SettingsAck {
+    id: String,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl SettingsAck {
+    fn ok(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            ok: true,
+            error: None,
+        }
+    }
+
+    fn err(id: &str, error: impl Into<String>) -> Self {
+        Self {
+            id: id.to_string(),
+            ok: false,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Applies a single [`SettingsCommand`] leaf write to the persisted
+/// `RuntimeConfig` and saves it back through `nvs_store`. Round-trips
+/// through `serde_json::Value` so any `RuntimeConfig` field can be
+/// addressed without a hand-maintained list of settable keys; a `network/*`
+/// write is additionally run back through `validate_network_update`'s
+/// rules once merged, since those are the only cross-field invariants this
+/// config has.
+fn apply_settings_command(nvs_store: &NvsStore, command: &SettingsCommand) -> Result<(), String> {
+    let runtime = nvs_store
+        .load_runtime_config()
+        .map_err(|err| format!("failed to load runtime config: {err:#}"))?;
+
+    let mut doc = serde_json::to_value(&runtime).map_err(|err| err.to_string())?;
+    let pointer = format!("/{}", command.path.trim_matches('/').replace('.', "/"));
+    let slot = doc
+        .pointer_mut(&pointer)
+        .ok_or_else(|| format!("unknown settings path `{}`", command.path))?;
+    *slot = command.value.clone();
+
+    let updated: RuntimeConfig = serde_json::from_value(doc)
+        .map_err(|err| format!("invalid value for `{}`: {err}", command.path))?;
+
+    let update = NetworkConfigUpdate {
+        wifi_ssid: updated.network.wifi_ssid.clone(),
+        wifi_pass: None,
+        mqtt_host: updated.network.mqtt_host.clone(),
+        mqtt_port: updated.network.mqtt_port,
+        mqtt_user: updated.network.mqtt_user.clone(),
+        mqtt_pass: None,
+        mqtt_tls: updated.network.mqtt_tls,
+        ota_password: None,
+        use_static_ip: updated.network.use_static_ip,
+        static_ip: updated.network.static_ip,
+        gateway: updated.network.gateway,
+        subnet: updated.network.subnet,
+        dns: updated.network.dns,
+        use_static_ipv6: updated.network.ipv6_mode == Ipv6Mode::Static,
+        ipv6_address: updated.network.ipv6_address,
+        ipv6_prefix_len: updated.network.ipv6_prefix_len,
+        ipv6_gateway: updated.network.ipv6_gateway,
+        ipv6_dns: updated.network.ipv6_dns,
+        wifi_auth: updated
+            .network
+            .wifi_auth
+            .and_then(|mode| serde_json::to_value(mode).ok())
+            .and_then(|value| value.as_str().map(str::to_string)),
+        wifi_identity: updated.network.wifi_identity.clone(),
+        wifi_username: updated.network.wifi_username.clone(),
+    };
+    validate_network_update(&update)?;
+
+    nvs_store
+        .save_runtime_config(&updated)
+        .map_err(|err| format!("failed to save runtime config: {err:#}"))
+}
+
+/// Parses and applies a raw `TOPIC_CMD_SENSOR_SETTINGS` payload, returning
+/// the ack to publish back. Malformed JSON has no correlation id to ack
+/// against, so it's logged and dropped rather than published anywhere.
+fn handle_settings_command(nvs_store: &NvsStore, payload: &[u8]) -> Option<SettingsAck> {
+    let command: SettingsCommand = match serde_json::from_slice(payload) {
+        Ok(command) => command,
+        Err(err) => {
+            warn!("dropping malformed settings command: {err}");
+            return None;
+        }
+    };
+
+    match apply_settings_command(nvs_store, &command) {
+        Ok(()) => Some(SettingsAck::ok(&command.id)),
+        Err(err) => Some(SettingsAck::err(&command.id, err)),
+    }
 }
 
 fn validate_ota_apply_request(update: &OtaApplyRequest) -> Result<(), &'static str> {
@@ -1117,6 +1993,8 @@ fn validate_ota_apply_request(update: &OtaApplyRequest) -> Result<(), &'static s
 fn apply_ota_update(
     ota_state: &Arc<Mutex<OtaRuntimeState>>,
     nvs_store: &NvsStore,
+    mqtt: Arc<Mutex<EspMqttClient<'static>>>,
+    device_id: String,
     update: OtaApplyRequest,
 ) -> anyhow::Result<OtaApplyResponse> {
     let runtime = nvs_store.load_runtime_config().unwrap_or_default();
@@ -1141,8 +2019,12 @@ fn apply_ota_update(
         ota.last_sha256 = None;
         ota.last_source_url = Some(update.url.clone());
     }
+    publish_ota_status(&mqtt, &device_id, ota_state);
 
     let ota_state_for_thread = ota_state.clone();
+    let nvs_store_for_thread = nvs_store.clone();
+    let mqtt_for_thread = mqtt.clone();
+    let device_id_for_thread = device_id.clone();
     let spawn_result = thread::Builder::new()
         .name("ota-apply".into())
         .stack_size(16 * 1024)
@@ -1152,8 +2034,14 @@ fn apply_ota_update(
                 .sha256
                 .as_ref()
                 .map(|v| v.trim().to_ascii_lowercase());
-            let result =
-                download_and_apply_ota(&ota_state_for_thread, &update.url, expected_sha.as_deref());
+            let result = download_and_apply_ota(
+                &ota_state_for_thread,
+                &nvs_store_for_thread,
+                &mqtt_for_thread,
+                &device_id_for_thread,
+                &update.url,
+                expected_sha.as_deref(),
+            );
 
             match result {
                 Ok((bytes_written, digest_hex)) => {
@@ -1166,6 +2054,11 @@ fn apply_ota_update(
                         ota.last_sha256 = Some(digest_hex);
                         ota.last_completed_epoch = Some(chrono::Utc::now().timestamp());
                     }
+                    publish_ota_status(
+                        &mqtt_for_thread,
+                        &device_id_for_thread,
+                        &ota_state_for_thread,
+                    );
 
                     info!(
                         "sensor OTA apply completed successfully ({} bytes)",
@@ -1179,10 +2072,17 @@ fn apply_ota_update(
                 }
                 Err(err) => {
                     warn!("sensor OTA apply failed: {err:#}");
-                    let mut ota = ota_state_for_thread.lock().unwrap();
-                    ota.in_progress = false;
-                    ota.last_error = Some(err.to_string());
-                    ota.last_completed_epoch = Some(chrono::Utc::now().timestamp());
+                    {
+                        let mut ota = ota_state_for_thread.lock().unwrap();
+                        ota.in_progress = false;
+                        ota.last_error = Some(err.to_string());
+                        ota.last_completed_epoch = Some(chrono::Utc::now().timestamp());
+                    }
+                    publish_ota_status(
+                        &mqtt_for_thread,
+                        &device_id_for_thread,
+                        &ota_state_for_thread,
+                    );
                 }
             }
         });
@@ -1203,6 +2103,9 @@ fn apply_ota_update(
 
 fn download_and_apply_ota(
     ota_state: &Arc<Mutex<OtaRuntimeState>>,
+    nvs_store: &NvsStore,
+    mqtt: &Arc<Mutex<EspMqttClient<'static>>>,
+    device_id: &str,
     url: &str,
     expected_sha256: Option<&str>,
 ) -> anyhow::Result<(u64, String)> {
@@ -1251,12 +2154,19 @@ fn download_and_apply_ota(
         hasher.update(&chunk[..read]);
         total_written = total_written.saturating_add(read as u64);
 
-        let mut state = ota_state.lock().unwrap();
-        state.bytes_written = total_written;
-        if let Some(total) = state.total_bytes.filter(|value| *value > 0) {
-            let pct = (total_written.saturating_mul(100) / total).min(100);
-            state.progress_pct = Some(pct as u8);
+        {
+            let mut state = ota_state.lock().unwrap();
+            state.bytes_written = total_written;
+            if let Some(total) = state.total_bytes.filter(|value| *value > 0) {
+                let pct = (total_written.saturating_mul(100) / total).min(100);
+                state.progress_pct = Some(pct as u8);
+            }
         }
+        // `chunk` is sized at `OTA_CHUNK_SIZE`, so every loop iteration is a
+        // chunk boundary - publish progress here rather than on some other
+        // cadence so a fleet watching `TOPIC_SENSOR_OTA_STATUS` sees the same
+        // granularity the local portal's polling loop would.
+        publish_ota_status(mqtt, device_id, ota_state);
     }
 
     if total_written == 0 {
@@ -1284,6 +2194,11 @@ fn download_and_apply_ota(
         .map_err(|err| anyhow!("failed finalizing OTA image: {err:?}"))?;
     drop(ota);
 
+    ota_state.lock().unwrap().pending_verify = true;
+    if let Err(err) = nvs_store.save_ota_pending(true) {
+        warn!("failed to persist OTA pending-verify flag in NVS: {err:#}");
+    }
+
     Ok((total_written, digest_hex))
 }
 
@@ -1303,10 +2218,14 @@ fn ota_slot_label(query: SlotQuery) -> Option<String> {
     Some(slot.label.as_str().to_string())
 }
 
-fn build_ota_status_response(ota_state: &Arc<Mutex<OtaRuntimeState>>) -> OtaStatusResponse {
+fn build_ota_status_response(
+    device_id: &str,
+    ota_state: &Arc<Mutex<OtaRuntimeState>>,
+) -> OtaStatusResponse {
     let ota = ota_state.lock().unwrap();
 
     OtaStatusResponse {
+        device_id: device_id.to_string(),
         supported: true,
         in_progress: ota.in_progress,
         bytes_written: ota.bytes_written,
@@ -1319,22 +2238,160 @@ fn build_ota_status_response(ota_state: &Arc<Mutex<OtaRuntimeState>>) -> OtaStat
         running_slot: ota_slot_label(SlotQuery::Running),
         boot_slot: ota_slot_label(SlotQuery::Boot),
         update_slot: ota_slot_label(SlotQuery::Update),
+        pending_verify: ota.pending_verify,
+        last_result: ota.last_result,
+    }
+}
+
+/// Confirms or rolls back a pending OTA slot based on whether `run`'s
+/// post-boot health milestone (WiFi connected, MQTT status published, one
+/// sensor reading published) has been reached. A no-op once `pending_verify`
+/// is already false, so this can be called on every control-loop tick
+/// without re-triggering after the first resolution. Mirrors the
+/// controller's `check_ota_health`, gated on this milestone instead of
+/// `wifi_connected`/`mqtt_connected` booleans, since the sensor crate
+/// doesn't track those as persistent state outside this check.
+fn check_ota_health(
+    ota_state: &Arc<Mutex<OtaRuntimeState>>,
+    nvs_store: &NvsStore,
+    milestone_reached: bool,
+    boot_instant: Instant,
+) {
+    let mut ota = ota_state.lock().unwrap();
+    if !ota.pending_verify {
+        return;
+    }
+
+    if milestone_reached {
+        match EspOta::new().and_then(|mut slot| slot.mark_running_slot_valid()) {
+            Ok(()) => {
+                info!("boot health check passed; running slot marked valid");
+                ota.pending_verify = false;
+                ota.last_result = Some(OtaHealthState::Confirmed);
+                if let Err(err) = nvs_store.save_ota_pending(false) {
+                    warn!("failed to clear OTA pending-verify flag in NVS: {err:#}");
+                }
+            }
+            Err(err) => warn!("failed to mark running OTA slot valid: {err:?}"),
+        }
+        return;
+    }
+
+    if boot_instant.elapsed() < Duration::from_secs(OTA_CONFIRM_TIMEOUT_SECS) {
+        return;
+    }
+
+    warn!(
+        "OTA health check did not pass within {OTA_CONFIRM_TIMEOUT_SECS}s; marking the running \
+         slot invalid and rolling back to the previous slot"
+    );
+    ota.pending_verify = false;
+    ota.last_result = Some(OtaHealthState::RolledBack);
+    drop(ota);
+    if let Err(err) = nvs_store.save_ota_pending(false) {
+        warn!("failed to clear OTA pending-verify flag in NVS: {err:#}");
+    }
+    let rollback = EspOta::new().and_then(|mut slot| slot.mark_running_slot_invalid_and_reboot());
+    if let Err(err) = rollback {
+        warn!("failed to mark running OTA slot invalid, restarting anyway: {err:?}");
+        unsafe { esp_idf_svc::sys::esp_restart() };
+    }
+}
+
+/// Publishes the current `OtaRuntimeState` as a retained message on
+/// `TOPIC_SENSOR_OTA_STATUS`, so a broker-side subscriber gets the same
+/// progress visibility `/api/ota/status` polling gives the local portal,
+/// whether the update was triggered over HTTP or MQTT.
+fn publish_ota_status(
+    mqtt: &Arc<Mutex<EspMqttClient<'static>>>,
+    device_id: &str,
+    ota_state: &Arc<Mutex<OtaRuntimeState>>,
+) {
+    let payload = build_ota_status_response(device_id, ota_state);
+    let payload = match serde_json::to_vec(&payload) {
+        Ok(payload) => payload,
+        Err(err) => {
+            warn!("failed to encode OTA status payload: {err}");
+            return;
+        }
+    };
+
+    if let Err(err) =
+        mqtt.lock()
+            .unwrap()
+            .publish(TOPIC_SENSOR_OTA_STATUS, QoS::AtLeastOnce, true, &payload)
+    {
+        warn!("failed to publish OTA status: {err:?}");
+    }
+}
+
+/// Parses and applies a raw `TOPIC_CMD_SENSOR_OTA_APPLY` payload, the same
+/// way `/api/ota/apply` does. There's no ack topic here - progress and any
+/// download/apply failure (via `last_error`) show up in the retained
+/// `TOPIC_SENSOR_OTA_STATUS` publishes `apply_ota_update` triggers; a command
+/// rejected before that (malformed, failing validation, a wrong password, or
+/// an update already in progress) is only logged.
+fn handle_ota_apply_command(
+    ota_state: &Arc<Mutex<OtaRuntimeState>>,
+    nvs_store: &NvsStore,
+    mqtt: &Arc<Mutex<EspMqttClient<'static>>>,
+    device_id: &str,
+    payload: &[u8],
+) {
+    let update: OtaApplyRequest = match serde_json::from_slice(payload) {
+        Ok(update) => update,
+        Err(err) => {
+            warn!("dropping malformed OTA apply command: {err}");
+            return;
+        }
+    };
+
+    if let Err(message) = validate_ota_apply_request(&update) {
+        warn!("rejected OTA apply command: {message}");
+        return;
+    }
+
+    if let Err(err) =
+        apply_ota_update(ota_state, nvs_store, mqtt.clone(), device_id.to_string(), update)
+    {
+        warn!("failed to start OTA apply from MQTT command: {err:#}");
     }
 }
 
 fn create_mqtt_client(
     runtime: &RuntimeConfig,
+    nvs_store: &NvsStore,
+    device_id: &str,
 ) -> anyhow::Result<(
     EspMqttClient<'static>,
     esp_idf_svc::mqtt::client::EspMqttConnection,
 )> {
+    let scheme = if runtime.network.mqtt_tls {
+        "mqtts"
+    } else {
+        "mqtt"
+    };
     let url = format!(
-        "mqtt://{}:{}",
+        "{scheme}://{}:{}",
         runtime.network.mqtt_host, runtime.network.mqtt_port
     );
+    let client_id = format!("thermostat-sensor-{device_id}");
+    let lwt_topic = device_topic(device_id, TOPIC_SENSOR_STATUS);
 
-    let conf = MqttClientConfiguration {
-        client_id: Some("thermostat-sensor"),
+    let ca_cert_cstring = if runtime.network.mqtt_tls {
+        let pem = nvs_store.load_mqtt_ca_cert().unwrap_or_else(|err| {
+            warn!("failed to load custom MQTT CA cert from NVS: {err:#}");
+            None
+        });
+        pem.map(std::ffi::CString::new)
+            .transpose()
+            .map_err(|_| anyhow!("mqtt CA cert contains interior NUL bytes"))?
+    } else {
+        None
+    };
+
+    let mut conf = MqttClientConfiguration {
+        client_id: Some(&client_id),
         username: if runtime.network.mqtt_user.is_empty() {
             None
         } else {
@@ -1345,9 +2402,24 @@ fn create_mqtt_client(
         } else {
             Some(runtime.network.mqtt_pass.as_str())
         },
+        lwt: Some(LwtConfiguration {
+            topic: &lwt_topic,
+            payload: b"offline",
+            qos: QoS::AtLeastOnce,
+            retain: true,
+        }),
         ..Default::default()
     };
 
+    if runtime.network.mqtt_tls {
+        match ca_cert_cstring.as_deref() {
+            Some(cert) => {
+                conf.server_certificate = Some(X509::pem_until_nul(cert.to_bytes_with_nul()));
+            }
+            None => conf.crt_bundle_attach = Some(esp_idf_svc::sys::esp_crt_bundle_attach),
+        }
+    }
+
     Ok(EspMqttClient::new(&url, &conf)?)
 }
 
@@ -1370,6 +2442,86 @@ impl NvsStore {
         nvs.set_str(NVS_RUNTIME_KEY, &payload)?;
         Ok(())
     }
+
+    /// Whether the currently running OTA slot still awaits the post-boot
+    /// health check, surviving a power cycle so `run` knows to defer
+    /// `mark_running_slot_valid` rather than confirm an image it never
+    /// actually finished checking.
+    fn load_ota_pending(&self) -> anyhow::Result<bool> {
+        let _guard = self.lock.lock().unwrap();
+        let nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
+        Ok(nvs.get_u8(NVS_OTA_PENDING_KEY)?.unwrap_or(0) != 0)
+    }
+
+    fn save_ota_pending(&self, pending: bool) -> anyhow::Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
+        nvs.set_u8(NVS_OTA_PENDING_KEY, u8::from(pending))?;
+        Ok(())
+    }
+
+    /// A user-uploaded PEM bundle for validating the MQTT broker's TLS
+    /// certificate. `None` when no custom CA has been uploaded, in which case
+    /// the broker is validated against the device's bundled ESP-IDF CA store.
+    fn load_mqtt_ca_cert(&self) -> anyhow::Result<Option<String>> {
+        let _guard = self.lock.lock().unwrap();
+        let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
+        let mut buffer = vec![0_u8; 4096];
+
+        Ok(nvs
+            .get_str(NVS_MQTT_CA_CERT_KEY, &mut buffer)?
+            .map(str::to_string))
+    }
+
+    fn save_mqtt_ca_cert(&self, pem: &str) -> anyhow::Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
+        nvs.set_str(NVS_MQTT_CA_CERT_KEY, pem)?;
+        Ok(())
+    }
+
+    /// Whether the next boot should force `WorkingMode::Recovery` rather
+    /// than attempt a station connect. Set by `maintain_wifi_health` right
+    /// before it restarts after exhausting reconnection retries, and
+    /// consumed (read then cleared) once at boot, so a stuck SSID/password
+    /// reliably lands back in the provisioning portal instead of looping
+    /// through failed reconnect attempts forever.
+    fn load_recovery_mode(&self) -> anyhow::Result<bool> {
+        let _guard = self.lock.lock().unwrap();
+        let nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
+        Ok(nvs.get_u8(NVS_RECOVERY_MODE_KEY)?.unwrap_or(0) != 0)
+    }
+
+    fn save_recovery_mode(&self, recovery: bool) -> anyhow::Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let mut nvs = EspNvs::new(self.partition.clone(), NVS_NAMESPACE, true)?;
+        nvs.set_u8(NVS_RECOVERY_MODE_KEY, u8::from(recovery))?;
+        Ok(())
+    }
+}
+
+/// Logs why the chip just booted, distinguishing a watchdog-triggered
+/// recovery (task hang or interrupt-level stall) from the WiFi-grace
+/// `esp_restart()` in `maintain_wifi_health` and from an ordinary
+/// power-on/software reset, so field diagnostics don't have to guess which
+/// of the self-heal paths fired. `esp_restart()` itself reports as
+/// `ESP_RST_SW` either way, so this can't tell a WiFi-triggered restart
+/// apart from a deliberate one - only the watchdog reasons are unambiguous.
+fn log_reset_reason() {
+    let reason = unsafe { esp_idf_svc::sys::esp_reset_reason() };
+    let description = match reason {
+        esp_idf_svc::sys::esp_reset_reason_t_ESP_RST_POWERON => "power-on",
+        esp_idf_svc::sys::esp_reset_reason_t_ESP_RST_SW => "software restart (esp_restart)",
+        esp_idf_svc::sys::esp_reset_reason_t_ESP_RST_PANIC => "panic",
+        esp_idf_svc::sys::esp_reset_reason_t_ESP_RST_INT_WDT => "interrupt watchdog",
+        esp_idf_svc::sys::esp_reset_reason_t_ESP_RST_TASK_WDT => "task watchdog",
+        esp_idf_svc::sys::esp_reset_reason_t_ESP_RST_WDT => "other watchdog",
+        esp_idf_svc::sys::esp_reset_reason_t_ESP_RST_DEEPSLEEP => "deep sleep wakeup",
+        esp_idf_svc::sys::esp_reset_reason_t_ESP_RST_BROWNOUT => "brownout",
+        esp_idf_svc::sys::esp_reset_reason_t_ESP_RST_SDIO => "SDIO",
+        _ => "unknown",
+    };
+    info!("boot reset reason: {description} (raw={reason})");
 }
 
 fn init_watchdog(timeout_sec: u32) -> anyhow::Result<()> {
@@ -1412,28 +2564,146 @@ fn is_wifi_station_connected() -> bool {
     rc == esp_idf_svc::sys::ESP_OK
 }
 
-fn maintain_wifi_health(wifi_disconnected_since: &mut Option<Instant>) {
+/// Graduated recovery from a lost WiFi association, replacing the old
+/// "wait out the grace window, then reboot" behavior. `Reconnecting` retries
+/// association with exponential backoff; once `WIFI_RECONNECT_MAX_RETRIES`
+/// is exhausted the radio is stopped for `Cooldown` before another burst
+/// starts; only once cumulative downtime also exceeds `WIFI_RESTART_GRACE_MS`
+/// does `maintain_wifi_health` set the `WorkingMode::Recovery` NVS flag and
+/// fall through to `esp_restart`, so the device comes back up in the
+/// provisioning portal instead of looping through the same dead credentials
+/// forever. This preserves in-RAM sensor history across transient AP
+/// hiccups instead of rebooting on every disconnect.
+#[derive(Debug)]
+enum WifiRecoveryState {
+    Stable,
+    Reconnecting {
+        disconnected_since: Instant,
+        retry_count: u32,
+        next_attempt: Instant,
+    },
+    Cooldown {
+        disconnected_since: Instant,
+        resume_at: Instant,
+    },
+}
+
+impl Default for WifiRecoveryState {
+    fn default() -> Self {
+        WifiRecoveryState::Stable
+    }
+}
+
+fn maintain_wifi_health(
+    state: &mut WifiRecoveryState,
+    wifi: &mut EspWifi<'static>,
+    sys_loop: &EspSystemEventLoop,
+    nvs_store: &NvsStore,
+) {
     if is_wifi_station_connected() {
-        *wifi_disconnected_since = None;
+        if !matches!(state, WifiRecoveryState::Stable) {
+            info!("wifi reassociated; recovery state cleared");
+        }
+        *state = WifiRecoveryState::Stable;
         return;
     }
 
-    match wifi_disconnected_since {
-        Some(disconnected_since)
-            if disconnected_since.elapsed().as_millis() as u64 >= WIFI_RESTART_GRACE_MS =>
-        {
-            warn!(
-                "wifi disconnected for {}s; restarting device for recovery",
-                WIFI_RESTART_GRACE_MS / 1000
+    let now = Instant::now();
+    match state {
+        WifiRecoveryState::Stable => {
+            *state = WifiRecoveryState::Reconnecting {
+                disconnected_since: now,
+                retry_count: 0,
+                next_attempt: now,
+            };
+        }
+        WifiRecoveryState::Reconnecting {
+            disconnected_since,
+            retry_count,
+            next_attempt,
+        } => {
+            if now < *next_attempt {
+                return;
+            }
+
+            let down_for_ms = now.duration_since(*disconnected_since).as_millis() as u64;
+            if *retry_count >= WIFI_RECONNECT_MAX_RETRIES {
+                if down_for_ms >= WIFI_RESTART_GRACE_MS {
+                    warn!(
+                        "wifi down for {}s after {retry_count} reconnect attempts; credentials \
+                         or the SSID itself look permanently gone, so restarting into the \
+                         provisioning portal instead of retrying forever",
+                        down_for_ms / 1000
+                    );
+                    if let Err(err) = nvs_store.save_recovery_mode(true) {
+                        warn!("failed to persist recovery-mode flag before restart: {err:#}");
+                    }
+                    thread::sleep(Duration::from_millis(100));
+                    unsafe { esp_idf_svc::sys::esp_restart() };
+                }
+
+                warn!(
+                    "wifi reconnect retries exhausted; stopping radio for a {}s cooldown before \
+                     another burst",
+                    WIFI_COOLDOWN_MS / 1000
+                );
+                let _ = wifi.stop();
+                *state = WifiRecoveryState::Cooldown {
+                    disconnected_since: *disconnected_since,
+                    resume_at: now + Duration::from_millis(WIFI_COOLDOWN_MS),
+                };
+                return;
+            }
+
+            *retry_count += 1;
+            let attempt = *retry_count;
+            let delay_ms = wifi_reconnect_backoff_ms(attempt);
+            *next_attempt = now + Duration::from_millis(delay_ms);
+
+            info!(
+                "wifi reconnect attempt {attempt}/{WIFI_RECONNECT_MAX_RETRIES} \
+                 (next retry backs off {delay_ms}ms if this fails)"
             );
-            thread::sleep(Duration::from_millis(100));
-            unsafe { esp_idf_svc::sys::esp_restart() };
+            match BlockingWifi::wrap(wifi, sys_loop.clone()) {
+                Ok(mut blocking) => {
+                    let _ = blocking.disconnect();
+                    match blocking.connect().and_then(|()| blocking.wait_netif_up()) {
+                        Ok(()) => info!("wifi reconnect attempt {attempt} succeeded"),
+                        Err(err) => warn!("wifi reconnect attempt {attempt} failed: {err:#}"),
+                    }
+                }
+                Err(err) => warn!("failed to wrap wifi driver for reconnect: {err:#}"),
+            }
+        }
+        WifiRecoveryState::Cooldown {
+            disconnected_since,
+            resume_at,
+        } => {
+            if now < *resume_at {
+                return;
+            }
+            info!("wifi cooldown elapsed; restarting the radio and resuming reconnect attempts");
+            if let Err(err) = wifi.start() {
+                warn!("failed to restart wifi radio after cooldown: {err:#}");
+            }
+            *state = WifiRecoveryState::Reconnecting {
+                disconnected_since: *disconnected_since,
+                retry_count: 0,
+                next_attempt: now,
+            };
         }
-        Some(_) => {}
-        None => *wifi_disconnected_since = Some(Instant::now()),
     }
 }
 
-fn celsius_to_fahrenheit(temp_c: f32) -> f32 {
-    temp_c * 9.0 / 5.0 + 32.0
+/// Exponential backoff delay for reconnect `attempt` (1-indexed): 1s, 2s,
+/// 4s, 8s, ... doubling, capped at `WIFI_RECONNECT_MAX_DELAY_MS`, with a
+/// +/-10% jitter so a whole fleet reassociating against a rebooting AP
+/// doesn't retry in lockstep.
+fn wifi_reconnect_backoff_ms(attempt: u32) -> u64 {
+    let exponent = attempt.saturating_sub(1).min(6);
+    let base = WIFI_RECONNECT_BASE_DELAY_MS.saturating_mul(1u64 << exponent);
+    let capped = base.min(WIFI_RECONNECT_MAX_DELAY_MS);
+    let jitter_pct = u64::from(unsafe { esp_idf_svc::sys::esp_random() } % 21);
+    capped * (90 + jitter_pct) / 100
 }
+
@@ -1,10 +1,344 @@
-use std::time::Duration;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
-use anyhow::Context;
-use rumqttc::{AsyncClient, MqttOptions, QoS};
+use rumqttc::{AsyncClient, Event, Incoming, LastWill, MqttOptions, QoS};
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
 use tracing::{info, warn};
 
-use thermostat_common::{TOPIC_SENSOR_HUMIDITY, TOPIC_SENSOR_STATUS, TOPIC_SENSOR_TEMP};
+use thermostat_common::{
+    TOPIC_CMD_SENSOR_CONFIG, TOPIC_SENSOR_HUMIDITY, TOPIC_SENSOR_STATUS, TOPIC_SENSOR_TEMP,
+};
+
+const RECONNECT_BACKOFF_INITIAL_SECS: u64 = 2;
+const RECONNECT_BACKOFF_MAX_SECS: u64 = 60;
+const HA_SENSOR_DEVICE_ID: &str = "thermostat-sensor";
+
+/// A single temperature/humidity reading, abstracted away from how it was
+/// obtained so the publish loop in `run()` doesn't care whether it came from
+/// `SimulatedSource` or real hardware.
+#[derive(Debug, Clone, Copy)]
+struct Reading {
+    temperature_f: f32,
+    humidity: f32,
+}
+
+/// Seam between the publish loop and however a reading is actually produced,
+/// so the same binary runs in simulation on a dev box or against real
+/// DS18B20/DHT11 hardware without forking the publish path.
+trait SensorSource {
+    async fn read(&mut self) -> anyhow::Result<Reading>;
+}
+
+/// Publish interval (in fractional seconds, so `SENSOR_PUBLISH_HZ` can push
+/// well above one message per second) and calibration offsets, retunable at
+/// runtime over MQTT instead of requiring a restart. `mqtt_event_loop`
+/// applies incoming `TOPIC_CMD_SENSOR_CONFIG` commands here; `run`'s publish
+/// loop watches the same value to rebuild its interval and adjust each
+/// reading before publishing.
+#[derive(Debug, Clone, Copy)]
+struct LiveConfig {
+    interval_secs: f64,
+    temp_offset_f: f32,
+    humidity_offset: f32,
+}
+
+impl Default for LiveConfig {
+    fn default() -> Self {
+        let interval_secs = std::env::var("SENSOR_PUBLISH_HZ")
+            .ok()
+            .and_then(|value| value.parse::<f64>().ok())
+            .filter(|hz| *hz > 0.0)
+            .map(|hz| 1.0 / hz)
+            .unwrap_or(30.0);
+        Self {
+            interval_secs,
+            temp_offset_f: 0.0,
+            humidity_offset: 0.0,
+        }
+    }
+}
+
+/// Partial update applied over the current `LiveConfig`; any field omitted
+/// from the incoming JSON command is left unchanged.
+#[derive(Debug, Deserialize)]
+struct SensorConfigCommand {
+    interval_secs: Option<f64>,
+    temp_offset_f: Option<f32>,
+    humidity_offset: Option<f32>,
+}
+
+/// Shape of `SimulatedSource`'s deterministic test signal, selected by the
+/// `SENSOR_WAVEFORM` env var so the publisher can stand in for a real sensor
+/// while driving a known, reproducible load at the broker/controller.
+#[derive(Debug, Clone, Copy)]
+enum Waveform {
+    /// Ramps linearly from 0 to `amplitude` over `period_ticks`, then resets.
+    Sawtooth,
+    /// `amplitude * sin(2π · tick / period_ticks)`.
+    Sine,
+    /// Climbs linearly without ever resetting, for testing long-running
+    /// drift/clamping behavior downstream.
+    Ramp,
+    /// No variation; always the base value.
+    Constant,
+}
+
+impl Waveform {
+    fn from_env() -> Self {
+        match std::env::var("SENSOR_WAVEFORM").as_deref() {
+            Ok("sine") => Self::Sine,
+            Ok("ramp") => Self::Ramp,
+            Ok("constant") => Self::Constant,
+            _ => Self::Sawtooth,
+        }
+    }
+
+    fn offset(self, tick: u64, amplitude: f32, period_ticks: u64) -> f32 {
+        let period_ticks = period_ticks.max(1);
+        match self {
+            Self::Sawtooth => {
+                let phase = (tick % period_ticks) as f32 / period_ticks as f32;
+                phase * amplitude
+            }
+            Self::Ramp => (tick as f32 / period_ticks as f32) * amplitude,
+            Self::Sine => {
+                let phase = tick as f32 / period_ticks as f32;
+                amplitude * (std::f32::consts::TAU * phase).sin()
+            }
+            Self::Constant => 0.0,
+        }
+    }
+}
+
+/// `SENSOR_WAVEFORM`/`SENSOR_AMPLITUDE`/`SENSOR_PERIOD_TICKS`, read once at
+/// startup since this is a load-testing knob rather than something a
+/// real deployment retunes at runtime like `LiveConfig`.
+struct WaveformConfig {
+    waveform: Waveform,
+    amplitude: f32,
+    period_ticks: u64,
+}
+
+impl WaveformConfig {
+    fn from_env() -> Self {
+        let amplitude = std::env::var("SENSOR_AMPLITUDE")
+            .ok()
+            .and_then(|value| value.parse::<f32>().ok())
+            .unwrap_or(1.6);
+        let period_ticks = std::env::var("SENSOR_PERIOD_TICKS")
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap_or(8);
+        Self {
+            waveform: Waveform::from_env(),
+            amplitude,
+            period_ticks,
+        }
+    }
+}
+
+/// Simulated readings for development off real hardware, or a deterministic
+/// test signal for stressing the broker/controller (see `Waveform`).
+struct SimulatedSource {
+    tick: u64,
+    waveform: WaveformConfig,
+}
+
+impl SimulatedSource {
+    fn new() -> Self {
+        Self {
+            tick: 0,
+            waveform: WaveformConfig::from_env(),
+        }
+    }
+}
+
+impl SensorSource for SimulatedSource {
+    async fn read(&mut self) -> anyhow::Result<Reading> {
+        self.tick = self.tick.saturating_add(1);
+        let offset = self.waveform.waveform.offset(
+            self.tick,
+            self.waveform.amplitude,
+            self.waveform.period_ticks,
+        );
+        Ok(Reading {
+            temperature_f: 68.0 + offset,
+            humidity: (42.0 + offset).clamp(0.0, 100.0),
+        })
+    }
+}
+
+#[cfg(feature = "hardware")]
+fn make_source() -> hardware::HardwareSource {
+    hardware::HardwareSource::new()
+}
+
+#[cfg(not(feature = "hardware"))]
+fn make_source() -> SimulatedSource {
+    SimulatedSource::new()
+}
+
+/// Real DS18B20 (1-Wire) + DHT11 backend for the Linux target, mirroring the
+/// ESP target's `SensorSuite` in `esp.rs` but against Linux GPIO via
+/// `linux-embedded-hal`/`gpio-cdev` instead of `esp-idf-hal`.
+#[cfg(feature = "hardware")]
+mod hardware {
+    use anyhow::{anyhow, Context};
+    use dht_sensor::dht11;
+    use ds18b20::{Ds18b20, Resolution};
+    use gpio_cdev::{Chip, LineRequestFlags};
+    use linux_embedded_hal::{CdevPin, Delay};
+    use one_wire_bus::OneWire;
+
+    use super::{Reading, SensorSource};
+
+    const DS18B20_GPIO: u32 = 4;
+    const DHT11_GPIO: u32 = 17;
+    const GPIO_CHIP: &str = "/dev/gpiochip0";
+
+    pub struct HardwareSource;
+
+    impl HardwareSource {
+        pub fn new() -> Self {
+            Self
+        }
+
+        /// Both reads are blocking (one-wire conversion delay, DHT11
+        /// bit-banging), so this runs on the blocking thread pool rather
+        /// than stalling the async runtime.
+        fn read_blocking() -> anyhow::Result<Reading> {
+            let mut chip = Chip::new(GPIO_CHIP).context("failed to open gpio chip")?;
+            let mut delay = Delay;
+
+            let ds18b20_line = chip
+                .get_line(DS18B20_GPIO)
+                .context("failed to get DS18B20 gpio line")?
+                .request(LineRequestFlags::OUTPUT, 1, "ds18b20")
+                .context("failed to request DS18B20 gpio line")?;
+            let mut one_wire = OneWire::new(CdevPin::new(ds18b20_line)?)
+                .map_err(|err| anyhow!("one-wire bus init failed: {err:?}"))?;
+
+            let address = one_wire
+                .devices(false, &mut delay)
+                .filter_map(Result::ok)
+                .find(|addr| addr.family_code() == ds18b20::FAMILY_CODE)
+                .ok_or_else(|| anyhow!("no DS18B20 found on GPIO{DS18B20_GPIO}"))?;
+            let sensor = Ds18b20::new::<core::convert::Infallible>(address)
+                .map_err(|err| anyhow!("invalid DS18B20 address {address:?}: {err:?}"))?;
+            ds18b20::start_simultaneous_temp_measurement(&mut one_wire, &mut delay)
+                .map_err(|err| anyhow!("failed to start DS18B20 conversion: {err:?}"))?;
+            Resolution::Bits12.delay_for_measurement_time(&mut delay);
+            let temp_c = sensor
+                .read_data(&mut one_wire, &mut delay)
+                .map_err(|err| anyhow!("failed to read DS18B20 data: {err:?}"))?
+                .temperature;
+            let temperature_f = temp_c * 9.0 / 5.0 + 32.0;
+
+            let dht11_line = chip
+                .get_line(DHT11_GPIO)
+                .context("failed to get DHT11 gpio line")?
+                .request(LineRequestFlags::OUTPUT, 1, "dht11")
+                .context("failed to request DHT11 gpio line")?;
+            let mut dht11_pin = CdevPin::new(dht11_line)?;
+            let humidity = dht11::blocking::read(&mut delay, &mut dht11_pin)
+                .map_err(|err| {
+                    anyhow!("failed to read DHT11 humidity on GPIO{DHT11_GPIO}: {err:?}")
+                })?
+                .relative_humidity as f32;
+
+            Ok(Reading {
+                temperature_f,
+                humidity,
+            })
+        }
+    }
+
+    impl SensorSource for HardwareSource {
+        async fn read(&mut self) -> anyhow::Result<Reading> {
+            tokio::task::spawn_blocking(Self::read_blocking)
+                .await
+                .context("hardware sensor read task panicked")?
+        }
+    }
+}
+
+/// How each reading is serialized onto its MQTT topic, selected by the
+/// `SENSOR_PAYLOAD_FORMAT` env var. `Plain` is the original bare-float
+/// string, kept as the default for back-compat with existing subscribers;
+/// `Json` adds the unit and capture time so consumers can correlate
+/// readings and reject stale samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PayloadFormat {
+    Plain,
+    Json,
+}
+
+impl PayloadFormat {
+    fn from_env() -> Self {
+        match std::env::var("SENSOR_PAYLOAD_FORMAT").as_deref() {
+            Ok("json") => Self::Json,
+            _ => Self::Plain,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SensorPayload {
+    value: f32,
+    unit: &'static str,
+    ts: String,
+    seq: u64,
+}
+
+/// `seq` is a monotonic counter bumped once per publish cycle (shared by the
+/// temperature and humidity messages from that cycle) so a subscriber can
+/// detect drops or reordering under load; it's only carried in `Json` mode
+/// since `Plain` has no structure to hold it.
+fn format_reading_payload(
+    value: f32,
+    unit: &'static str,
+    seq: u64,
+    format: PayloadFormat,
+) -> String {
+    match format {
+        PayloadFormat::Plain => format!("{value:.1}"),
+        PayloadFormat::Json => {
+            let payload = SensorPayload {
+                value,
+                unit,
+                ts: chrono::Utc::now().to_rfc3339(),
+                seq,
+            };
+            serde_json::to_string(&payload).expect("SensorPayload serialization is infallible")
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct HaSensorDevice {
+    identifiers: Vec<String>,
+    name: String,
+    model: String,
+}
+
+#[derive(Debug, Serialize)]
+struct HaSensorDiscovery {
+    name: String,
+    unique_id: String,
+    state_topic: String,
+    unit_of_measurement: String,
+    device_class: String,
+    availability_topic: String,
+    payload_available: String,
+    payload_not_available: String,
+    device: HaSensorDevice,
+}
 
 pub async fn run() -> anyhow::Result<()> {
     tracing_subscriber::fmt()
@@ -23,49 +357,224 @@ pub async fn run() -> anyhow::Result<()> {
         let pass = std::env::var("MQTT_PASS").unwrap_or_default();
         mqtt_options.set_credentials(user, pass);
     }
+    mqtt_options.set_last_will(LastWill::new(
+        TOPIC_SENSOR_STATUS,
+        "offline",
+        QoS::AtLeastOnce,
+        true,
+    ));
 
-    let (mqtt, mut eventloop) = AsyncClient::new(mqtt_options, 32);
+    let (mqtt, eventloop) = AsyncClient::new(mqtt_options, 32);
+    mqtt.subscribe(TOPIC_CMD_SENSOR_CONFIG, QoS::AtMostOnce)
+        .await?;
 
-    mqtt.publish(TOPIC_SENSOR_STATUS, QoS::AtLeastOnce, true, "online")
-        .await
-        .context("failed to publish sensor online status")?;
+    let ha_discovery_prefix =
+        std::env::var("HA_DISCOVERY_PREFIX").unwrap_or_else(|_| "homeassistant".to_string());
 
-    tokio::spawn(async move {
-        loop {
-            if let Err(err) = eventloop.poll().await {
-                warn!("sensor mqtt poll error: {err}");
-                tokio::time::sleep(Duration::from_secs(2)).await;
-            }
-        }
-    });
+    let connected = Arc::new(AtomicBool::new(false));
+    let (config_tx, mut config_rx) = watch::channel(LiveConfig::default());
+    tokio::spawn(mqtt_event_loop(
+        mqtt.clone(),
+        eventloop,
+        connected.clone(),
+        ha_discovery_prefix,
+        config_tx,
+    ));
 
     info!("sensor publisher started");
 
-    let mut tick: u64 = 0;
-    let mut interval = tokio::time::interval(Duration::from_secs(30));
+    let mut source = make_source();
+    let mut config = *config_rx.borrow();
+    let initial_period = Duration::from_secs_f64(config.interval_secs.max(0.001));
+    let mut interval = tokio::time::interval(initial_period);
+    let payload_format = PayloadFormat::from_env();
+    let mut seq: u64 = 0;
 
     loop {
         interval.tick().await;
-        tick = tick.saturating_add(1);
 
-        // Hardware integration point:
-        // replace these simulated readings with DS18B20 + DHT11 drivers on ESP target.
-        let temperature_f = 68.0 + ((tick % 8) as f32 * 0.2);
-        let humidity = 42.0 + ((tick % 6) as f32 * 0.5);
+        if config_rx.has_changed().unwrap_or(false) {
+            let new_config = *config_rx.borrow_and_update();
+            if new_config.interval_secs != config.interval_secs {
+                let period = Duration::from_secs_f64(new_config.interval_secs.max(0.001));
+                interval = tokio::time::interval(period);
+                interval.tick().await;
+            }
+            config = new_config;
+        }
 
-        let temp_payload = format!("{temperature_f:.1}");
-        let humidity_payload = format!("{humidity:.1}");
+        if !connected.load(Ordering::Relaxed) {
+            warn!("mqtt disconnected, skipping sensor publish");
+            continue;
+        }
+
+        let reading = match source.read().await {
+            Ok(reading) => reading,
+            Err(err) => {
+                warn!("failed to read sensor, publishing offline: {err:#}");
+                if let Err(err) = mqtt
+                    .publish(TOPIC_SENSOR_STATUS, QoS::AtLeastOnce, true, "offline")
+                    .await
+                {
+                    warn!("failed to publish sensor offline status: {err}");
+                }
+                continue;
+            }
+        };
 
-        mqtt.publish(TOPIC_SENSOR_TEMP, QoS::AtLeastOnce, true, temp_payload)
+        let calibrated_temp = reading.temperature_f + config.temp_offset_f;
+        let calibrated_humidity = reading.humidity + config.humidity_offset;
+        seq = seq.wrapping_add(1);
+
+        let temp_payload = format_reading_payload(calibrated_temp, "°F", seq, payload_format);
+        let humidity_payload =
+            format_reading_payload(calibrated_humidity, "%", seq, payload_format);
+
+        if let Err(err) = mqtt
+            .publish(TOPIC_SENSOR_TEMP, QoS::AtLeastOnce, true, temp_payload)
+            .await
+        {
+            warn!("failed to publish sensor temperature: {err}");
+        }
+        if let Err(err) = mqtt
+            .publish(
+                TOPIC_SENSOR_HUMIDITY,
+                QoS::AtLeastOnce,
+                true,
+                humidity_payload,
+            )
             .await
-            .context("failed to publish sensor temperature")?;
-        mqtt.publish(
-            TOPIC_SENSOR_HUMIDITY,
-            QoS::AtLeastOnce,
-            true,
-            humidity_payload,
-        )
-        .await
-        .context("failed to publish sensor humidity")?;
+        {
+            warn!("failed to publish sensor humidity: {err}");
+        }
+    }
+}
+
+/// Polls the MQTT event loop, reconnecting with a capped exponential backoff
+/// on poll errors. `rumqttc` does not replay our birth message on a fresh
+/// connection, and the retained `offline` last will may have replaced it, so a `ConnAck` with
+/// `session_present == false` re-publishes the retained online status rather
+/// than assuming the broker still has it. `connected` is surfaced to the
+/// publish loop so it can skip publishes while disconnected instead of
+/// erroring out.
+async fn mqtt_event_loop(
+    mqtt: AsyncClient,
+    mut eventloop: rumqttc::EventLoop,
+    connected: Arc<AtomicBool>,
+    ha_discovery_prefix: String,
+    config_tx: watch::Sender<LiveConfig>,
+) {
+    let mut backoff_secs = RECONNECT_BACKOFF_INITIAL_SECS;
+
+    loop {
+        match eventloop.poll().await {
+            Ok(Event::Incoming(Incoming::ConnAck(ack))) => {
+                backoff_secs = RECONNECT_BACKOFF_INITIAL_SECS;
+                connected.store(true, Ordering::Relaxed);
+                if !ack.session_present {
+                    info!("mqtt session not present, re-publishing online status");
+                    if let Err(err) = mqtt
+                        .publish(TOPIC_SENSOR_STATUS, QoS::AtLeastOnce, true, "online")
+                        .await
+                    {
+                        warn!("failed to publish sensor online status: {err}");
+                    }
+                    if let Err(err) = publish_ha_discovery(&mqtt, &ha_discovery_prefix).await {
+                        warn!("failed to publish Home Assistant discovery config: {err:#}");
+                    }
+                }
+            }
+            Ok(Event::Incoming(Incoming::Publish(message))) => {
+                if message.topic == TOPIC_CMD_SENSOR_CONFIG {
+                    apply_sensor_config_command(&config_tx, &message.payload);
+                }
+            }
+            Ok(_) => {}
+            Err(err) => {
+                connected.store(false, Ordering::Relaxed);
+                warn!("sensor mqtt poll error: {err}, retrying in {backoff_secs}s");
+                tokio::time::sleep(Duration::from_secs(backoff_secs)).await;
+                backoff_secs = (backoff_secs * 2).min(RECONNECT_BACKOFF_MAX_SECS);
+            }
+        }
     }
 }
+
+/// Parses a `TOPIC_CMD_SENSOR_CONFIG` payload and merges any present fields
+/// into the live config, leaving fields the command omits untouched. Logs
+/// and ignores a malformed payload rather than tearing down the event loop.
+fn apply_sensor_config_command(config_tx: &watch::Sender<LiveConfig>, payload: &[u8]) {
+    let command: SensorConfigCommand = match serde_json::from_slice(payload) {
+        Ok(command) => command,
+        Err(err) => {
+            warn!("ignoring malformed sensor config command: {err}");
+            return;
+        }
+    };
+
+    config_tx.send_modify(|config| {
+        if let Some(interval_secs) = command.interval_secs {
+            config.interval_secs = interval_secs;
+        }
+        if let Some(temp_offset_f) = command.temp_offset_f {
+            config.temp_offset_f = temp_offset_f;
+        }
+        if let Some(humidity_offset) = command.humidity_offset {
+            config.humidity_offset = humidity_offset;
+        }
+    });
+}
+
+/// Publishes retained Home Assistant MQTT discovery config so the sensor
+/// appears automatically as a device with temperature and humidity entities,
+/// rather than requiring the user to hand-configure a dashboard against
+/// `TOPIC_SENSOR_TEMP`/`TOPIC_SENSOR_HUMIDITY`.
+async fn publish_ha_discovery(mqtt: &AsyncClient, prefix: &str) -> anyhow::Result<()> {
+    let device = HaSensorDevice {
+        identifiers: vec![HA_SENSOR_DEVICE_ID.to_string()],
+        name: "Smart Thermostat Sensor".to_string(),
+        model: "DS18B20 + DHT11".to_string(),
+    };
+
+    let temperature = HaSensorDiscovery {
+        name: "Thermostat Sensor Temperature".to_string(),
+        unique_id: format!("{HA_SENSOR_DEVICE_ID}-temperature"),
+        state_topic: TOPIC_SENSOR_TEMP.to_string(),
+        unit_of_measurement: "°F".to_string(),
+        device_class: "temperature".to_string(),
+        availability_topic: TOPIC_SENSOR_STATUS.to_string(),
+        payload_available: "online".to_string(),
+        payload_not_available: "offline".to_string(),
+        device: device.clone(),
+    };
+    let humidity = HaSensorDiscovery {
+        name: "Thermostat Sensor Humidity".to_string(),
+        unique_id: format!("{HA_SENSOR_DEVICE_ID}-humidity"),
+        state_topic: TOPIC_SENSOR_HUMIDITY.to_string(),
+        unit_of_measurement: "%".to_string(),
+        device_class: "humidity".to_string(),
+        availability_topic: TOPIC_SENSOR_STATUS.to_string(),
+        payload_available: "online".to_string(),
+        payload_not_available: "offline".to_string(),
+        device,
+    };
+
+    let temperature_topic = format!("{prefix}/sensor/{HA_SENSOR_DEVICE_ID}/temperature/config");
+    let humidity_topic = format!("{prefix}/sensor/{HA_SENSOR_DEVICE_ID}/humidity/config");
+
+    mqtt.publish(
+        temperature_topic,
+        QoS::AtLeastOnce,
+        true,
+        serde_json::to_vec(&temperature)?,
+    )
+    .await?;
+    mqtt.publish(
+        humidity_topic,
+        QoS::AtLeastOnce,
+        true,
+        serde_json::to_vec(&humidity)?,
+    )
+    .await?;
+    Ok(())
+}